@@ -1,18 +1,57 @@
 //! Tauri command handlers for GRBL controller operations.
 
+use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, State};
 
+use crate::events::{
+    self, HeartbeatEmitter, StatusStream, CONNECTION_STATE_EVENT, FRAME_PROGRESS_EVENT,
+    JOB_LINE_ERROR_EVENT, STEP_EVENT,
+};
 use crate::grbl::{
-    ConnectionState, Controller, ControllerError, ControllerSnapshot, MachineStatus,
-    OverrideAdjust, PortInfo, RapidOverride,
+    ConnectionEvent, ConnectionState, Controller, ControllerError, ControllerRegistry,
+    ControllerSnapshot, DeviceId, DeviceInfo, FrameRequest, LastError, MachineStatus,
+    OverrideAdjust, Parameters, ParserState, PortInfo, RapidOverride, RecoverySummary,
+    SettingWriteResult, StreamLineError, StreamProgress, DEFAULT_DEVICE,
+};
+use crate::grbl::gcode::{self, Diagnostic};
+use crate::grbl::protocol::{
+    self, AlarmInfo, GrblSetting, JogAxis, JogDirection, JogPreset, LineEnding, ReportFields,
+    ResetKind, Units,
 };
-use crate::grbl::protocol::{FrameMode, Units};
 use crate::grbl::protocol::SUPPORTED_BAUD_RATES;
+use crate::grbl::serial_log::{self, LogFileInfo, SerialLogSettings};
+use crate::grbl::settings_file::{self, SettingsFileError, SettingsFormat};
+use crate::grbl::status::Position;
+use crate::grbl::step::StepStatus;
+use crate::grbl::usage::{UsageCounter, UsageStats};
+use crate::grbl::worker::{self, TestResult};
+use crate::preferences::{self, Preferences};
 
-/// Application state holding the controller
+/// Application state holding the device controllers
 pub struct AppState {
-    pub controller: Arc<Controller>,
+    pub devices: Arc<ControllerRegistry>,
+    pub heartbeat: Arc<HeartbeatEmitter>,
+    pub status_stream: Arc<StatusStream>,
+    pub preferences: Arc<Preferences>,
+    pub usage: Arc<UsageCounter>,
+}
+
+impl AppState {
+    /// Controller for a device, or the default device for None
+    pub fn controller(
+        &self,
+        device_id: Option<DeviceId>,
+    ) -> Result<Arc<Controller>, ControllerError> {
+        self.devices.get(device_id)
+    }
+
+    /// Register another device and start polling its status
+    fn add_device(&self, app: &AppHandle) -> (DeviceId, Arc<Controller>) {
+        let (id, controller) = self.devices.add();
+        self.status_stream.spawn(app.clone(), id, &controller);
+        (id, controller)
+    }
 }
 
 /// Error type for Tauri commands with structured error info
@@ -30,6 +69,7 @@ impl From<ControllerError> for CommandError {
     fn from(e: ControllerError) -> Self {
         let (code, details) = match &e {
             ControllerError::NotConnected => ("NOT_CONNECTED".into(), None),
+            ControllerError::Disconnected(_) => ("DISCONNECTED".into(), None),
             ControllerError::AlreadyConnected => ("ALREADY_CONNECTED".into(), None),
             ControllerError::Timeout(attempts) => {
                 ("TIMEOUT".into(), Some(format!("{} attempts", attempts)))
@@ -38,7 +78,10 @@ impl From<ControllerError> for CommandError {
             ControllerError::Alarm(code) => ("ALARM".into(), Some(format!("code {}", code))),
             ControllerError::InvalidState(_) => ("INVALID_STATE".into(), None),
             ControllerError::Serial(_) => ("SERIAL_ERROR".into(), None),
+            ControllerError::File(_) => ("FILE_ERROR".into(), None),
+            ControllerError::Cancelled => ("CANCELLED".into(), None),
             ControllerError::Internal(_) => ("INTERNAL_ERROR".into(), None),
+            ControllerError::UnknownDevice(_) => ("UNKNOWN_DEVICE".into(), None),
         };
 
         Self {
@@ -51,12 +94,19 @@ impl From<ControllerError> for CommandError {
 
 type CommandResult<T> = Result<T, CommandError>;
 
-/// List available serial ports
+/// List available serial ports.
+///
+/// Repeated calls within a second get the same list; pass `refresh` to
+/// enumerate again regardless (e.g. from a Refresh button).
 #[tauri::command]
-pub fn list_serial_ports(state: State<AppState>) -> CommandResult<Vec<PortInfo>> {
+pub fn list_serial_ports(
+    state: State<AppState>,
+    refresh: Option<bool>,
+) -> CommandResult<Vec<PortInfo>> {
     state
-        .controller
-        .list_ports()
+        .devices
+        .default_controller()
+        .list_ports(refresh.unwrap_or(false))
         .map_err(CommandError::from)
 }
 
@@ -66,67 +116,455 @@ pub fn get_baud_rates() -> Vec<u32> {
     SUPPORTED_BAUD_RATES.to_vec()
 }
 
-/// Connect to a GRBL device
+/// Get the baud rate that last connected to this port (null if none saved).
+///
+/// Matches by USB serial number when the port has one, so the preference
+/// follows the device if its path changes.
 #[tauri::command]
-pub fn connect(state: State<AppState>, port: String, baud_rate: u32) -> CommandResult<()> {
+pub fn get_last_baud_for_port(state: State<AppState>, path: String) -> Option<u32> {
+    let serial = port_serial_number(&state.devices.default_controller(), &path);
     state
-        .controller
+        .preferences
+        .last_baud_for_port(&path, serial.as_deref())
+}
+
+/// USB serial number of a currently listed port
+fn port_serial_number(controller: &Controller, path: &str) -> Option<String> {
+    let ports = controller.list_ports(false).ok()?;
+    preferences::serial_number_for(&ports, path)
+}
+
+/// Get file extensions that can be imported into the workspace
+#[tauri::command]
+pub fn supported_import_formats() -> Vec<String> {
+    crate::workspace::supported_extensions()
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+/// After connecting, warn when laser mode (`$32`) is off, or turn it on
+/// when the user opted in. With laser mode off the laser stays on during
+/// rapids and feed holds. Reading the settings is best effort; a failure
+/// leaves the connection alone.
+fn check_laser_mode(app: &AppHandle, device_id: DeviceId, controller: &Controller, auto: bool) {
+    match controller.laser_mode() {
+        Ok(Some(false)) if auto => {
+            if let Err(e) = controller.ensure_laser_mode() {
+                events::emit_warning(
+                    app,
+                    device_id,
+                    "LASER_MODE_OFF",
+                    format!("Laser mode ($32) is off and couldn't be turned on: {}", e),
+                );
+            }
+        }
+        Ok(Some(false)) => events::emit_warning(
+            app,
+            device_id,
+            "LASER_MODE_OFF",
+            "Laser mode ($32) is off, so the laser stays on during rapids and feed holds. \
+             Turn it on for laser engraving."
+                .into(),
+        ),
+        Ok(_) => {}
+        Err(e) => log::warn!("Failed to check laser mode: {}", e),
+    }
+}
+
+/// Connect to a GRBL device, remembering the baud rate for this port on
+/// success. Returns the id of the device it connected.
+///
+/// Without a `device_id`, the first device that isn't connected is used
+/// (the default device for single-device setups); if every device is in
+/// use, a new one is added.
+#[tauri::command]
+pub fn connect(
+    app: AppHandle,
+    state: State<AppState>,
+    port: String,
+    baud_rate: u32,
+    device_id: Option<DeviceId>,
+) -> CommandResult<DeviceId> {
+    let (id, controller) = match device_id {
+        Some(id) => (id, state.controller(Some(id))?),
+        None => match state.devices.free_device() {
+            Some(device) => device,
+            None => state.add_device(&app),
+        },
+    };
+    controller
         .connect(&port, baud_rate)
+        .map_err(CommandError::from)?;
+    let serial = port_serial_number(&controller, &port);
+    state
+        .preferences
+        .remember_baud(&port, serial.as_deref(), baud_rate);
+    check_laser_mode(&app, id, &controller, state.preferences.auto_laser_mode());
+    Ok(id)
+}
+
+/// Check whether a port answers like a GRBL device, without connecting.
+///
+/// Opens the port on its own, sends a status query and closes it again,
+/// leaving every device's connection as it was. Refused while a device is
+/// connected to the port, or any device is still connecting (its port
+/// isn't known yet), since opening the port would disturb it.
+#[tauri::command]
+pub fn test_port(
+    state: State<AppState>,
+    path: String,
+    baud_rate: u32,
+) -> CommandResult<TestResult> {
+    for (id, controller) in state.devices.all() {
+        let in_use = match controller.connection_state() {
+            ConnectionState::Connected { port, .. } if port == path => {
+                format!("Device {} is connected to {}", id, path)
+            }
+            ConnectionState::Connecting => "A connection attempt is in progress".to_string(),
+            _ => continue,
+        };
+        return Err(ControllerError::InvalidState(in_use).into());
+    }
+    worker::test_port(&path, baud_rate).map_err(|e| ControllerError::from(e).into())
+}
+
+/// Start connecting to a GRBL device without waiting for it.
+///
+/// Returns `Connecting` immediately and emits `connection-state-changed`
+/// with the final state (Connected or Error) once the attempt finishes.
+/// Without a `device_id` the default device connects.
+#[tauri::command]
+pub fn connect_async(
+    app: AppHandle,
+    state: State<AppState>,
+    port: String,
+    baud_rate: u32,
+    device_id: Option<DeviceId>,
+) -> CommandResult<ConnectionState> {
+    let id = device_id.unwrap_or(DEFAULT_DEVICE);
+    let device = state.controller(Some(id))?;
+    let controller = device.clone();
+    let preferences = state.preferences.clone();
+    let path = port.clone();
+    device
+        .connect_async(port, baud_rate, move |result, connection| {
+            if result.is_ok() {
+                let serial = port_serial_number(&controller, &path);
+                preferences.remember_baud(&path, serial.as_deref(), baud_rate);
+            }
+            if let Err(e) = events::emit_for_device(&app, CONNECTION_STATE_EVENT, id, connection) {
+                log::warn!("Failed to emit connection state: {}", e);
+            }
+            if result.is_ok() {
+                check_laser_mode(&app, id, &controller, preferences.auto_laser_mode());
+            }
+        })
         .map_err(CommandError::from)
 }
 
+/// List the registered devices with their connection state
+#[tauri::command]
+pub fn list_devices(state: State<AppState>) -> Vec<DeviceInfo> {
+    state.devices.list()
+}
+
+/// Disconnect and forget a device added by `connect`. The default device
+/// can't be removed.
+#[tauri::command]
+pub fn remove_device(state: State<AppState>, device_id: DeviceId) -> CommandResult<()> {
+    state.devices.remove(device_id).map_err(CommandError::from)
+}
+
 /// Disconnect from the device
 #[tauri::command]
-pub fn disconnect(state: State<AppState>) -> CommandResult<()> {
-    state.controller.disconnect().map_err(CommandError::from)
+pub fn disconnect(state: State<AppState>, device_id: Option<DeviceId>) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .disconnect()
+        .map_err(CommandError::from)
 }
 
 /// Get current connection state
 #[tauri::command]
-pub fn get_connection_state(state: State<AppState>) -> ConnectionState {
-    state.controller.connection_state()
+pub fn get_connection_state(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<ConnectionState> {
+    Ok(state.controller(device_id)?.connection_state())
+}
+
+/// Get this session's connection lifecycle events, oldest first (the
+/// last 200)
+#[tauri::command]
+pub fn get_connection_events(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<Vec<ConnectionEvent>> {
+    Ok(state.controller(device_id)?.connection_events())
 }
 
 /// Check if connected
 #[tauri::command]
-pub fn is_connected(state: State<AppState>) -> bool {
-    state.controller.is_connected()
+pub fn is_connected(state: State<AppState>, device_id: Option<DeviceId>) -> CommandResult<bool> {
+    Ok(state.controller(device_id)?.is_connected())
 }
 
 /// Poll machine status (queries device and returns latest status)
 #[tauri::command]
-pub fn poll_status(state: State<AppState>) -> CommandResult<MachineStatus> {
-    state.controller.poll_status().map_err(CommandError::from)
+pub fn poll_status(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<MachineStatus> {
+    state
+        .controller(device_id)?
+        .poll_status()
+        .map_err(CommandError::from)
 }
 
 /// Get cached status without polling
 #[tauri::command]
-pub fn get_status(state: State<AppState>) -> MachineStatus {
-    state.controller.status()
+pub fn get_status(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<MachineStatus> {
+    Ok(state.controller(device_id)?.status())
+}
+
+/// Get spindle/laser power as a percentage of $30 (null if unknown)
+#[tauri::command]
+pub fn get_spindle_power_percent(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<Option<f64>> {
+    Ok(state.controller(device_id)?.spindle_power_percent())
+}
+
+/// Set the interval of the background heartbeat event
+#[tauri::command]
+pub fn set_heartbeat_interval(state: State<AppState>, interval_ms: u64) {
+    state.heartbeat.set_interval_ms(interval_ms);
+}
+
+/// Set the background status poll rates (Hz) for moving and idle states
+#[tauri::command]
+pub fn set_poll_rates(state: State<AppState>, active_hz: f64, idle_hz: f64) {
+    state.status_stream.set_poll_rates(active_hz, idle_hz);
 }
 
 /// Get full controller snapshot (connection state + status + messages)
 #[tauri::command]
-pub fn get_controller_snapshot(state: State<AppState>) -> ControllerSnapshot {
-    state.controller.snapshot()
+pub fn get_controller_snapshot(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<ControllerSnapshot> {
+    Ok(state.controller(device_id)?.snapshot())
+}
+
+/// Get the most recent controller error with its structured variant (null if none)
+#[tauri::command]
+pub fn get_last_error(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<Option<LastError>> {
+    Ok(state.controller(device_id)?.last_error())
+}
+
+/// Query the G-code parser modal state ($G)
+#[tauri::command]
+pub fn get_parser_state(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<ParserState> {
+    state
+        .controller(device_id)?
+        .read_parser_state()
+        .map_err(CommandError::from)
+}
+
+/// Query the stored coordinate parameters ($#)
+#[tauri::command]
+pub fn get_parameters(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<Parameters> {
+    state
+        .controller(device_id)?
+        .read_parameters()
+        .map_err(CommandError::from)
 }
 
 /// Send home command
+///
+/// Runs off the main thread so `soft_reset` can still be invoked to abort
+/// homing while this call is blocked.
+#[tauri::command(async)]
+pub fn home(state: State<AppState>, device_id: Option<DeviceId>) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .home()
+        .map_err(CommandError::from)
+}
+
+/// Send unlock command; resolves to false if the device stayed in Alarm
 #[tauri::command]
-pub fn home(state: State<AppState>) -> CommandResult<()> {
-    state.controller.home().map_err(CommandError::from)
+pub fn unlock(state: State<AppState>, device_id: Option<DeviceId>) -> CommandResult<bool> {
+    state
+        .controller(device_id)?
+        .unlock()
+        .map_err(CommandError::from)
+}
+
+/// Get the pending alarm with its description (null if none)
+#[tauri::command]
+pub fn get_current_alarm(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<Option<AlarmInfo>> {
+    Ok(state.controller(device_id)?.current_alarm())
+}
+
+/// Restore GRBL defaults with `$RST` (Settings, Parameters, or All).
+///
+/// Wipes device configuration stored in EEPROM; the machine must be Idle.
+#[tauri::command]
+pub fn reset_grbl(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    kind: ResetKind,
+) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .reset_settings(kind)
+        .map_err(CommandError::from)
+}
+
+/// Read `$$` settings from the device with names, descriptions, and units
+#[tauri::command]
+pub fn get_grbl_settings_described(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<Vec<GrblSetting>> {
+    let settings = state
+        .controller(device_id)?
+        .read_settings()
+        .map_err(CommandError::from)?;
+    Ok(settings
+        .iter()
+        .map(|(&number, value)| protocol::describe_setting(number, value))
+        .collect())
+}
+
+/// Read one `$N` setting's raw value, null if the device doesn't have it
+#[tauri::command]
+pub fn get_grbl_setting(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    number: u32,
+) -> CommandResult<Option<String>> {
+    state
+        .controller(device_id)?
+        .read_setting(number)
+        .map_err(CommandError::from)
+}
+
+/// Turn on laser mode (`$32=1`) if it's off. Returns whether it changed.
+#[tauri::command]
+pub fn ensure_laser_mode(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<bool> {
+    state
+        .controller(device_id)?
+        .ensure_laser_mode()
+        .map_err(CommandError::from)
+}
+
+/// Choose the status report fields (`$10`). Returns the fields in effect
+/// once written and read back, with a warning for any the app relies on
+/// that are now left out.
+#[tauri::command]
+pub fn set_report_fields(
+    app: AppHandle,
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    fields: ReportFields,
+) -> CommandResult<ReportFields> {
+    let id = device_id.unwrap_or(DEFAULT_DEVICE);
+    let mask = state.controller(Some(id))?.set_report_mask(fields.mask())?;
+    let fields = ReportFields::from_mask(mask);
+    let missing = fields.missing_required();
+    if !missing.is_empty() {
+        events::emit_warning(
+            &app,
+            id,
+            "REPORT_FIELDS",
+            format!(
+                "Status reports no longer include {} ($10={}); positions and job \
+                 buffering may be wrong until it is turned back on",
+                missing.join(" or "),
+                mask
+            ),
+        );
+    }
+    Ok(fields)
+}
+
+/// Whether laser mode is turned on automatically after connecting
+#[tauri::command]
+pub fn get_auto_laser_mode(state: State<AppState>) -> bool {
+    state.preferences.auto_laser_mode()
+}
+
+/// Opt in or out of turning on laser mode automatically after connecting
+/// (otherwise a `warning` event is emitted when it's off)
+#[tauri::command]
+pub fn set_auto_laser_mode(state: State<AppState>, enabled: bool) {
+    state.preferences.set_auto_laser_mode(enabled);
+}
+
+/// Save the device's `$$` settings to a `.txt` or `.json` file
+#[tauri::command]
+pub fn export_grbl_settings(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    path: String,
+) -> CommandResult<()> {
+    let path = PathBuf::from(path);
+    let format = SettingsFormat::from_path(&path).map_err(settings_file_error)?;
+    let settings = state.controller(device_id)?.read_settings()?;
+    std::fs::write(&path, settings_file::format_settings(&settings, format))
+        .map_err(|e| ControllerError::File(e.to_string()))?;
+    Ok(())
 }
 
-/// Send unlock command
+/// Write settings from a `.txt` or `.json` backup back to the device.
+///
+/// The whole file is validated before anything is written. Returns the
+/// outcome of each setting; rejected ones don't stop the rest.
 #[tauri::command]
-pub fn unlock(state: State<AppState>) -> CommandResult<()> {
-    state.controller.unlock().map_err(CommandError::from)
+pub fn import_grbl_settings(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    path: String,
+) -> CommandResult<Vec<SettingWriteResult>> {
+    let path = PathBuf::from(path);
+    let format = SettingsFormat::from_path(&path).map_err(settings_file_error)?;
+    let contents =
+        std::fs::read_to_string(&path).map_err(|e| ControllerError::File(e.to_string()))?;
+    let settings = settings_file::parse_settings(&contents, format).map_err(settings_file_error)?;
+    Ok(state.controller(device_id)?.write_settings(&settings)?)
+}
+
+fn settings_file_error(e: SettingsFileError) -> CommandError {
+    ControllerError::File(e.to_string()).into()
 }
 
 /// Send jog command
 #[tauri::command]
 pub fn jog(
     state: State<AppState>,
+    device_id: Option<DeviceId>,
     x: Option<f64>,
     y: Option<f64>,
     z: Option<f64>,
@@ -134,77 +572,554 @@ pub fn jog(
     incremental: bool,
 ) -> CommandResult<()> {
     state
-        .controller
+        .controller(device_id)?
         .jog(x, y, z, feed, incremental)
         .map_err(CommandError::from)
 }
 
+/// Jog while a job is paused by feed hold; `cycle_start` returns to
+/// where the job stopped and resumes it
+#[tauri::command]
+pub fn jog_during_hold(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    x: Option<f64>,
+    y: Option<f64>,
+    z: Option<f64>,
+    feed: f64,
+    incremental: bool,
+) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .jog_during_hold(x, y, z, feed, incremental)
+        .map_err(CommandError::from)
+}
+
+/// Jog one step of a stored preset (index into `get_jog_presets`)
+#[tauri::command]
+pub fn jog_preset(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    preset_index: usize,
+    axis: JogAxis,
+    direction: JogDirection,
+) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .jog_preset(preset_index, axis, direction)
+        .map_err(CommandError::from)
+}
+
+/// Get the stored jog presets
+#[tauri::command]
+pub fn get_jog_presets(state: State<AppState>) -> Vec<JogPreset> {
+    state.preferences.jog_presets()
+}
+
+/// Replace the stored jog presets
+#[tauri::command]
+pub fn set_jog_presets(state: State<AppState>, presets: Vec<JogPreset>) -> CommandResult<()> {
+    if let Some(bad) = presets.iter().find(|p| p.step_mm <= 0.0 || p.feed <= 0.0) {
+        return Err(ControllerError::InvalidState(format!(
+            "Jog preset \"{}\" needs a positive step and feed",
+            bad.label
+        ))
+        .into());
+    }
+    for (_, controller) in state.devices.all() {
+        controller.set_jog_presets(presets.clone());
+    }
+    state.preferences.set_jog_presets(presets);
+    Ok(())
+}
+
+/// Whether incremental jogs across machine zero out of the homed travel
+/// are refused
+#[tauri::command]
+pub fn get_jog_travel_check(state: State<AppState>) -> bool {
+    state.preferences.jog_travel_check()
+}
+
+/// Turn the jog travel check on or off (off for machines that travel both
+/// ways from machine zero)
+#[tauri::command]
+pub fn set_jog_travel_check(state: State<AppState>, enabled: bool) {
+    for (_, controller) in state.devices.all() {
+        controller.set_jog_travel_check(enabled);
+    }
+    state.preferences.set_jog_travel_check(enabled);
+}
+
+/// Get the lowest feed accepted for jogs and frames (units/min)
+#[tauri::command]
+pub fn get_min_feed(state: State<AppState>) -> f64 {
+    state.preferences.min_feed()
+}
+
+/// Set the lowest feed accepted for jogs and frames (units/min)
+#[tauri::command]
+pub fn set_min_feed(state: State<AppState>, min_feed: f64) -> CommandResult<()> {
+    if !(min_feed.is_finite() && min_feed >= 0.0) {
+        return Err(ControllerError::InvalidState(format!(
+            "Minimum feed must be zero or more, got {}",
+            min_feed
+        ))
+        .into());
+    }
+    for (_, controller) in state.devices.all() {
+        controller.set_min_feed(min_feed);
+    }
+    state.preferences.set_min_feed(min_feed);
+    Ok(())
+}
+
+/// Get the line ending sent after each command
+#[tauri::command]
+pub fn get_line_ending(state: State<AppState>) -> LineEnding {
+    state.preferences.line_ending()
+}
+
+/// Set the line ending sent after each command (`Lf` for stock GRBL)
+#[tauri::command]
+pub fn set_line_ending(state: State<AppState>, line_ending: LineEnding) -> CommandResult<()> {
+    for (_, controller) in state.devices.all() {
+        controller.set_line_ending(line_ending)?;
+    }
+    state.preferences.set_line_ending(line_ending);
+    Ok(())
+}
+
+/// Get the serial log options
+#[tauri::command]
+pub fn get_serial_log_settings(state: State<AppState>) -> SerialLogSettings {
+    state.devices.default_controller().serial_log().settings()
+}
+
+/// Enable or disable the serial log and set its rotation size
+#[tauri::command]
+pub fn set_serial_log_settings(state: State<AppState>, settings: SerialLogSettings) {
+    for (_, controller) in state.devices.all() {
+        controller.serial_log().set_settings(settings);
+    }
+    let serial_log = state.devices.default_controller().serial_log();
+    state.preferences.set_serial_log(serial_log.settings());
+}
+
+/// List serial log files (current and rotated), newest first
+#[tauri::command]
+pub fn get_log_files(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<Vec<LogFileInfo>> {
+    Ok(state.controller(device_id)?.serial_log().log_files())
+}
+
+/// Read the last `lines` lines of a serial log file.
+///
+/// Only files in the log directory can be read.
+#[tauri::command]
+pub fn read_log_tail(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    path: String,
+    lines: usize,
+) -> CommandResult<Vec<String>> {
+    let file_error = |e: std::io::Error| ControllerError::File(e.to_string());
+    let path = PathBuf::from(path).canonicalize().map_err(file_error)?;
+    let in_log_dir = state
+        .controller(device_id)?
+        .serial_log()
+        .directory()
+        .and_then(|dir| dir.canonicalize().ok())
+        .is_some_and(|dir| path.parent() == Some(dir.as_path()));
+    if !in_log_dir || !serial_log::is_log_file(&path) {
+        return Err(ControllerError::File(format!("Not a serial log: {}", path.display())).into());
+    }
+    Ok(serial_log::read_tail(&path, lines).map_err(file_error)?)
+}
+
 /// Cancel active jog
 #[tauri::command]
-pub fn jog_cancel(state: State<AppState>) -> CommandResult<()> {
-    state.controller.jog_cancel().map_err(CommandError::from)
+pub fn jog_cancel(state: State<AppState>, device_id: Option<DeviceId>) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .jog_cancel()
+        .map_err(CommandError::from)
 }
 
 /// Send feed hold (pause)
 #[tauri::command]
-pub fn feed_hold(state: State<AppState>) -> CommandResult<()> {
-    state.controller.feed_hold().map_err(CommandError::from)
+pub fn feed_hold(state: State<AppState>, device_id: Option<DeviceId>) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .feed_hold()
+        .map_err(CommandError::from)
 }
 
 /// Send cycle start (resume)
 #[tauri::command]
-pub fn cycle_start(state: State<AppState>) -> CommandResult<()> {
-    state.controller.cycle_start().map_err(CommandError::from)
+pub fn cycle_start(state: State<AppState>, device_id: Option<DeviceId>) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .cycle_start()
+        .map_err(CommandError::from)
 }
 
 /// Send soft reset
 #[tauri::command]
-pub fn soft_reset(state: State<AppState>) -> CommandResult<()> {
-    state.controller.soft_reset().map_err(CommandError::from)
+pub fn soft_reset(state: State<AppState>, device_id: Option<DeviceId>) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .soft_reset()
+        .map_err(CommandError::from)
+}
+
+/// Stop any jog, job or step session and soft reset to a known state
+#[tauri::command]
+pub fn stop_all(state: State<AppState>, device_id: Option<DeviceId>) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .stop_all()
+        .map_err(CommandError::from)
+}
+
+/// Abort the command currently waiting for a response
+#[tauri::command]
+pub fn abort_current_command(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<bool> {
+    Ok(state.controller(device_id)?.abort_current_command())
+}
+
+/// Recover from a fault (soft reset, re-read device info, clear alarms)
+#[tauri::command]
+pub fn recover_device(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    rehome: bool,
+) -> CommandResult<RecoverySummary> {
+    state
+        .controller(device_id)?
+        .recover(rehome)
+        .map_err(CommandError::from)
 }
 
 /// Adjust feed rate override
 #[tauri::command]
-pub fn feed_override(state: State<AppState>, adjust: OverrideAdjust) -> CommandResult<()> {
+pub fn feed_override(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    adjust: OverrideAdjust,
+) -> CommandResult<()> {
     state
-        .controller
+        .controller(device_id)?
         .feed_override(adjust)
         .map_err(CommandError::from)
 }
 
 /// Set rapid override preset
 #[tauri::command]
-pub fn rapid_override(state: State<AppState>, preset: RapidOverride) -> CommandResult<()> {
+pub fn rapid_override(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    preset: RapidOverride,
+) -> CommandResult<()> {
     state
-        .controller
+        .controller(device_id)?
         .rapid_override(preset)
         .map_err(CommandError::from)
 }
 
 /// Adjust spindle/laser power override
 #[tauri::command]
-pub fn spindle_override(state: State<AppState>, adjust: OverrideAdjust) -> CommandResult<()> {
+pub fn spindle_override(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    adjust: OverrideAdjust,
+) -> CommandResult<()> {
     state
-        .controller
+        .controller(device_id)?
         .spindle_override(adjust)
         .map_err(CommandError::from)
 }
 
-/// Run a frame/boundary trace
-#[tauri::command]
+/// Run a frame/boundary trace (see [`FrameRequest`])
+///
+/// Runs off the main thread and returns when the trace finishes, emitting a
+/// `frame-progress` event as each edge completes. Live position comes from
+/// the status stream meanwhile.
+#[tauri::command(async)]
 pub fn run_frame(
+    app: AppHandle,
     state: State<AppState>,
-    x_min: f64,
-    x_max: f64,
-    y_min: f64,
-    y_max: f64,
-    feed: f64,
-    power: u32,
+    device_id: Option<DeviceId>,
+    frame: FrameRequest,
+) -> CommandResult<()> {
+    let id = device_id.unwrap_or(DEFAULT_DEVICE);
+    let emit_progress = |progress| {
+        if let Err(e) = events::emit_for_device(&app, FRAME_PROGRESS_EVENT, id, progress) {
+            log::warn!("Failed to emit frame progress: {}", e);
+        }
+    };
+    state
+        .controller(device_id)?
+        .run_frame(frame, emit_progress)
+        .map_err(CommandError::from)
+}
+
+/// Move to the work coordinate origin
+#[tauri::command]
+pub fn go_to_zero(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    feed: Option<f64>,
+) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .go_to_work_zero(feed)
+        .map_err(CommandError::from)
+}
+
+/// Move to the machine coordinate origin
+#[tauri::command]
+pub fn go_to_machine_zero(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    feed: Option<f64>,
+) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .go_to_machine_zero(feed)
+        .map_err(CommandError::from)
+}
+
+/// Store the current machine position as the G28 position, returning it
+#[tauri::command]
+pub fn set_g28_position(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<Position> {
+    state
+        .controller(device_id)?
+        .set_g28_position()
+        .map_err(CommandError::from)
+}
+
+/// Store the current machine position as the G30 position, returning it
+#[tauri::command]
+pub fn set_g30_position(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<Position> {
+    state
+        .controller(device_id)?
+        .set_g30_position()
+        .map_err(CommandError::from)
+}
+
+/// Move to the stored G28 position (Z raised to the safe height first)
+#[tauri::command]
+pub fn go_to_g28(state: State<AppState>, device_id: Option<DeviceId>) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .go_to_g28()
+        .map_err(CommandError::from)
+}
+
+/// Move to the stored G30 position (Z raised to the safe height first)
+#[tauri::command]
+pub fn go_to_g30(state: State<AppState>, device_id: Option<DeviceId>) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .go_to_g30()
+        .map_err(CommandError::from)
+}
+
+/// Move to the park position set in workspace settings (machine coordinates)
+#[tauri::command]
+pub fn park(state: State<AppState>, device_id: Option<DeviceId>) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .park()
+        .map_err(CommandError::from)
+}
+
+/// Rapid to absolute coordinates in `units` (work coordinates unless
+/// `machine_coords`)
+#[tauri::command]
+pub fn rapid_move(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    x: Option<f64>,
+    y: Option<f64>,
+    z: Option<f64>,
+    machine_coords: bool,
     units: Units,
-    mode: FrameMode,
 ) -> CommandResult<()> {
     state
-        .controller
-        .run_frame(x_min, x_max, y_min, y_max, feed, power, units, mode)
+        .controller(device_id)?
+        .rapid_move(x, y, z, machine_coords, units)
+        .map_err(CommandError::from)
+}
+
+/// Emit a `job-line-error` event for each line GRBL rejects
+fn emit_line_errors(
+    app: AppHandle,
+    device_id: Option<DeviceId>,
+) -> impl FnMut(&StreamLineError) + Send + 'static {
+    let id = device_id.unwrap_or(DEFAULT_DEVICE);
+    move |error| {
+        if let Err(e) = events::emit_for_device(&app, JOB_LINE_ERROR_EVENT, id, error.clone()) {
+            log::warn!("Failed to emit job line error: {}", e);
+        }
+    }
+}
+
+/// Stream an in-memory G-code program
+///
+/// Stops at the first `error:N` unless `continue_on_error` is set; either
+/// way a `job-line-error` event is emitted for each rejected line.
+#[tauri::command]
+pub fn run_gcode_program(
+    app: AppHandle,
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    lines: Vec<String>,
+    strip_comments: bool,
+    continue_on_error: Option<bool>,
+) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .run_gcode_program(
+            lines,
+            strip_comments,
+            continue_on_error.unwrap_or(false),
+            emit_line_errors(app, device_id),
+        )
+        .map_err(CommandError::from)
+}
+
+/// Stream a G-code file from disk without loading it into memory
+///
+/// Errors are handled as in `run_gcode_program`.
+#[tauri::command]
+pub fn run_gcode_file(
+    app: AppHandle,
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    path: String,
+    strip_comments: bool,
+    continue_on_error: Option<bool>,
+) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .run_gcode_file(
+            &PathBuf::from(path),
+            strip_comments,
+            continue_on_error.unwrap_or(false),
+            emit_line_errors(app, device_id),
+        )
         .map_err(CommandError::from)
 }
+
+/// Check a program for obvious mistakes before streaming it, without a
+/// device round-trip. Warnings only; GRBL's `$C` check mode has the final say.
+#[tauri::command]
+pub fn validate_gcode(lines: Vec<String>) -> Vec<Diagnostic> {
+    gcode::validate(&lines)
+}
+
+/// Re-home and resume a program interrupted by power loss from a line
+/// index (e.g. shortly before the last job's `last_acked_line`)
+#[tauri::command(async)]
+pub fn resume_from_line(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    lines: Vec<String>,
+    start_index: usize,
+    strip_comments: bool,
+) -> CommandResult<()> {
+    state
+        .controller(device_id)?
+        .resume_from_line(lines, start_index, strip_comments)
+        .map_err(CommandError::from)
+}
+
+/// Emit the step session position, or just the device id once step mode
+/// has ended
+fn emit_step(app: &AppHandle, device_id: Option<DeviceId>, status: Option<StepStatus>) {
+    let id = device_id.unwrap_or(DEFAULT_DEVICE);
+    if let Err(e) = events::emit_for_device(app, STEP_EVENT, id, status) {
+        log::warn!("Failed to emit step status: {}", e);
+    }
+}
+
+/// Enter step mode for a program, sending nothing until `step_next`
+#[tauri::command]
+pub fn step_mode_begin(
+    app: AppHandle,
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+    lines: Vec<String>,
+) -> CommandResult<StepStatus> {
+    let status = state.controller(device_id)?.step_mode_begin(lines)?;
+    emit_step(&app, device_id, Some(status.clone()));
+    Ok(status)
+}
+
+/// Send the next line in step mode and wait for GRBL to accept it
+///
+/// A `step-changed` event follows each step, including one GRBL rejected.
+#[tauri::command(async)]
+pub fn step_next(
+    app: AppHandle,
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<StepStatus> {
+    let controller = state.controller(device_id)?;
+    let result = controller.step_next();
+    emit_step(&app, device_id, controller.step_status());
+    result.map_err(CommandError::from)
+}
+
+/// Leave step mode
+#[tauri::command]
+pub fn step_abort(
+    app: AppHandle,
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<()> {
+    state.controller(device_id)?.step_abort();
+    emit_step(&app, device_id, None);
+    Ok(())
+}
+
+/// Get progress of the current (or last) streamed program
+#[tauri::command]
+pub fn get_job_progress(
+    state: State<AppState>,
+    device_id: Option<DeviceId>,
+) -> CommandResult<Option<StreamProgress>> {
+    Ok(state.controller(device_id)?.job_progress())
+}
+
+/// Total laser-on time and distance over all jobs, for maintenance
+#[tauri::command]
+pub fn get_usage_stats(state: State<AppState>) -> UsageStats {
+    state.usage.stats()
+}
+
+/// Zero the usage totals (e.g. after replacing the diode or tube)
+#[tauri::command]
+pub fn reset_usage_stats(state: State<AppState>) {
+    state.usage.reset();
+}
+
+/// Set the smoothing factor (0.01..=1) for streamed job time estimates
+#[tauri::command]
+pub fn set_eta_smoothing(state: State<AppState>, smoothing: f64) {
+    for (_, controller) in state.devices.all() {
+        controller.set_rate_smoothing(smoothing);
+    }
+}
@@ -0,0 +1,244 @@
+//! Background event emitters pushed to the frontend.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::grbl::{Controller, ControllerRegistry, DeviceId, MachineState};
+
+/// Event name for connection heartbeats
+pub const HEARTBEAT_EVENT: &str = "heartbeat";
+
+/// Default heartbeat interval
+pub const DEFAULT_HEARTBEAT_INTERVAL_MS: u64 = 2000;
+
+/// Lower bound so a bad setting can't flood the frontend
+const MIN_HEARTBEAT_INTERVAL_MS: u64 = 250;
+
+/// Event name for the outcome of a non-blocking connect
+pub const CONNECTION_STATE_EVENT: &str = "connection-state-changed";
+
+/// Event name for per-edge frame trace progress
+pub const FRAME_PROGRESS_EVENT: &str = "frame-progress";
+
+/// Event name for a streamed job stopped by a GRBL error
+pub const JOB_ERROR_EVENT: &str = "job-error";
+
+/// Event name for a streamed job the machine has finished running
+pub const JOB_COMPLETE_EVENT: &str = "job-complete";
+
+/// Event name for each line GRBL rejects while streaming
+pub const JOB_LINE_ERROR_EVENT: &str = "job-line-error";
+
+/// Event name for step mode moving to another line (just the device id
+/// once it ends)
+pub const STEP_EVENT: &str = "step-changed";
+
+/// Event name for status snapshots
+pub const STATUS_EVENT: &str = "status";
+
+/// Event name for override percentages changing in a status report
+pub const OVERRIDE_CHANGED_EVENT: &str = "override-changed";
+
+/// Event name for conditions the user should know about that don't stop
+/// anything (e.g. laser mode off after connecting)
+pub const WARNING_EVENT: &str = "warning";
+
+/// Event name for each connection lifecycle step (attempt, connected,
+/// welcome, disconnect, loss)
+pub const CONNECTION_EVENT: &str = "connection-event";
+
+/// Default poll rate while the machine is moving
+pub const DEFAULT_ACTIVE_POLL_HZ: f64 = 5.0;
+
+/// Default poll rate while the machine is idle or asleep
+pub const DEFAULT_IDLE_POLL_HZ: f64 = 1.0;
+
+/// Poll rate limits (GRBL recommends no more than ~5-10Hz)
+const MIN_POLL_HZ: f64 = 0.2;
+const MAX_POLL_HZ: f64 = 20.0;
+
+/// How often to check for a connection while disconnected
+const DISCONNECTED_CHECK_MS: u64 = 250;
+
+/// Payload of a per-device event: the device's id alongside the fields
+/// of the payload itself
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DeviceEvent<T> {
+    pub device_id: DeviceId,
+    #[serde(flatten)]
+    pub payload: T,
+}
+
+/// Emit `payload` tagged with the device it came from. Its fields sit
+/// beside `device_id`, so it must serialize as a struct, map or enum (an
+/// Option of one adds nothing when None).
+pub fn emit_for_device<T: serde::Serialize + Clone>(
+    app: &AppHandle,
+    event: &str,
+    device_id: DeviceId,
+    payload: T,
+) -> tauri::Result<()> {
+    app.emit(event, DeviceEvent { device_id, payload })
+}
+
+/// Payload of a `warning` event
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Warning {
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Emit a `warning` event for a device, logging it too
+pub fn emit_warning(app: &AppHandle, device_id: DeviceId, code: &'static str, message: String) {
+    log::warn!("Device {}: {}", device_id, message);
+    if let Err(e) = emit_for_device(app, WARNING_EVENT, device_id, Warning { code, message }) {
+        log::warn!("Failed to emit warning: {}", e);
+    }
+}
+
+/// Emits a low-rate `heartbeat` event per device with connection liveness.
+///
+/// Runs for the lifetime of the app, including while disconnected, so the
+/// UI can tell the backend is alive even when no device is attached.
+pub struct HeartbeatEmitter {
+    interval_ms: AtomicU64,
+}
+
+impl HeartbeatEmitter {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            interval_ms: AtomicU64::new(DEFAULT_HEARTBEAT_INTERVAL_MS),
+        })
+    }
+
+    /// Set the heartbeat interval (clamped to a sane minimum).
+    pub fn set_interval_ms(&self, interval_ms: u64) {
+        self.interval_ms.store(
+            interval_ms.max(MIN_HEARTBEAT_INTERVAL_MS),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Start the heartbeat thread, covering devices as they're added.
+    pub fn spawn(self: &Arc<Self>, app: AppHandle, devices: Arc<ControllerRegistry>) {
+        let emitter = self.clone();
+        thread::Builder::new()
+            .name("heartbeat".into())
+            .spawn(move || loop {
+                for (id, controller) in devices.all() {
+                    let heartbeat = controller.heartbeat();
+                    if let Err(e) = emit_for_device(&app, HEARTBEAT_EVENT, id, heartbeat) {
+                        log::warn!("Failed to emit heartbeat: {}", e);
+                    }
+                }
+                let interval = emitter.interval_ms.load(Ordering::Relaxed);
+                thread::sleep(Duration::from_millis(interval));
+            })
+            .expect("Failed to spawn heartbeat thread");
+    }
+}
+
+/// Polls a device's status and emits `status` snapshots while connected,
+/// plus `override-changed` when a report shows new override percentages.
+/// Connection lifecycle events go out as `connection-event`, connected or
+/// not.
+///
+/// Polls fast while the machine is moving (Run/Jog/Home) and slowly while
+/// Idle/Sleep, based on the last parsed state. Other states (Hold, Alarm,
+/// Door, ...) use the active rate so the UI reacts promptly when they clear.
+pub struct StatusStream {
+    active_interval_ms: AtomicU64,
+    idle_interval_ms: AtomicU64,
+}
+
+impl StatusStream {
+    pub fn new() -> Arc<Self> {
+        let stream = Arc::new(Self {
+            active_interval_ms: AtomicU64::new(0),
+            idle_interval_ms: AtomicU64::new(0),
+        });
+        stream.set_poll_rates(DEFAULT_ACTIVE_POLL_HZ, DEFAULT_IDLE_POLL_HZ);
+        stream
+    }
+
+    /// Set the active and idle poll rates in Hz (clamped to sane limits).
+    pub fn set_poll_rates(&self, active_hz: f64, idle_hz: f64) {
+        self.active_interval_ms
+            .store(hz_to_interval_ms(active_hz), Ordering::Relaxed);
+        self.idle_interval_ms
+            .store(hz_to_interval_ms(idle_hz), Ordering::Relaxed);
+    }
+
+    /// Poll interval for the given machine state
+    fn interval_for(&self, state: MachineState) -> Duration {
+        let interval = match state {
+            MachineState::Idle | MachineState::Sleep => &self.idle_interval_ms,
+            _ => &self.active_interval_ms,
+        };
+        Duration::from_millis(interval.load(Ordering::Relaxed))
+    }
+
+    /// Start the status polling thread for a device. The thread ends once
+    /// the device is removed from the registry.
+    pub fn spawn(
+        self: &Arc<Self>,
+        app: AppHandle,
+        device_id: DeviceId,
+        controller: &Arc<Controller>,
+    ) {
+        let stream = self.clone();
+        let device: Weak<Controller> = Arc::downgrade(controller);
+        thread::Builder::new()
+            .name(format!("status-stream-{}", device_id))
+            .spawn(move || loop {
+                let Some(controller) = device.upgrade() else {
+                    log::debug!("Status stream for device {} stopped", device_id);
+                    return;
+                };
+                for event in controller.take_new_connection_events() {
+                    if let Err(e) = emit_for_device(&app, CONNECTION_EVENT, device_id, event) {
+                        log::warn!("Failed to emit connection event: {}", e);
+                    }
+                }
+                if !controller.is_connected() {
+                    drop(controller);
+                    thread::sleep(Duration::from_millis(DISCONNECTED_CHECK_MS));
+                    continue;
+                }
+
+                // Failures are expected around disconnects; the snapshot
+                // still carries the stale flag and connection state
+                if let Err(e) = controller.poll_status() {
+                    log::debug!("Status poll failed: {}", e);
+                }
+                if let Some(overrides) = controller.take_override_change() {
+                    if let Err(e) =
+                        emit_for_device(&app, OVERRIDE_CHANGED_EVENT, device_id, overrides)
+                    {
+                        log::warn!("Failed to emit override change: {}", e);
+                    }
+                }
+                if let Some(job) = controller.take_job_complete() {
+                    if let Err(e) = emit_for_device(&app, JOB_COMPLETE_EVENT, device_id, job) {
+                        log::warn!("Failed to emit job completion: {}", e);
+                    }
+                }
+                let snapshot = controller.snapshot();
+                drop(controller);
+                let interval = stream.interval_for(snapshot.status.state);
+                if let Err(e) = emit_for_device(&app, STATUS_EVENT, device_id, snapshot) {
+                    log::warn!("Failed to emit status: {}", e);
+                }
+                thread::sleep(interval);
+            })
+            .expect("Failed to spawn status stream thread");
+    }
+}
+
+fn hz_to_interval_ms(hz: f64) -> u64 {
+    let hz = if hz.is_finite() { hz } else { MIN_POLL_HZ };
+    (1000.0 / hz.clamp(MIN_POLL_HZ, MAX_POLL_HZ)).round() as u64
+}
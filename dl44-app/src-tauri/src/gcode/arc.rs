@@ -0,0 +1,156 @@
+//! Circular arcs of G2/G3 moves.
+//!
+//! Arcs run in the XY plane (G17) from the current position to the target,
+//! around a center given either as I/J offsets from the start or as a
+//! radius R (negative for the arc longer than a half circle). G2 turns
+//! clockwise and G3 counterclockwise, seen from +Z with Y up; coinciding
+//! endpoints with I/J offsets make a full circle. Arcs are flattened to
+//! line segments for bounds and measured exactly for lengths.
+
+use std::f64::consts::TAU;
+
+use super::path::{Point, Polyline};
+
+/// Maximum segments used for a single arc
+const MAX_ARC_SEGMENTS: usize = 256;
+
+/// How a G2/G3 move gives its center
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArcCenter {
+    /// I/J offsets of the center from the start point
+    Offset(f64, f64),
+    /// Radius; negative selects the arc longer than a half circle
+    Radius(f64),
+}
+
+/// A G2/G3 move resolved to its center and swept angle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arc {
+    pub from: Point,
+    pub to: Point,
+    pub center: Point,
+    /// Signed angle swept from `from` to `to` (radians, negative =
+    /// clockwise)
+    pub sweep: f64,
+}
+
+impl Arc {
+    /// Arc of a G2 (`clockwise`) or G3 move. None when an R arc has no
+    /// center (zero radius or coinciding endpoints).
+    pub fn new(from: Point, to: Point, center: ArcCenter, clockwise: bool) -> Option<Self> {
+        let center = match center {
+            ArcCenter::Offset(i, j) => (from.0 + i, from.1 + j),
+            ArcCenter::Radius(r) => {
+                let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+                let chord = dx.hypot(dy);
+                if chord < f64::EPSILON || r == 0.0 {
+                    return None;
+                }
+                // Center sits off the chord midpoint, to the right of the
+                // chord for a clockwise arc under a half circle. A radius
+                // too short to span the chord puts it on the midpoint.
+                let mut offset = (r * r - chord * chord / 4.0).max(0.0).sqrt();
+                if clockwise == (r < 0.0) {
+                    offset = -offset;
+                }
+                (
+                    (from.0 + to.0) / 2.0 + offset * dy / chord,
+                    (from.1 + to.1) / 2.0 - offset * dx / chord,
+                )
+            }
+        };
+
+        let angle = |p: Point| (p.1 - center.1).atan2(p.0 - center.0);
+        let mut sweep = angle(to) - angle(from);
+        if clockwise && sweep >= 0.0 {
+            sweep -= TAU;
+        } else if !clockwise && sweep <= 0.0 {
+            sweep += TAU;
+        }
+        Some(Self {
+            from,
+            to,
+            center,
+            sweep,
+        })
+    }
+
+    pub fn radius(&self) -> f64 {
+        (self.from.0 - self.center.0).hypot(self.from.1 - self.center.1)
+    }
+
+    /// Length along the arc (XY only)
+    pub fn length(&self) -> f64 {
+        self.radius() * self.sweep.abs()
+    }
+
+    /// Points along the arc within `tolerance` of it, excluding the start
+    /// and ending exactly on the target
+    pub fn flatten(&self, tolerance: f64) -> Polyline {
+        let r = self.radius();
+        // Segment angle that keeps the chord within tolerance of the arc
+        let max_step = 2.0 * (1.0 - (tolerance / r).min(1.0)).acos();
+        let n = if max_step > 0.0 {
+            ((self.sweep.abs() / max_step).ceil() as usize).clamp(1, MAX_ARC_SEGMENTS)
+        } else {
+            MAX_ARC_SEGMENTS
+        };
+
+        let start = (self.from.1 - self.center.1).atan2(self.from.0 - self.center.0);
+        let mut out: Polyline = (1..n)
+            .map(|i| {
+                let (sin, cos) = (start + self.sweep * i as f64 / n as f64).sin_cos();
+                (self.center.0 + r * cos, self.center.1 + r * sin)
+            })
+            .collect();
+        out.push(self.to);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn test_clockwise_arc_with_offsets() {
+        // G2 X10 Y0 I5 J0 from the origin: half circle over the top
+        let arc = Arc::new((0.0, 0.0), (10.0, 0.0), ArcCenter::Offset(5.0, 0.0), true).unwrap();
+        assert_eq!(arc.center, (5.0, 0.0));
+        assert!(close(arc.sweep, -PI));
+        assert!(close(arc.length(), 5.0 * PI));
+
+        let points = arc.flatten(0.01);
+        assert_eq!(points.last(), Some(&(10.0, 0.0)));
+        let top = points.iter().map(|p| p.1).fold(f64::MIN, f64::max);
+        assert!((top - 5.0).abs() < 0.01, "{}", top);
+        assert!(points.iter().all(|p| close((p.0 - 5.0).hypot(p.1), 5.0)));
+    }
+
+    #[test]
+    fn test_radius_arcs_and_full_circle() {
+        // Quarter circle each way, then the three-quarter arc via negative R
+        let cw = Arc::new((0.0, 0.0), (5.0, 5.0), ArcCenter::Radius(5.0), true).unwrap();
+        assert!(close(cw.center.0, 5.0) && close(cw.center.1, 0.0));
+        assert!(close(cw.length(), 2.5 * PI));
+
+        let ccw = Arc::new((0.0, 0.0), (5.0, 5.0), ArcCenter::Radius(5.0), false).unwrap();
+        assert!(close(ccw.center.0, 0.0) && close(ccw.center.1, 5.0));
+
+        let major = Arc::new((0.0, 0.0), (5.0, 5.0), ArcCenter::Radius(-5.0), true).unwrap();
+        assert!(close(major.center.0, 0.0) && close(major.center.1, 5.0));
+        assert!(close(major.sweep, -1.5 * PI));
+
+        let circle = Arc::new((0.0, 0.0), (0.0, 0.0), ArcCenter::Offset(0.0, 2.0), false).unwrap();
+        assert!(close(circle.length(), 4.0 * PI));
+        assert_eq!(
+            Arc::new((1.0, 1.0), (1.0, 1.0), ArcCenter::Radius(2.0), true),
+            None
+        );
+    }
+}
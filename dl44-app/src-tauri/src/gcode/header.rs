@@ -0,0 +1,278 @@
+//! Descriptive header for exported programs.
+//!
+//! Exported G-code can start with `( ... )` comment lines naming the app
+//! version, workspace, generation time and bed size, followed by a summary
+//! of each operation, so files can be identified later. GRBL ignores
+//! comments and the streamer skips full-line comments without sending them.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::arc::{Arc, ArcCenter};
+use super::job::LineSources;
+use crate::workspace::document::{CutMode, DocumentList};
+
+/// Summary of one document's part of a program
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationSummary {
+    pub name: String,
+    pub mode: CutMode,
+    pub passes: u32,
+    /// G-code lines generated for the document
+    pub lines: usize,
+    /// Length of laser-on moves over all passes (mm)
+    pub burn_length: f64,
+}
+
+/// Metadata rendered as comments at the top of an exported program
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcodeHeaderInfo {
+    pub app_version: String,
+    /// Workspace file name, None while unsaved
+    pub workspace: Option<String>,
+    /// Generation time (seconds since the Unix epoch)
+    pub generated_at: u64,
+    /// Bed size (mm)
+    pub bed_width: f64,
+    pub bed_height: f64,
+    /// Operations in program order
+    pub operations: Vec<OperationSummary>,
+}
+
+impl GcodeHeaderInfo {
+    /// Header for a program just generated from `documents`, with
+    /// `sources` mapping its lines back to them.
+    pub fn new(
+        lines: &[String],
+        sources: &LineSources,
+        documents: &DocumentList,
+        workspace: Option<String>,
+        bed_width: f64,
+        bed_height: f64,
+    ) -> Self {
+        let mut operations: Vec<(_, OperationSummary)> = Vec::new();
+        let burn = burn_lengths(lines);
+        for (index, burn) in burn.into_iter().enumerate() {
+            let Some(id) = sources.document_at(index) else {
+                continue;
+            };
+            if operations.last().map(|(last, _)| *last) != Some(id) {
+                let Some(doc) = documents.get(id) else {
+                    continue;
+                };
+                let settings = doc.effective_cut_settings();
+                operations.push((
+                    id,
+                    OperationSummary {
+                        name: doc.name.clone(),
+                        mode: settings.mode,
+                        passes: settings.passes,
+                        lines: 0,
+                        burn_length: 0.0,
+                    },
+                ));
+            }
+            if let Some((_, op)) = operations.last_mut() {
+                op.lines += 1;
+                op.burn_length += burn;
+            }
+        }
+
+        Self {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            workspace,
+            generated_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            bed_width,
+            bed_height,
+            operations: operations.into_iter().map(|(_, op)| op).collect(),
+        }
+    }
+
+    /// Header as `( ... )` comment lines
+    pub fn render(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("DL-44 {}", self.app_version),
+            format!(
+                "Workspace: {}",
+                self.workspace.as_deref().unwrap_or("unsaved")
+            ),
+            format!("Generated: {}", format_utc(self.generated_at)),
+            format!("Bed: {} x {} mm", self.bed_width, self.bed_height),
+            format!(
+                "Operations: {}, {:.1} mm burn length",
+                self.operations.len(),
+                self.operations.iter().map(|op| op.burn_length).sum::<f64>()
+            ),
+        ];
+        for (i, op) in self.operations.iter().enumerate() {
+            lines.push(format!(
+                "{}. {} - {:?}, {} pass{}, {} lines, {:.1} mm",
+                i + 1,
+                op.name,
+                op.mode,
+                op.passes,
+                if op.passes == 1 { "" } else { "es" },
+                op.lines,
+                op.burn_length
+            ));
+        }
+        lines.into_iter().map(|text| comment(&text)).collect()
+    }
+}
+
+/// Wrap text in a `( ... )` comment. GRBL comments don't nest, so
+/// parentheses in the text become brackets.
+fn comment(text: &str) -> String {
+    format!("({})", text.replace('(', "[").replace(')', "]"))
+}
+
+/// Laser-on move length of each line (mm), following the modal motion
+/// mode, position and power through the program. G2/G3 arcs are measured
+/// along the arc.
+fn burn_lengths(lines: &[String]) -> Vec<f64> {
+    let (mut x, mut y) = (0.0, 0.0);
+    let (mut motion, mut power) = ("G0", 0.0);
+    lines
+        .iter()
+        .map(|line| {
+            let (mut nx, mut ny) = (x, y);
+            let (mut offset, mut radius) = ((0.0, 0.0), None);
+            for word in line.split_whitespace() {
+                let value = word.get(1..).and_then(|v| v.parse::<f64>().ok());
+                match (word.chars().next(), value) {
+                    (Some('G'), _) if matches!(word, "G0" | "G1" | "G2" | "G3") => motion = word,
+                    (Some('X'), Some(v)) => nx = v,
+                    (Some('Y'), Some(v)) => ny = v,
+                    (Some('I'), Some(v)) => offset.0 = v,
+                    (Some('J'), Some(v)) => offset.1 = v,
+                    (Some('R'), Some(v)) => radius = Some(v),
+                    (Some('S'), Some(v)) => power = v,
+                    _ => {}
+                }
+            }
+            let length = match motion {
+                _ if power <= 0.0 => 0.0,
+                "G1" => (nx - x).hypot(ny - y),
+                "G2" | "G3" => {
+                    let center =
+                        radius.map_or(ArcCenter::Offset(offset.0, offset.1), ArcCenter::Radius);
+                    Arc::new((x, y), (nx, ny), center, motion == "G2")
+                        .map_or(0.0, |arc| arc.length())
+                }
+                _ => 0.0,
+            };
+            (x, y) = (nx, ny);
+            length
+        })
+        .collect()
+}
+
+/// Format seconds since the Unix epoch as an ISO 8601 UTC timestamp
+fn format_utc(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let time = secs % 86_400;
+
+    // Civil date from days since 1970-01-01 (proleptic Gregorian)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gcode::{generate_job, RasterParams};
+    use crate::workspace::document::{
+        BoundingBox, CutSettings, Document, DocumentKind, SvgContent, Transform,
+    };
+
+    #[test]
+    fn test_header_summarizes_operations() {
+        let mut list = DocumentList::new();
+        list.add(Document {
+            id: 0,
+            name: "Outline (v2)".into(),
+            source_path: None,
+            kind: DocumentKind::Svg(SvgContent {
+                width: 10.0,
+                height: 10.0,
+                paths: Vec::new(),
+                raw_svg:
+                    r#"<svg viewBox="0 0 10 10"><rect x="1" y="1" width="2" height="2"/></svg>"#
+                        .into(),
+            }),
+            transform: Transform::default(),
+            visible: true,
+            locked: false,
+            original_bounds: BoundingBox::new(0.0, 0.0, 10.0, 10.0),
+            cut_settings: Some(CutSettings {
+                feed: 500.0,
+                power: 1000,
+                passes: 2,
+                mode: CutMode::Cut,
+            }),
+        });
+
+        let (lines, sources) =
+            generate_job(&list, &RasterParams::default(), &[], None, 1000).unwrap();
+        let mut header = GcodeHeaderInfo::new(&lines, &sources, &list, None, 400.0, 300.0);
+        header.generated_at = 1_792_108_800;
+
+        let op = &header.operations[0];
+        assert_eq!((op.passes, op.mode), (2, CutMode::Cut));
+        assert_eq!(op.lines, lines.len() - 4, "all but header and footer");
+        // 8 mm perimeter traced twice
+        assert!((op.burn_length - 16.0).abs() < 1e-6, "{}", op.burn_length);
+
+        let rendered = header.render();
+        assert_eq!(rendered[1], "(Workspace: unsaved)");
+        assert_eq!(rendered[2], "(Generated: 2026-10-16T00:00:00Z)");
+        assert_eq!(rendered[3], "(Bed: 400 x 300 mm)");
+        assert!(rendered[5].starts_with("(1. Outline [v2] - Cut, 2 passes, "));
+        assert!(rendered
+            .iter()
+            .all(|l| l.starts_with('(') && l.ends_with(')')));
+    }
+
+    #[test]
+    fn test_burn_lengths_follow_arcs() {
+        let lines: Vec<String> = [
+            "G0 X0 Y0",
+            "M4 S500",
+            "G2 X10 Y0 I5 J0 F600",
+            "G3 X20 R5",
+            "G0 X0",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        let half_circle = 5.0 * std::f64::consts::PI;
+        let burn = burn_lengths(&lines);
+        assert_eq!((burn[0], burn[1], burn[4]), (0.0, 0.0, 0.0));
+        assert!((burn[2] - half_circle).abs() < 1e-6, "{}", burn[2]);
+        assert!((burn[3] - half_circle).abs() < 1e-6, "{}", burn[3]);
+    }
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(951_827_696), "2000-02-29T12:34:56Z");
+    }
+}
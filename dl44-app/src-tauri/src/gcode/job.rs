@@ -0,0 +1,437 @@
+//! Combined job generation for the whole workspace.
+
+use super::arc::{Arc, ArcCenter};
+use super::path::{Point, DEFAULT_TOLERANCE};
+use super::raster::{generate_raster, RasterParams};
+use super::vector::{generate_vector, svg_polylines};
+use super::GenerateError;
+use crate::workspace::document::{
+    BoundingBox, CutMode, Document, DocumentId, DocumentKind, DocumentList,
+};
+
+/// Program header: absolute mm, dynamic laser power, laser off
+pub const JOB_HEADER: &[&str] = &["G90 G21", "M4 S0"];
+
+/// Program footer: laser off, program end
+pub const JOB_FOOTER: &[&str] = &["M5", "M2"];
+
+/// Side table mapping program line indices to the document that
+/// generated them.
+///
+/// Stored as the first line of each run of lines from the same source, so
+/// the table stays small for long raster programs. Header and footer lines
+/// have no source document.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LineSources {
+    /// (first line index, source document), in line order
+    runs: Vec<(usize, Option<DocumentId>)>,
+}
+
+impl LineSources {
+    /// Document that generated the line at `index`, if any
+    pub fn document_at(&self, index: usize) -> Option<DocumentId> {
+        let run = self.runs.partition_point(|&(start, _)| start <= index);
+        run.checked_sub(1).and_then(|run| self.runs[run].1)
+    }
+
+    pub(crate) fn start(&mut self, index: usize, source: Option<DocumentId>) {
+        self.runs.push((index, source));
+    }
+}
+
+/// Execution order of an operation (engrave before score before cut).
+///
+/// Cutting last keeps parts from shifting or dropping out before they
+/// have been engraved.
+fn operation_order(mode: CutMode) -> u8 {
+    match mode {
+        CutMode::Engrave => 0,
+        CutMode::Score => 1,
+        CutMode::Cut => 2,
+    }
+}
+
+/// Generate G-code for a single document.
+///
+/// With a `safe_z`, vector travels lift between paths and passes; raster
+/// scanline hops stay low and only the travel to the first row lifts.
+/// Power is clamped to `max_power` (the device's `$30`); a non-empty
+/// `power_schedule` sets the power of each pass instead.
+pub fn generate_document(
+    doc: &Document,
+    raster: &RasterParams,
+    power_schedule: &[u32],
+    safe_z: Option<f64>,
+    max_power: u32,
+) -> Result<Vec<String>, GenerateError> {
+    check_power_schedule(power_schedule, max_power)?;
+    let mut settings = doc.effective_cut_settings();
+    settings.power = settings.power.min(max_power);
+    match &doc.kind {
+        DocumentKind::Bitmap(content) => {
+            let bounds = doc.workspace_bounds();
+            let mut lines = generate_raster(content, &bounds, &settings, power_schedule, raster)?;
+            if let (Some(z), false) = (safe_z, lines.is_empty()) {
+                lines.insert(0, format!("G0 Z{z:.3}"));
+                lines.insert(2, "G0 Z0".to_string());
+            }
+            Ok(lines)
+        }
+        DocumentKind::Svg(svg) => {
+            let polylines = svg_polylines(doc, svg);
+            Ok(generate_vector(
+                &polylines,
+                &settings,
+                power_schedule,
+                safe_z,
+            ))
+        }
+    }
+}
+
+/// Reject power schedule entries above `max_power`.
+///
+/// Unlike the document power, which is clamped, a schedule is an explicit
+/// per-pass taper and silently flattening it would change the result.
+fn check_power_schedule(power_schedule: &[u32], max_power: u32) -> Result<(), GenerateError> {
+    match power_schedule.iter().position(|&p| p > max_power) {
+        Some(pass) => Err(GenerateError::PowerSchedule {
+            pass: pass + 1,
+            power: power_schedule[pass],
+            max: max_power,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Generate one program for all visible documents.
+///
+/// Documents run in z-order within each operation type, with engrave
+/// operations before vector ones. Hidden documents are skipped, the same
+/// as for [`DocumentList::combined_bounds`] used by the frame trace; with
+/// nothing visible the program is empty (no header or footer). The power
+/// schedule applies to every document's passes.
+///
+/// Also returns which document each line came from, so streaming errors
+/// can be traced back to it.
+pub fn generate_job(
+    documents: &DocumentList,
+    raster: &RasterParams,
+    power_schedule: &[u32],
+    safe_z: Option<f64>,
+    max_power: u32,
+) -> Result<(Vec<String>, LineSources), GenerateError> {
+    check_power_schedule(power_schedule, max_power)?;
+    let mut sources = LineSources::default();
+    let mut docs: Vec<&Document> = documents.visible().collect();
+    if docs.is_empty() {
+        return Ok((Vec::new(), sources));
+    }
+    // Stable sort keeps z-order within each operation
+    docs.sort_by_key(|d| operation_order(d.effective_cut_settings().mode));
+
+    let mut lines: Vec<String> = JOB_HEADER.iter().map(|l| l.to_string()).collect();
+    sources.start(0, None);
+    for doc in docs {
+        let settings = doc.effective_cut_settings();
+        sources.start(lines.len(), Some(doc.id));
+        lines.push(format!("; {} ({:?})", doc.name, settings.mode));
+        lines.extend(generate_document(
+            doc,
+            raster,
+            power_schedule,
+            safe_z,
+            max_power,
+        )?);
+    }
+    sources.start(lines.len(), None);
+    lines.extend(JOB_FOOTER.iter().map(|l| l.to_string()));
+
+    Ok((lines, sources))
+}
+
+/// Extents of all G0-G3 moves in a program, including laser-off travel
+/// such as raster overscan and the bulge of arcs. `None` if the program
+/// has no moves.
+pub fn program_extents(lines: &[String]) -> Option<BoundingBox> {
+    let (mut x, mut y) = (None, None);
+    let mut extents: Option<BoundingBox> = None;
+    let mut include = |point| grow(&mut extents, point);
+    for line in lines {
+        let mut words = line.split_whitespace();
+        let clockwise = match words.next() {
+            Some("G0" | "G1") => None,
+            Some("G2") => Some(true),
+            Some("G3") => Some(false),
+            _ => continue,
+        };
+        let from = x.zip(y);
+        let (mut offset, mut radius) = ((0.0, 0.0), None);
+        for word in words {
+            let value = word.get(1..).and_then(|v| v.parse::<f64>().ok());
+            match word.chars().next() {
+                Some('X') => x = value.or(x),
+                Some('Y') => y = value.or(y),
+                Some('I') => offset.0 = value.unwrap_or(0.0),
+                Some('J') => offset.1 = value.unwrap_or(0.0),
+                Some('R') => radius = value,
+                _ => {}
+            }
+        }
+        let Some(to) = x.zip(y) else {
+            continue;
+        };
+        if let (Some(clockwise), Some(from)) = (clockwise, from) {
+            let center = radius.map_or(ArcCenter::Offset(offset.0, offset.1), ArcCenter::Radius);
+            if let Some(arc) = Arc::new(from, to, center, clockwise) {
+                for point in arc.flatten(DEFAULT_TOLERANCE) {
+                    include(point);
+                }
+            }
+        }
+        include(to);
+    }
+    extents
+}
+
+/// Grow `extents` to include a point. Unlike [`BoundingBox::merge`], a
+/// box with zero width or height (a single straight line) still counts.
+fn grow(extents: &mut Option<BoundingBox>, (x, y): Point) {
+    let b = extents.get_or_insert(BoundingBox::new(x, y, x, y));
+    *b = BoundingBox::new(
+        b.x_min.min(x),
+        b.y_min.min(y),
+        b.x_max.max(x),
+        b.y_max.max(y),
+    );
+}
+
+/// Tight frame of what a job would mark, without generating it.
+///
+/// Covers the bounds of visible bitmaps and the flattened paths of visible
+/// SVGs, so unlike [`DocumentList::combined_bounds`] it leaves out empty
+/// margins of an SVG canvas. Raster overscan isn't included; it runs with
+/// the laser off. `None` if nothing visible would be marked.
+pub fn job_frame(documents: &DocumentList) -> Option<BoundingBox> {
+    let mut frame = None;
+    for doc in documents.visible() {
+        match &doc.kind {
+            DocumentKind::Bitmap(_) => {
+                let bounds = doc.workspace_bounds();
+                grow(&mut frame, (bounds.x_min, bounds.y_min));
+                grow(&mut frame, (bounds.x_max, bounds.y_max));
+            }
+            DocumentKind::Svg(svg) => {
+                for point in svg_polylines(doc, svg).into_iter().flatten() {
+                    grow(&mut frame, point);
+                }
+            }
+        }
+    }
+    frame
+}
+
+/// Check that every move of a program stays on a `width` x `height` mm bed
+pub fn check_bed_limits(lines: &[String], width: f64, height: f64) -> Result<(), GenerateError> {
+    match program_extents(lines) {
+        Some(extents)
+            if extents.x_min < 0.0
+                || extents.y_min < 0.0
+                || extents.x_max > width
+                || extents.y_max > height =>
+        {
+            Err(GenerateError::OutOfBounds {
+                extents,
+                width,
+                height,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::document::{BoundingBox, CutSettings, SvgContent, Transform};
+
+    const RECT_SVG: &str =
+        r#"<svg viewBox="0 0 10 10"><rect x="1" y="1" width="2" height="2"/></svg>"#;
+
+    fn svg_doc(name: &str, mode: CutMode) -> Document {
+        Document {
+            id: 0,
+            name: name.into(),
+            source_path: None,
+            kind: DocumentKind::Svg(SvgContent {
+                width: 10.0,
+                height: 10.0,
+                paths: Vec::new(),
+                raw_svg: RECT_SVG.into(),
+            }),
+            transform: Transform::default(),
+            visible: true,
+            locked: false,
+            original_bounds: BoundingBox::new(0.0, 0.0, 10.0, 10.0),
+            cut_settings: Some(CutSettings {
+                feed: 500.0,
+                power: 1000,
+                passes: 1,
+                mode,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_job_orders_operations_and_skips_hidden() {
+        let mut list = DocumentList::new();
+        list.add(svg_doc("cut", CutMode::Cut));
+        list.add(svg_doc("score", CutMode::Score));
+        let hidden = list.add(svg_doc("hidden", CutMode::Score));
+        list.get_mut(hidden).unwrap().visible = false;
+
+        let (lines, _) = generate_job(&list, &RasterParams::default(), &[], None, 1000).unwrap();
+        let comments: Vec<&String> = lines.iter().filter(|l| l.starts_with(';')).collect();
+        assert_eq!(comments, ["; score (Score)", "; cut (Cut)"]);
+        assert_eq!(lines[..2], JOB_HEADER[..]);
+        assert_eq!(lines[lines.len() - 2..], JOB_FOOTER[..]);
+        assert!(lines.contains(&"G0 X1.000 Y1.000".to_string()));
+    }
+
+    #[test]
+    fn test_hidden_document_excluded_from_frame_and_job() {
+        let mut list = DocumentList::new();
+        list.add(svg_doc("shown", CutMode::Cut));
+        let hidden = list.add(svg_doc("hidden", CutMode::Cut));
+        {
+            let doc = list.get_mut(hidden).unwrap();
+            doc.visible = false;
+            doc.transform.x = 100.0;
+        }
+
+        let frame = list.combined_bounds();
+        assert_eq!((frame.x_max, frame.y_max), (10.0, 10.0));
+        // The job frame hugs the rectangle inside the SVG canvas
+        let tight = job_frame(&list).unwrap();
+        assert_eq!(
+            (tight.x_min, tight.y_min, tight.x_max, tight.y_max),
+            (1.0, 1.0, 3.0, 3.0)
+        );
+
+        let (lines, _) = generate_job(&list, &RasterParams::default(), &[], None, 1000).unwrap();
+        assert!(!lines.iter().any(|l| l.contains("hidden")));
+        let extents = program_extents(&lines).unwrap();
+        assert!(extents.x_max <= frame.x_max);
+
+        list.get_mut(1).unwrap().visible = false;
+        assert!(list.combined_bounds().is_empty());
+        let (lines, _) = generate_job(&list, &RasterParams::default(), &[], None, 1000).unwrap();
+        assert!(lines.is_empty());
+    }
+
+    #[test]
+    fn test_line_sources_map_to_documents() {
+        let mut list = DocumentList::new();
+        let cut = list.add(svg_doc("cut", CutMode::Cut));
+        let score = list.add(svg_doc("score", CutMode::Score));
+
+        let (lines, sources) =
+            generate_job(&list, &RasterParams::default(), &[], None, 1000).unwrap();
+        let cut_start = lines.iter().position(|l| l == "; cut (Cut)").unwrap();
+
+        assert_eq!(sources.document_at(0), None);
+        assert_eq!(sources.document_at(JOB_HEADER.len()), Some(score));
+        assert_eq!(sources.document_at(cut_start - 1), Some(score));
+        assert_eq!(sources.document_at(cut_start + 1), Some(cut));
+        assert_eq!(sources.document_at(lines.len() - 1), None);
+        assert_eq!(sources.document_at(lines.len() + 10), None);
+    }
+
+    #[test]
+    fn test_job_safe_z_lifts_travels() {
+        let mut list = DocumentList::new();
+        list.add(svg_doc("cut", CutMode::Cut));
+
+        let (lines, _) =
+            generate_job(&list, &RasterParams::default(), &[], Some(3.0), 1000).unwrap();
+        let travel = lines.iter().position(|l| l == "G0 X1.000 Y1.000").unwrap();
+        assert_eq!(lines[travel - 1], "G0 Z3.000");
+        assert_eq!(lines[travel + 1], "G0 Z0");
+    }
+
+    #[test]
+    fn test_job_power_clamped_to_max() {
+        let mut list = DocumentList::new();
+        list.add(svg_doc("cut", CutMode::Cut));
+
+        let (lines, _) = generate_job(&list, &RasterParams::default(), &[], None, 255).unwrap();
+        assert!(lines.iter().any(|l| l.ends_with(" S255")));
+        assert!(!lines.iter().any(|l| l.contains("S1000")));
+    }
+
+    #[test]
+    fn test_power_schedule_per_pass() {
+        let with_passes = |passes| {
+            let mut doc = svg_doc("cut", CutMode::Cut);
+            doc.cut_settings.as_mut().unwrap().passes = passes;
+            let mut list = DocumentList::new();
+            list.add(doc);
+            list
+        };
+        let raster = RasterParams::default();
+
+        let list = with_passes(3);
+        let (lines, _) = generate_job(&list, &raster, &[900, 600, 300], None, 1000).unwrap();
+        let powers: Vec<&str> = lines
+            .iter()
+            .filter(|l| l.starts_with("G1") && l.contains(" S"))
+            .filter_map(|l| l.rsplit(' ').next())
+            .collect();
+        assert_eq!(powers, ["S900", "S600", "S300"]);
+
+        // Passes past the end of the schedule reuse its last entry
+        let list = with_passes(4);
+        let (lines, _) = generate_job(&list, &raster, &[900, 600], None, 1000).unwrap();
+        assert_eq!(lines.iter().filter(|l| l.ends_with(" S600")).count(), 3);
+
+        assert!(matches!(
+            generate_job(&list, &raster, &[900, 1200], None, 1000),
+            Err(GenerateError::PowerSchedule {
+                pass: 2,
+                power: 1200,
+                max: 1000
+            })
+        ));
+    }
+
+    #[test]
+    fn test_bed_limits_include_travel() {
+        let lines: Vec<String> = ["G90 G21", "G0 X-2.5 Y1", "G1 X10 F3000 S0", "G1 Y5"]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        let extents = program_extents(&lines).unwrap();
+        assert_eq!(
+            (extents.x_min, extents.y_min, extents.x_max, extents.y_max),
+            (-2.5, 1.0, 10.0, 5.0)
+        );
+        assert!(matches!(
+            check_bed_limits(&lines, 400.0, 400.0),
+            Err(GenerateError::OutOfBounds { .. })
+        ));
+        assert!(check_bed_limits(&lines[2..], 400.0, 400.0).is_ok());
+    }
+
+    #[test]
+    fn test_extents_include_arcs() {
+        // Half circles over the top, then under the bottom
+        let lines: Vec<String> = ["G0 X0 Y0", "G2 X10 Y0 I5 J0 F600", "G3 X20 R5"]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        let extents = program_extents(&lines).unwrap();
+        assert_eq!((extents.x_min, extents.x_max), (0.0, 20.0));
+        assert!((extents.y_max - 5.0).abs() < 0.01, "{}", extents.y_max);
+        assert!((extents.y_min + 5.0).abs() < 0.01, "{}", extents.y_min);
+    }
+}
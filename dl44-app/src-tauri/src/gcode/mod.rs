@@ -0,0 +1,76 @@
+//! G-code generation for workspace documents.
+//!
+//! Converts visible documents into a laser program: bitmaps are engraved as
+//! serpentine raster scanlines and SVGs are traced as vector paths. All
+//! output is in workspace millimetres (absolute, y-down, same frame as the
+//! canvas and the frame trace).
+
+pub mod arc;
+pub mod header;
+pub mod job;
+pub mod path;
+pub mod preflight;
+pub mod raster;
+pub mod render;
+pub mod vector;
+
+use thiserror::Error;
+
+use crate::workspace::document::BoundingBox;
+
+pub use header::GcodeHeaderInfo;
+pub use job::{check_bed_limits, generate_job, job_frame, LineSources};
+pub use raster::RasterParams;
+
+/// Append a rapid travel to `(x, y)`.
+///
+/// With a safe Z, the head is raised first and lowered back to work Z0
+/// (the cutting / pen-down height) on arrival.
+fn push_travel(lines: &mut Vec<String>, x: f64, y: f64, safe_z: Option<f64>) {
+    match safe_z {
+        Some(z) => {
+            lines.push(format!("G0 Z{z:.3}"));
+            lines.push(format!("G0 X{x:.3} Y{y:.3}"));
+            lines.push("G0 Z0".to_string());
+        }
+        None => lines.push(format!("G0 X{x:.3} Y{y:.3}")),
+    }
+}
+
+/// Laser power for a zero-based pass.
+///
+/// Pass N uses `power_schedule[N]`, passes past the end of the schedule
+/// reuse its last entry, and an empty schedule keeps `power` throughout.
+fn pass_power(power_schedule: &[u32], pass: usize, power: u32) -> u32 {
+    power_schedule
+        .get(pass)
+        .or(power_schedule.last())
+        .copied()
+        .unwrap_or(power)
+}
+
+/// G-code generation errors
+#[derive(Error, Debug)]
+pub enum GenerateError {
+    #[error("Invalid image data: {0}")]
+    ImageData(String),
+
+    #[error("Image error: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("Invalid SVG: {0}")]
+    Svg(String),
+
+    #[error(
+        "Job moves span X {:.1}..{:.1}, Y {:.1}..{:.1} mm, outside the {width}x{height} mm bed",
+        .extents.x_min, .extents.x_max, .extents.y_min, .extents.y_max
+    )]
+    OutOfBounds {
+        extents: BoundingBox,
+        width: f64,
+        height: f64,
+    },
+
+    #[error("Power schedule entry S{power} for pass {pass} exceeds the maximum S{max}")]
+    PowerSchedule { pass: usize, power: u32, max: u32 },
+}
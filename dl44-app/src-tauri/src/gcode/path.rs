@@ -0,0 +1,445 @@
+//! SVG path data flattening.
+//!
+//! Converts SVG `d` attribute data into polylines. Curves (cubic/quadratic
+//! Béziers and elliptical arcs) are flattened to line segments within a
+//! chord tolerance.
+
+/// 2D point (x, y)
+pub type Point = (f64, f64);
+
+/// Connected sequence of points
+pub type Polyline = Vec<Point>;
+
+/// Default flattening tolerance in document units (mm)
+pub const DEFAULT_TOLERANCE: f64 = 0.05;
+
+/// Maximum segments used for a single curve
+const MAX_CURVE_SEGMENTS: usize = 256;
+
+/// Cursor over path data
+struct PathParser<'a> {
+    s: &'a [u8],
+    i: usize,
+}
+
+impl<'a> PathParser<'a> {
+    fn new(d: &'a str) -> Self {
+        Self {
+            s: d.as_bytes(),
+            i: 0,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while self.i < self.s.len() && matches!(self.s[self.i], b' ' | b'\t' | b'\n' | b'\r' | b',')
+        {
+            self.i += 1;
+        }
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_separators();
+        self.i >= self.s.len()
+    }
+
+    /// Consume a command letter, if one is next.
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let c = *self.s.get(self.i)?;
+        if c.is_ascii_alphabetic() && c != b'e' && c != b'E' {
+            self.i += 1;
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    /// Consume a number (handles forms like `-1.5e3`, `.5`, and `1.5.5`).
+    fn number(&mut self) -> Option<f64> {
+        self.skip_separators();
+        let start = self.i;
+        let mut end = self.i;
+        let bytes = self.s;
+
+        if matches!(bytes.get(end), Some(b'+' | b'-')) {
+            end += 1;
+        }
+        let mut seen_dot = false;
+        let mut seen_digit = false;
+        while let Some(&c) = bytes.get(end) {
+            if c.is_ascii_digit() {
+                seen_digit = true;
+            } else if c == b'.' && !seen_dot {
+                seen_dot = true;
+            } else {
+                break;
+            }
+            end += 1;
+        }
+        if !seen_digit {
+            return None;
+        }
+        if matches!(bytes.get(end), Some(b'e' | b'E')) {
+            let mut exp_end = end + 1;
+            if matches!(bytes.get(exp_end), Some(b'+' | b'-')) {
+                exp_end += 1;
+            }
+            if bytes.get(exp_end).is_some_and(|c| c.is_ascii_digit()) {
+                while bytes.get(exp_end).is_some_and(|c| c.is_ascii_digit()) {
+                    exp_end += 1;
+                }
+                end = exp_end;
+            }
+        }
+
+        let text = std::str::from_utf8(&bytes[start..end]).ok()?;
+        let value = text.parse().ok()?;
+        self.i = end;
+        Some(value)
+    }
+
+    /// Consume an arc flag (`0` or `1`, which may be written without separators).
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let c = *self.s.get(self.i)?;
+        let flag = match c {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.i += 1;
+        Some(flag)
+    }
+
+    fn pair(&mut self) -> Option<Point> {
+        Some((self.number()?, self.number()?))
+    }
+}
+
+/// Flatten SVG path data into polylines.
+///
+/// Each subpath (started by a moveto) becomes a separate polyline. Parsing
+/// stops at the first malformed segment, keeping everything before it.
+pub fn flatten_path(d: &str, tolerance: f64) -> Vec<Polyline> {
+    let mut parser = PathParser::new(d);
+    let mut polylines: Vec<Polyline> = Vec::new();
+    let mut current_line: Polyline = Vec::new();
+
+    let mut cmd: Option<u8> = None;
+    let mut pos: Point = (0.0, 0.0);
+    let mut subpath_start: Point = (0.0, 0.0);
+    // Last control point, for smooth curve reflection (S/T)
+    let mut last_cubic_ctrl: Option<Point> = None;
+    let mut last_quad_ctrl: Option<Point> = None;
+
+    while !parser.at_end() {
+        if let Some(c) = parser.command() {
+            cmd = Some(c);
+        }
+        let Some(c) = cmd else {
+            break;
+        };
+        let relative = c.is_ascii_lowercase();
+        let offset = |p: Point, pos: Point| {
+            if relative {
+                (p.0 + pos.0, p.1 + pos.1)
+            } else {
+                p
+            }
+        };
+
+        let mut cubic_ctrl = None;
+        let mut quad_ctrl = None;
+
+        match c.to_ascii_uppercase() {
+            b'M' => {
+                let Some(p) = parser.pair() else { break };
+                if current_line.len() > 1 {
+                    polylines.push(std::mem::take(&mut current_line));
+                }
+                current_line.clear();
+                pos = offset(p, pos);
+                subpath_start = pos;
+                current_line.push(pos);
+                // Subsequent coordinate pairs are implicit linetos
+                cmd = Some(if relative { b'l' } else { b'L' });
+            }
+            b'L' => {
+                let Some(p) = parser.pair() else { break };
+                pos = offset(p, pos);
+                current_line.push(pos);
+            }
+            b'H' => {
+                let Some(x) = parser.number() else { break };
+                pos.0 = if relative { pos.0 + x } else { x };
+                current_line.push(pos);
+            }
+            b'V' => {
+                let Some(y) = parser.number() else { break };
+                pos.1 = if relative { pos.1 + y } else { y };
+                current_line.push(pos);
+            }
+            b'C' | b'S' => {
+                let c1 = if c.eq_ignore_ascii_case(&b'C') {
+                    let Some(c1) = parser.pair() else { break };
+                    offset(c1, pos)
+                } else {
+                    reflect(last_cubic_ctrl, pos)
+                };
+                let (Some(c2), Some(end)) = (parser.pair(), parser.pair()) else {
+                    break;
+                };
+                let (c2, end) = (offset(c2, pos), offset(end, pos));
+                cubic_to(&mut current_line, pos, c1, c2, end, tolerance);
+                cubic_ctrl = Some(c2);
+                pos = end;
+            }
+            b'Q' | b'T' => {
+                let ctrl = if c.eq_ignore_ascii_case(&b'Q') {
+                    let Some(ctrl) = parser.pair() else { break };
+                    offset(ctrl, pos)
+                } else {
+                    reflect(last_quad_ctrl, pos)
+                };
+                let Some(end) = parser.pair() else { break };
+                let end = offset(end, pos);
+                // Elevate to cubic
+                let c1 = (
+                    pos.0 + 2.0 / 3.0 * (ctrl.0 - pos.0),
+                    pos.1 + 2.0 / 3.0 * (ctrl.1 - pos.1),
+                );
+                let c2 = (
+                    end.0 + 2.0 / 3.0 * (ctrl.0 - end.0),
+                    end.1 + 2.0 / 3.0 * (ctrl.1 - end.1),
+                );
+                cubic_to(&mut current_line, pos, c1, c2, end, tolerance);
+                quad_ctrl = Some(ctrl);
+                pos = end;
+            }
+            b'A' => {
+                let (Some(rx), Some(ry), Some(rotation)) =
+                    (parser.number(), parser.number(), parser.number())
+                else {
+                    break;
+                };
+                let (Some(large_arc), Some(sweep)) = (parser.flag(), parser.flag()) else {
+                    break;
+                };
+                let Some(end) = parser.pair() else { break };
+                let end = offset(end, pos);
+                arc_to(
+                    &mut current_line,
+                    pos,
+                    (rx, ry),
+                    rotation,
+                    large_arc,
+                    sweep,
+                    end,
+                    tolerance,
+                );
+                pos = end;
+            }
+            b'Z' => {
+                if !current_line.is_empty() {
+                    current_line.push(subpath_start);
+                }
+                pos = subpath_start;
+                if current_line.len() > 1 {
+                    polylines.push(std::mem::take(&mut current_line));
+                }
+                current_line.clear();
+                current_line.push(pos);
+                // Z takes no arguments; a new command letter must follow
+                cmd = None;
+            }
+            _ => break,
+        }
+
+        last_cubic_ctrl = cubic_ctrl;
+        last_quad_ctrl = quad_ctrl;
+    }
+
+    if current_line.len() > 1 {
+        polylines.push(current_line);
+    }
+
+    polylines
+}
+
+/// Reflect a control point about the current position (for S/T commands).
+fn reflect(ctrl: Option<Point>, pos: Point) -> Point {
+    match ctrl {
+        Some(c) => (2.0 * pos.0 - c.0, 2.0 * pos.1 - c.1),
+        None => pos,
+    }
+}
+
+fn distance(a: Point, b: Point) -> f64 {
+    ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt()
+}
+
+/// Number of line segments for a curve whose control polygon has `length`.
+fn curve_segments(length: f64, tolerance: f64) -> usize {
+    let tolerance = tolerance.max(1e-4);
+    ((length / tolerance).sqrt().ceil() as usize).clamp(1, MAX_CURVE_SEGMENTS)
+}
+
+/// Append a flattened cubic Bézier (excluding the start point).
+fn cubic_to(out: &mut Polyline, p0: Point, c1: Point, c2: Point, p3: Point, tolerance: f64) {
+    let length = distance(p0, c1) + distance(c1, c2) + distance(c2, p3);
+    let n = curve_segments(length, tolerance);
+    for i in 1..=n {
+        let t = i as f64 / n as f64;
+        let mt = 1.0 - t;
+        let a = mt * mt * mt;
+        let b = 3.0 * mt * mt * t;
+        let c = 3.0 * mt * t * t;
+        let d = t * t * t;
+        out.push((
+            a * p0.0 + b * c1.0 + c * c2.0 + d * p3.0,
+            a * p0.1 + b * c1.1 + c * c2.1 + d * p3.1,
+        ));
+    }
+}
+
+/// Append a flattened elliptical arc (excluding the start point).
+///
+/// Uses the endpoint-to-center conversion from the SVG spec (F.6.5).
+#[allow(clippy::too_many_arguments)]
+pub fn arc_to(
+    out: &mut Polyline,
+    from: Point,
+    radii: (f64, f64),
+    rotation_deg: f64,
+    large_arc: bool,
+    sweep: bool,
+    to: Point,
+    tolerance: f64,
+) {
+    let (mut rx, mut ry) = (radii.0.abs(), radii.1.abs());
+    if from == to {
+        return;
+    }
+    if rx < f64::EPSILON || ry < f64::EPSILON {
+        out.push(to);
+        return;
+    }
+
+    let phi = rotation_deg.to_radians();
+    let (sin_phi, cos_phi) = phi.sin_cos();
+
+    let dx2 = (from.0 - to.0) / 2.0;
+    let dy2 = (from.1 - to.1) / 2.0;
+    let x1p = cos_phi * dx2 + sin_phi * dy2;
+    let y1p = -sin_phi * dx2 + cos_phi * dy2;
+
+    // Scale up radii that are too small to span the endpoints
+    let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let num = rx * rx * ry * ry - rx * rx * y1p * y1p - ry * ry * x1p * x1p;
+    let den = rx * rx * y1p * y1p + ry * ry * x1p * x1p;
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let coef = sign * (num / den).max(0.0).sqrt();
+    let cxp = coef * rx * y1p / ry;
+    let cyp = -coef * ry * x1p / rx;
+
+    let cx = cos_phi * cxp - sin_phi * cyp + (from.0 + to.0) / 2.0;
+    let cy = sin_phi * cxp + cos_phi * cyp + (from.1 + to.1) / 2.0;
+
+    let angle = |u: Point, v: Point| (u.0 * v.1 - u.1 * v.0).atan2(u.0 * v.0 + u.1 * v.1);
+    let u = ((x1p - cxp) / rx, (y1p - cyp) / ry);
+    let v = ((-x1p - cxp) / rx, (-y1p - cyp) / ry);
+    let theta1 = angle((1.0, 0.0), u);
+    let mut dtheta = angle(u, v);
+    if !sweep && dtheta > 0.0 {
+        dtheta -= std::f64::consts::TAU;
+    } else if sweep && dtheta < 0.0 {
+        dtheta += std::f64::consts::TAU;
+    }
+
+    // Segment angle that keeps the chord within tolerance of the arc
+    let r = rx.max(ry);
+    let max_step = 2.0 * (1.0 - (tolerance / r).min(1.0)).acos();
+    let n = if max_step > 0.0 {
+        ((dtheta.abs() / max_step).ceil() as usize).clamp(1, MAX_CURVE_SEGMENTS)
+    } else {
+        MAX_CURVE_SEGMENTS
+    };
+
+    for i in 1..n {
+        let t = theta1 + dtheta * i as f64 / n as f64;
+        let (sin_t, cos_t) = t.sin_cos();
+        out.push((
+            cx + rx * cos_t * cos_phi - ry * sin_t * sin_phi,
+            cy + rx * cos_t * sin_phi + ry * sin_t * cos_phi,
+        ));
+    }
+    // Land exactly on the endpoint
+    out.push(to);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Point, b: Point) -> bool {
+        (a.0 - b.0).abs() < 1e-6 && (a.1 - b.1).abs() < 1e-6
+    }
+
+    #[test]
+    fn test_relative_square() {
+        let lines = flatten_path("m10,10 h20 v20 h-20 z", DEFAULT_TOLERANCE);
+        assert_eq!(lines.len(), 1);
+        let expected = vec![
+            (10.0, 10.0),
+            (30.0, 10.0),
+            (30.0, 30.0),
+            (10.0, 30.0),
+            (10.0, 10.0),
+        ];
+        assert_eq!(lines[0], expected);
+    }
+
+    #[test]
+    fn test_implicit_lineto_and_compact_numbers() {
+        let lines = flatten_path("M0 0 10-5.5.5 1", DEFAULT_TOLERANCE);
+        assert_eq!(lines[0], vec![(0.0, 0.0), (10.0, -5.5), (0.5, 1.0)]);
+    }
+
+    #[test]
+    fn test_multiple_subpaths() {
+        let lines = flatten_path("M0 0 L1 0 M5 5 L6 5", DEFAULT_TOLERANCE);
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn test_curves_end_at_endpoint() {
+        let lines = flatten_path("M0 0 C0 10 10 10 10 0 Q15 -5 20 0", DEFAULT_TOLERANCE);
+        let last = *lines[0].last().unwrap();
+        assert!(close(last, (20.0, 0.0)));
+        assert!(lines[0].len() > 4);
+    }
+
+    #[test]
+    fn test_arc_semicircle() {
+        let lines = flatten_path("M0 0 A10 10 0 0 1 20 0", DEFAULT_TOLERANCE);
+        let points = &lines[0];
+        assert!(close(*points.last().unwrap(), (20.0, 0.0)));
+        // Every point lies on the circle centred at (10, 0)
+        for p in points {
+            assert!((distance(*p, (10.0, 0.0)) - 10.0).abs() < 1e-6);
+        }
+        // Sweep flag 1 goes through negative y (SVG y-down, clockwise on screen)
+        assert!(points.iter().any(|p| p.1 < -9.0));
+    }
+
+    #[test]
+    fn test_compact_arc_flags() {
+        let lines = flatten_path("M0 0a10 10 0 0120 0", DEFAULT_TOLERANCE);
+        assert!(close(*lines[0].last().unwrap(), (20.0, 0.0)));
+    }
+}
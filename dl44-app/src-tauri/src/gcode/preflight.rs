@@ -0,0 +1,190 @@
+//! Checks on a generated program before it runs.
+//!
+//! A single pass over the program's moves gives what a pre-cut dialog
+//! shows: how long the job takes, which documents leave the bed, and
+//! whether any rapid would move with the laser powered.
+
+use super::arc::{Arc, ArcCenter};
+use super::job::LineSources;
+use super::path::{Point, DEFAULT_TOLERANCE};
+use crate::workspace::document::{BoundingBox, DocumentId};
+
+/// Rapid rate assumed when the device's `$110`/`$111` aren't known
+/// (mm/min)
+pub const DEFAULT_RAPID_RATE: f64 = 3000.0;
+
+/// One G0-G3 move
+struct Move {
+    /// Line index in the program
+    index: usize,
+    rapid: bool,
+    /// Straight-line or arc length (mm)
+    length: f64,
+    /// Points along the move, ending on the target
+    points: Vec<Point>,
+    /// Feed rate in effect (mm/min)
+    feed: Option<f64>,
+    /// Spindle on (M3/M4) with nonzero S
+    powered: bool,
+}
+
+/// Moves of a program in absolute mm, following modal motion, feed,
+/// spindle state and power. Moves before both X and Y are known are
+/// skipped, and the first move after counts as starting on its target.
+fn moves(lines: &[String]) -> Vec<Move> {
+    let mut moves = Vec::new();
+    let (mut x, mut y) = (None, None);
+    let (mut motion, mut feed, mut spindle_on, mut power) = (None, None, false, 0.0);
+    for (index, line) in lines.iter().enumerate() {
+        let code = line.split(';').next().unwrap_or("");
+        let mut has_axis = false;
+        let (mut offset, mut radius) = ((0.0, 0.0), None);
+        let from = x.zip(y);
+        for word in code.split_whitespace() {
+            let value = word.get(1..).and_then(|v| v.parse::<f64>().ok());
+            match (word.chars().next(), value) {
+                (Some('G'), Some(g)) if [0.0, 1.0, 2.0, 3.0].contains(&g) => motion = Some(g),
+                (Some('M'), Some(m)) if m == 3.0 || m == 4.0 => spindle_on = true,
+                (Some('M'), Some(m)) if m == 5.0 || m == 2.0 || m == 30.0 => spindle_on = false,
+                (Some('F'), Some(f)) => feed = Some(f),
+                (Some('S'), Some(s)) => power = s,
+                (Some('X'), Some(v)) => (x, has_axis) = (Some(v), true),
+                (Some('Y'), Some(v)) => (y, has_axis) = (Some(v), true),
+                (Some('I'), Some(v)) => offset.0 = v,
+                (Some('J'), Some(v)) => offset.1 = v,
+                (Some('R'), Some(v)) => radius = Some(v),
+                _ => {}
+            }
+        }
+        let (Some(motion), Some(to)) = (motion, x.zip(y)) else {
+            continue;
+        };
+        if !has_axis {
+            continue;
+        }
+        // The start of the first move is wherever the head was
+        let from = from.unwrap_or(to);
+
+        let arc = (motion >= 2.0)
+            .then(|| {
+                let center =
+                    radius.map_or(ArcCenter::Offset(offset.0, offset.1), ArcCenter::Radius);
+                Arc::new(from, to, center, motion == 2.0)
+            })
+            .flatten();
+        let (length, points) = match arc {
+            Some(arc) => (arc.length(), arc.flatten(DEFAULT_TOLERANCE)),
+            None => ((to.0 - from.0).hypot(to.1 - from.1), vec![to]),
+        };
+        moves.push(Move {
+            index,
+            rapid: motion == 0.0,
+            length,
+            points,
+            feed,
+            powered: spindle_on && power > 0.0,
+        });
+    }
+    moves
+}
+
+/// What a pre-cut check needs to know about a program
+#[derive(Debug, Clone)]
+pub struct ProgramCheck {
+    /// Run time without acceleration, so a lower bound (seconds)
+    pub estimated_seconds: f64,
+    /// Lines with a G0 move while the laser is on with nonzero power
+    pub powered_rapids: Vec<usize>,
+    /// Extents of each document's moves, in program order
+    pub document_extents: Vec<(DocumentId, BoundingBox)>,
+}
+
+/// Check a program. Rapids run at `rapid_rate` and feed moves at their F
+/// (mm/min); a feed move without a feed rate counts as a rapid.
+pub fn check_program(lines: &[String], sources: &LineSources, rapid_rate: f64) -> ProgramCheck {
+    let mut check = ProgramCheck {
+        estimated_seconds: 0.0,
+        powered_rapids: Vec::new(),
+        document_extents: Vec::new(),
+    };
+    for m in moves(lines) {
+        let rate = if m.rapid {
+            rapid_rate
+        } else {
+            m.feed.filter(|f| *f > 0.0).unwrap_or(rapid_rate)
+        };
+        check.estimated_seconds += m.length / rate * 60.0;
+        if m.rapid && m.powered {
+            check.powered_rapids.push(m.index);
+        }
+
+        let Some(id) = sources.document_at(m.index) else {
+            continue;
+        };
+        if check.document_extents.last().map(|(last, _)| *last) != Some(id) {
+            let (x, y) = m.points[0];
+            check
+                .document_extents
+                .push((id, BoundingBox::new(x, y, x, y)));
+        }
+        if let Some((_, b)) = check.document_extents.last_mut() {
+            for &(x, y) in &m.points {
+                *b = BoundingBox::new(
+                    b.x_min.min(x),
+                    b.y_min.min(y),
+                    b.x_max.max(x),
+                    b.y_max.max(y),
+                );
+            }
+        }
+    }
+    check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_estimate_and_powered_rapids() {
+        let lines = program(&[
+            "G90 G21",
+            "M4 S0",
+            "G0 X0 Y0",
+            "G1 X30 Y40 F600 S500",
+            // S500 is still in effect: fires without laser mode
+            "G0 X30 Y0",
+            "G3 X30 Y20 I0 J10",
+            "M5",
+            "G0 X0 Y0",
+        ]);
+        let check = check_program(&lines, &LineSources::default(), 1200.0);
+        // 50mm at 600, 40mm rapid, half circle r=10 at 600, rapid home
+        let expected = 5.0 + 2.0 + std::f64::consts::PI + 1300f64.sqrt() / 20.0;
+        assert!((check.estimated_seconds - expected).abs() < 1e-9);
+        assert_eq!(check.powered_rapids, [4]);
+        assert!(check.document_extents.is_empty());
+    }
+
+    #[test]
+    fn test_document_extents_follow_sources() {
+        let lines = program(&["G0 X5 Y5", "G1 X15 Y5 F100 S10", "G0 X-2 Y30", "G1 X-2 Y40"]);
+        let mut sources = LineSources::default();
+        sources.start(0, Some(1));
+        sources.start(2, Some(2));
+        let check = check_program(&lines, &sources, DEFAULT_RAPID_RATE);
+        let extents: Vec<_> = check
+            .document_extents
+            .iter()
+            .map(|(id, b)| (*id, [b.x_min, b.y_min, b.x_max, b.y_max]))
+            .collect();
+        assert_eq!(
+            extents,
+            [(1, [5.0, 5.0, 15.0, 5.0]), (2, [-2.0, 30.0, -2.0, 40.0])]
+        );
+    }
+}
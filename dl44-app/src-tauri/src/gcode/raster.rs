@@ -0,0 +1,565 @@
+//! Raster (scanline) G-code generation for bitmap documents.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::{DynamicImage, GrayImage, Luma};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+use super::{pass_power, GenerateError};
+use crate::workspace::document::{BitmapContent, BoundingBox, CutSettings};
+
+/// How grayscale is converted to laser power
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DitherMode {
+    /// Power proportional to darkness
+    #[default]
+    None,
+    /// 1-bit error diffusion to the right and the next row
+    FloydSteinberg,
+    /// 1-bit error diffusion over two rows, dropping a quarter of the error
+    /// (higher contrast, less muddy shadows)
+    Atkinson,
+    /// 1-bit, pixels darker than mid-gray are burned at full power
+    Threshold,
+}
+
+/// Raster scan parameters
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RasterParams {
+    /// Distance between scanlines (mm)
+    pub line_interval: f64,
+    /// Alternate scan direction on each line (serpentine)
+    pub bidirectional: bool,
+    /// Laser-off run-up beyond each end of a scanline (mm), so the gantry
+    /// is at full speed before the burn region instead of over-burning
+    /// the edges while it accelerates and decelerates
+    pub overscan_mm: f64,
+    /// Grayscale conversion, applied at scan resolution
+    pub dither: DitherMode,
+    /// Invert the image (for materials that mark light, e.g. anodized aluminum)
+    pub invert: bool,
+    /// Brightness offset as a fraction of full range (-1.0..1.0, 0 = unchanged)
+    pub brightness: f64,
+    /// Contrast adjustment around mid-gray (-1.0..1.0, 0 = unchanged)
+    pub contrast: f64,
+    /// Gamma correction (> 1 lightens midtones, 1 = unchanged)
+    pub gamma: f64,
+}
+
+impl Default for RasterParams {
+    fn default() -> Self {
+        Self {
+            line_interval: 0.1,
+            bidirectional: true,
+            overscan_mm: 2.5,
+            dither: DitherMode::None,
+            invert: false,
+            brightness: 0.0,
+            contrast: 0.0,
+            gamma: 1.0,
+        }
+    }
+}
+
+/// Decode a bitmap document's data URL
+pub fn decode_bitmap(content: &BitmapContent) -> Result<DynamicImage, GenerateError> {
+    let b64 = content
+        .data_url
+        .split_once(";base64,")
+        .map(|(_, data)| data)
+        .ok_or_else(|| GenerateError::ImageData("not a base64 data URL".into()))?;
+    let bytes = STANDARD
+        .decode(b64)
+        .map_err(|e| GenerateError::ImageData(e.to_string()))?;
+    Ok(image::load_from_memory(&bytes)?)
+}
+
+/// Convert a decoded image to grayscale with the tone adjustments of
+/// `params` applied, in order: brightness, contrast, gamma, invert
+pub fn adjust_image(image: &DynamicImage, params: &RasterParams) -> GrayImage {
+    let gamma = if params.gamma > 0.0 {
+        params.gamma
+    } else {
+        1.0
+    };
+    let lut: Vec<u8> = (0..=255u8)
+        .map(|luma| {
+            let mut v = luma as f64 / 255.0 + params.brightness;
+            v = (v - 0.5) * (1.0 + params.contrast) + 0.5;
+            v = v.clamp(0.0, 1.0).powf(1.0 / gamma);
+            if params.invert {
+                v = 1.0 - v;
+            }
+            (v * 255.0).round() as u8
+        })
+        .collect();
+
+    // Composite onto white: transparent pixels are background, even though
+    // they usually hold black color values
+    let gray = image.to_luma_alpha8();
+    GrayImage::from_fn(gray.width(), gray.height(), |x, y| {
+        let [luma, alpha] = gray.get_pixel(x, y).0;
+        let luma = (luma as u16 * alpha as u16 / 255) as u8 + (255 - alpha);
+        Luma([lut[luma as usize]])
+    })
+}
+
+/// Generate raster G-code for a bitmap placed at `bounds` (workspace mm).
+///
+/// Darker pixels get more power (black = `settings.power`, or the pass's
+/// entry in `power_schedule`; white = off).
+/// Runs of equal power are merged into single moves, and blank margins at
+/// either end of a scanline are skipped. With overscan, each scanline is
+/// extended at both ends by laser-off moves at the engraving feed, so the
+/// travel extends `overscan_mm` beyond the burned region.
+pub fn generate_raster(
+    content: &BitmapContent,
+    bounds: &BoundingBox,
+    settings: &CutSettings,
+    power_schedule: &[u32],
+    params: &RasterParams,
+) -> Result<Vec<String>, GenerateError> {
+    let image = adjust_image(&decode_bitmap(content)?, params);
+    Ok(raster_lines(
+        &image,
+        bounds,
+        settings,
+        power_schedule,
+        params,
+    ))
+}
+
+/// PNG data URL of what a bitmap engraves: the image after tone
+/// adjustment and dithering, with one pixel row per scanline.
+///
+/// Darker is more power, as in [`generate_raster`].
+pub fn preview_raster(
+    content: &BitmapContent,
+    bounds: &BoundingBox,
+    params: &RasterParams,
+) -> Result<String, GenerateError> {
+    let image = adjust_image(&decode_bitmap(content)?, params);
+    let rows = scanlines(&image, bounds, params);
+    let width = rows.first().map_or(0, |row| row.len()) as u32;
+    let preview = GrayImage::from_raw(width, rows.len() as u32, rows.concat())
+        .ok_or_else(|| GenerateError::ImageData("empty raster".into()))?;
+    let mut png = Vec::new();
+    preview.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+    Ok(format!("data:image/png;base64,{}", STANDARD.encode(png)))
+}
+
+/// Luma of each scanline across `bounds`, dithered per `params`.
+/// Each scanline samples the image row under its center.
+fn scanlines(image: &GrayImage, bounds: &BoundingBox, params: &RasterParams) -> Vec<Vec<u8>> {
+    let (img_w, img_h) = image.dimensions();
+    if img_w == 0 || img_h == 0 || bounds.is_empty() || params.line_interval <= 0.0 {
+        return Vec::new();
+    }
+
+    let rows = ((bounds.height() / params.line_interval).floor() as usize).max(1);
+    let mut scanlines: Vec<Vec<u8>> = (0..rows)
+        .map(|row| {
+            let offset = (row as f64 + 0.5) * params.line_interval;
+            let img_y = ((offset / bounds.height() * img_h as f64) as u32).min(img_h - 1);
+            (0..img_w).map(|x| image.get_pixel(x, img_y)[0]).collect()
+        })
+        .collect();
+    dither(&mut scanlines, params.dither);
+    scanlines
+}
+
+/// Scanline generation on a decoded grayscale image
+pub fn raster_lines(
+    image: &GrayImage,
+    bounds: &BoundingBox,
+    settings: &CutSettings,
+    power_schedule: &[u32],
+    params: &RasterParams,
+) -> Vec<String> {
+    let scanlines = scanlines(image, bounds, params);
+    if scanlines.is_empty() {
+        return Vec::new();
+    }
+
+    let pixel_w = bounds.width() / image.width() as f64;
+    let feed = settings.feed;
+    let overscan = params.overscan_mm.max(0.0);
+
+    let mut lines = Vec::new();
+    for pass in 0..settings.passes.max(1) as usize {
+        let power = pass_power(power_schedule, pass, settings.power);
+        let mut forward = true;
+        for (row, luma) in scanlines.iter().enumerate() {
+            let y = bounds.y_min + (row as f64 + 0.5) * params.line_interval;
+
+            let powers: Vec<u32> = luma.iter().map(|&l| pixel_power(l, power)).collect();
+            let Some(first) = powers.iter().position(|&p| p > 0) else {
+                continue;
+            };
+            let last = powers.iter().rposition(|&p| p > 0).unwrap_or(first);
+
+            // Runs of equal power as (start pixel, end pixel exclusive, power)
+            let mut runs: Vec<(usize, usize, u32)> = Vec::new();
+            for (x, &power) in powers.iter().enumerate().take(last + 1).skip(first) {
+                match runs.last_mut() {
+                    Some(run) if run.2 == power => run.1 = x + 1,
+                    _ => runs.push((x, x + 1, power)),
+                }
+            }
+
+            let edge = |px: usize| bounds.x_min + px as f64 * pixel_w;
+            // Burn moves as (target x, power) in scan direction
+            let (x0, dir, moves): (f64, f64, Vec<(f64, u32)>) = if forward {
+                let moves = runs.iter().map(|&(_, end, p)| (edge(end), p)).collect();
+                (edge(first), 1.0, moves)
+            } else {
+                let moves = runs
+                    .iter()
+                    .rev()
+                    .map(|&(start, _, p)| (edge(start), p))
+                    .collect();
+                (edge(last + 1), -1.0, moves)
+            };
+
+            if overscan > 0.0 {
+                let x_lead = x0 - dir * overscan;
+                lines.push(format!("G0 X{x_lead:.3} Y{y:.3}"));
+                lines.push(scan_move(x0, 0, true, feed));
+            } else {
+                lines.push(format!("G0 X{x0:.3} Y{y:.3}"));
+            }
+            let first_burn = overscan <= 0.0;
+            for (i, &(x, power)) in moves.iter().enumerate() {
+                lines.push(scan_move(x, power, first_burn && i == 0, feed));
+            }
+            if overscan > 0.0 {
+                if let Some(&(x_end, _)) = moves.last() {
+                    lines.push(scan_move(x_end + dir * overscan, 0, false, feed));
+                }
+            }
+
+            if params.bidirectional {
+                forward = !forward;
+            }
+        }
+    }
+
+    lines
+}
+
+/// Reduce scanline luma to black/white according to `mode` (in place)
+fn dither(rows: &mut [Vec<u8>], mode: DitherMode) {
+    // Error shares as (dx, dy, weight); weights are over `divisor`
+    let (kernel, divisor): (&[(isize, usize, f32)], f32) = match mode {
+        DitherMode::None => return,
+        DitherMode::Threshold => (&[], 1.0),
+        DitherMode::FloydSteinberg => {
+            (&[(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)], 16.0)
+        }
+        DitherMode::Atkinson => (
+            &[
+                (1, 0, 1.0),
+                (2, 0, 1.0),
+                (-1, 1, 1.0),
+                (0, 1, 1.0),
+                (1, 1, 1.0),
+                (0, 2, 1.0),
+            ],
+            8.0,
+        ),
+    };
+
+    let mut values: Vec<Vec<f32>> = rows
+        .iter()
+        .map(|row| row.iter().map(|&l| l as f32).collect())
+        .collect();
+    for y in 0..values.len() {
+        for x in 0..values[y].len() {
+            let old = values[y][x];
+            let new = if old < 128.0 { 0.0 } else { 255.0 };
+            rows[y][x] = new as u8;
+            let error = old - new;
+            for &(dx, dy, weight) in kernel {
+                let Some(target) = values.get_mut(y + dy) else {
+                    continue;
+                };
+                if let Some(v) = x.checked_add_signed(dx).and_then(|tx| target.get_mut(tx)) {
+                    *v += error * weight / divisor;
+                }
+            }
+        }
+    }
+}
+
+/// Laser power for a grayscale value (0 = black = full power)
+fn pixel_power(luma: u8, max_power: u32) -> u32 {
+    ((255 - luma) as f64 / 255.0 * max_power as f64).round() as u32
+}
+
+fn scan_move(x: f64, power: u32, first: bool, feed: f64) -> String {
+    if first {
+        format!("G1 X{x:.3} F{feed:.0} S{power}")
+    } else {
+        format!("G1 X{x:.3} S{power}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::document::CutMode;
+    use image::{Rgba, RgbaImage};
+
+    fn settings() -> CutSettings {
+        CutSettings {
+            feed: 3000.0,
+            power: 1000,
+            passes: 1,
+            mode: CutMode::Engrave,
+        }
+    }
+
+    #[test]
+    fn test_raster_merges_runs_and_skips_margins() {
+        // White, black, black, gray, white
+        let mut image = GrayImage::new(5, 1);
+        for (x, v) in [255u8, 0, 0, 128, 255].into_iter().enumerate() {
+            image.put_pixel(x as u32, 0, Luma([v]));
+        }
+        let bounds = BoundingBox::new(10.0, 20.0, 15.0, 20.1);
+        let params = RasterParams {
+            overscan_mm: 0.0,
+            ..RasterParams::default()
+        };
+
+        let lines = raster_lines(&image, &bounds, &settings(), &[], &params);
+        assert_eq!(
+            lines,
+            vec![
+                "G0 X11.000 Y20.050",
+                "G1 X13.000 F3000 S1000",
+                "G1 X14.000 S498",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_raster_serpentine_and_blank_rows() {
+        let mut image = GrayImage::from_pixel(2, 3, Luma([0]));
+        // Middle row blank
+        image.put_pixel(0, 1, Luma([255]));
+        image.put_pixel(1, 1, Luma([255]));
+        let bounds = BoundingBox::new(0.0, 0.0, 2.0, 3.0);
+        let params = RasterParams {
+            line_interval: 1.0,
+            bidirectional: true,
+            overscan_mm: 0.0,
+            ..RasterParams::default()
+        };
+
+        let lines = raster_lines(&image, &bounds, &settings(), &[], &params);
+        assert_eq!(
+            lines,
+            vec![
+                "G0 X0.000 Y0.500",
+                "G1 X2.000 F3000 S1000",
+                // Blank row skipped; the next row continues back from the right
+                "G0 X2.000 Y2.500",
+                "G1 X0.000 F3000 S1000",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_raster_overscan() {
+        let image = GrayImage::from_pixel(2, 2, Luma([0]));
+        let bounds = BoundingBox::new(10.0, 0.0, 12.0, 2.0);
+        let params = RasterParams {
+            line_interval: 1.0,
+            bidirectional: true,
+            overscan_mm: 3.0,
+            ..RasterParams::default()
+        };
+
+        let lines = raster_lines(&image, &bounds, &settings(), &[], &params);
+        assert_eq!(
+            lines,
+            vec![
+                "G0 X7.000 Y0.500",
+                "G1 X10.000 F3000 S0",
+                "G1 X12.000 S1000",
+                "G1 X15.000 S0",
+                // Reverse row overscans past the right edge, then the left
+                "G0 X15.000 Y1.500",
+                "G1 X12.000 F3000 S0",
+                "G1 X10.000 S1000",
+                "G1 X7.000 S0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dither_is_binary_and_preserves_tone() {
+        for mode in [DitherMode::FloydSteinberg, DitherMode::Atkinson] {
+            let mut rows = vec![vec![128u8; 32]; 32];
+            dither(&mut rows, mode);
+            assert!(rows.iter().flatten().all(|&l| l == 0 || l == 255));
+            let black = rows.iter().flatten().filter(|&&l| l == 0).count();
+            // Mid-gray comes out roughly half black
+            assert!((400..=624).contains(&black), "{mode:?}: {black} black");
+        }
+
+        let mut rows = vec![vec![100u8, 127, 128, 200]];
+        dither(&mut rows, DitherMode::Threshold);
+        assert_eq!(rows, vec![vec![0, 0, 255, 255]]);
+    }
+
+    #[test]
+    fn test_raster_dither_uses_full_power() {
+        let image = GrayImage::from_pixel(4, 1, Luma([64]));
+        let bounds = BoundingBox::new(0.0, 0.0, 4.0, 0.1);
+        let params = RasterParams {
+            overscan_mm: 0.0,
+            dither: DitherMode::Threshold,
+            ..RasterParams::default()
+        };
+
+        let lines = raster_lines(&image, &bounds, &settings(), &[], &params);
+        assert_eq!(lines, vec!["G0 X0.000 Y0.050", "G1 X4.000 F3000 S1000"]);
+    }
+
+    #[test]
+    fn test_preview_matches_scanlines() {
+        let image = DynamicImage::ImageLuma8(GrayImage::from_pixel(4, 2, Luma([64])));
+        let mut png = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let content = BitmapContent {
+            width: 4,
+            height: 2,
+            data_url: format!("data:image/png;base64,{}", STANDARD.encode(png)),
+            format: "png".into(),
+            thumbnail: None,
+        };
+        let params = RasterParams {
+            line_interval: 1.0,
+            invert: true,
+            dither: DitherMode::Threshold,
+            ..RasterParams::default()
+        };
+
+        // Three scanlines over 3mm; inverted dark gray thresholds to white
+        let bounds = BoundingBox::new(0.0, 0.0, 4.0, 3.0);
+        let url = preview_raster(&content, &bounds, &params).unwrap();
+        let preview = decode_bitmap(&BitmapContent {
+            data_url: url,
+            ..content
+        })
+        .unwrap()
+        .to_luma8();
+        assert_eq!(preview.dimensions(), (4, 3));
+        assert!(preview.pixels().all(|p| p[0] == 255));
+    }
+
+    #[test]
+    fn test_transparent_pixels_are_background() {
+        // Opaque black, transparent black, opaque black
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_fn(3, 1, |x, _| {
+            Rgba([0, 0, 0, if x == 1 { 0 } else { 255 }])
+        }));
+        let bounds = BoundingBox::new(0.0, 0.0, 3.0, 0.1);
+        let params = RasterParams {
+            overscan_mm: 0.0,
+            ..RasterParams::default()
+        };
+
+        let adjusted = adjust_image(&image, &params);
+        assert_eq!(adjusted.get_pixel(1, 0)[0], 255);
+        let lines = raster_lines(&adjusted, &bounds, &settings(), &[], &params);
+        assert_eq!(
+            lines,
+            vec![
+                "G0 X0.000 Y0.050",
+                "G1 X1.000 F3000 S1000",
+                "G1 X2.000 S0",
+                "G1 X3.000 S1000",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invert_flips_power() {
+        // A white pixel is normally skipped; inverted it burns at full power
+        let image = DynamicImage::ImageLuma8(GrayImage::from_pixel(1, 1, Luma([255])));
+        let bounds = BoundingBox::new(0.0, 0.0, 1.0, 0.1);
+        let mut params = RasterParams {
+            overscan_mm: 0.0,
+            ..RasterParams::default()
+        };
+
+        let plain = adjust_image(&image, &params);
+        assert!(raster_lines(&plain, &bounds, &settings(), &[], &params).is_empty());
+
+        params.invert = true;
+        let inverted = adjust_image(&image, &params);
+        assert_eq!(
+            raster_lines(&inverted, &bounds, &settings(), &[], &params),
+            vec!["G0 X0.000 Y0.050", "G1 X1.000 F3000 S1000"]
+        );
+    }
+
+    #[test]
+    fn test_tone_adjustments() {
+        let image = DynamicImage::ImageLuma8(GrayImage::from_fn(3, 1, |x, _| {
+            Luma([[0u8, 128, 255][x as usize]])
+        }));
+        let luma = |params: RasterParams| {
+            let gray = adjust_image(&image, &params);
+            [
+                gray.get_pixel(0, 0)[0],
+                gray.get_pixel(1, 0)[0],
+                gray.get_pixel(2, 0)[0],
+            ]
+        };
+
+        assert_eq!(luma(RasterParams::default()), [0, 128, 255]);
+        assert_eq!(
+            luma(RasterParams {
+                brightness: 0.2,
+                ..RasterParams::default()
+            }),
+            [51, 179, 255]
+        );
+        assert_eq!(
+            luma(RasterParams {
+                contrast: 1.0,
+                ..RasterParams::default()
+            }),
+            [0, 129, 255]
+        );
+        // Gamma lightens midtones only
+        let [black, mid, white] = luma(RasterParams {
+            gamma: 2.0,
+            ..RasterParams::default()
+        });
+        assert_eq!((black, white), (0, 255));
+        assert!(mid > 170);
+    }
+
+    #[test]
+    fn test_decode_rejects_non_data_url() {
+        let content = BitmapContent {
+            width: 1,
+            height: 1,
+            data_url: "file.png".into(),
+            format: "png".into(),
+            thumbnail: None,
+        };
+        assert!(matches!(
+            decode_bitmap(&content),
+            Err(GenerateError::ImageData(_))
+        ));
+    }
+}
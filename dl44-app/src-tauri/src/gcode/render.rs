@@ -0,0 +1,173 @@
+//! Rendering vector documents to bitmaps.
+//!
+//! The document's SVG source is rendered with resvg, so groups, transforms,
+//! strokes and text come out as they would in a browser, over the
+//! document's workspace bounds on a white background. Colors are reduced
+//! to gray levels, since a laser only sees how dark a pixel is.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::{DynamicImage, GrayImage, RgbaImage};
+use resvg::{tiny_skia, usvg};
+use std::io::Cursor;
+
+use super::vector::Placement;
+use super::GenerateError;
+use crate::workspace::document::{BitmapContent, BoundingBox, Document, SvgContent};
+use crate::workspace::import::thumbnail_data_url;
+
+/// Largest image a document may be rendered to (pixels)
+pub const MAX_RENDER_PIXELS: u64 = 64_000_000;
+
+const MM_PER_INCH: f64 = 25.4;
+
+/// Pixel size of `bounds` (workspace mm) rendered at `dpi`, at least 1x1
+pub fn render_dimensions(bounds: &BoundingBox, dpi: f64) -> (u32, u32) {
+    let pixels = |mm: f64| ((mm * dpi / MM_PER_INCH).ceil() as u32).max(1);
+    (pixels(bounds.width()), pixels(bounds.height()))
+}
+
+/// Render an SVG document at `dpi` over its workspace bounds
+pub fn render_svg(doc: &Document, svg: &SvgContent, dpi: f64) -> Result<GrayImage, GenerateError> {
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    let tree = usvg::Tree::from_str(&svg.raw_svg, &options)
+        .map_err(|e| GenerateError::Svg(e.to_string()))?;
+
+    let bounds = doc.workspace_bounds();
+    let (width, height) = render_dimensions(&bounds, dpi);
+    let mut pixmap = tiny_skia::Pixmap::new(width, height).ok_or_else(|| {
+        GenerateError::Svg(format!("Can't allocate a {}x{} px image", width, height))
+    })?;
+    pixmap.fill(tiny_skia::Color::WHITE);
+
+    // usvg maps the viewBox onto the SVG's size in px; undo that to get
+    // user units, which are placed like vector paths (mm)
+    let size = tree.size();
+    let user_x = svg.width / size.width() as f64;
+    let user_y = svg.height / size.height() as f64;
+    // The image spans the bounds exactly, so each axis gets its own scale
+    let scale_x = width as f64 / bounds.width().max(1e-9);
+    let scale_y = height as f64 / bounds.height().max(1e-9);
+    let placement = Placement::for_document(doc, svg);
+    let transform = tiny_skia::Transform::from_row(
+        (user_x * placement.scale * scale_x) as f32,
+        0.0,
+        0.0,
+        (user_y * placement.scale * scale_y) as f32,
+        ((placement.offset.0 - bounds.x_min) * scale_x) as f32,
+        ((placement.offset.1 - bounds.y_min) * scale_y) as f32,
+    );
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    // Opaque over white, so premultiplied RGBA is plain RGBA
+    let rgba = RgbaImage::from_raw(width, height, pixmap.take())
+        .ok_or_else(|| GenerateError::Svg("Rendered image has the wrong size".into()))?;
+    Ok(DynamicImage::ImageRgba8(rgba).to_luma8())
+}
+
+/// Bitmap content for a rendered image, as if it had been imported as PNG
+pub fn bitmap_content(image: &GrayImage) -> Result<BitmapContent, GenerateError> {
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+    Ok(BitmapContent {
+        width: image.width(),
+        height: image.height(),
+        data_url: format!("data:image/png;base64,{}", STANDARD.encode(&png)),
+        format: "png".into(),
+        thumbnail: thumbnail_data_url(&png),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::document::{DocumentKind, Transform};
+
+    fn svg_document(body: &str, transform: Transform) -> (Document, SvgContent) {
+        let svg = SvgContent {
+            width: 10.0,
+            height: 10.0,
+            paths: Vec::new(),
+            raw_svg: format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">{}</svg>"#,
+                body
+            ),
+        };
+        let doc = Document {
+            id: 1,
+            name: "shape".into(),
+            source_path: None,
+            kind: DocumentKind::Svg(svg.clone()),
+            transform,
+            visible: true,
+            locked: false,
+            original_bounds: BoundingBox::new(0.0, 0.0, 10.0, 10.0),
+            cut_settings: None,
+        };
+        (doc, svg)
+    }
+
+    fn dark(image: &GrayImage) -> usize {
+        image.pixels().filter(|p| p[0] < 128).count()
+    }
+
+    #[test]
+    fn test_render_fills_at_dpi() {
+        // 10mm square at 2x scale is 20mm: 200 px at 254 dpi
+        let transform = Transform {
+            x: 5.0,
+            y: 5.0,
+            scale: 2.0,
+            rotation: 0.0,
+        };
+        // Left half filled, with a hole cut by an opposite-winding ring
+        let (doc, svg) = svg_document(r#"<path d="M0 0 H5 V10 H0 Z M1 1 V9 H4 V1 Z"/>"#, transform);
+        let image = render_svg(&doc, &svg, 254.0).unwrap();
+        assert_eq!(image.dimensions(), (200, 200));
+        assert_eq!(image.get_pixel(5, 100)[0], 0);
+        assert_eq!(image.get_pixel(50, 100)[0], 255);
+        assert_eq!(image.get_pixel(150, 100)[0], 255);
+        assert_eq!(dark(&image), 100 * 200 - 60 * 160);
+    }
+
+    #[test]
+    fn test_render_strokes_unfilled_paths() {
+        let (doc, svg) = svg_document(
+            r##"<path d="M0 5.5 H10" fill="none" stroke="#000"/>
+                <path d="M0 0 H10 V2 H0 Z" fill="none"/>"##,
+            Transform::default(),
+        );
+        // One pixel per mm: the 1mm stroke covers the row centered on 5.5,
+        // and the outline without a stroke isn't drawn
+        let image = render_svg(&doc, &svg, MM_PER_INCH).unwrap();
+        assert_eq!(image.dimensions(), (10, 10));
+        assert_eq!(dark(&image), 10);
+        assert!((0..10).all(|x| image.get_pixel(x, 5)[0] == 0));
+    }
+
+    #[test]
+    fn test_render_groups_and_colors() {
+        // The transformed group fills the right half, the gray rect is
+        // nested in a scaled group covering the top left quarter
+        let (doc, svg) = svg_document(
+            r##"<g transform="translate(5 0)"><rect width="5" height="10"/></g>
+                <g transform="scale(0.5)"><rect width="10" height="10" fill="#808080"/></g>"##,
+            Transform::default(),
+        );
+        let image = render_svg(&doc, &svg, MM_PER_INCH).unwrap();
+        assert!((5..10).all(|x| (0..10).all(|y| image.get_pixel(x, y)[0] == 0)));
+        assert_eq!(image.get_pixel(2, 2)[0], 128);
+        assert_eq!(image.get_pixel(2, 7)[0], 255);
+    }
+
+    #[test]
+    fn test_render_rejects_invalid_svg() {
+        let (mut doc, mut svg) = svg_document("", Transform::default());
+        svg.raw_svg = "not an svg".into();
+        doc.kind = DocumentKind::Svg(svg.clone());
+        assert!(matches!(
+            render_svg(&doc, &svg, MM_PER_INCH),
+            Err(GenerateError::Svg(_))
+        ));
+    }
+}
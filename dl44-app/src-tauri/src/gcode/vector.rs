@@ -0,0 +1,135 @@
+//! Vector path G-code generation.
+
+use super::path::{flatten_path, Point, Polyline, DEFAULT_TOLERANCE};
+use super::{pass_power, push_travel};
+use crate::workspace::document::{CutSettings, Document, SvgContent};
+use crate::workspace::import::{extract_svg_paths, parse_svg_origin};
+
+/// Maps SVG user coordinates into workspace coordinates
+#[derive(Debug, Clone, Copy)]
+pub struct Placement {
+    /// SVG viewBox origin (subtracted before scaling)
+    pub origin: Point,
+    /// Workspace position of the document's top-left corner
+    pub offset: Point,
+    /// Document scale factor
+    pub scale: f64,
+}
+
+impl Placement {
+    /// Placement for an SVG document from its transform and viewBox
+    pub fn for_document(doc: &Document, svg: &SvgContent) -> Self {
+        Self {
+            origin: parse_svg_origin(&svg.raw_svg),
+            offset: (doc.transform.x, doc.transform.y),
+            scale: doc.transform.scale,
+        }
+    }
+
+    pub fn apply(&self, p: Point) -> Point {
+        (
+            self.offset.0 + (p.0 - self.origin.0) * self.scale,
+            self.offset.1 + (p.1 - self.origin.1) * self.scale,
+        )
+    }
+}
+
+/// Flatten all paths of an SVG document into workspace-space polylines.
+///
+/// Falls back to extracting shapes from the raw SVG when the document
+/// was saved without extracted paths.
+pub fn svg_polylines(doc: &Document, svg: &SvgContent) -> Vec<Polyline> {
+    let placement = Placement::for_document(doc, svg);
+    // Tolerance is in workspace mm, so shrink it in SVG units as scale grows
+    let tolerance = DEFAULT_TOLERANCE / placement.scale.max(1e-6);
+
+    let extracted;
+    let paths = if svg.paths.is_empty() {
+        extracted = extract_svg_paths(&svg.raw_svg);
+        &extracted
+    } else {
+        &svg.paths
+    };
+
+    paths
+        .iter()
+        .flat_map(|p| flatten_path(&p.d, tolerance))
+        .map(|line| line.into_iter().map(|p| placement.apply(p)).collect())
+        .collect()
+}
+
+/// Generate G-code tracing polylines with the given settings.
+///
+/// Travel uses G0 (laser off in GRBL laser mode); cutting moves use G1 with
+/// the configured power, or the pass's entry in `power_schedule`. Every
+/// pass repeats all polylines in order.
+pub fn generate_vector(
+    polylines: &[Polyline],
+    settings: &CutSettings,
+    power_schedule: &[u32],
+    safe_z: Option<f64>,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    let feed = settings.feed;
+
+    for pass in 0..settings.passes.max(1) as usize {
+        let power = pass_power(power_schedule, pass, settings.power);
+        for polyline in polylines {
+            let Some(&(x0, y0)) = polyline.first() else {
+                continue;
+            };
+            push_travel(&mut lines, x0, y0, safe_z);
+            for (i, &(x, y)) in polyline.iter().enumerate().skip(1) {
+                if i == 1 {
+                    lines.push(format!("G1 X{x:.3} Y{y:.3} F{feed:.0} S{power}"));
+                } else {
+                    lines.push(format!("G1 X{x:.3} Y{y:.3}"));
+                }
+            }
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::document::CutMode;
+
+    #[test]
+    fn test_generate_vector_passes() {
+        let settings = CutSettings {
+            feed: 600.0,
+            power: 800,
+            passes: 2,
+            mode: CutMode::Cut,
+        };
+        let lines = generate_vector(
+            &[vec![(0.0, 0.0), (10.0, 0.0), (10.0, 5.0)]],
+            &settings,
+            &[],
+            None,
+        );
+        assert_eq!(
+            lines[..3],
+            [
+                "G0 X0.000 Y0.000",
+                "G1 X10.000 Y0.000 F600 S800",
+                "G1 X10.000 Y5.000",
+            ]
+        );
+        assert_eq!(lines.len(), 6);
+    }
+
+    #[test]
+    fn test_placement_applies_origin_and_scale() {
+        let placement = Placement {
+            origin: (-5.0, 10.0),
+            offset: (100.0, 50.0),
+            scale: 2.0,
+        };
+        assert_eq!(placement.apply((-5.0, 10.0)), (100.0, 50.0));
+        assert_eq!(placement.apply((0.0, 20.0)), (110.0, 70.0));
+    }
+}
@@ -0,0 +1,136 @@
+//! Connection lifecycle history.
+//!
+//! A short, timestamped record of connect attempts, welcomes, disconnects
+//! and lost connections, for support questions like "it keeps dropping
+//! every 10 minutes". Unlike the serial log it holds no traffic, only one
+//! entry per lifecycle step, and lives in memory for the session.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Entries kept; older ones are dropped
+pub const MAX_CONNECTION_EVENTS: usize = 200;
+
+/// Step in a connection's lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConnectionEventKind {
+    /// Opening a port
+    ConnectAttempt,
+    /// Opening a port again after the last connection was lost
+    Reconnect,
+    /// The port is open and the device answered
+    Connected,
+    /// The device sent its welcome banner (on connect or after a reset)
+    WelcomeReceived,
+    /// Disconnected on request
+    Disconnected,
+    /// A connect attempt failed
+    ConnectFailed,
+    /// The device went away while connected (cable pulled, adapter reset)
+    ConnectionLost,
+}
+
+/// One lifecycle entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionEvent {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub kind: ConnectionEventKind,
+    /// Port and baud rate, welcome banner, or error message
+    pub message: Option<String>,
+}
+
+/// Bounded history of connection events, plus those not yet sent to the UI
+#[derive(Debug, Default)]
+pub struct ConnectionLog {
+    events: VecDeque<ConnectionEvent>,
+    unsent: VecDeque<ConnectionEvent>,
+}
+
+impl ConnectionLog {
+    /// Add an event stamped with the current time
+    pub fn record(&mut self, kind: ConnectionEventKind, message: Option<String>) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        log::info!(
+            "Connection event {:?}{}",
+            kind,
+            message
+                .as_deref()
+                .map_or(String::new(), |m| format!(": {}", m))
+        );
+        let event = ConnectionEvent {
+            timestamp_ms,
+            kind,
+            message,
+        };
+        for queue in [&mut self.events, &mut self.unsent] {
+            if queue.len() == MAX_CONNECTION_EVENTS {
+                queue.pop_front();
+            }
+            queue.push_back(event.clone());
+        }
+    }
+
+    /// Events in chronological order
+    pub fn events(&self) -> Vec<ConnectionEvent> {
+        self.events.iter().cloned().collect()
+    }
+
+    /// Events recorded since the last call, oldest first
+    pub fn take_unsent(&mut self) -> Vec<ConnectionEvent> {
+        self.unsent.drain(..).collect()
+    }
+
+    /// Whether the last connection ended by being lost, with no
+    /// successful connection or disconnect since (failed retries don't
+    /// count)
+    pub fn connection_was_lost(&self) -> bool {
+        use ConnectionEventKind::*;
+        self.events
+            .iter()
+            .rev()
+            .find(|e| matches!(e.kind, Connected | Disconnected | ConnectionLost))
+            .is_some_and(|e| e.kind == ConnectionLost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ConnectionEventKind::*;
+
+    #[test]
+    fn test_log_is_bounded_and_tracks_unsent() {
+        let mut log = ConnectionLog::default();
+        log.record(ConnectAttempt, Some("/dev/ttyUSB0 @ 115200".into()));
+        log.record(Connected, None);
+        assert_eq!(log.take_unsent().len(), 2);
+        assert!(log.take_unsent().is_empty());
+
+        for _ in 0..MAX_CONNECTION_EVENTS {
+            log.record(WelcomeReceived, None);
+        }
+        let events = log.events();
+        assert_eq!(events.len(), MAX_CONNECTION_EVENTS);
+        assert!(events.iter().all(|e| e.kind == WelcomeReceived));
+        assert_eq!(log.take_unsent().len(), MAX_CONNECTION_EVENTS);
+    }
+
+    #[test]
+    fn test_lost_connection_survives_failed_retries() {
+        let mut log = ConnectionLog::default();
+        assert!(!log.connection_was_lost());
+        log.record(Connected, None);
+        log.record(ConnectionLost, Some("Serial device disconnected".into()));
+        log.record(Reconnect, None);
+        log.record(ConnectFailed, Some("Port not found".into()));
+        assert!(log.connection_was_lost());
+
+        log.record(Reconnect, None);
+        log.record(Connected, None);
+        assert!(!log.connection_was_lost());
+    }
+}
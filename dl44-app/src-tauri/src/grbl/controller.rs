@@ -6,16 +6,65 @@
 //! access issues and providing centralized timeout handling.
 
 use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
-use super::protocol;
-use super::serial::PortInfo;
-use super::status::{MachineState, MachineStatus};
-use super::worker::{WorkerError, WorkerHandle, HOMING_TIMEOUT_MS};
+use super::connection_log::{ConnectionEvent, ConnectionEventKind, ConnectionLog};
+use super::protocol::{self, FirmwareVersion, ResetKind, Response};
+use super::registry::DeviceId;
+use super::resume::ResumeState;
+use super::serial::{ConnectErrorKind, PortInfo};
+use super::serial_log::SerialLog;
+use super::status::{
+    FeedRates, MachineState, MachineStatus, Overrides, Parameters, ParserState, Position,
+};
+use super::step::{StepSession, StepStatus};
+use super::stream::{self, StreamJob, StreamLineError, StreamProgress};
+use super::usage::{JobUsage, UsageCounter};
+use super::worker::{WorkerError, WorkerHandle, HOMING_TIMEOUT_MS, QUERY_TIMEOUT_MS};
+
+/// Timeout for the laser-off command sent before disconnecting
+const SHUTDOWN_COMMAND_TIMEOUT_MS: u64 = 500;
+
+/// Slack added to the expected duration of a frame edge before timing out
+const FRAME_EDGE_MARGIN_MS: u64 = 5000;
+
+/// Max S value assumed when `$30` hasn't been read
+pub const DEFAULT_MAX_POWER: u32 = 1000;
+
+/// Queued planner blocks below which a running job is about to starve
+const LOW_PLANNER_BLOCKS: u32 = 2;
+
+/// Time GRBL may take to accept a stepped line. Dwells and other lines
+/// that wait for the planner to empty only answer once motion finishes.
+const STEP_LINE_TIMEOUT_MS: u64 = 30_000;
+
+/// Slack when checking targets against machine travel (mm)
+const TRAVEL_TOLERANCE: f64 = 1e-6;
+
+/// How long a serial port list is reused before ports are enumerated again
+const PORT_LIST_MAX_AGE_MS: u64 = 1000;
+
+/// Longest wait for the machine to stop when jogging during a hold: for
+/// the queued job moves to finish, or for a jog to end
+const HOLD_JOG_IDLE_TIMEOUT_MS: u64 = 120_000;
+
+/// Status poll interval while waiting for the machine to stop
+const IDLE_POLL_MS: u64 = 100;
+
+/// How long [`Controller::stop_all`] lets a feed hold decelerate before
+/// resetting anyway (ms)
+const STOP_HOLD_TIMEOUT_MS: u64 = 2000;
 
 /// Controller errors (UI-facing)
-#[derive(Error, Debug, Clone, serde::Serialize)]
+#[derive(Error, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum ControllerError {
     #[error("Serial error: {0}")]
     Serial(String),
@@ -23,6 +72,9 @@ pub enum ControllerError {
     #[error("Not connected to device")]
     NotConnected,
 
+    #[error("Device disconnected: {0}")]
+    Disconnected(String),
+
     #[error("Already connected")]
     AlreadyConnected,
 
@@ -38,15 +90,26 @@ pub enum ControllerError {
     #[error("Invalid state for operation: {0}")]
     InvalidState(String),
 
+    #[error("File error: {0}")]
+    File(String),
+
+    #[error("Command cancelled")]
+    Cancelled,
+
     #[error("Internal error: {0}")]
     Internal(String),
+
+    #[error("Unknown device {0}")]
+    UnknownDevice(DeviceId),
 }
 
 impl From<WorkerError> for ControllerError {
     fn from(e: WorkerError) -> Self {
         match e {
-            WorkerError::OpenFailed(msg) => ControllerError::Serial(msg),
+            WorkerError::OpenFailed(_, msg) => ControllerError::Serial(msg),
+            e @ WorkerError::NoResponse => ControllerError::Serial(e.to_string()),
             WorkerError::Io(msg) => ControllerError::Serial(msg),
+            WorkerError::Disconnected(msg) => ControllerError::Disconnected(msg),
             WorkerError::NotConnected => ControllerError::NotConnected,
             WorkerError::Timeout { attempts } => ControllerError::Timeout(attempts),
             WorkerError::GrblError(code) => ControllerError::GrblError(code),
@@ -54,6 +117,8 @@ impl From<WorkerError> for ControllerError {
             WorkerError::WorkerDead => {
                 ControllerError::Internal("Worker thread not responding".into())
             }
+            WorkerError::Busy => ControllerError::InvalidState("A job is running".into()),
+            WorkerError::Cancelled => ControllerError::Cancelled,
         }
     }
 }
@@ -63,8 +128,15 @@ impl From<WorkerError> for ControllerError {
 pub enum ConnectionState {
     Disconnected,
     Connecting,
-    Connected { port: String, baud: u32 },
-    Error(String),
+    Connected {
+        port: String,
+        baud: u32,
+    },
+    /// Connect failed or the device was lost; `kind` says why
+    Error {
+        message: String,
+        kind: ConnectErrorKind,
+    },
 }
 
 impl Default for ConnectionState {
@@ -78,14 +150,322 @@ impl Default for ConnectionState {
 struct ControllerState {
     connection: ConnectionState,
     status: MachineStatus,
-    last_error: Option<String>,
+    last_error: Option<ControllerError>,
     welcome_message: Option<String>,
+    /// Firmware version parsed from the welcome message
+    firmware: Option<FirmwareVersion>,
     /// Alarm code if device entered alarm during polling (with unique ID for dedup)
     pending_alarm: Option<(u32, u64)>, // (alarm_code, alarm_id)
     /// Counter for generating unique alarm IDs
     alarm_id_counter: u64,
     /// Whether the last status poll got a fresh response
     status_is_fresh: bool,
+    /// When the last fresh status report arrived
+    last_status_at: Option<Instant>,
+    /// Last G-code parser state read via `$G`
+    parser_state: Option<ParserState>,
+    /// GRBL settings read via `$$` (setting number -> raw value)
+    settings: BTreeMap<u32, String>,
+    /// Build info lines read via `$I`
+    build_info: Vec<String>,
+    /// Progress of the current (or last) streamed program
+    job: Option<Arc<Mutex<StreamProgress>>>,
+    /// Laser usage of the current job, until recorded when it ends
+    job_usage: Option<JobUsage>,
+    /// Persisted usage totals jobs are recorded into (shared by devices)
+    usage: Option<Arc<UsageCounter>>,
+    /// Z height to raise to before travel moves (None = don't touch Z)
+    safe_z: Option<f64>,
+    /// Homing cycle has completed since connect/reset
+    is_homed: bool,
+    /// Planner size, learned as the most free blocks seen in a `Bf` report
+    planner_capacity: u32,
+    /// Park position in machine coordinates (mm)
+    park_position: (f64, f64),
+    /// Jog presets addressed by index from keyboard/button input
+    jog_presets: Vec<protocol::JogPreset>,
+    /// Overrides from a poll that changed them, until taken for the UI
+    override_change: Option<Overrides>,
+    /// Program being sent line by line in step mode
+    step: Option<StepSession>,
+    /// Line rate smoothing for streamed jobs (None = stream default)
+    rate_smoothing: Option<f64>,
+    /// Line ending last applied to the worker
+    line_ending: protocol::LineEnding,
+    /// Refuse incremental jogs that leave the homed travel across machine
+    /// zero (see [`ControllerState::check_jog_travel`])
+    jog_travel_check: bool,
+    /// Lowest feed accepted for jogs and frames (units/min)
+    min_feed: f64,
+    /// Last serial port list and when it was enumerated
+    port_list: Option<(Instant, Vec<PortInfo>)>,
+    /// Connection lifecycle history for support
+    connection_log: ConnectionLog,
+    /// Jogging during a held job (see [`Controller::jog_during_hold`])
+    hold_jog: Option<HoldJog>,
+    /// A held job's remaining lines are kept back after a jog was refused
+    /// for its queued moves, so a resume runs only those moves
+    hold_drain: bool,
+    /// Job the machine finished running, until taken for the UI
+    job_complete: Option<StreamProgress>,
+}
+
+/// A held job set aside for jogging
+#[derive(Debug, Clone)]
+struct HoldJog {
+    /// Machine position where the job stopped, returned to before resuming
+    resume_at: Position,
+    /// Spindle mode (`M3`/`M4`) turned off for jogging, restored on resume
+    spindle: Option<String>,
+    /// The laser is known to be off (`M5` sent); jogs are refused until then
+    laser_off: bool,
+    /// Fastest feed jogged at, used for the move back (mm/min)
+    feed: f64,
+}
+
+impl ControllerState {
+    /// Add the current job's laser usage to the totals. Called once the
+    /// job has ended, or before its progress is dropped.
+    fn record_job_usage(&mut self) {
+        if let (Some(job), Some(usage)) = (self.job_usage.take(), &self.usage) {
+            usage.record(&job);
+        }
+    }
+
+    /// Mark a fully acknowledged job finished once the machine has run it:
+    /// GRBL reported the program end, or the status is Idle with an empty
+    /// planner (or no `Bf` to tell). Call with a fresh status.
+    fn track_job_finished(&mut self) {
+        let Some(job) = &self.job else {
+            return;
+        };
+        let mut progress = job.lock();
+        if progress.state != stream::StreamState::Completed || progress.finished {
+            return;
+        }
+        let planner_empty = match self.status.buffer {
+            Some((free, _)) => free >= self.planner_capacity,
+            None => true,
+        };
+        if progress.program_end || (self.status.state == MachineState::Idle && planner_empty) {
+            progress.finished = true;
+            self.job_complete = Some(progress.clone());
+        }
+    }
+
+    /// Whether the device reports positions in inches (`$13=1`)
+    fn reports_inches(&self) -> bool {
+        self.settings.get(&13).is_some_and(|v| v.trim() == "1")
+    }
+
+    /// Current feed rate in both units. None until `$13` is known: until
+    /// then a report in inches can't be told from one in mm.
+    fn feed_rates(&self) -> Option<FeedRates> {
+        if !self.settings.contains_key(&13) {
+            return None;
+        }
+        // Reports in inches were converted to mm when polled
+        self.status
+            .feed_rate
+            .map(|feed| FeedRates::new(feed, protocol::Units::Mm))
+    }
+
+    /// After `$X`: drop the pending alarm if a fresh status report shows
+    /// the device left Alarm. Returns whether it did.
+    fn confirm_unlocked(&mut self) -> bool {
+        let unlocked = self.status_is_fresh && self.status.state != MachineState::Alarm;
+        if unlocked {
+            self.pending_alarm = None;
+        }
+        unlocked
+    }
+
+    /// Parse a cached numeric GRBL setting.
+    fn setting_f64(&self, number: u32) -> Option<f64> {
+        self.settings.get(&number)?.trim().parse().ok()
+    }
+
+    /// Whether homing is enabled according to cached settings (`$22`).
+    fn homing_enabled(&self) -> bool {
+        self.setting_f64(22).is_some_and(|v| v != 0.0)
+    }
+
+    /// Whether an axis travels in positive machine coordinates once homed.
+    ///
+    /// GRBL's machine space is negative from machine zero, except on
+    /// builds with `HOMING_FORCE_SET_ORIGIN` (`$I` option `Z`), where
+    /// axes homing toward negative (`$23` bit set) travel positive. Until
+    /// build info has been read, the stock layout is assumed.
+    fn homed_travel_positive(&self, axis: usize) -> bool {
+        let mask = self.setting_f64(23).unwrap_or(0.0) as u32;
+        mask & (1 << axis) != 0 && protocol::has_build_option(&self.build_info, 'Z')
+    }
+
+    /// Check a machine coordinate against an axis's `$130`-`$132` travel.
+    ///
+    /// Travel runs from machine zero toward the homed side: `-travel..0`
+    /// on stock GRBL, `0..travel` where [`Self::homed_travel_positive`].
+    /// Returns why the coordinate is out of range, or None when it's
+    /// within it or the travel isn't known.
+    fn travel_violation(&self, axis: usize, machine: f64) -> Option<String> {
+        let setting = 130 + axis as u32;
+        let travel = self.setting_f64(setting)?;
+        let (min, max) = if self.homed_travel_positive(axis) {
+            (0.0, travel)
+        } else {
+            (-travel, 0.0)
+        };
+        (machine < min - TRAVEL_TOLERANCE || machine > max + TRAVEL_TOLERANCE).then(|| {
+            format!(
+                "outside the {:.3} to {:.3} mm travel (${})",
+                min, max, setting
+            )
+        })
+    }
+
+    /// Refuse an incremental jog that would cross machine zero out of the
+    /// homed travel, as when jogging into the homing corner.
+    ///
+    /// Only applies once homed with homing enabled (`$22`), since machine
+    /// zero means nothing otherwise, and can be turned off for machines
+    /// that travel both ways.
+    fn check_jog_travel(&self, deltas: [Option<f64>; 3]) -> Result<(), ControllerError> {
+        if !self.jog_travel_check || !self.homing_enabled() || !self.is_homed {
+            return Ok(());
+        }
+        let pos = &self.status.machine_pos;
+        for (axis, (delta, current)) in deltas.into_iter().zip([pos.x, pos.y, pos.z]).enumerate() {
+            let Some(delta) = delta else {
+                continue;
+            };
+            let target = current + delta;
+            let positive = self.homed_travel_positive(axis);
+            let outside = if positive {
+                target < -TRAVEL_TOLERANCE
+            } else {
+                target > TRAVEL_TOLERANCE
+            };
+            if outside {
+                return Err(ControllerError::InvalidState(format!(
+                    "Jog would take {} to machine {:.3}, across machine zero out of the \
+                     homed travel ({} side); turn off the jog travel check if this \
+                     machine travels both ways",
+                    ["X", "Y", "Z"][axis],
+                    target,
+                    if positive { "positive" } else { "negative" }
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Maximum S value from `$30`, or [`DEFAULT_MAX_POWER`] if unknown.
+    fn max_power(&self) -> u32 {
+        self.setting_f64(30)
+            .filter(|max| *max > 0.0)
+            .map_or(DEFAULT_MAX_POWER, |max| max.round() as u32)
+    }
+
+    /// Feed for a frame trace, capped at the max rate (`$110`/`$111`) of
+    /// the axis with the longer side. Unknown rates leave `feed` unchanged.
+    fn frame_feed(&self, feed: f64, width: f64, height: f64, units: protocol::Units) -> f64 {
+        let setting = if width >= height { 110 } else { 111 };
+        let Some(max_mm) = self.setting_f64(setting).filter(|max| *max > 0.0) else {
+            return feed;
+        };
+        let max = match units {
+            protocol::Units::Mm => max_mm,
+            protocol::Units::Inches => max_mm / 25.4,
+        };
+        if feed > max {
+            log::info!(
+                "Frame feed {:.0} clamped to ${} max rate {:.0}",
+                feed,
+                setting,
+                max
+            );
+            max
+        } else {
+            feed
+        }
+    }
+
+    /// Refuse a feed below the configured minimum, or one that would be
+    /// sent as zero with `decimals` places (as `F0.000`)
+    fn check_feed(&self, feed: f64, decimals: usize) -> Result<(), ControllerError> {
+        if !feed.is_finite() || feed < self.min_feed {
+            return Err(ControllerError::InvalidState(format!(
+                "Feed rate {} is below the minimum of {}",
+                feed, self.min_feed
+            )));
+        }
+        let sent = format!("{:.*}", decimals, feed);
+        if !matches!(sent.parse::<f64>(), Ok(f) if f > 0.0) {
+            return Err(ControllerError::InvalidState(format!(
+                "Feed rate {} would be sent as F{}",
+                feed, sent
+            )));
+        }
+        Ok(())
+    }
+
+    /// Refuse a frame whose edges (`x` and `y` as min, max in `units`)
+    /// leave the `$130`/`$131` travel on the homed side of machine zero (see
+    /// [`Self::travel_violation`]). An axis with unknown travel isn't checked.
+    fn check_frame_travel(
+        &self,
+        x: (f64, f64),
+        y: (f64, f64),
+        units: protocol::Units,
+    ) -> Result<(), ControllerError> {
+        let scale = match units {
+            protocol::Units::Mm => 1.0,
+            protocol::Units::Inches => 25.4,
+        };
+        for (i, (axis, (min, max))) in [("X", x), ("Y", y)].into_iter().enumerate() {
+            if self.setting_f64(130 + i as u32).is_none() {
+                continue;
+            }
+            let Some(offset) = self.status.work_offset else {
+                return Err(ControllerError::InvalidState(
+                    "Work offset not reported yet - cannot check travel limits".into(),
+                ));
+            };
+            let offset = if i == 0 { offset.x } else { offset.y };
+            for value in [min, max] {
+                let machine = value * scale + offset;
+                if let Some(why) = self.travel_violation(i, machine) {
+                    return Err(ControllerError::InvalidState(format!(
+                        "Frame {} {:.3} (machine {:.3}) is {}",
+                        axis, value, machine, why
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Keep the cached overrides for a report without `Ov`, and note a
+    /// report whose `Ov` differs from them.
+    ///
+    /// GRBL only includes `Ov` every 10-20 reports or right after a change,
+    /// so a missing field means "unchanged" rather than "unknown".
+    fn track_overrides(&mut self, status: &mut MachineStatus) {
+        match status.overrides {
+            None => status.overrides = self.status.overrides,
+            Some(overrides) if self.status.overrides != Some(overrides) => {
+                self.override_change = Some(overrides);
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// Current spindle/laser power as a percentage of `$30` (max spindle speed).
+    fn spindle_power_percent(&self) -> Option<f64> {
+        let max = self.setting_f64(30).filter(|max| *max > 0.0)?;
+        let speed = self.status.spindle_speed?;
+        Some(speed / max * 100.0)
+    }
 }
 
 /// GRBL controller instance.
@@ -96,6 +476,17 @@ struct ControllerState {
 pub struct Controller {
     worker: WorkerHandle,
     state: Mutex<ControllerState>,
+    /// Set while a motion command is being sent (see [`Controller::begin_motion`])
+    motion_busy: AtomicBool,
+}
+
+/// Held for the duration of a motion command; clears the busy flag on drop
+struct MotionGuard<'a>(&'a AtomicBool);
+
+impl Drop for MotionGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
 }
 
 impl Controller {
@@ -103,7 +494,13 @@ impl Controller {
     pub fn new() -> Self {
         Self {
             worker: WorkerHandle::spawn(),
-            state: Mutex::new(ControllerState::default()),
+            state: Mutex::new(ControllerState {
+                jog_presets: protocol::default_jog_presets(),
+                jog_travel_check: true,
+                min_feed: protocol::DEFAULT_MIN_FEED,
+                ..ControllerState::default()
+            }),
+            motion_busy: AtomicBool::new(false),
         }
     }
 
@@ -112,32 +509,103 @@ impl Controller {
         Arc::new(Self::new())
     }
 
+    /// Persistent log of serial traffic (disabled until configured)
+    pub fn serial_log(&self) -> Arc<SerialLog> {
+        self.worker.serial_log()
+    }
+
     /// List available serial ports.
     ///
+    /// A list enumerated within [`PORT_LIST_MAX_AGE_MS`] is reused unless
+    /// `refresh` is set, so repeated calls from the UI don't rescan.
+    ///
     /// Note: This doesn't use the worker since port enumeration is fast.
-    pub fn list_ports(&self) -> Result<Vec<PortInfo>, ControllerError> {
-        super::serial::list_ports().map_err(|e| ControllerError::Serial(e.to_string()))
+    pub fn list_ports(&self, refresh: bool) -> Result<Vec<PortInfo>, ControllerError> {
+        if !refresh {
+            if let Some((at, ports)) = &self.state.lock().port_list {
+                if at.elapsed() < Duration::from_millis(PORT_LIST_MAX_AGE_MS) {
+                    return Ok(ports.clone());
+                }
+            }
+        }
+        let ports =
+            super::serial::list_ports().map_err(|e| ControllerError::Serial(e.to_string()))?;
+        self.state.lock().port_list = Some((Instant::now(), ports.clone()));
+        Ok(ports)
     }
 
     /// Connect to a GRBL device.
+    ///
+    /// Blocks until the device's welcome message arrives (or times out).
     pub fn connect(&self, port: &str, baud_rate: u32) -> Result<(), ControllerError> {
-        // Check if already connected
-        {
-            let state = self.state.lock();
-            if matches!(state.connection, ConnectionState::Connected { .. }) {
-                return Err(ControllerError::AlreadyConnected);
-            }
-        }
+        self.begin_connect(port, baud_rate)?;
+        self.finish_connect(port, baud_rate)
+    }
 
-        // Update state to connecting
-        {
-            let mut state = self.state.lock();
-            state.connection = ConnectionState::Connecting;
-            state.last_error = None;
-            state.pending_alarm = None;
+    /// Start connecting without waiting for the device.
+    ///
+    /// Returns `ConnectionState::Connecting` immediately; the connection
+    /// completes on a background thread, which then calls `on_done` with the
+    /// outcome and the resulting connection state.
+    pub fn connect_async<F>(
+        self: &Arc<Self>,
+        port: String,
+        baud_rate: u32,
+        on_done: F,
+    ) -> Result<ConnectionState, ControllerError>
+    where
+        F: FnOnce(Result<(), ControllerError>, ConnectionState) + Send + 'static,
+    {
+        self.begin_connect(&port, baud_rate)?;
+        let controller = self.clone();
+        thread::Builder::new()
+            .name("connect".into())
+            .spawn(move || {
+                let result = controller.finish_connect(&port, baud_rate);
+                on_done(result, controller.connection_state());
+            })
+            .map_err(|e| {
+                let mut state = self.state.lock();
+                state.connection = ConnectionState::Error {
+                    message: e.to_string(),
+                    kind: ConnectErrorKind::Other(e.to_string()),
+                };
+                state
+                    .connection_log
+                    .record(ConnectionEventKind::ConnectFailed, Some(e.to_string()));
+                ControllerError::Internal(format!("Failed to spawn connect thread: {}", e))
+            })?;
+        Ok(ConnectionState::Connecting)
+    }
+
+    /// Claim the connection for a new connect attempt.
+    fn begin_connect(&self, port: &str, baud_rate: u32) -> Result<(), ControllerError> {
+        let mut state = self.state.lock();
+        match state.connection {
+            ConnectionState::Connected { .. } => return Err(ControllerError::AlreadyConnected),
+            ConnectionState::Connecting => {
+                return Err(ControllerError::InvalidState(
+                    "A connection attempt is already in progress".into(),
+                ))
+            }
+            _ => {}
         }
+        state.connection = ConnectionState::Connecting;
+        state.last_error = None;
+        state.pending_alarm = None;
+        let kind = if state.connection_log.connection_was_lost() {
+            ConnectionEventKind::Reconnect
+        } else {
+            ConnectionEventKind::ConnectAttempt
+        };
+        state
+            .connection_log
+            .record(kind, Some(format!("{} @ {}", port, baud_rate)));
+        Ok(())
+    }
 
-        // Attempt connection via worker
+    /// Open the port and wait for the welcome message.
+    fn finish_connect(&self, port: &str, baud_rate: u32) -> Result<(), ControllerError> {
         match self.worker.connect(port, baud_rate) {
             Ok(welcome_msg) => {
                 let mut state = self.state.lock();
@@ -145,22 +613,45 @@ impl Controller {
                     port: port.to_string(),
                     baud: baud_rate,
                 };
+                state.connection_log.record(
+                    ConnectionEventKind::Connected,
+                    Some(format!("{} @ {}", port, baud_rate)),
+                );
                 if !welcome_msg.is_empty() {
+                    state.firmware = protocol::parse_welcome(&welcome_msg);
+                    state.connection_log.record(
+                        ConnectionEventKind::WelcomeReceived,
+                        Some(welcome_msg.clone()),
+                    );
                     state.welcome_message = Some(welcome_msg);
                 }
+                // Reports carry `Ov` only now and then. GRBL resets the
+                // overrides to 100% with the welcome, and includes `Ov` in
+                // its first report after; without a reset this holds until
+                // a report says otherwise.
+                state.status.overrides = Some(Overrides::NORMAL);
                 Ok(())
             }
             Err(e) => {
-                let error_msg = e.to_string();
                 let mut state = self.state.lock();
-                state.connection = ConnectionState::Error(error_msg.clone());
-                state.last_error = Some(error_msg);
-                Err(e.into())
+                state.connection = ConnectionState::Error {
+                    message: e.to_string(),
+                    kind: e.connect_error_kind(),
+                };
+                state
+                    .connection_log
+                    .record(ConnectionEventKind::ConnectFailed, Some(e.to_string()));
+                let error = ControllerError::from(e);
+                state.last_error = Some(error.clone());
+                Err(error)
             }
         }
     }
 
     /// Disconnect from the device.
+    ///
+    /// Turns the laser off first (see [`Controller::laser_off_for_disconnect`]),
+    /// so disconnecting never leaves it firing.
     pub fn disconnect(&self) -> Result<(), ControllerError> {
         // Check if connected
         {
@@ -170,16 +661,71 @@ impl Controller {
             }
         }
 
+        self.laser_off_for_disconnect();
         self.worker.disconnect()?;
 
         let mut state = self.state.lock();
         state.connection = ConnectionState::Disconnected;
+        state
+            .connection_log
+            .record(ConnectionEventKind::Disconnected, None);
+        Self::clear_session(&mut state);
+
+        Ok(())
+    }
+
+    /// Forget everything learned from the device during a connection
+    fn clear_session(state: &mut ControllerState) {
         state.status = MachineStatus::default();
         state.welcome_message = None;
+        state.firmware = None;
         state.pending_alarm = None;
         state.status_is_fresh = false;
+        state.last_status_at = None;
+        state.parser_state = None;
+        state.is_homed = false;
+        state.settings.clear();
+        state.build_info.clear();
+        state.planner_capacity = 0;
+        state.step = None;
+        state.hold_jog = None;
+        state.hold_drain = false;
+        state.record_job_usage();
+    }
 
-        Ok(())
+    /// Record a worker error and convert it for the UI.
+    ///
+    /// A lost device means the worker has already dropped the port, so the
+    /// connection moves to `ConnectionState::Error` and the session is
+    /// cleared as if disconnected. Timeouts and other errors leave the
+    /// connection alone.
+    fn worker_error(&self, e: WorkerError) -> ControllerError {
+        let mut state = self.state.lock();
+        if let WorkerError::Disconnected(_) = &e {
+            log::warn!("{}", e);
+            state.connection = ConnectionState::Error {
+                message: e.to_string(),
+                kind: e.connect_error_kind(),
+            };
+            state
+                .connection_log
+                .record(ConnectionEventKind::ConnectionLost, Some(e.to_string()));
+            Self::clear_session(&mut state);
+        }
+        let error = ControllerError::from(e);
+        state.last_error = Some(error.clone());
+        error
+    }
+
+    /// Remember `error` as the last error and hand it back.
+    fn record_error(&self, error: ControllerError) -> ControllerError {
+        self.state.lock().last_error = Some(error.clone());
+        error
+    }
+
+    /// Most recent error, with its variant for programmatic handling.
+    pub fn last_error(&self) -> Option<LastError> {
+        self.state.lock().last_error.as_ref().map(LastError::from)
     }
 
     /// Get current connection state.
@@ -187,6 +733,16 @@ impl Controller {
         self.state.lock().connection.clone()
     }
 
+    /// Connection lifecycle events of this session, oldest first
+    pub fn connection_events(&self) -> Vec<ConnectionEvent> {
+        self.state.lock().connection_log.events()
+    }
+
+    /// Connection events recorded since the last call, for the UI
+    pub fn take_new_connection_events(&self) -> Vec<ConnectionEvent> {
+        self.state.lock().connection_log.take_unsent()
+    }
+
     /// Check if connected.
     pub fn is_connected(&self) -> bool {
         matches!(
@@ -210,10 +766,44 @@ impl Controller {
 
                 // Update freshness indicator
                 state.status_is_fresh = result.is_fresh;
+                if result.is_fresh {
+                    state.last_status_at = Some(Instant::now());
+                }
 
                 // Update status if we got one
-                if let Some(status) = result.status {
+                if let Some(mut status) = result.status {
+                    // Device reports inches; keep the cache in mm
+                    if state.reports_inches() {
+                        status.convert_from_inches();
+                    }
+                    if let Some((planner_free, _)) = status.buffer {
+                        state.planner_capacity = state.planner_capacity.max(planner_free);
+                    }
+                    state.track_overrides(&mut status);
+                    if let Some(wco) = state.status.work_offset {
+                        status.fill_work_offset(wco);
+                    }
                     state.status = status;
+                    if result.is_fresh {
+                        let job_running = state
+                            .job
+                            .as_ref()
+                            .is_some_and(|job| job.lock().is_running());
+                        let ControllerState {
+                            status, job_usage, ..
+                        } = &mut *state;
+                        if let Some(job_usage) = job_usage {
+                            job_usage.sample(status, Instant::now());
+                        }
+                        if !job_running {
+                            state.record_job_usage();
+                        }
+                        state.track_job_finished();
+                    }
+                    // Steppers may drift while asleep; homing must be redone
+                    if state.status.state == MachineState::Sleep {
+                        state.is_homed = false;
+                    }
                     // Clear stale alarm if we have a fresh, non-alarm state
                     if result.is_fresh && state.status.state != MachineState::Alarm {
                         state.pending_alarm = None;
@@ -230,147 +820,975 @@ impl Controller {
                     if should_set {
                         state.alarm_id_counter += 1;
                         state.pending_alarm = Some((alarm_code, state.alarm_id_counter));
-                        state.last_error = Some(format!("ALARM:{}", alarm_code));
+                        state.last_error = Some(ControllerError::Alarm(alarm_code));
                     }
                 }
 
                 // Record error if we saw one
                 if let Some(error_code) = result.error {
-                    state.last_error = Some(format!("error:{}", error_code));
+                    state.last_error = Some(ControllerError::GrblError(error_code));
                 }
 
                 Ok(state.status.clone())
             }
             Err(e) => {
-                let mut state = self.state.lock();
-                state.last_error = Some(e.to_string());
-                state.status_is_fresh = false;
-                Err(e.into())
+                self.state.lock().status_is_fresh = false;
+                Err(self.worker_error(e))
             }
         }
     }
 
+    /// Take the job the machine last finished running, if not taken yet
+    /// (for the `job-complete` event).
+    pub fn take_job_complete(&self) -> Option<StreamProgress> {
+        self.state.lock().job_complete.take()
+    }
+
     /// Get cached machine status (without polling).
     pub fn status(&self) -> MachineStatus {
         self.state.lock().status.clone()
     }
 
-    /// Send home command.
-    ///
-    /// Uses a longer timeout since homing can take 30+ seconds on large machines.
-    pub fn home(&self) -> Result<(), ControllerError> {
-        if !self.is_connected() {
-            return Err(ControllerError::NotConnected);
-        }
+    /// Override percentages, if a poll saw them change since the last call.
+    pub fn take_override_change(&self) -> Option<Overrides> {
+        self.state.lock().override_change.take()
+    }
 
-        // Homing: no retries (it either works or alarms), long timeout
-        self.worker
-            .send_command_with_policy(protocol::system::HOME, 0, HOMING_TIMEOUT_MS)
-            .map_err(|e| {
-                let mut state = self.state.lock();
-                state.last_error = Some(e.to_string());
-                e.into()
+    /// Query the G-code parser modal state (`$G`) and cache it.
+    pub fn read_parser_state(&self) -> Result<ParserState, ControllerError> {
+        let lines = self.send_query(protocol::system::VIEW_GCODE_STATE)?;
+
+        let parser_state = lines
+            .iter()
+            .find_map(|line| match line {
+                Response::ParserState(gc) => ParserState::parse(gc),
+                _ => None,
             })
+            .ok_or_else(|| ControllerError::Internal("No parser state in $G response".into()))?;
+
+        self.state.lock().parser_state = Some(parser_state.clone());
+        Ok(parser_state)
     }
 
-    /// Send unlock command.
-    pub fn unlock(&self) -> Result<(), ControllerError> {
-        // Clear pending alarm on unlock attempt
-        self.state.lock().pending_alarm = None;
-        self.send_command(protocol::system::UNLOCK)
+    /// Read the stored coordinate parameters (`$#`): G54-G59 offsets,
+    /// G28/G30 positions, G92 offset, tool length offset, and last probe.
+    pub fn read_parameters(&self) -> Result<Parameters, ControllerError> {
+        let lines = self.send_query(protocol::system::VIEW_PARAMETERS)?;
+        let mut params = Parameters::from_responses(&lines);
+        if params.work_offsets.is_empty() {
+            return Err(ControllerError::Internal(
+                "No work offsets in $# response".into(),
+            ));
+        }
+
+        // Device reports inches; keep values in mm
+        if self.state.lock().reports_inches() {
+            params.convert_from_inches();
+        }
+        Ok(params)
     }
 
-    /// Send jog command.
-    pub fn jog(
-        &self,
-        x: Option<f64>,
-        y: Option<f64>,
-        z: Option<f64>,
-        feed: f64,
-        incremental: bool,
-    ) -> Result<(), ControllerError> {
-        // Validate state - can only jog when idle or already jogging
+    /// Read all GRBL settings (`$$`) and cache them.
+    pub fn read_settings(&self) -> Result<BTreeMap<u32, String>, ControllerError> {
+        let lines = self.send_query(protocol::system::VIEW_SETTINGS)?;
+
+        let settings: BTreeMap<u32, String> = lines
+            .into_iter()
+            .filter_map(|line| match line {
+                Response::Setting(n, value) => Some((n, value)),
+                _ => None,
+            })
+            .collect();
+
+        self.state.lock().settings = settings.clone();
+        Ok(settings)
+    }
+
+    /// Read one GRBL setting, None if the device doesn't have it.
+    ///
+    /// Served from the settings cache, which is filled with a full `$$`
+    /// read the first time and kept current by [`Controller::write_settings`].
+    pub fn read_setting(&self, number: u32) -> Result<Option<String>, ControllerError> {
         {
             let state = self.state.lock();
-            match state.status.state {
-                MachineState::Idle | MachineState::Jog => {}
-                other => {
-                    return Err(ControllerError::InvalidState(format!(
-                        "Cannot jog in {:?} state",
-                        other
-                    )));
-                }
+            if !state.settings.is_empty() {
+                return Ok(state.settings.get(&number).cloned());
             }
         }
-
-        let cmd = protocol::build_jog_command(x, y, z, feed, incremental);
-        self.send_command(&cmd)
+        Ok(self.read_settings()?.remove(&number))
     }
 
-    /// Cancel active jog.
-    pub fn jog_cancel(&self) -> Result<(), ControllerError> {
-        self.send_realtime(protocol::JOG_CANCEL)
+    /// A GRBL setting from the cache, without querying the device. None
+    /// if settings haven't been read yet or the device has no such setting.
+    pub fn cached_setting(&self, number: u32) -> Option<String> {
+        self.state.lock().settings.get(&number).cloned()
     }
 
-    /// Send feed hold (pause).
-    pub fn feed_hold(&self) -> Result<(), ControllerError> {
-        self.send_realtime(protocol::realtime::FEED_HOLD)
+    /// Whether laser mode (`$32=1`) is on; None if the device has no `$32`
+    /// (GRBL before 1.1).
+    pub fn laser_mode(&self) -> Result<Option<bool>, ControllerError> {
+        Ok(self.read_setting(32)?.map(|value| value.trim() == "1"))
     }
 
-    /// Send cycle start (resume).
-    pub fn cycle_start(&self) -> Result<(), ControllerError> {
-        self.send_realtime(protocol::realtime::CYCLE_START)
+    /// Write the status report mask (`$10`) and read the settings back to
+    /// confirm it. Returns the mask now in effect.
+    ///
+    /// Only allowed while Idle, like any settings write. Position display
+    /// and streaming rely on `MPos` and `Bf` (see
+    /// [`protocol::ReportFields::missing_required`]).
+    pub fn set_report_mask(&self, mask: u8) -> Result<u8, ControllerError> {
+        let results = self.write_settings(&[(10, mask.to_string())])?;
+        if let Some(error) = results.into_iter().find_map(|result| result.error) {
+            return Err(ControllerError::InvalidState(format!(
+                "The device refused $10={}: {}",
+                mask, error
+            )));
+        }
+        let confirmed = self.cached_setting(10);
+        match confirmed.as_deref().map(|value| value.trim().parse::<u8>()) {
+            Some(Ok(value)) if value == mask => {
+                log::info!("Status report mask set ($10={})", mask);
+                Ok(value)
+            }
+            _ => Err(ControllerError::InvalidState(format!(
+                "$10 reads back as {} after writing {}",
+                confirmed.as_deref().unwrap_or("nothing"),
+                mask
+            ))),
+        }
     }
 
-    /// Send soft reset.
-    pub fn soft_reset(&self) -> Result<(), ControllerError> {
-        let result = self.send_realtime(protocol::realtime::SOFT_RESET);
-
-        // Reset cached state on soft reset
-        if result.is_ok() {
-            let mut state = self.state.lock();
-            state.status = MachineStatus::default();
-            state.pending_alarm = None;
-            state.status_is_fresh = false;
+    /// Turn on laser mode (`$32=1`) if it's off, so the laser goes out
+    /// during rapids and feed holds. Returns whether the setting changed.
+    ///
+    /// Only allowed while Idle, like any settings write.
+    pub fn ensure_laser_mode(&self) -> Result<bool, ControllerError> {
+        match self.laser_mode()? {
+            Some(true) => return Ok(false),
+            Some(false) => {}
+            None => {
+                return Err(ControllerError::InvalidState(
+                    "The device has no laser mode setting ($32)".into(),
+                ))
+            }
         }
-
-        result
+        let results = self.write_settings(&[(32, "1".into())])?;
+        if let Some(error) = results.into_iter().find_map(|result| result.error) {
+            return Err(ControllerError::InvalidState(format!(
+                "The device refused laser mode: {}",
+                error
+            )));
+        }
+        log::info!("Laser mode turned on ($32=1)");
+        Ok(true)
     }
 
-    /// Send a command with default retry/timeout policy.
-    fn send_command(&self, cmd: &str) -> Result<(), ControllerError> {
+    /// Restore device defaults with `$RST`.
+    ///
+    /// Destructive: wipes the selected configuration from the device EEPROM.
+    /// Only allowed while Idle. The settings cache is re-read afterwards
+    /// when `$` settings were reset.
+    pub fn reset_settings(&self, kind: ResetKind) -> Result<(), ControllerError> {
         if !self.is_connected() {
             return Err(ControllerError::NotConnected);
         }
+        let machine_state = self.state.lock().status.state;
+        if machine_state != MachineState::Idle {
+            return Err(ControllerError::InvalidState(format!(
+                "Cannot reset device settings in {:?} state (must be Idle)",
+                machine_state
+            )));
+        }
 
-        self.worker.send_command(cmd).map_err(|e| {
-            let mut state = self.state.lock();
-            state.last_error = Some(e.to_string());
-            e.into()
-        })
+        log::warn!("Resetting GRBL device data: {}", kind.command());
+        self.send_command(kind.command())?;
+
+        if kind.resets_settings() {
+            self.read_settings()?;
+        }
+        Ok(())
     }
 
-    /// Send a real-time command.
-    fn send_realtime(&self, cmd: u8) -> Result<(), ControllerError> {
+    /// Write `$N=value` settings, e.g. restored from a backup file.
+    ///
+    /// Only allowed while Idle, and nothing is written if any value has
+    /// control or realtime characters (see
+    /// [`protocol::is_valid_setting_value`]). A setting the device rejects
+    /// is reported in its result and the rest are still written; losing
+    /// the connection stops the import. Accepted values go into the
+    /// settings cache as they are written, and the cache is re-read
+    /// afterwards for the device's own formatting.
+    pub fn write_settings(
+        &self,
+        settings: &[(u32, String)],
+    ) -> Result<Vec<SettingWriteResult>, ControllerError> {
         if !self.is_connected() {
             return Err(ControllerError::NotConnected);
         }
+        let machine_state = self.state.lock().status.state;
+        if machine_state != MachineState::Idle {
+            return Err(ControllerError::InvalidState(format!(
+                "Cannot write device settings in {:?} state (must be Idle)",
+                machine_state
+            )));
+        }
+        if let Some((number, _)) = settings
+            .iter()
+            .find(|(_, value)| !protocol::is_valid_setting_value(value))
+        {
+            return Err(ControllerError::InvalidState(format!(
+                "Setting ${} has control or realtime characters in its value",
+                number
+            )));
+        }
 
-        self.worker.send_realtime(cmd).map_err(|e| {
-            let mut state = self.state.lock();
-            state.last_error = Some(e.to_string());
-            e.into()
-        })
+        let mut results = Vec::with_capacity(settings.len());
+        for (number, value) in settings {
+            let error = match self.send_command(&format!("${}={}", number, value)) {
+                Ok(()) => {
+                    self.state.lock().settings.insert(*number, value.clone());
+                    None
+                }
+                Err(e @ (ControllerError::NotConnected | ControllerError::Disconnected(_))) => {
+                    return Err(e)
+                }
+                Err(e) => {
+                    log::warn!("Setting ${}={} rejected: {}", number, value, e);
+                    Some(e.to_string())
+                }
+            };
+            results.push(SettingWriteResult {
+                number: *number,
+                value: value.clone(),
+                error,
+            });
+        }
+
+        self.read_settings()?;
+        Ok(results)
     }
-}
 
-impl Default for Controller {
-    fn default() -> Self {
-        Self::new()
+    /// Read build info (`$I`) and cache it.
+    pub fn read_build_info(&self) -> Result<Vec<String>, ControllerError> {
+        let lines = self.send_query(protocol::system::VIEW_BUILD_INFO)?;
+
+        let build_info: Vec<String> = lines
+            .into_iter()
+            .filter_map(|line| match line {
+                Response::Other(text) | Response::Message(text) => Some(text),
+                _ => None,
+            })
+            .collect();
+
+        self.state.lock().build_info = build_info.clone();
+        Ok(build_info)
     }
-}
 
-/// Override adjustment type
+    /// Recover from a fault: soft reset, re-read device info, and clear alarms.
+    ///
+    /// Works from any machine state, including Alarm. Steps:
+    /// 1. Soft reset and wait for the welcome message
+    /// 2. Re-read settings (`$$`) and build info (`$I`)
+    /// 3. Poll status and clear any pending alarm
+    /// 4. If the machine is still alarmed, home (when `rehome` is set and
+    ///    homing is enabled via `$22`) or unlock
+    pub fn recover(&self, rehome: bool) -> Result<RecoverySummary, ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+
+        let mut summary = RecoverySummary::default();
+
+        // 1. Soft reset - bypass send_realtime so we also wait for the banner
+        let welcome = self.worker.reset().map_err(|e| self.worker_error(e))?;
+        {
+            let mut state = self.state.lock();
+            state.status = MachineStatus::default();
+            state.status_is_fresh = false;
+            state.parser_state = None;
+            state.is_homed = false;
+            if !welcome.is_empty() {
+                state.firmware = protocol::parse_welcome(&welcome);
+                state.welcome_message = Some(welcome.clone());
+                state
+                    .connection_log
+                    .record(ConnectionEventKind::WelcomeReceived, Some(welcome.clone()));
+            }
+        }
+        summary.reset = true;
+        summary.welcome_message = (!welcome.is_empty()).then_some(welcome);
+
+        // 2. Re-read device info
+        summary.settings_read = self.read_settings()?.len();
+        summary.build_info = self.read_build_info()?;
+
+        // 3. Refresh status; a reset clears any alarm we were tracking
+        let status = self.poll_status()?;
+        {
+            let mut state = self.state.lock();
+            summary.alarm_cleared = state.pending_alarm.take().is_some();
+            state.last_error = None;
+        }
+
+        // 4. Leave the machine usable if the reset put it in Alarm
+        if status.state == MachineState::Alarm {
+            let homing_enabled = self.state.lock().homing_enabled();
+            if rehome && homing_enabled {
+                self.home()?;
+                summary.homed = true;
+            } else {
+                summary.unlocked = self.unlock()?;
+            }
+            self.poll_status()?;
+        }
+
+        Ok(summary)
+    }
+
+    /// Current spindle/laser power as a percentage of the `$30` max.
+    ///
+    /// Returns None until settings have been read or if no spindle speed
+    /// has been reported.
+    pub fn spindle_power_percent(&self) -> Option<f64> {
+        self.state.lock().spindle_power_percent()
+    }
+
+    /// Maximum laser power (S value) the device accepts, from `$30`.
+    ///
+    /// Falls back to [`DEFAULT_MAX_POWER`] until settings have been read.
+    pub fn max_power(&self) -> u32 {
+        self.state.lock().max_power()
+    }
+
+    /// Reject power above `$30`, which GRBL would otherwise clamp silently.
+    fn check_power(&self, power: u32) -> Result<(), ControllerError> {
+        let max = self.max_power();
+        if power > max {
+            return Err(ControllerError::InvalidState(format!(
+                "Power S{} exceeds the device maximum S{} ($30)",
+                power, max
+            )));
+        }
+        Ok(())
+    }
+
+    /// Send home command.
+    ///
+    /// Uses a longer timeout since homing can take 30+ seconds on large machines.
+    pub fn home(&self) -> Result<(), ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+        let _motion = self.begin_motion()?;
+
+        // Homing: no retries (it either works or alarms), long timeout
+        self.worker
+            .send_command_with_policy(protocol::system::HOME, 0, HOMING_TIMEOUT_MS)
+            .map_err(|e| self.record_error(e.into()))?;
+
+        self.state.lock().is_homed = true;
+        Ok(())
+    }
+
+    /// Send unlock (`$X`) and re-poll to confirm the device left Alarm.
+    ///
+    /// Returns false if it is still in Alarm (e.g. a limit switch is still
+    /// triggered); the pending alarm is kept until a fresh report confirms
+    /// it cleared.
+    pub fn unlock(&self) -> Result<bool, ControllerError> {
+        self.send_command(protocol::system::UNLOCK)?;
+        self.poll_status()?;
+        Ok(self.state.lock().confirm_unlocked())
+    }
+
+    /// The alarm awaiting attention, if any
+    pub fn current_alarm(&self) -> Option<protocol::AlarmInfo> {
+        let state = self.state.lock();
+        state
+            .pending_alarm
+            .map(|(code, _)| protocol::describe_alarm(code))
+    }
+
+    /// Send jog command.
+    pub fn jog(
+        &self,
+        x: Option<f64>,
+        y: Option<f64>,
+        z: Option<f64>,
+        feed: f64,
+        incremental: bool,
+    ) -> Result<(), ControllerError> {
+        let _motion = self.begin_motion()?;
+        self.ensure_no_pending_alarm()?;
+        self.ensure_homed()?;
+
+        // Validate state - can only jog when idle or already jogging
+        {
+            let state = self.state.lock();
+            match state.status.state {
+                MachineState::Idle | MachineState::Jog => {}
+                other => {
+                    return Err(ControllerError::InvalidState(format!(
+                        "Cannot jog in {:?} state",
+                        other
+                    )));
+                }
+            }
+            if incremental {
+                state.check_jog_travel([x, y, z])?;
+            }
+            state.check_feed(feed, protocol::JOG_FEED_DECIMALS)?;
+        }
+
+        let cmd = protocol::build_jog_command(x, y, z, feed, incremental);
+        self.send_command(&cmd)
+    }
+
+    /// Jog one step of the preset at `preset_index` along `axis`.
+    pub fn jog_preset(
+        &self,
+        preset_index: usize,
+        axis: protocol::JogAxis,
+        direction: protocol::JogDirection,
+    ) -> Result<(), ControllerError> {
+        let preset = self
+            .state
+            .lock()
+            .jog_presets
+            .get(preset_index)
+            .cloned()
+            .ok_or_else(|| {
+                ControllerError::InvalidState(format!("No jog preset {}", preset_index + 1))
+            })?;
+        let [x, y, z] = preset.step(axis, direction);
+        self.jog(x, y, z, preset.feed, true)
+    }
+
+    /// Turn the check against jogging across machine zero on or off.
+    pub fn set_jog_travel_check(&self, enabled: bool) {
+        self.state.lock().jog_travel_check = enabled;
+    }
+
+    /// Set the lowest feed accepted for jogs and frames (units/min).
+    pub fn set_min_feed(&self, min_feed: f64) {
+        self.state.lock().min_feed = min_feed;
+    }
+
+    /// Replace the jog presets used by [`Controller::jog_preset`].
+    pub fn set_jog_presets(&self, presets: Vec<protocol::JogPreset>) {
+        self.state.lock().jog_presets = presets;
+    }
+
+    /// Jog while a job is paused by feed hold, to reposition or inspect
+    /// the work, then return and resume with [`Controller::cycle_start`].
+    ///
+    /// Needs `$J=` jogging: GRBL 1.1 or later (see
+    /// [`protocol::supports_jogging`]). GRBL 1.1 accepts a jog only when
+    /// Idle or already jogging, not in Hold, and leaving Hold runs whatever
+    /// the device has queued, with the laser as programmed: GRBL restores
+    /// the spindle on resume, so those moves can't be run with the beam
+    /// off. A jog therefore never resumes the job itself. The first jog of
+    /// a hold stops sending the job; if moves are still queued (or the
+    /// buffer state is unknown, without `Bf` reports) the jog is refused,
+    /// and the user's own cycle start runs just those moves to Idle. Once
+    /// nothing is queued, the machine leaves Hold without moving, the
+    /// laser is turned off (`M5`), since GRBL jogs with the spindle state
+    /// of the job, and jogging is allowed.
+    ///
+    /// Only allowed while a streamed job is held (`Hold:0`), stopped after
+    /// such a resume, or already set aside by an earlier call.
+    pub fn jog_during_hold(
+        &self,
+        x: Option<f64>,
+        y: Option<f64>,
+        z: Option<f64>,
+        feed: f64,
+        incremental: bool,
+    ) -> Result<(), ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+        self.ensure_no_pending_alarm()?;
+        self.begin_hold_jog()?;
+
+        {
+            let mut state = self.state.lock();
+            match state.status.state {
+                MachineState::Idle | MachineState::Jog => {}
+                other => {
+                    return Err(ControllerError::InvalidState(format!(
+                        "Cannot jog in {:?} state",
+                        other
+                    )));
+                }
+            }
+            if incremental {
+                state.check_jog_travel([x, y, z])?;
+            }
+            state.check_feed(feed, protocol::JOG_FEED_DECIMALS)?;
+            let Some(hold) = state.hold_jog.as_mut() else {
+                return Err(ControllerError::InvalidState(
+                    "The held job is no longer set aside for jogging".into(),
+                ));
+            };
+            if !hold.laser_off {
+                return Err(ControllerError::InvalidState(
+                    "Laser state unknown - resume or stop the job instead of jogging".into(),
+                ));
+            }
+            hold.feed = hold.feed.max(feed);
+        }
+
+        let cmd = protocol::build_jog_command(x, y, z, feed, incremental);
+        self.send_command(&cmd)
+    }
+
+    /// Set a held job aside for jogging: stop sending it, let the queued
+    /// moves finish, and turn the laser off. No-op if already done.
+    fn begin_hold_jog(&self) -> Result<(), ControllerError> {
+        {
+            let state = self.state.lock();
+            if state.hold_jog.is_some() {
+                return Ok(());
+            }
+            let job_running = state
+                .job
+                .as_ref()
+                .is_some_and(|progress| progress.lock().is_running());
+            let held = state.status.state == MachineState::Hold && state.status.can_resume();
+            let drained = state.hold_drain && state.status.state == MachineState::Idle;
+            if !job_running || !(held || drained) {
+                return Err(ControllerError::InvalidState(
+                    "Jogging during a hold needs a job paused by feed hold (Hold:0)".into(),
+                ));
+            }
+            if let Some(firmware) = state
+                .firmware
+                .as_ref()
+                .filter(|f| !protocol::supports_jogging(f))
+            {
+                return Err(ControllerError::InvalidState(format!(
+                    "Firmware {} has no $J= jogging (needs GRBL 1.1 or later)",
+                    firmware.version
+                )));
+            }
+        }
+
+        // Stop sending the job, then make sure leaving Hold can't move
+        let in_flight_drained = self
+            .worker
+            .suspend_stream(true)
+            .map_err(|e| self.worker_error(e))?;
+        let queued = {
+            let mut state = self.state.lock();
+            let planner_empty = state.status.buffer.is_some_and(|(free, _)| {
+                state.planner_capacity > 0 && free >= state.planner_capacity
+            });
+            let queued =
+                !in_flight_drained || (state.status.state == MachineState::Hold && !planner_empty);
+            state.hold_drain = queued;
+            queued
+        };
+        if queued {
+            return Err(ControllerError::InvalidState(
+                "The held job still has moves queued, which would run with the laser on. \
+                 Resume to finish them (the rest of the job waits), then jog"
+                    .into(),
+            ));
+        }
+        let stopped = if self.state.lock().status.state == MachineState::Hold {
+            self.send_realtime(protocol::realtime::CYCLE_START)
+                .and_then(|()| self.wait_for_idle(HOLD_JOG_IDLE_TIMEOUT_MS))
+        } else {
+            self.wait_for_idle(HOLD_JOG_IDLE_TIMEOUT_MS)
+        };
+        let status = match stopped {
+            Ok(status) => status,
+            Err(e) => {
+                // Leave the job held, as it was
+                let _ = self.send_realtime(protocol::realtime::FEED_HOLD);
+                let _ = self.worker.suspend_stream(false);
+                self.state.lock().hold_drain = false;
+                return Err(e);
+            }
+        };
+        log::info!(
+            "Job set aside for jogging at machine {:?}",
+            status.machine_pos
+        );
+
+        // From here on cycle start resumes the job, even if the laser
+        // can't be turned off and jogging is refused
+        {
+            let mut state = self.state.lock();
+            state.hold_drain = false;
+            state.hold_jog = Some(HoldJog {
+                resume_at: status.machine_pos,
+                spindle: None,
+                laser_off: false,
+                feed: 0.0,
+            });
+        }
+        let spindle = self.read_parser_state()?.spindle;
+        if spindle != "M5" {
+            self.send_command("M5")?;
+        }
+        if let Some(hold) = self.state.lock().hold_jog.as_mut() {
+            hold.spindle = (spindle != "M5").then_some(spindle);
+            hold.laser_off = true;
+        }
+        Ok(())
+    }
+
+    /// Jog back to where a held job stopped, restore the laser, and send
+    /// the rest of the job.
+    fn end_hold_jog(&self, hold: HoldJog) -> Result<(), ControllerError> {
+        let status = self.wait_for_idle(HOLD_JOG_IDLE_TIMEOUT_MS)?;
+        let (from, to) = (status.machine_pos, hold.resume_at);
+        let moved = [from.x - to.x, from.y - to.y, from.z - to.z]
+            .iter()
+            .any(|d| d.abs() > TRAVEL_TOLERANCE);
+        if moved {
+            self.send_command(&protocol::build_machine_jog_command(to, hold.feed))?;
+            self.wait_for_idle(HOLD_JOG_IDLE_TIMEOUT_MS)?;
+        }
+        if let Some(spindle) = &hold.spindle {
+            self.send_command(spindle)?;
+        }
+
+        self.state.lock().hold_jog = None;
+        self.worker
+            .suspend_stream(false)
+            .map_err(|e| self.worker_error(e))?;
+        log::info!("Job resumed after jogging");
+        Ok(())
+    }
+
+    /// Poll status until the machine is Idle, returning that status.
+    fn wait_for_idle(&self, timeout_ms: u64) -> Result<MachineStatus, ControllerError> {
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        loop {
+            let status = self.poll_status()?;
+            match status.state {
+                MachineState::Idle => return Ok(status),
+                MachineState::Alarm => {
+                    return Err(ControllerError::InvalidState(
+                        "Alarm while waiting for the machine to stop".into(),
+                    ))
+                }
+                other if Instant::now() >= deadline => {
+                    return Err(ControllerError::InvalidState(format!(
+                        "Machine still in {:?} after {} ms",
+                        other, timeout_ms
+                    )))
+                }
+                _ => thread::sleep(Duration::from_millis(IDLE_POLL_MS)),
+            }
+        }
+    }
+
+    /// Cancel active jog.
+    pub fn jog_cancel(&self) -> Result<(), ControllerError> {
+        self.send_realtime(protocol::JOG_CANCEL)
+    }
+
+    /// Send feed hold (pause).
+    pub fn feed_hold(&self) -> Result<(), ControllerError> {
+        self.send_realtime(protocol::realtime::FEED_HOLD)
+    }
+
+    /// Send cycle start (resume).
+    ///
+    /// Refused while a hold is still decelerating (`Hold:1`) or a safety
+    /// door is not yet ready to resume. After jogging during a hold, first
+    /// returns to where the job stopped (see [`Controller::jog_during_hold`]).
+    /// After a jog was refused for queued moves, resuming runs only those;
+    /// once they have stopped, the next cycle start sends the rest of the job.
+    pub fn cycle_start(&self) -> Result<(), ControllerError> {
+        let (hold_jog, hold_drain, state) = {
+            let state = self.state.lock();
+            (state.hold_jog.clone(), state.hold_drain, state.status.state)
+        };
+        if let Some(hold) = hold_jog {
+            return self.end_hold_jog(hold);
+        }
+        // Queued moves of a held job have run without jogging: send the rest
+        if hold_drain && state == MachineState::Idle {
+            self.state.lock().hold_drain = false;
+            self.worker
+                .suspend_stream(false)
+                .map_err(|e| self.worker_error(e))?;
+            return Ok(());
+        }
+        {
+            let state = self.state.lock();
+            if !state.status.can_resume() {
+                return Err(ControllerError::InvalidState(format!(
+                    "Cannot resume yet: {:?}:{} is not complete",
+                    state.status.state,
+                    state.status.sub_state.unwrap_or(0)
+                )));
+            }
+        }
+        self.send_realtime(protocol::realtime::CYCLE_START)
+    }
+
+    /// Abort the command the worker is currently blocked on.
+    ///
+    /// The pending call returns `ControllerError::Cancelled`. Returns false
+    /// if nothing was in flight.
+    pub fn abort_current_command(&self) -> bool {
+        self.worker.cancel_pending()
+    }
+
+    /// Send soft reset.
+    ///
+    /// Delivered ahead of any in-flight command, which is cancelled; this is
+    /// how a long homing cycle is aborted.
+    pub fn soft_reset(&self) -> Result<(), ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+
+        // Priority path: reaches the device and cancels the pending command
+        // even while the worker is blocked (e.g. aborting a homing cycle)
+        let result = self
+            .worker
+            .send_realtime_priority(protocol::realtime::SOFT_RESET)
+            .map_err(|e| self.record_error(e.into()));
+
+        // Reset cached state on soft reset
+        if result.is_ok() {
+            let mut state = self.state.lock();
+            state.status = MachineStatus::default();
+            state.pending_alarm = None;
+            state.status_is_fresh = false;
+            state.is_homed = false;
+            state.hold_jog = None;
+            state.hold_drain = false;
+        }
+
+        result
+    }
+
+    /// Stop everything and return to a known state.
+    ///
+    /// Cancels the pending command and any jog, then sends feed hold and
+    /// the three override resets, and polls status until the hold has
+    /// brought the machine to rest (`Hold:0`, Idle or any state without
+    /// motion) before a soft reset aborts a streaming job. Resetting while
+    /// moving loses position and raises `ALARM:3`, so the reset only goes
+    /// out mid-motion if the machine hasn't stopped within
+    /// `STOP_HOLD_TIMEOUT_MS` or status can't be read. Every byte goes on
+    /// the priority path, so nothing waits for an `ok` and a busy worker
+    /// can't delay the stop. Afterwards job progress and any step session
+    /// are cleared. Safe from any state; when not connected only the local
+    /// job state is cleared.
+    pub fn stop_all(&self) -> Result<(), ControllerError> {
+        self.worker.cancel_pending();
+        {
+            let mut state = self.state.lock();
+            state.step = None;
+            state.hold_jog = None;
+            state.hold_drain = false;
+            state.record_job_usage();
+            state.job = None;
+        }
+        if !self.is_connected() {
+            return Ok(());
+        }
+
+        for byte in [
+            protocol::JOG_CANCEL,
+            protocol::realtime::FEED_HOLD,
+            protocol::realtime::FEED_OVR_RESET,
+            protocol::realtime::RAPID_OVR_RESET,
+            protocol::realtime::SPINDLE_OVR_RESET,
+        ] {
+            self.worker
+                .send_realtime_priority(byte)
+                .map_err(|e| self.record_error(e.into()))?;
+        }
+        if !self.wait_for_hold_complete(STOP_HOLD_TIMEOUT_MS) {
+            log::warn!("Machine didn't come to rest after feed hold, resetting anyway");
+        }
+        self.soft_reset()
+    }
+
+    /// Poll status until the machine isn't moving: a completed hold
+    /// (`Hold:0`), Idle, or any state other than Run, Jog or a hold still
+    /// decelerating. Homing ignores a feed hold, so Home doesn't wait.
+    /// False on timeout or when status can't be read.
+    fn wait_for_hold_complete(&self, timeout_ms: u64) -> bool {
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        loop {
+            let Ok(status) = self.poll_status() else {
+                return false;
+            };
+            let moving = match status.state {
+                MachineState::Run | MachineState::Jog => true,
+                MachineState::Hold => status.sub_state != Some(0),
+                _ => false,
+            };
+            if !moving {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(IDLE_POLL_MS));
+        }
+    }
+
+    /// Make the device safe and disconnect (for app exit).
+    ///
+    /// Cancels whatever request is waiting on the device, then disconnects,
+    /// which turns the laser off. No-op when not connected.
+    pub fn shutdown(&self) {
+        if !self.is_connected() {
+            return;
+        }
+        log::info!("Controller shutdown: turning laser off and disconnecting");
+        self.worker.cancel_pending();
+
+        if let Err(e) = self.disconnect() {
+            log::warn!("Failed to disconnect during shutdown: {}", e);
+        }
+    }
+
+    /// Turn the laser off before the port closes.
+    ///
+    /// Stops motion and any running job with a soft reset, which also kills
+    /// the laser; when the machine is at rest, `M5` is sent instead so the
+    /// position is kept, and the spindle override goes back to 100% for the
+    /// next session. In Alarm GRBL has already stopped the spindle and
+    /// would refuse `M5`. Every step has a short timeout and failures are
+    /// only logged, so an unresponsive device can't block the disconnect.
+    fn laser_off_for_disconnect(&self) {
+        let job_running = self.job_progress().is_some_and(|p| p.is_running());
+        let machine_state = self.state.lock().status.state;
+        let in_motion = matches!(
+            machine_state,
+            MachineState::Run | MachineState::Jog | MachineState::Hold | MachineState::Home
+        );
+        let laser_off = if job_running || in_motion {
+            self.worker.send_realtime(protocol::realtime::SOFT_RESET)
+        } else if machine_state == MachineState::Alarm {
+            Ok(())
+        } else {
+            self.worker
+                .send_command_with_policy("M5", 0, SHUTDOWN_COMMAND_TIMEOUT_MS)
+                .and_then(|()| {
+                    self.worker
+                        .send_realtime(protocol::realtime::SPINDLE_OVR_RESET)
+                })
+        };
+        if let Err(e) = laser_off {
+            log::warn!("Failed to turn laser off before disconnecting: {}", e);
+        }
+    }
+
+    /// Refuse motion while an alarm is pending.
+    ///
+    /// The cached status can still read Idle when an alarm was seen during
+    /// polling, so motion commands check the pending alarm explicitly.
+    fn ensure_no_pending_alarm(&self) -> Result<(), ControllerError> {
+        match self.state.lock().pending_alarm {
+            Some((code, _)) => Err(ControllerError::Alarm(code)),
+            None => Ok(()),
+        }
+    }
+
+    /// Claim the machine for a motion command.
+    ///
+    /// Fails with "machine busy" while a program is streaming or another
+    /// motion command (homing, a frame, a go-to-zero) is still being sent,
+    /// so their lines can't interleave on the serial line. The claim ends
+    /// when the guard drops; a streamed job releases it by completing,
+    /// failing, or being cancelled (including by soft reset).
+    fn begin_motion(&self) -> Result<MotionGuard<'_>, ControllerError> {
+        let job_running = self
+            .state
+            .lock()
+            .job
+            .as_ref()
+            .is_some_and(|progress| progress.lock().is_running());
+        if job_running {
+            return Err(ControllerError::InvalidState(
+                "Machine busy: a job is running".into(),
+            ));
+        }
+        if self
+            .motion_busy
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return Err(ControllerError::InvalidState(
+                "Machine busy: another motion command is in progress".into(),
+            ));
+        }
+        Ok(MotionGuard(&self.motion_busy))
+    }
+
+    /// Refuse motion until homed when homing is enabled (`$22=1`).
+    fn ensure_homed(&self) -> Result<(), ControllerError> {
+        let state = self.state.lock();
+        if state.homing_enabled() && !state.is_homed {
+            return Err(ControllerError::InvalidState(
+                "Machine not homed - run homing ($H) before moving".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Send a command with default retry/timeout policy.
+    fn send_command(&self, cmd: &str) -> Result<(), ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+
+        self.worker
+            .send_command(cmd)
+            .map_err(|e| self.worker_error(e))
+    }
+
+    /// Send a query command and collect its response lines.
+    fn send_query(&self, cmd: &str) -> Result<Vec<Response>, ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+
+        self.worker
+            .send_query(cmd, QUERY_TIMEOUT_MS)
+            .map_err(|e| self.worker_error(e))
+    }
+
+    /// Send a real-time command.
+    fn send_realtime(&self, cmd: u8) -> Result<(), ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+
+        self.worker
+            .send_realtime(cmd)
+            .map_err(|e| self.worker_error(e))
+    }
+}
+
+impl Default for Controller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Controller {
+    fn drop(&mut self) {
+        // Runs before the worker is dropped, so the port is still open
+        self.shutdown();
+    }
+}
+
+/// Override adjustment type
 #[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub enum OverrideAdjust {
     /// Reset to 100%
@@ -431,99 +1849,1831 @@ impl Controller {
         self.send_realtime(cmd)
     }
 
-    /// Run a frame/boundary trace.
-    ///
-    /// Traces a rectangle from (x_min, y_min) to (x_max, y_max) at the
-    /// specified feed rate and laser power (S value).
+    /// Run a frame/boundary trace.
+    ///
+    /// Traces the rectangle of `frame`, expanded by its margin on all
+    /// sides, at its feed rate and laser power (S value). Blocks until the
+    /// trace finishes, calling `on_edge` as each edge completes.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Not connected
+    /// - Machine not in Idle state
+    /// - Margin is negative or not finite
+    /// - Frame has zero area (x_min == x_max or y_min == y_max, no margin)
+    /// - Feed is below the minimum (see [`Controller::set_min_feed`])
+    /// - Frame leaves the `$130`/`$131` travel
+    pub fn run_frame(
+        &self,
+        frame: FrameRequest,
+        mut on_edge: impl FnMut(FrameProgress),
+    ) -> Result<(), ControllerError> {
+        let FrameRequest {
+            x_min,
+            x_max,
+            y_min,
+            y_max,
+            margin,
+            feed,
+            power,
+            units,
+            mode,
+        } = frame;
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+
+        if !(margin.is_finite() && margin >= 0.0) {
+            return Err(ControllerError::InvalidState(format!(
+                "Frame margin must be zero or more, got {}",
+                margin
+            )));
+        }
+        // Normalize inverted bounds (min > max) before expanding
+        let (x_min, x_max) = (x_min.min(x_max) - margin, x_min.max(x_max) + margin);
+        let (y_min, y_max) = (y_min.min(y_max) - margin, y_min.max(y_max) + margin);
+
+        // Validate bounds - must have non-zero area
+        let width = (x_max - x_min).abs();
+        let height = (y_max - y_min).abs();
+        if width < f64::EPSILON || height < f64::EPSILON {
+            return Err(ControllerError::InvalidState(
+                "Frame must have non-zero width and height".into(),
+            ));
+        }
+        self.check_power(power)?;
+        let feed = {
+            let state = self.state.lock();
+            state.check_feed(feed, protocol::FRAME_FEED_DECIMALS)?;
+            state.frame_feed(feed, width, height, units)
+        };
+
+        let _motion = self.begin_motion()?;
+        self.ensure_no_pending_alarm()?;
+        self.ensure_homed()?;
+
+        // Validate state - can only frame when idle
+        {
+            let state = self.state.lock();
+            if state.status.state != MachineState::Idle {
+                return Err(ControllerError::InvalidState(format!(
+                    "Cannot run frame in {:?} state",
+                    state.status.state
+                )));
+            }
+            state.check_frame_travel((x_min, x_max), (y_min, y_max), units)?;
+        }
+
+        let plan = protocol::build_frame_plan(x_min, x_max, y_min, y_max, feed, power, units, mode);
+        for line in &plan.setup {
+            self.send_command(&format!("{}\n", line))?;
+        }
+
+        // Wait out each edge with a sync dwell so progress tracks the
+        // machine rather than the planner buffer
+        let total_edges = plan.edges.len();
+        let result = plan.edges.iter().enumerate().try_for_each(|(i, edge)| {
+            self.send_command(&format!("{}\n", edge.gcode))?;
+            let expected_ms = (edge.length / feed.max(1.0) * 60_000.0) as u64;
+            self.worker
+                .send_command_with_policy(
+                    protocol::SYNC_DWELL,
+                    0,
+                    expected_ms * 2 + FRAME_EDGE_MARGIN_MS,
+                )
+                .map_err(|e| self.worker_error(e))?;
+            on_edge(FrameProgress {
+                edge: i + 1,
+                total_edges,
+                x: edge.x,
+                y: edge.y,
+            });
+            Ok(())
+        });
+
+        // Always try to turn the laser off, even if an edge failed
+        for line in &plan.teardown {
+            let stopped = self.send_command(&format!("{}\n", line));
+            if result.is_ok() {
+                stopped?;
+            }
+        }
+        result
+    }
+}
+
+impl Controller {
+    /// Stream an in-memory G-code program.
+    ///
+    /// Returns once the job has started; progress is available from
+    /// [`Controller::job_progress`] and the snapshot. With `strip_comments`,
+    /// inline comments are removed before sending to save RX buffer space.
+    ///
+    /// `on_line_error` runs on the worker thread for each line GRBL rejects.
+    /// With `continue_on_error` the job keeps streaming past those lines
+    /// instead of stopping at the first.
+    pub fn run_gcode_program(
+        &self,
+        lines: Vec<String>,
+        strip_comments: bool,
+        continue_on_error: bool,
+        on_line_error: impl FnMut(&StreamLineError) + Send + 'static,
+    ) -> Result<(), ControllerError> {
+        let job = StreamJob::from_lines(lines, strip_comments)
+            .continue_on_error(continue_on_error)
+            .on_line_error(on_line_error);
+        self.start_stream(job)
+    }
+
+    /// Stream an in-memory G-code program, running `on_finish` with the
+    /// final progress once it completes, fails, or is cancelled.
+    ///
+    /// `on_finish` runs on the worker thread and is not called if the job
+    /// fails to start.
+    pub fn run_gcode_program_with(
+        &self,
+        lines: Vec<String>,
+        strip_comments: bool,
+        on_finish: impl FnOnce(&StreamProgress) + Send + 'static,
+    ) -> Result<(), ControllerError> {
+        let job = StreamJob::from_lines(lines, strip_comments).on_finish(on_finish);
+        self.start_stream(job)
+    }
+
+    /// Stream a G-code file line-by-line without loading it into memory.
+    ///
+    /// The file is read twice: once up front to count sendable lines for
+    /// progress, then incrementally while streaming. Errors are handled as
+    /// in [`Controller::run_gcode_program`].
+    pub fn run_gcode_file(
+        &self,
+        path: &Path,
+        strip_comments: bool,
+        continue_on_error: bool,
+        on_line_error: impl FnMut(&StreamLineError) + Send + 'static,
+    ) -> Result<(), ControllerError> {
+        let open = || File::open(path).map_err(|e| ControllerError::File(e.to_string()));
+
+        let mut total_lines = 0;
+        for line in BufReader::new(open()?).lines() {
+            let line = line.map_err(|e| ControllerError::File(e.to_string()))?;
+            if stream::prepare_line(&line, strip_comments).is_some() {
+                total_lines += 1;
+            }
+        }
+
+        let source = Box::new(BufReader::new(open()?).lines());
+        let job = StreamJob::new(source, total_lines, strip_comments)
+            .continue_on_error(continue_on_error)
+            .on_line_error(on_line_error);
+        self.start_stream(job)
+    }
+
+    /// Resume a program interrupted by power loss from `start_index`.
+    ///
+    /// Re-homes to recover the machine position, then streams a preamble
+    /// restoring the modal state and position the lines before
+    /// `start_index` left behind (see [`ResumeState`]), followed by the
+    /// rest of the program. Requires homing (`$22`) to be enabled.
+    pub fn resume_from_line(
+        &self,
+        lines: Vec<String>,
+        start_index: usize,
+        strip_comments: bool,
+    ) -> Result<(), ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+        if start_index >= lines.len() {
+            return Err(ControllerError::InvalidState(format!(
+                "Resume line {} is past the end of the program ({} lines)",
+                start_index + 1,
+                lines.len()
+            )));
+        }
+        if !self.state.lock().homing_enabled() {
+            return Err(ControllerError::InvalidState(
+                "Resuming needs homing ($22) enabled to recover the machine position".into(),
+            ));
+        }
+
+        let preamble = ResumeState::scan(&lines[..start_index]).preamble();
+        log::info!(
+            "Resuming program at line {} after: {}",
+            start_index + 1,
+            preamble.join(" | ")
+        );
+
+        self.home()?;
+        // Homing clears the alarm a power loss leaves behind
+        self.state.lock().pending_alarm = None;
+        self.poll_status()?;
+
+        self.start_stream(
+            StreamJob::from_lines_at(lines, start_index, strip_comments).with_preamble(preamble),
+        )
+    }
+
+    /// Start stepping through a program one line at a time with
+    /// [`Controller::step_next`], replacing any open step session.
+    pub fn step_mode_begin(&self, lines: Vec<String>) -> Result<StepStatus, ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+        let _motion = self.begin_motion()?;
+        self.ensure_no_pending_alarm()?;
+        self.ensure_homed()?;
+
+        let session = StepSession::new(lines);
+        let status = session.status();
+        self.state.lock().step = Some(session);
+        Ok(status)
+    }
+
+    /// Send the next line of the step session and wait for GRBL to accept
+    /// it. A line GRBL rejects is still stepped past, and its error returned.
+    pub fn step_next(&self) -> Result<StepStatus, ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+        let next = match &self.state.lock().step {
+            Some(step) => step.next_line(),
+            None => {
+                return Err(ControllerError::InvalidState(
+                    "Step mode is not active".into(),
+                ))
+            }
+        };
+        let Some(next) = next else {
+            return Err(ControllerError::InvalidState(
+                "Step mode reached the end of the program".into(),
+            ));
+        };
+        let _motion = self.begin_motion()?;
+        self.ensure_no_pending_alarm()?;
+
+        let result = self
+            .worker
+            .send_command_with_policy(&next.line, 0, STEP_LINE_TIMEOUT_MS)
+            .map_err(|e| self.worker_error(e));
+        let mut state = self.state.lock();
+        let step = state.step.as_mut().ok_or(ControllerError::Cancelled)?;
+        if matches!(result, Ok(()) | Err(ControllerError::GrblError(_))) {
+            step.advance();
+        }
+        result.map(|_| step.status())
+    }
+
+    /// End step mode. Motion already sent still completes; use a soft
+    /// reset to stop it.
+    pub fn step_abort(&self) {
+        self.state.lock().step = None;
+    }
+
+    /// Position of the open step session, if any
+    pub fn step_status(&self) -> Option<StepStatus> {
+        self.state.lock().step.as_ref().map(StepSession::status)
+    }
+
+    /// Get progress of the current (or last) streamed program.
+    pub fn job_progress(&self) -> Option<StreamProgress> {
+        self.state.lock().job.as_ref().map(|p| p.lock().clone())
+    }
+
+    fn start_stream(&self, job: StreamJob) -> Result<(), ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+
+        let _motion = self.begin_motion()?;
+        self.ensure_no_pending_alarm()?;
+        self.ensure_homed()?;
+
+        {
+            let state = self.state.lock();
+            if state.status.state != MachineState::Idle {
+                return Err(ControllerError::InvalidState(format!(
+                    "Cannot start job in {:?} state",
+                    state.status.state
+                )));
+            }
+        }
+
+        let job = match self.state.lock().rate_smoothing {
+            Some(smoothing) => job.rate_smoothing(smoothing),
+            None => job,
+        };
+        let progress = self
+            .worker
+            .start_stream(job)
+            .map_err(|e| self.worker_error(e))?;
+
+        let mut state = self.state.lock();
+        state.record_job_usage();
+        state.job = Some(progress);
+        state.job_usage = Some(JobUsage::default());
+        Ok(())
+    }
+}
+
+/// Outcome of writing one setting with [`Controller::write_settings`]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SettingWriteResult {
+    pub number: u32,
+    pub value: String,
+    /// Why the device rejected the setting, if it did
+    pub error: Option<String>,
+}
+
+/// Lightweight connection liveness info for the UI
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Heartbeat {
+    pub connected: bool,
+    /// Whether the last status poll got a fresh response
+    pub fresh: bool,
+    /// Milliseconds since the last fresh status report (None if never)
+    pub ms_since_last_status: Option<u64>,
+    /// Whether the serial worker thread answered a ping. None while a
+    /// motion command holds the worker, which then can't answer promptly.
+    /// False means the worker is wedged, not that the device is silent.
+    pub worker_alive: Option<bool>,
+}
+
+impl Controller {
+    /// Get connection liveness info without touching the device.
+    pub fn heartbeat(&self) -> Heartbeat {
+        let worker_alive =
+            (!self.motion_busy.load(Ordering::Acquire)).then(|| self.worker.ping().is_ok());
+        let state = self.state.lock();
+        Heartbeat {
+            connected: matches!(state.connection, ConnectionState::Connected { .. }),
+            fresh: state.status_is_fresh,
+            ms_since_last_status: state.last_status_at.map(|t| t.elapsed().as_millis() as u64),
+            worker_alive,
+        }
+    }
+}
+
+/// A frame/boundary trace for [`Controller::run_frame`]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FrameRequest {
+    /// Boundary coordinates (units); inverted min/max are swapped
+    pub x_min: f64,
+    pub x_max: f64,
+    pub y_min: f64,
+    pub y_max: f64,
+    /// Distance to trace outside the boundary (units, >= 0)
+    #[serde(default)]
+    pub margin: f64,
+    /// Feed rate in units/min (capped at the `$110`/`$111` max rate)
+    pub feed: f64,
+    /// Laser power (S value, typically 0-1000)
+    pub power: u32,
+    /// Units mode (mm or inches)
+    #[serde(default)]
+    pub units: protocol::Units,
+    /// Laser mode (M4 low power, M3 constant, or laser off)
+    pub mode: protocol::FrameMode,
+}
+
+/// Progress of a frame trace, reported after each edge completes
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct FrameProgress {
+    /// Edges completed so far (1-based)
+    pub edge: usize,
+    /// Total edges in the frame
+    pub total_edges: usize,
+    /// X of the corner just reached
+    pub x: f64,
+    /// Y of the corner just reached
+    pub y: f64,
+}
+
+/// Summary of the steps performed by [`Controller::recover`]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct RecoverySummary {
+    /// Soft reset was sent
+    pub reset: bool,
+    /// Welcome message received after reset
+    pub welcome_message: Option<String>,
+    /// Number of settings read from `$$`
+    pub settings_read: usize,
+    /// Build info lines read from `$I`
+    pub build_info: Vec<String>,
+    /// A pending alarm was cleared
+    pub alarm_cleared: bool,
+    /// Machine was re-homed
+    pub homed: bool,
+    /// Machine was unlocked with `$X`
+    pub unlocked: bool,
+}
+
+/// Most recent controller error for the UI.
+///
+/// Carries the error variant so the UI can react to specific errors (e.g.
+/// offer unlock on `Alarm`) without parsing the message.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LastError {
+    /// The error itself, serialized as the enum (e.g. `{"Alarm": 9}`)
+    pub error: ControllerError,
+    /// Human-readable message
+    pub message: String,
+}
+
+impl From<&ControllerError> for LastError {
+    fn from(error: &ControllerError) -> Self {
+        Self {
+            error: error.clone(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Serializable snapshot of controller state for the UI
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ControllerSnapshot {
+    pub connection: ConnectionState,
+    pub status: MachineStatus,
+    pub welcome_message: Option<String>,
+    /// Firmware version parsed from the welcome message
+    pub firmware: Option<FirmwareVersion>,
+    pub last_error: Option<LastError>,
+    /// Pending alarm: (alarm_code, unique_id) - ID for deduplication
+    pub pending_alarm: Option<(u32, u64)>,
+    /// Whether the last status poll got a fresh response (false = stale/timeout)
+    pub status_is_fresh: bool,
+    /// Last known G-code parser state (from `$G`)
+    pub parser_state: Option<ParserState>,
+    /// Progress of the current (or last) streamed program
+    pub job: Option<StreamProgress>,
+    /// Spindle/laser power as a percentage of `$30` (None if `$30` unknown)
+    pub spindle_power_percent: Option<f64>,
+    /// Homing cycle has completed since connect/reset
+    pub is_homed: bool,
+    /// Free planner blocks (None if the device doesn't report `Bf`)
+    pub planner_free: Option<u32>,
+    /// Free serial RX buffer bytes (None if the device doesn't report `Bf`)
+    pub rx_free: Option<u32>,
+    /// A running job has almost no motion queued and may stutter
+    pub buffer_low: bool,
+    /// Current feed rate in mm/min whatever units the device reports in
+    /// (None until `$13` is known)
+    pub feed_rate_mm_min: Option<f64>,
+    /// A held job is set aside for jogging; cycle start returns and resumes
+    pub hold_jog: bool,
+    /// A held job's remaining lines are kept back so that cycle start runs
+    /// only the moves already queued, after which it can be jogged
+    pub hold_drain: bool,
+}
+
+impl Controller {
+    /// Move to the work coordinate origin (X0 Y0).
+    ///
+    /// Raises Z to the configured safe height first. Rapid move unless a
+    /// feed rate is given.
+    pub fn go_to_work_zero(&self, feed: Option<f64>) -> Result<(), ControllerError> {
+        self.go_to_zero(protocol::ZeroTarget::Work, feed)
+    }
+
+    /// Move to the machine coordinate origin (G53 X0 Y0).
+    pub fn go_to_machine_zero(&self, feed: Option<f64>) -> Result<(), ControllerError> {
+        self.go_to_zero(protocol::ZeroTarget::Machine, feed)
+    }
+
+    fn go_to_zero(
+        &self,
+        target: protocol::ZeroTarget,
+        feed: Option<f64>,
+    ) -> Result<(), ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+
+        let _motion = self.begin_motion()?;
+        self.ensure_no_pending_alarm()?;
+        self.ensure_homed()?;
+
+        let safe_z = {
+            let state = self.state.lock();
+            if state.status.state != MachineState::Idle {
+                return Err(ControllerError::InvalidState(format!(
+                    "Cannot move to zero in {:?} state",
+                    state.status.state
+                )));
+            }
+            state.safe_z
+        };
+
+        let gcode = protocol::build_go_to_zero_gcode(target, feed, safe_z);
+        for line in gcode.lines() {
+            self.send_command(line)?;
+        }
+
+        Ok(())
+    }
+
+    /// Move to the park position (machine coordinates) to clear the work area.
+    ///
+    /// Requires Idle and a homed machine. The position must lie within
+    /// `$130`/`$131` travel, which runs from machine zero toward minus on
+    /// stock GRBL or plus with the origin forced at the homed corner.
+    pub fn park(&self) -> Result<(), ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+
+        let _motion = self.begin_motion()?;
+        self.ensure_no_pending_alarm()?;
+        self.ensure_homed()?;
+
+        let (x, y, safe_z) = {
+            let state = self.state.lock();
+            if state.status.state != MachineState::Idle {
+                return Err(ControllerError::InvalidState(format!(
+                    "Cannot park in {:?} state",
+                    state.status.state
+                )));
+            }
+            let (x, y) = state.park_position;
+            for (i, (axis, value)) in [("X", x), ("Y", y)].into_iter().enumerate() {
+                if let Some(why) = state.travel_violation(i, value) {
+                    return Err(ControllerError::InvalidState(format!(
+                        "Park {} {:.3} is {}",
+                        axis, value, why
+                    )));
+                }
+            }
+            (x, y, state.safe_z)
+        };
+
+        for line in protocol::build_park_gcode(x, y, safe_z).lines() {
+            self.send_command(line)?;
+        }
+
+        Ok(())
+    }
+
+    /// Rapid (`G0`) to absolute coordinates for positioning between
+    /// operations, in machine coordinates or the active work coordinate
+    /// system. Unlike a jog this goes through the planner.
     ///
-    /// # Arguments
-    /// * `x_min`, `x_max`, `y_min`, `y_max` - Boundary coordinates
-    /// * `feed` - Feed rate in units/min
-    /// * `power` - Laser power (S value, typically 0-1000)
-    /// * `units` - Units mode (mm or inches)
-    /// * `mode` - Laser mode (M4 low power, M3 constant, or laser off)
-    ///
-    /// # Errors
-    /// Returns an error if:
-    /// - Not connected
-    /// - Machine not in Idle state
-    /// - Frame has zero area (x_min == x_max or y_min == y_max)
-    pub fn run_frame(
+    /// Targets are in `units`. Requires Idle and a homed machine. Each
+    /// target must lie within the `$130`-`$132` travel on the homed side of
+    /// machine zero.
+    pub fn rapid_move(
         &self,
-        x_min: f64,
-        x_max: f64,
-        y_min: f64,
-        y_max: f64,
-        feed: f64,
-        power: u32,
+        x: Option<f64>,
+        y: Option<f64>,
+        z: Option<f64>,
+        machine_coords: bool,
         units: protocol::Units,
-        mode: protocol::FrameMode,
     ) -> Result<(), ControllerError> {
+        let targets = [("X", x, 130), ("Y", y, 131), ("Z", z, 132)];
+        if targets.iter().all(|(_, value, _)| value.is_none()) {
+            return Err(ControllerError::InvalidState(
+                "Rapid move needs at least one axis".into(),
+            ));
+        }
+        if let Some((axis, value, _)) = targets
+            .iter()
+            .find(|(_, value, _)| value.is_some_and(|v| !v.is_finite()))
+        {
+            return Err(ControllerError::InvalidState(format!(
+                "Rapid move {} target {:?} is not a finite number",
+                axis,
+                value.unwrap_or_default()
+            )));
+        }
         if !self.is_connected() {
             return Err(ControllerError::NotConnected);
         }
 
-        // Validate bounds - must have non-zero area
-        // Note: inverted bounds (min > max) are normalized in build_frame_gcode
-        let width = (x_max - x_min).abs();
-        let height = (y_max - y_min).abs();
-        if width < f64::EPSILON || height < f64::EPSILON {
-            return Err(ControllerError::InvalidState(
-                "Frame must have non-zero width and height".into(),
-            ));
-        }
+        let _motion = self.begin_motion()?;
+        self.ensure_no_pending_alarm()?;
+        self.ensure_homed()?;
 
-        // Validate state - can only frame when idle
         {
             let state = self.state.lock();
             if state.status.state != MachineState::Idle {
                 return Err(ControllerError::InvalidState(format!(
-                    "Cannot run frame in {:?} state",
+                    "Cannot rapid move in {:?} state",
                     state.status.state
                 )));
             }
+            let scale = match units {
+                protocol::Units::Mm => 1.0,
+                protocol::Units::Inches => 25.4,
+            };
+            let offsets = if machine_coords {
+                Some([0.0; 3])
+            } else {
+                state.status.work_offset.map(|p| [p.x, p.y, p.z])
+            };
+            for (i, (axis, value, setting)) in targets.into_iter().enumerate() {
+                let (Some(value), Some(_)) = (value, state.setting_f64(setting)) else {
+                    continue;
+                };
+                let Some(offsets) = offsets else {
+                    return Err(ControllerError::InvalidState(
+                        "Work offset not reported yet - cannot check travel limits".into(),
+                    ));
+                };
+                let machine = value * scale + offsets[i];
+                if let Some(why) = state.travel_violation(i, machine) {
+                    return Err(ControllerError::InvalidState(format!(
+                        "{} {:.3} (machine {:.3}) is {}",
+                        axis, value, machine, why
+                    )));
+                }
+            }
         }
 
-        let gcode =
-            protocol::build_frame_gcode(x_min, x_max, y_min, y_max, feed, power, units, mode);
+        self.send_command(&protocol::build_rapid_move_gcode(
+            x,
+            y,
+            z,
+            machine_coords,
+            units,
+        ))
+    }
 
-        // Send each line of the frame GCode
-        for line in gcode.lines() {
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
-            }
-            self.send_command(&format!("{}\n", line))?;
+    /// Store the current machine position as the `G28` position
+    /// (`G28.1`) and return it as read back with `$#`.
+    pub fn set_g28_position(&self) -> Result<Position, ControllerError> {
+        self.set_predefined_position(protocol::PredefinedPosition::G28)
+    }
+
+    /// Store the current machine position as the `G30` position (`G30.1`).
+    pub fn set_g30_position(&self) -> Result<Position, ControllerError> {
+        self.set_predefined_position(protocol::PredefinedPosition::G30)
+    }
+
+    /// Rapid to the stored `G28` position, raising Z to the safe height
+    /// first. See [`protocol::build_predefined_position_gcode`] for why.
+    pub fn go_to_g28(&self) -> Result<(), ControllerError> {
+        self.go_to_predefined_position(protocol::PredefinedPosition::G28)
+    }
+
+    /// Rapid to the stored `G30` position, raising Z to the safe height first.
+    pub fn go_to_g30(&self) -> Result<(), ControllerError> {
+        self.go_to_predefined_position(protocol::PredefinedPosition::G30)
+    }
+
+    /// Claim the machine for a predefined position command: connected,
+    /// no alarm, homed, and Idle.
+    fn begin_predefined_position(
+        &self,
+        which: protocol::PredefinedPosition,
+    ) -> Result<MotionGuard<'_>, ControllerError> {
+        if !self.is_connected() {
+            return Err(ControllerError::NotConnected);
+        }
+
+        let motion = self.begin_motion()?;
+        self.ensure_no_pending_alarm()?;
+        self.ensure_homed()?;
+
+        let state = self.state.lock();
+        if state.status.state != MachineState::Idle {
+            return Err(ControllerError::InvalidState(format!(
+                "Cannot use the {:?} position in {:?} state",
+                which, state.status.state
+            )));
+        }
+        Ok(motion)
+    }
+
+    fn set_predefined_position(
+        &self,
+        which: protocol::PredefinedPosition,
+    ) -> Result<Position, ControllerError> {
+        {
+            let _motion = self.begin_predefined_position(which)?;
+            self.send_command(&which.set_command())?;
+        }
+
+        let params = self.read_parameters()?;
+        let stored = match which {
+            protocol::PredefinedPosition::G28 => params.g28,
+            protocol::PredefinedPosition::G30 => params.g30,
+        };
+        stored.ok_or_else(|| {
+            ControllerError::Internal(format!("No {:?} position in $# response", which))
+        })
+    }
+
+    fn go_to_predefined_position(
+        &self,
+        which: protocol::PredefinedPosition,
+    ) -> Result<(), ControllerError> {
+        let _motion = self.begin_predefined_position(which)?;
+        let safe_z = self.state.lock().safe_z;
+
+        for line in protocol::build_predefined_position_gcode(which, safe_z).lines() {
+            self.send_command(line)?;
         }
 
         Ok(())
     }
-}
 
-/// Serializable snapshot of controller state for the UI
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct ControllerSnapshot {
-    pub connection: ConnectionState,
-    pub status: MachineStatus,
-    pub welcome_message: Option<String>,
-    pub last_error: Option<String>,
-    /// Pending alarm: (alarm_code, unique_id) - ID for deduplication
-    pub pending_alarm: Option<(u32, u64)>,
-    /// Whether the last status poll got a fresh response (false = stale/timeout)
-    pub status_is_fresh: bool,
-}
+    /// Set the park position in machine coordinates (mm).
+    pub fn set_park_position(&self, x: f64, y: f64) {
+        self.state.lock().park_position = (x, y);
+    }
+
+    /// Set the line ending appended to commands and streamed lines, for
+    /// the current connection and later ones.
+    pub fn set_line_ending(
+        &self,
+        line_ending: protocol::LineEnding,
+    ) -> Result<(), ControllerError> {
+        self.worker
+            .set_line_ending(line_ending)
+            .map_err(|e| self.worker_error(e))?;
+        self.state.lock().line_ending = line_ending;
+        Ok(())
+    }
+
+    /// Take over the settings the app applies to every device (jog
+    /// presets, travel check and minimum feed, safe Z, park position, rate smoothing,
+    /// line ending, serial log options and usage counter) from another
+    /// controller, for a newly added device.
+    pub fn inherit_settings(&self, other: &Controller) {
+        let line_ending = {
+            let from = other.state.lock();
+            let mut state = self.state.lock();
+            state.jog_presets = from.jog_presets.clone();
+            state.safe_z = from.safe_z;
+            state.park_position = from.park_position;
+            state.rate_smoothing = from.rate_smoothing;
+            state.jog_travel_check = from.jog_travel_check;
+            state.min_feed = from.min_feed;
+            state.usage = from.usage.clone();
+            from.line_ending
+        };
+        if let Err(e) = self.set_line_ending(line_ending) {
+            log::warn!("Failed to apply the line ending to a new device: {}", e);
+        }
+        self.serial_log().inherit(&other.serial_log());
+    }
+
+    /// Record finished jobs' laser usage into `usage`
+    pub fn set_usage_counter(&self, usage: Arc<UsageCounter>) {
+        self.state.lock().usage = Some(usage);
+    }
+
+    /// Set the Z height used for travel moves (None disables Z moves).
+    /// The workspace settings' `safe_z`/`use_safe_z` are the source.
+    pub fn set_safe_z(&self, safe_z: Option<f64>) {
+        self.state.lock().safe_z = safe_z;
+    }
+
+    /// Set how strongly the line rate behind a job's remaining-time
+    /// estimate is smoothed: the weight of the newest sample, 0.01..=1.
+    /// Applies from the next job.
+    pub fn set_rate_smoothing(&self, smoothing: f64) {
+        self.state.lock().rate_smoothing = Some(smoothing);
+    }
 
-impl Controller {
     /// Get a serializable snapshot of controller state.
     pub fn snapshot(&self) -> ControllerSnapshot {
         let state = self.state.lock();
+        let job = state.job.as_ref().map(|p| p.lock().clone());
+        let (planner_free, rx_free) = state.status.buffer.unzip();
+        let queued = planner_free.map(|free| state.planner_capacity.saturating_sub(free));
+        let buffer_low = job.as_ref().is_some_and(|j| j.is_running())
+            && state.hold_jog.is_none()
+            && !state.hold_drain
+            && queued.is_some_and(|queued| queued < LOW_PLANNER_BLOCKS);
         ControllerSnapshot {
             connection: state.connection.clone(),
             status: state.status.clone(),
             welcome_message: state.welcome_message.clone(),
-            last_error: state.last_error.clone(),
+            firmware: state.firmware.clone(),
+            last_error: state.last_error.as_ref().map(LastError::from),
             pending_alarm: state.pending_alarm,
             status_is_fresh: state.status_is_fresh,
+            parser_state: state.parser_state.clone(),
+            job,
+            spindle_power_percent: state.spindle_power_percent(),
+            is_homed: state.is_homed,
+            planner_free,
+            rx_free,
+            buffer_low,
+            feed_rate_mm_min: state.feed_rates().map(|rates| rates.mm_per_min),
+            hold_jog: state.hold_jog.is_some(),
+            hold_drain: state.hold_drain,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 10 x 10 frame at 1000 units/min with the laser off
+    fn frame_request() -> FrameRequest {
+        FrameRequest {
+            x_min: 0.0,
+            x_max: 10.0,
+            y_min: 0.0,
+            y_max: 10.0,
+            margin: 0.0,
+            feed: 1000.0,
+            power: 0,
+            units: protocol::Units::Mm,
+            mode: protocol::FrameMode::LaserOff,
+        }
+    }
+
+    fn connected_controller() -> Controller {
+        let controller = Controller::new();
+        controller.state.lock().connection = ConnectionState::Connected {
+            port: "test".into(),
+            baud: protocol::DEFAULT_BAUD_RATE,
+        };
+        controller
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_overrides_known_from_connect() {
+        let (path, _slave, _received) = super::super::worker::tests::fake_device();
+        let controller = Controller::new();
+        controller.connect(&path, 115200).unwrap();
+        assert_eq!(
+            controller.snapshot().status.overrides,
+            Some(Overrides::NORMAL)
+        );
+
+        // The fake device's reports leave out `Ov`
+        let status = controller.poll_status().unwrap();
+        assert_eq!(status.overrides, Some(Overrides::NORMAL));
+        assert_eq!(controller.take_override_change(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_frame_margin_expands_trace() {
+        let (path, _slave, received) = super::super::worker::tests::fake_device();
+        let controller = Controller::new();
+        controller.connect(&path, 115200).unwrap();
+        controller.state.lock().status.state = MachineState::Idle;
+        let frame = |margin| {
+            controller.run_frame(
+                FrameRequest {
+                    x_min: 10.0,
+                    x_max: 0.0,
+                    y_max: 5.0,
+                    margin,
+                    ..frame_request()
+                },
+                |_| {},
+            )
+        };
+
+        assert!(matches!(frame(-1.0), Err(ControllerError::InvalidState(_))));
+        frame(2.0).unwrap();
+        let moves: Vec<String> = received
+            .lock()
+            .iter()
+            .filter(|line| line.starts_with("G0"))
+            .cloned()
+            .collect();
+        assert_eq!(
+            moves,
+            [
+                "G0 X-2.000 Y-2.000",
+                "G0 X12.000 Y-2.000",
+                "G0 X12.000 Y7.000",
+                "G0 X-2.000 Y7.000",
+                "G0 X-2.000 Y-2.000",
+            ]
+        );
+
+        // The margin has to fit the travel too
+        {
+            let mut state = controller.state.lock();
+            state.settings.insert(130, "100.000".into());
+            state.settings.insert(131, "100.000".into());
+            state.status.work_offset = Some(Position::new(-99.0, -50.0, 0.0));
+        }
+        assert!(frame(0.0).is_ok());
+        match frame(2.0) {
+            Err(ControllerError::InvalidState(msg)) => assert!(msg.contains("$130"), "{msg}"),
+            other => panic!("expected travel rejection, got {other:?}"),
+        }
+
+        // Stock GRBL travels negative, so a frame past machine zero is out
+        // even though it's within the travel's magnitude
+        controller.state.lock().status.work_offset = Some(Position::new(-5.0, -50.0, 0.0));
+        match frame(0.0) {
+            Err(ControllerError::InvalidState(msg)) => {
+                assert!(msg.contains("-100.000 to 0.000 mm travel ($130)"), "{msg}")
+            }
+            other => panic!("expected travel rejection, got {other:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_disconnect_turns_laser_off() {
+        let (path, _slave, received) = super::super::worker::tests::fake_device();
+        let controller = Controller::new();
+        controller.connect(&path, 115200).unwrap();
+        controller.state.lock().status.state = MachineState::Idle;
+
+        controller.disconnect().unwrap();
+        assert!(matches!(
+            controller.connection_state(),
+            ConnectionState::Disconnected
+        ));
+        // Give the fake device time to record the last realtime byte
+        thread::sleep(Duration::from_millis(100));
+        let received = received.lock().clone();
+        assert_eq!(
+            received,
+            [
+                "M5".to_string(),
+                format!("0x{:02X}", protocol::realtime::SPINDLE_OVR_RESET)
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_jog_during_hold_sets_job_aside() {
+        let (path, _slave, received) = super::super::worker::tests::fake_device();
+        let controller = Controller::new();
+        controller.connect(&path, 115200).unwrap();
+        controller.state.lock().status.state = MachineState::Idle;
+
+        // Not held yet
+        assert!(matches!(
+            controller.jog_during_hold(Some(5.0), None, None, 1000.0, true),
+            Err(ControllerError::InvalidState(_))
+        ));
+
+        let program = vec!["G1 X1".to_string(); 20_000];
+        controller
+            .run_gcode_program(program, false, false, |_| {})
+            .unwrap();
+        {
+            let mut state = controller.state.lock();
+            state.status.state = MachineState::Hold;
+            state.status.sub_state = Some(0);
+        }
+        let moves = || received.lock().iter().filter(|l| *l == "G1 X1").count();
+
+        // Moves may still be queued (no `Bf` reports): the jog is refused
+        // without leaving Hold, and the job's lines stop
+        assert!(matches!(
+            controller.jog_during_hold(Some(5.0), None, None, 1000.0, true),
+            Err(ControllerError::InvalidState(_))
+        ));
+        assert!(controller.snapshot().hold_drain);
+        // Lines written before the refusal may still be arriving
+        thread::sleep(Duration::from_millis(100));
+        let sent_at_hold = moves();
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(moves(), sent_at_hold);
+        assert!(!received.lock().contains(&"~".to_string()));
+
+        // The user's resume runs only what was queued
+        controller.cycle_start().unwrap();
+        controller.poll_status().unwrap();
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(moves(), sent_at_hold);
+
+        controller
+            .jog_during_hold(Some(5.0), None, None, 1000.0, true)
+            .unwrap();
+        let snapshot = controller.snapshot();
+        assert!(snapshot.hold_jog && !snapshot.hold_drain);
+        controller.cycle_start().unwrap();
+        assert!(!controller.snapshot().hold_jog);
+
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while controller.job_progress().is_some_and(|p| p.is_running()) {
+            assert!(Instant::now() < deadline, "job didn't finish");
+            thread::sleep(Duration::from_millis(10));
         }
+        assert_eq!(
+            controller.job_progress().unwrap().state,
+            stream::StreamState::Completed
+        );
+
+        // Laser off, jog, laser back on, all between program lines, and
+        // only the user's resume left Hold
+        let received = received.lock().clone();
+        let at = |line: &str| received.iter().position(|l| l == line).unwrap();
+        let (laser_off, jog, laser_on) = (at("M5"), at("$J=G91 X5.000 F1000.000"), at("M4"));
+        assert_eq!(received.iter().filter(|l| *l == "~").count(), 1);
+        assert!(at("~") < at("$G") && at("$G") < laser_off);
+        assert!(!received[at("~")..laser_on].contains(&"G1 X1".to_string()));
+        assert!(laser_off < jog && jog < laser_on);
+        assert!(received[..laser_off].contains(&"G1 X1".to_string()));
+        assert!(received[laser_on..].contains(&"G1 X1".to_string()));
+        assert_eq!(received.iter().filter(|l| *l == "G1 X1").count(), 20_000);
+        controller.disconnect().unwrap();
+    }
+
+    #[test]
+    fn test_motion_refused_until_homed() {
+        let controller = connected_controller();
+        controller.state.lock().settings.insert(22, "1".into());
+
+        assert!(matches!(
+            controller.jog(Some(1.0), None, None, 1000.0, true),
+            Err(ControllerError::InvalidState(_))
+        ));
+        assert!(matches!(
+            controller.run_gcode_program(vec!["G0 X1".into()], false, false, |_| {}),
+            Err(ControllerError::InvalidState(_))
+        ));
+    }
+
+    #[test]
+    fn test_resume_requires_homing_and_valid_line() {
+        let controller = connected_controller();
+        let lines = vec!["G0 X1".to_string(), "G1 X2".to_string()];
+        let refused = |result: Result<(), ControllerError>, text: &str| match result {
+            Err(ControllerError::InvalidState(msg)) => msg.contains(text),
+            _ => false,
+        };
+
+        assert!(refused(
+            controller.resume_from_line(lines.clone(), 1, false),
+            "homing ($22)"
+        ));
+        controller.state.lock().settings.insert(22, "1".into());
+        assert!(refused(
+            controller.resume_from_line(lines, 2, false),
+            "past the end"
+        ));
+    }
+
+    #[test]
+    fn test_step_mode_lifecycle() {
+        let controller = connected_controller();
+        let step_error = |result: Result<StepStatus, ControllerError>, text: &str| match result {
+            Err(ControllerError::InvalidState(msg)) => msg.contains(text),
+            _ => false,
+        };
+        assert!(step_error(controller.step_next(), "not active"));
+
+        let status = controller
+            .step_mode_begin(vec!["; only comments".into(), String::new()])
+            .unwrap();
+        assert_eq!((status.next, status.total_lines), (None, 2));
+        assert!(step_error(controller.step_next(), "end of the program"));
+
+        controller.step_abort();
+        assert_eq!(controller.step_status(), None);
+        assert!(step_error(controller.step_next(), "not active"));
+    }
+
+    #[test]
+    fn test_jog_refused_across_machine_zero() {
+        let controller = connected_controller();
+        {
+            let mut state = controller.state.lock();
+            state.settings.insert(22, "1".into());
+            state.is_homed = true;
+            state.status.state = MachineState::Idle;
+            state.status.machine_pos = Position {
+                x: -1.0,
+                y: -5.0,
+                z: -1.0,
+            };
+        }
+        let refused = |result: Result<(), ControllerError>| matches!(result, Err(ControllerError::InvalidState(msg)) if msg.contains("machine zero"));
+
+        // Stock GRBL: machine space is negative
+        assert!(refused(controller.jog(Some(2.0), None, None, 1000.0, true)));
+        assert!(refused(controller.jog(
+            None,
+            Some(1.0),
+            Some(1.5),
+            1000.0,
+            true
+        )));
+        // Moves within the travel get past the check (and fail to send here)
+        assert!(!refused(controller.jog(
+            Some(-10.0),
+            Some(5.0),
+            None,
+            1000.0,
+            true
+        )));
+        // Absolute jogs are left to soft limits
+        assert!(!refused(controller.jog(
+            Some(2.0),
+            None,
+            None,
+            1000.0,
+            false
+        )));
+
+        // Force-origin build homing X toward negative: X travels positive
+        {
+            let mut state = controller.state.lock();
+            state.settings.insert(23, "1".into());
+            state.build_info = vec!["[OPT:VZL,15,128]".into()];
+        }
+        assert!(!refused(controller.jog(
+            Some(2.0),
+            None,
+            None,
+            1000.0,
+            true
+        )));
+        assert!(refused(controller.jog(
+            Some(-2.0),
+            None,
+            None,
+            1000.0,
+            true
+        )));
+
+        controller.set_jog_travel_check(false);
+        assert!(!refused(controller.jog(
+            Some(-2.0),
+            None,
+            None,
+            1000.0,
+            true
+        )));
+    }
+
+    #[test]
+    fn test_inherit_settings() {
+        let controller = Controller::new();
+        controller.set_safe_z(Some(5.0));
+        controller.set_park_position(10.0, 20.0);
+        controller
+            .set_line_ending(protocol::LineEnding::CrLf)
+            .unwrap();
+
+        let added = Controller::new();
+        added.inherit_settings(&controller);
+        let state = added.state.lock();
+        assert_eq!(state.safe_z, Some(5.0));
+        assert_eq!(state.park_position, (10.0, 20.0));
+        assert_eq!(state.line_ending, protocol::LineEnding::CrLf);
+    }
+
+    #[test]
+    fn test_stop_all_clears_job_and_step() {
+        let controller = Controller::new();
+        // Safe while disconnected
+        assert!(controller.stop_all().is_ok());
+
+        let controller = connected_controller();
+        let usage = Arc::new(UsageCounter::new());
+        controller.set_usage_counter(usage.clone());
+        controller.state.lock().status.state = MachineState::Idle;
+        controller.step_mode_begin(vec!["G0 X1".into()]).unwrap();
+        {
+            let mut state = controller.state.lock();
+            state.job = Some(StreamJob::from_lines(vec!["G1 X10".into()], false).progress());
+            state.job_usage = Some(JobUsage::default());
+            state.is_homed = true;
+        }
+
+        assert!(controller.stop_all().is_ok());
+        assert!(controller.job_progress().is_none());
+        assert_eq!(controller.step_status(), None);
+        // The stopped job still counts toward laser usage
+        assert_eq!(usage.stats().job_count, 1);
+        // The soft reset forgets the cached machine state
+        assert!(!controller.state.lock().is_homed);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stop_all_waits_for_hold_to_complete() {
+        let report = Arc::new(Mutex::new(String::from(
+            "<Run|MPos:0.000,0.000,0.000|FS:1000,0>",
+        )));
+        let (path, _slave, received) =
+            super::super::worker::tests::fake_device_reporting(report.clone());
+        let controller = Controller::new();
+        controller.connect(&path, 115200).unwrap();
+
+        // Decelerating until the device reports the hold complete
+        *report.lock() = "<Hold:1|MPos:0.000,0.000,0.000|FS:500,0>".into();
+        let stopped = thread::spawn({
+            let report = report.clone();
+            move || {
+                thread::sleep(Duration::from_millis(300));
+                *report.lock() = "<Hold:0|MPos:0.000,0.000,0.000|FS:0,0>".into();
+                Instant::now()
+            }
+        });
+        controller.stop_all().unwrap();
+        let reset_at = Instant::now();
+        assert!(reset_at >= stopped.join().unwrap());
+        assert!(received.lock().contains(&"!".to_string()));
+        controller.disconnect().unwrap();
+    }
+
+    #[test]
+    fn test_motion_refused_while_job_running() {
+        let controller = connected_controller();
+        controller.state.lock().status.state = MachineState::Idle;
+        let progress = StreamJob::from_lines(vec!["G1 X10".into()], false).progress();
+        controller.state.lock().job = Some(progress.clone());
+
+        let busy = |result: Result<(), ControllerError>| match result {
+            Err(ControllerError::InvalidState(msg)) => msg.starts_with("Machine busy"),
+            _ => false,
+        };
+        assert!(busy(controller.run_frame(
+            FrameRequest {
+                power: 10,
+                ..frame_request()
+            },
+            |_| {},
+        )));
+        assert!(busy(controller.jog(Some(1.0), None, None, 1000.0, true)));
+        assert!(busy(controller.go_to_work_zero(None)));
+        assert!(busy(controller.run_gcode_program(
+            vec!["G0 X1".into()],
+            false,
+            false,
+            |_| {}
+        )));
+
+        // Finished job releases the machine
+        progress.lock().state = stream::StreamState::Completed;
+        assert!(!busy(controller.jog(Some(1.0), None, None, 1000.0, true)));
+
+        // Concurrent motion command in progress
+        let _motion = controller.begin_motion().unwrap();
+        assert!(busy(controller.go_to_work_zero(None)));
+    }
+
+    #[test]
+    fn test_override_changes_tracked() {
+        let mut state = ControllerState::default();
+        let mut report = |raw: &str| {
+            let mut status = MachineStatus::parse(raw).unwrap();
+            state.track_overrides(&mut status);
+            state.status = status;
+            state.override_change.take()
+        };
+        let overrides = |feed, rapid, spindle| Overrides {
+            feed,
+            rapid,
+            spindle,
+        };
+
+        assert_eq!(
+            report("<Idle|MPos:0.000,0.000,0.000|Ov:100,100,100>"),
+            Some(overrides(100, 100, 100))
+        );
+        assert_eq!(report("<Idle|MPos:0.000,0.000,0.000|Ov:100,100,100>"), None);
+        // Ov absent: unchanged, and the cached value is kept
+        assert_eq!(report("<Run|MPos:1.000,0.000,0.000>"), None);
+        assert_eq!(
+            report("<Run|MPos:2.000,0.000,0.000|Ov:120,100,90>"),
+            Some(overrides(120, 100, 90))
+        );
+        assert_eq!(report("<Run|MPos:3.000,0.000,0.000>"), None);
+        assert_eq!(state.status.overrides, Some(overrides(120, 100, 90)));
+    }
+
+    #[test]
+    fn test_work_offset_kept_between_reports() {
+        let mut state = ControllerState::default();
+        let mut report = |raw: &str| {
+            let mut status = MachineStatus::parse(raw).unwrap();
+            if let Some(wco) = state.status.work_offset {
+                status.fill_work_offset(wco);
+            }
+            state.status = status;
+            state.status.work_pos
+        };
+
+        // No offset seen yet
+        assert_eq!(report("<Idle|MPos:10.000,10.000,0.000>"), None);
+        assert_eq!(
+            report("<Idle|MPos:10.000,10.000,0.000|WCO:4.000,3.000,0.000>"),
+            Some(Position::new(6.0, 7.0, 0.0))
+        );
+        assert_eq!(
+            report("<Run|MPos:20.000,10.000,0.000>"),
+            Some(Position::new(16.0, 7.0, 0.0))
+        );
+        assert_eq!(
+            report("<Run|MPos:20.000,15.000,0.000|FS:500,0>"),
+            Some(Position::new(16.0, 12.0, 0.0))
+        );
+        // A new WCO replaces the cached one
+        assert_eq!(
+            report("<Idle|MPos:20.000,15.000,0.000|WCO:0.000,0.000,0.000>"),
+            Some(Position::new(20.0, 15.0, 0.0))
+        );
+        assert_eq!(
+            report("<Idle|MPos:21.000,15.000,0.000>"),
+            Some(Position::new(21.0, 15.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn test_snapshot_buffer_fields() {
+        let controller = connected_controller();
+        let snapshot = controller.snapshot();
+        assert_eq!((snapshot.planner_free, snapshot.rx_free), (None, None));
+        assert!(!snapshot.buffer_low);
+
+        let progress = StreamJob::from_lines(vec!["G1 X10".into()], false).progress();
+        {
+            let mut state = controller.state.lock();
+            state.planner_capacity = 15;
+            state.status.buffer = Some((14, 100));
+            state.job = Some(progress.clone());
+        }
+        let snapshot = controller.snapshot();
+        assert_eq!(snapshot.planner_free, Some(14));
+        assert_eq!(snapshot.rx_free, Some(100));
+        assert!(snapshot.buffer_low);
+
+        // Plenty queued, or no job running: not low
+        controller.state.lock().status.buffer = Some((5, 100));
+        assert!(!controller.snapshot().buffer_low);
+        controller.state.lock().status.buffer = Some((15, 128));
+        progress.lock().state = stream::StreamState::Completed;
+        assert!(!controller.snapshot().buffer_low);
+    }
+
+    #[test]
+    fn test_job_finished_once_machine_is_done() {
+        let mut state = ControllerState::default();
+        let progress = StreamJob::from_lines(vec!["G1 X10".into()], false).progress();
+        state.job = Some(progress.clone());
+        state.planner_capacity = 15;
+        let report = |state: &mut ControllerState, raw: &str| {
+            state.status = MachineStatus::parse(raw).unwrap();
+            state.track_job_finished();
+            state.job_complete.take()
+        };
+
+        // Still sending, then acknowledged but moving
+        assert!(report(&mut state, "<Idle|MPos:0.000,0.000,0.000|Bf:15,128>").is_none());
+        progress.lock().state = stream::StreamState::Completed;
+        assert!(report(&mut state, "<Run|MPos:1.000,0.000,0.000|Bf:14,128>").is_none());
+        assert!(report(&mut state, "<Idle|MPos:1.000,0.000,0.000|Bf:14,128>").is_none());
+
+        let done = report(&mut state, "<Idle|MPos:10.000,0.000,0.000|Bf:15,128>").unwrap();
+        assert!(done.finished && !done.program_end);
+        // Reported once
+        assert!(report(&mut state, "<Idle|MPos:10.000,0.000,0.000|Bf:15,128>").is_none());
+
+        // GRBL's program end message is enough on its own
+        let progress = StreamJob::from_lines(vec!["M2".into()], false).progress();
+        state.job = Some(progress.clone());
+        {
+            let mut progress = progress.lock();
+            progress.state = stream::StreamState::Completed;
+            progress.program_end = true;
+        }
+        assert!(report(&mut state, "<Run|MPos:10.000,0.000,0.000|Bf:14,128>").is_some());
+    }
+
+    #[test]
+    fn test_lost_connection_logs_reconnect() {
+        use ConnectionEventKind::*;
+        let controller = connected_controller();
+        controller.worker_error(WorkerError::Disconnected("cable pulled".into()));
+        assert!(controller.begin_connect("/dev/ttyUSB0", 115200).is_ok());
+
+        let events = controller.take_new_connection_events();
+        let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, [ConnectionLost, Reconnect]);
+        assert_eq!(events[1].message.as_deref(), Some("/dev/ttyUSB0 @ 115200"));
+        assert_eq!(controller.connection_events(), events);
+        assert!(controller.take_new_connection_events().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_feed_rate_mm_min() {
+        let controller = connected_controller();
+        let report = |controller: &Controller| {
+            let mut status = MachineStatus::parse("<Run|MPos:1.000,0.000,0.000|FS:40,0>").unwrap();
+            let mut state = controller.state.lock();
+            if state.reports_inches() {
+                status.convert_from_inches();
+            }
+            state.status = status;
+        };
+
+        // Units unknown: no normalized feed
+        report(&controller);
+        assert_eq!(controller.snapshot().feed_rate_mm_min, None);
+
+        controller.state.lock().settings.insert(13, "0".into());
+        report(&controller);
+        assert_eq!(controller.snapshot().feed_rate_mm_min, Some(40.0));
+
+        controller.state.lock().settings.insert(13, "1".into());
+        report(&controller);
+        let feed = controller.snapshot().feed_rate_mm_min.unwrap();
+        assert!((feed - 1016.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_frame_request_defaults() {
+        let frame: FrameRequest = serde_json::from_value(serde_json::json!({
+            "x_min": 0.0,
+            "x_max": 10.0,
+            "y_min": 0.0,
+            "y_max": 10.0,
+            "feed": 1000.0,
+            "power": 0,
+            "mode": "LaserOff",
+        }))
+        .unwrap();
+        assert_eq!(frame, frame_request());
+    }
+
+    #[test]
+    fn test_frame_power_above_max_rejected() {
+        let controller = connected_controller();
+        controller.state.lock().settings.insert(30, "1000".into());
+        let frame = |power| {
+            controller.run_frame(
+                FrameRequest {
+                    power,
+                    mode: protocol::FrameMode::LowPower,
+                    ..frame_request()
+                },
+                |_| {},
+            )
+        };
+
+        match frame(5000) {
+            Err(ControllerError::InvalidState(msg)) => assert!(msg.contains("$30")),
+            other => panic!("expected power rejection, got {other:?}"),
+        }
+        // Within range gets past validation (fails later: no device)
+        let within = frame(1000);
+        assert!(!matches!(within, Err(ControllerError::InvalidState(_))));
+    }
+
+    #[test]
+    fn test_feed_below_minimum_rejected() {
+        let controller = connected_controller();
+        controller.state.lock().status.state = MachineState::Idle;
+        let rejected = |result: Result<(), ControllerError>, text: &str| match result {
+            Err(ControllerError::InvalidState(msg)) => msg.contains(text),
+            _ => false,
+        };
+        let jog = |feed| controller.jog(Some(1.0), None, None, feed, true);
+        let frame = |feed| {
+            controller.run_frame(
+                FrameRequest {
+                    feed,
+                    ..frame_request()
+                },
+                |_| {},
+            )
+        };
+
+        assert!(rejected(jog(0.0005), "minimum"));
+        assert!(rejected(frame(0.0005), "minimum"));
+
+        // Without a minimum, feeds are still refused once they format as zero
+        controller.set_min_feed(0.0);
+        assert!(rejected(jog(0.0004), "F0.000"));
+        assert!(rejected(frame(0.4), "F0"));
+        // Within range gets past validation (fails later: no device)
+        assert!(!matches!(
+            jog(0.0005),
+            Err(ControllerError::InvalidState(_))
+        ));
+    }
+
+    #[test]
+    fn test_last_error_keeps_variant() {
+        let controller = connected_controller();
+        assert!(controller.last_error().is_none());
+
+        controller.record_error(ControllerError::Alarm(9));
+        let last = controller.snapshot().last_error.unwrap();
+        assert_eq!(last.error, ControllerError::Alarm(9));
+        assert_eq!(last.message, "Device in alarm state (code 9)");
+        assert_eq!(
+            serde_json::to_value(&last.error).unwrap(),
+            serde_json::json!({ "Alarm": 9 })
+        );
+    }
+
+    #[test]
+    fn test_frame_feed_clamped_to_max_rate() {
+        let controller = connected_controller();
+        let mut state = controller.state.lock();
+        let mm = protocol::Units::Mm;
+        assert_eq!(state.frame_feed(5000.0, 100.0, 10.0, mm), 5000.0);
+
+        state.settings.insert(110, "3000.000".into());
+        state.settings.insert(111, "2000.000".into());
+        assert_eq!(state.frame_feed(5000.0, 100.0, 10.0, mm), 3000.0);
+        assert_eq!(state.frame_feed(5000.0, 10.0, 100.0, mm), 2000.0);
+        assert_eq!(state.frame_feed(1000.0, 100.0, 10.0, mm), 1000.0);
+        let inches = state.frame_feed(500.0, 4.0, 1.0, protocol::Units::Inches);
+        assert!((inches - 3000.0 / 25.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rapid_move_validates_targets() {
+        let controller = connected_controller();
+        {
+            let mut state = controller.state.lock();
+            state.status.state = MachineState::Idle;
+            state.settings.insert(130, "400".into());
+            state.status.work_offset = Some(Position::new(-350.0, -10.0, 0.0));
+        }
+        let refused = |result: Result<(), ControllerError>, text: &str| match result {
+            Err(ControllerError::InvalidState(msg)) => msg.contains(text),
+            _ => false,
+        };
+
+        assert!(refused(
+            controller.rapid_move(None, None, None, false, protocol::Units::Mm),
+            "at least one axis"
+        ));
+        assert!(refused(
+            controller.rapid_move(Some(f64::NAN), None, None, true, protocol::Units::Mm),
+            "not a finite number"
+        ));
+        assert!(refused(
+            controller.rapid_move(Some(-420.0), Some(5.0), None, true, protocol::Units::Mm),
+            "outside the -400.000 to 0.000 mm travel ($130)"
+        ));
+        // Stock GRBL travels negative from machine zero
+        assert!(refused(
+            controller.rapid_move(Some(20.0), None, None, true, protocol::Units::Mm),
+            "machine 20.000"
+        ));
+        // X-100 in work coordinates is X-450 in machine coordinates
+        assert!(refused(
+            controller.rapid_move(Some(-100.0), None, None, false, protocol::Units::Mm),
+            "machine -450.000"
+        ));
+        // X-2 inches is X-50.8 mm, X-400.8 in machine coordinates
+        assert!(refused(
+            controller.rapid_move(Some(-2.0), None, None, false, protocol::Units::Inches),
+            "machine -400.800"
+        ));
+    }
+
+    #[test]
+    fn test_predefined_positions_require_idle() {
+        let controller = connected_controller();
+        controller.state.lock().status.state = MachineState::Hold;
+
+        for result in [controller.go_to_g28(), controller.go_to_g30()] {
+            assert!(matches!(result, Err(ControllerError::InvalidState(_))));
+        }
+        assert!(matches!(
+            controller.set_g28_position(),
+            Err(ControllerError::InvalidState(_))
+        ));
+        // The motion claim is released after a refusal
+        assert!(controller.begin_motion().is_ok());
+    }
+
+    #[test]
+    fn test_park_outside_travel_rejected() {
+        let controller = connected_controller();
+        {
+            let mut state = controller.state.lock();
+            state.status.state = MachineState::Idle;
+            state.settings.insert(130, "400.000".into());
+            state.settings.insert(131, "400.000".into());
+        }
+        controller.set_park_position(-10.0, -450.0);
+
+        match controller.park() {
+            Err(ControllerError::InvalidState(msg)) => assert!(msg.contains("$131")),
+            other => panic!("expected travel rejection, got {other:?}"),
+        }
+
+        // Within the magnitude but on the wrong side of machine zero
+        controller.set_park_position(10.0, -10.0);
+        match controller.park() {
+            Err(ControllerError::InvalidState(msg)) => {
+                assert!(msg.contains("-400.000 to 0.000 mm travel ($130)"), "{msg}")
+            }
+            other => panic!("expected travel rejection, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_cycle_start_waits_for_hold_complete() {
+        let controller = connected_controller();
+        {
+            let mut state = controller.state.lock();
+            state.status.state = MachineState::Hold;
+            state.status.sub_state = Some(1);
+        }
+        assert!(matches!(
+            controller.cycle_start(),
+            Err(ControllerError::InvalidState(_))
+        ));
+
+        controller.state.lock().status.sub_state = Some(0);
+        assert!(!matches!(
+            controller.cycle_start(),
+            Err(ControllerError::InvalidState(_))
+        ));
+    }
+
+    #[test]
+    fn test_connect_async_reports_failure() {
+        let controller = Controller::new_shared();
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let on_done = move |result, state| tx.send((result, state)).unwrap();
+        let state = controller
+            .connect_async("/dev/dl44-no-such-port".into(), 115200, on_done)
+            .unwrap();
+        assert!(matches!(state, ConnectionState::Connecting));
+
+        let (result, state) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(result.is_err());
+        assert!(matches!(
+            state,
+            ConnectionState::Error {
+                kind: ConnectErrorKind::PortNotFound,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_write_settings_requires_idle() {
+        let controller = connected_controller();
+        controller.state.lock().status.state = MachineState::Run;
+        assert!(matches!(
+            controller.write_settings(&[(30, "1000".into())]),
+            Err(ControllerError::InvalidState(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_settings_refuses_injected_lines() {
+        let (path, _slave, received) = super::super::worker::tests::fake_device();
+        let controller = Controller::new();
+        controller.connect(&path, 115200).unwrap();
+        controller.state.lock().status.state = MachineState::Idle;
+        received.lock().clear();
+
+        match controller.write_settings(&[(32, "1".into()), (30, "1\n$RST=*".into())]) {
+            Err(ControllerError::InvalidState(msg)) => assert!(msg.contains("$30"), "{msg}"),
+            other => panic!("expected the value to be refused, got {other:?}"),
+        }
+        thread::sleep(Duration::from_millis(50));
+        assert!(received.lock().is_empty());
+        controller.disconnect().unwrap();
+    }
+
+    #[test]
+    fn test_read_setting_served_from_cache() {
+        // Nothing cached and no device to read from
+        assert!(matches!(
+            Controller::new().read_setting(30),
+            Err(ControllerError::NotConnected)
+        ));
+
+        let controller = connected_controller();
+        controller.state.lock().settings.insert(30, "1000".into());
+        assert_eq!(
+            controller.read_setting(30).unwrap().as_deref(),
+            Some("1000")
+        );
+        assert_eq!(controller.read_setting(99).unwrap(), None);
+    }
+
+    #[test]
+    fn test_ensure_laser_mode() {
+        let controller = connected_controller();
+        controller.state.lock().settings.insert(30, "1000".into());
+        assert_eq!(controller.laser_mode().unwrap(), None);
+        assert!(matches!(
+            controller.ensure_laser_mode(),
+            Err(ControllerError::InvalidState(_))
+        ));
+
+        controller.state.lock().settings.insert(32, "1".into());
+        assert_eq!(controller.laser_mode().unwrap(), Some(true));
+        assert!(!controller.ensure_laser_mode().unwrap());
+
+        // Turning it on is a settings write, refused while running
+        {
+            let mut state = controller.state.lock();
+            state.settings.insert(32, "0".into());
+            state.status.state = MachineState::Run;
+        }
+        assert_eq!(controller.laser_mode().unwrap(), Some(false));
+        assert!(matches!(
+            controller.ensure_laser_mode(),
+            Err(ControllerError::InvalidState(_))
+        ));
+    }
+
+    #[test]
+    fn test_reset_settings_requires_idle() {
+        let controller = connected_controller();
+        controller.state.lock().status.state = MachineState::Run;
+        assert!(matches!(
+            controller.reset_settings(ResetKind::All),
+            Err(ControllerError::InvalidState(_))
+        ));
+    }
+
+    #[test]
+    fn test_spindle_power_percent() {
+        let controller = connected_controller();
+        controller.state.lock().status.spindle_speed = Some(400.0);
+        assert_eq!(controller.spindle_power_percent(), None);
+
+        controller.state.lock().settings.insert(30, "1000".into());
+        assert_eq!(controller.spindle_power_percent(), Some(40.0));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unlock_keeps_alarm_while_device_stays_in_alarm() {
+        let report = Arc::new(Mutex::new(String::from(
+            "<Alarm|MPos:0.000,0.000,0.000|FS:0,0>",
+        )));
+        let (path, _slave, received) =
+            super::super::worker::tests::fake_device_reporting(report.clone());
+        let controller = Controller::new();
+        controller.connect(&path, 115200).unwrap();
+        controller.state.lock().pending_alarm = Some((1, 1));
+        let alarm = controller.current_alarm().unwrap();
+        assert_eq!(alarm.code, 1);
+        assert!(alarm.description.starts_with("Hard limit"));
+
+        // The device accepts `$X` but still reports Alarm
+        assert!(!controller.unlock().unwrap());
+        assert!(received.lock().contains(&"$X".to_string()));
+        assert_eq!(controller.current_alarm().map(|a| a.code), Some(1));
+
+        *report.lock() = "<Idle|MPos:0.000,0.000,0.000|FS:0,0>".into();
+        assert!(controller.unlock().unwrap());
+        assert_eq!(controller.current_alarm(), None);
+        controller.disconnect().unwrap();
+    }
+
+    #[test]
+    fn test_heartbeat_pings_worker_unless_busy() {
+        let controller = Controller::new();
+        assert_eq!(controller.heartbeat().worker_alive, Some(true));
+
+        let _motion = controller.begin_motion().unwrap();
+        assert_eq!(controller.heartbeat().worker_alive, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_motion_refused_with_pending_alarm() {
+        let (path, _slave, received) = super::super::worker::tests::fake_device();
+        let controller = Controller::new();
+        controller.connect(&path, 115200).unwrap();
+        controller.state.lock().status.state = MachineState::Idle;
+        controller.state.lock().pending_alarm = Some((9, 1));
+        received.lock().clear();
+
+        // Cached state is still Idle, but the pending alarm must win
+        assert!(matches!(
+            controller.jog(Some(1.0), None, None, 1000.0, true),
+            Err(ControllerError::Alarm(9))
+        ));
+        assert!(matches!(
+            controller.run_frame(
+                FrameRequest {
+                    power: 10,
+                    ..frame_request()
+                },
+                |_| {},
+            ),
+            Err(ControllerError::Alarm(9))
+        ));
+        assert!(matches!(
+            controller.run_gcode_program(vec!["G0 X1".into()], false, false, |_| {}),
+            Err(ControllerError::Alarm(9))
+        ));
+        assert!(controller.job_progress().is_none());
+
+        // Nothing reached the device
+        thread::sleep(Duration::from_millis(100));
+        assert!(received.lock().is_empty());
+        controller.disconnect().unwrap();
     }
 }
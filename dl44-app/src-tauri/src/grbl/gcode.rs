@@ -0,0 +1,315 @@
+//! Local G-code syntax checks.
+//!
+//! A lightweight pass over a program before it's streamed, catching obvious
+//! mistakes (unbalanced comments, words GRBL doesn't know, numbers that
+//! don't parse, conflicting modal commands) without a device round-trip.
+//! GRBL's own check mode (`$C`) remains the authority; the rules here are
+//! deliberately conservative so a clean program never gets a warning.
+
+/// A problem found on one program line
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Diagnostic {
+    /// One-based line number in the program
+    pub line_number: usize,
+    pub message: String,
+}
+
+/// Word letters GRBL 1.1 accepts (A/B/C only on multi-axis builds, but
+/// accepted here to avoid false positives)
+const WORD_LETTERS: &str = "ABCFGIJKLMNPRSTXYZ";
+
+/// Axis words that make a line a move
+const AXIS_LETTERS: &str = "XYZABC";
+
+/// G-codes GRBL 1.1 supports
+const SUPPORTED_G: &[f64] = &[
+    0.0, 1.0, 2.0, 3.0, 4.0, 10.0, 17.0, 18.0, 19.0, 20.0, 21.0, 28.0, 28.1, 30.0, 30.1, 38.2,
+    38.3, 38.4, 38.5, 40.0, 43.1, 49.0, 53.0, 54.0, 55.0, 56.0, 57.0, 58.0, 59.0, 61.0, 80.0, 90.0,
+    91.0, 91.1, 92.0, 92.1, 93.0, 94.0,
+];
+
+/// M-codes GRBL 1.1 supports
+const SUPPORTED_M: &[f64] = &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 7.0, 8.0, 9.0, 30.0, 56.0];
+
+/// Modal group of a supported G-code, named for messages. Two codes from
+/// one group on a line is GRBL's "modal group violation".
+fn g_group(code: f64) -> &'static str {
+    match code {
+        c if [0.0, 1.0, 2.0, 3.0, 80.0].contains(&c) || (38.2..=38.5).contains(&c) => "motion",
+        c if [17.0, 18.0, 19.0].contains(&c) => "plane",
+        c if [20.0, 21.0].contains(&c) => "units",
+        c if [90.0, 91.0].contains(&c) => "distance",
+        c if [93.0, 94.0].contains(&c) => "feed rate mode",
+        c if (54.0..=59.0).contains(&c) => "coordinate system",
+        c if [40.0, 43.1, 49.0, 61.0, 91.1].contains(&c) => "modal",
+        _ => "non-modal",
+    }
+}
+
+/// Modal group of a supported M-code
+fn m_group(code: f64) -> &'static str {
+    match code as u32 {
+        0 | 1 | 2 | 30 => "stopping",
+        3..=5 => "spindle",
+        7..=9 => "coolant",
+        _ => "override",
+    }
+}
+
+/// Parse a GRBL number: optional sign, digits and at most one point
+fn parse_number(text: &str) -> Option<f64> {
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let valid = digits.chars().any(|c| c.is_ascii_digit())
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && digits.matches('.').count() <= 1;
+    if valid {
+        text.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Remove comments from a line, reporting unbalanced parentheses. As in
+/// GRBL, comments don't nest: the first `)` ends one.
+fn strip_comments(line: &str, problems: &mut Vec<String>) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut in_comment = false;
+    for c in line.chars() {
+        match c {
+            ';' if !in_comment => break,
+            '(' => in_comment = true,
+            ')' if in_comment => in_comment = false,
+            ')' => problems.push("Unmatched ')'".to_string()),
+            _ if !in_comment => out.push(c),
+            _ => {}
+        }
+    }
+    if in_comment {
+        problems.push("Unclosed comment".to_string());
+    }
+    out
+}
+
+/// Program state carried between lines
+struct Modal {
+    motion: f64,
+    inverse_time: bool,
+    feed_set: bool,
+}
+
+/// Check one line, returning its problems
+fn check_line(line: &str, modal: &mut Modal) -> Vec<String> {
+    let mut problems = Vec::new();
+    let trimmed = line.trim();
+    // System commands, jogs and program delimiters aren't G-code blocks
+    if trimmed.starts_with('$') || trimmed.starts_with('%') {
+        return problems;
+    }
+
+    let code = strip_comments(trimmed, &mut problems).to_ascii_uppercase();
+    let mut chars = code.chars().filter(|c| !c.is_whitespace()).peekable();
+    let mut words: Vec<(char, f64)> = Vec::new();
+    while let Some(letter) = chars.next() {
+        if !letter.is_ascii_alphabetic() {
+            problems.push(format!("Unexpected '{}'", letter));
+            continue;
+        }
+        let mut number = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+')) {
+            number.push(c);
+        }
+        if !WORD_LETTERS.contains(letter) {
+            problems.push(format!("Unsupported word '{}{}'", letter, number));
+            continue;
+        }
+        match parse_number(&number) {
+            Some(value) => words.push((letter, value)),
+            None if number.is_empty() => problems.push(format!("Missing value for {}", letter)),
+            None => problems.push(format!("Invalid number '{}' for {}", number, letter)),
+        }
+    }
+
+    // Command words: supported, and one per modal group
+    let mut groups: Vec<(&str, String)> = Vec::new();
+    for &(letter, value) in &words {
+        let group = match letter {
+            'G' if SUPPORTED_G.contains(&value) => g_group(value),
+            'M' if SUPPORTED_M.contains(&value) => m_group(value),
+            'G' | 'M' => {
+                problems.push(format!("Unsupported command {}{}", letter, value));
+                continue;
+            }
+            _ => continue,
+        };
+        let word = format!("{}{}", letter, value);
+        // GRBL allows several non-modal G-codes only if they don't conflict,
+        // which it checks itself; stay quiet about them here
+        if group != "non-modal" {
+            if let Some((_, first)) = groups.iter().find(|(g, _)| *g == group) {
+                problems.push(format!(
+                    "{} and {} are both {} commands",
+                    first, word, group
+                ));
+            }
+        }
+        groups.push((group, word));
+    }
+
+    // Other words may appear once per line
+    let mut seen = String::new();
+    for &(letter, _) in &words {
+        if !matches!(letter, 'G' | 'M') {
+            if seen.contains(letter) {
+                problems.push(format!("Repeated {} word", letter));
+            } else {
+                seen.push(letter);
+            }
+        }
+    }
+
+    let find = |letter: char| words.iter().find(|(l, _)| *l == letter).map(|(_, v)| *v);
+    let has_any = |letters: &str| words.iter().any(|(l, _)| letters.contains(*l));
+    let non_motion = words
+        .iter()
+        .any(|&(l, v)| l == 'G' && g_group(v) == "non-modal" && v != 4.0);
+
+    for &(letter, value) in &words {
+        if letter == 'G' {
+            match g_group(value) {
+                "motion" => modal.motion = value,
+                "feed rate mode" => modal.inverse_time = value == 93.0,
+                _ => {}
+            }
+        }
+    }
+    if let Some(feed) = find('F') {
+        if feed < 0.0 {
+            problems.push("Negative feed rate".to_string());
+        }
+        modal.feed_set = true;
+    }
+    if find('S').is_some_and(|s| s < 0.0) {
+        problems.push("Negative spindle speed".to_string());
+    }
+
+    // Moves in the current motion mode
+    if has_any(AXIS_LETTERS) && !non_motion {
+        let feed_move = [1.0, 2.0, 3.0].contains(&modal.motion);
+        if feed_move && !modal.inverse_time && !modal.feed_set {
+            problems.push(format!(
+                "G{} move before any feed rate is set",
+                modal.motion
+            ));
+        }
+        if feed_move && modal.inverse_time && find('F').is_none() {
+            problems.push("Inverse time (G93) move without an F word".to_string());
+        }
+        if [2.0, 3.0].contains(&modal.motion) && !has_any("IJKR") {
+            problems.push(format!("G{} arc without I/J/K offsets or R", modal.motion));
+        }
+    }
+
+    problems
+}
+
+/// Check a program line by line, returning warnings in line order.
+///
+/// Lines are numbered from one, counting blank and comment lines, so they
+/// match what an editor shows.
+pub fn validate(lines: &[String]) -> Vec<Diagnostic> {
+    let mut modal = Modal {
+        motion: 0.0,
+        inverse_time: false,
+        feed_set: false,
+    };
+    lines
+        .iter()
+        .enumerate()
+        .flat_map(|(index, line)| {
+            check_line(line, &mut modal)
+                .into_iter()
+                .map(move |message| Diagnostic {
+                    line_number: index + 1,
+                    message,
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(program: &[&str]) -> Vec<(usize, String)> {
+        let lines: Vec<String> = program.iter().map(|l| l.to_string()).collect();
+        validate(&lines)
+            .into_iter()
+            .map(|d| (d.line_number, d.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_program_has_no_warnings() {
+        let warnings = check(&[
+            "%",
+            "; header",
+            "(Generated by DL-44)",
+            "$H",
+            "G21 G90 G94",
+            "M4 S0",
+            "G0 X10 Y-5.5",
+            "G1 X15 Y20 F1200 S800 (cut)",
+            "Y25",
+            "G2 X20 Y25 I2.5 J0",
+            "G3 X15 Y25 R2.5",
+            "G28 X0 Y0",
+            "g91 g1 x+1 ; relative",
+            "",
+            "M5",
+            "M2",
+        ]);
+        assert_eq!(warnings, []);
+    }
+
+    #[test]
+    fn test_syntax_problems() {
+        let warnings = check(&["G0 X1 (open", "G0 X1)", "G0 X1.2.3 Y", "G0 E5 #"]);
+        assert_eq!(
+            warnings,
+            [
+                (1, "Unclosed comment".to_string()),
+                (2, "Unmatched ')'".to_string()),
+                (3, "Invalid number '1.2.3' for X".to_string()),
+                (3, "Missing value for Y".to_string()),
+                (4, "Unsupported word 'E5'".to_string()),
+                (4, "Unexpected '#'".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_modal_problems() {
+        let warnings = check(&[
+            "G1 X10",
+            "G0 G1 X1",
+            "G20 G21 M3 M5",
+            "X1 X2 F100",
+            "G2 X5 Y5",
+            "G41 M6",
+        ]);
+        assert_eq!(
+            warnings,
+            [
+                (1, "G1 move before any feed rate is set".to_string()),
+                (2, "G0 and G1 are both motion commands".to_string()),
+                (2, "G1 move before any feed rate is set".to_string()),
+                (3, "G20 and G21 are both units commands".to_string()),
+                (3, "M3 and M5 are both spindle commands".to_string()),
+                (4, "Repeated X word".to_string()),
+                (5, "G2 arc without I/J/K offsets or R".to_string()),
+                (6, "Unsupported command G41".to_string()),
+                (6, "Unsupported command M6".to_string()),
+            ]
+        );
+    }
+}
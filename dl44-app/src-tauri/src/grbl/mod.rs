@@ -3,19 +3,40 @@
 //! This module provides the core GRBL communication layer:
 //! - Protocol constants and command builders
 //! - Serial port enumeration
+//! - Connection lifecycle history
 //! - Status parsing and machine state
 //! - Worker thread for non-blocking serial I/O
+//! - G-code program streaming with RX buffer accounting
+//! - Local G-code syntax checks
+//! - Resuming interrupted programs
+//! - Line-by-line step mode for debugging programs
+//! - Persistent serial traffic log
+//! - Settings backup files
+//! - Laser usage counters for maintenance
 //! - High-level controller for coordinating operations
+//! - Registry of controllers for driving several devices
 
+pub mod connection_log;
 pub mod controller;
+pub mod gcode;
 pub mod protocol;
+pub mod registry;
+pub mod resume;
 pub mod serial;
+pub mod serial_log;
+pub mod settings_file;
 pub mod status;
+pub mod step;
+pub mod stream;
+pub mod usage;
 pub mod worker;
 
+pub use connection_log::ConnectionEvent;
 pub use controller::{
-    ConnectionState, Controller, ControllerError, ControllerSnapshot, OverrideAdjust,
-    RapidOverride,
+    ConnectionState, Controller, ControllerError, ControllerSnapshot, FrameRequest, LastError,
+    OverrideAdjust, RapidOverride, RecoverySummary, SettingWriteResult,
 };
+pub use registry::{ControllerRegistry, DeviceId, DeviceInfo, DEFAULT_DEVICE};
 pub use serial::PortInfo;
-pub use status::MachineStatus;
+pub use status::{MachineState, MachineStatus, Parameters, ParserState};
+pub use stream::{StreamLineError, StreamProgress, StreamState};
@@ -2,9 +2,21 @@
 //!
 //! Reference: https://github.com/gnea/grbl/wiki/Grbl-v1.1-Commands
 
+use super::status::Position;
+
 /// Default baud rate for GRBL controllers
 pub const DEFAULT_BAUD_RATE: u32 = 115200;
 
+/// Lowest feed rate sent with jogs and frames unless configured otherwise
+/// (units/min); slower feeds can stall the planner
+pub const DEFAULT_MIN_FEED: f64 = 1.0;
+
+/// Decimal places of the feed in jog commands
+pub const JOG_FEED_DECIMALS: usize = 3;
+
+/// Decimal places of the feed in frame traces
+pub const FRAME_FEED_DECIMALS: usize = 0;
+
 /// Common baud rates supported by GRBL devices
 pub const SUPPORTED_BAUD_RATES: &[u32] = &[9600, 19200, 38400, 57600, 115200, 230400];
 
@@ -70,12 +82,175 @@ pub mod system {
     pub const VIEW_GCODE_STATE: &str = "$G";
     /// View build info
     pub const VIEW_BUILD_INFO: &str = "$I";
+    /// View stored coordinate parameters
+    pub const VIEW_PARAMETERS: &str = "$#";
     /// View startup blocks
     pub const VIEW_STARTUP_BLOCKS: &str = "$N";
     /// Check G-code mode (dry run)
     pub const CHECK_MODE: &str = "$C";
 }
 
+/// GRBL `$RST` reset variants.
+///
+/// All of these wipe configuration stored in the device EEPROM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ResetKind {
+    /// `$RST=$` - restore `$` settings to build defaults
+    Settings,
+    /// `$RST=#` - clear G54-G59 work offsets and G28/G30 positions
+    Parameters,
+    /// `$RST=*` - settings, parameters, and startup blocks
+    All,
+}
+
+impl ResetKind {
+    /// The `$RST` command for this reset
+    pub fn command(&self) -> &'static str {
+        match self {
+            ResetKind::Settings => "$RST=$",
+            ResetKind::Parameters => "$RST=#",
+            ResetKind::All => "$RST=*",
+        }
+    }
+
+    /// Whether this reset changes `$` settings
+    pub fn resets_settings(&self) -> bool {
+        matches!(self, ResetKind::Settings | ResetKind::All)
+    }
+}
+
+/// Static description of a GRBL `$` setting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SettingInfo {
+    pub number: u32,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub units: &'static str,
+}
+
+const fn info(
+    number: u32,
+    name: &'static str,
+    description: &'static str,
+    units: &'static str,
+) -> SettingInfo {
+    SettingInfo {
+        number,
+        name,
+        description,
+        units,
+    }
+}
+
+/// Metadata for the GRBL v1.1 `$` settings
+#[rustfmt::skip]
+pub const SETTINGS_METADATA: &[SettingInfo] = &[
+    info(0, "Step pulse time", "Length of each step pulse", "microseconds"),
+    info(1, "Step idle delay", "Stepper hold time after motion (255 = always on)", "milliseconds"),
+    info(2, "Step pulse invert", "Inverts the step signal per axis", "mask"),
+    info(3, "Step direction invert", "Inverts the direction signal per axis", "mask"),
+    info(4, "Invert step enable pin", "Inverts the stepper enable signal", "boolean"),
+    info(5, "Invert limit pins", "Inverts the limit switch inputs", "boolean"),
+    info(6, "Invert probe pin", "Inverts the probe input", "boolean"),
+    info(10, "Status report options", "Data included in status reports", "mask"),
+    info(11, "Junction deviation", "Cornering speed through path junctions", "mm"),
+    info(12, "Arc tolerance", "Accuracy of G2/G3 arc segments", "mm"),
+    info(13, "Report in inches", "Report positions in inches instead of mm", "boolean"),
+    info(20, "Soft limits enable", "Reject moves beyond max travel (requires homing)", "boolean"),
+    info(21, "Hard limits enable", "Stop on limit switch trigger", "boolean"),
+    info(22, "Homing cycle enable", "Enable the $H homing cycle", "boolean"),
+    info(23, "Homing direction invert", "Home toward the negative direction per axis", "mask"),
+    info(24, "Homing locate feed rate", "Slow rate used to precisely locate switches", "mm/min"),
+    info(25, "Homing search seek rate", "Fast rate used to find switches", "mm/min"),
+    info(26, "Homing switch debounce delay", "Delay to debounce homing switches", "milliseconds"),
+    info(27, "Homing switch pull-off distance", "Distance to back off switches after homing", "mm"),
+    info(30, "Maximum spindle speed", "S value for full spindle/laser power", "RPM"),
+    info(31, "Minimum spindle speed", "S value for minimum spindle/laser power", "RPM"),
+    info(32, "Laser-mode enable", "Continuous motion with power changes (laser mode)", "boolean"),
+    info(100, "X steps/mm", "X axis travel resolution", "steps/mm"),
+    info(101, "Y steps/mm", "Y axis travel resolution", "steps/mm"),
+    info(102, "Z steps/mm", "Z axis travel resolution", "steps/mm"),
+    info(110, "X max rate", "Maximum X axis speed", "mm/min"),
+    info(111, "Y max rate", "Maximum Y axis speed", "mm/min"),
+    info(112, "Z max rate", "Maximum Z axis speed", "mm/min"),
+    info(120, "X acceleration", "X axis acceleration", "mm/sec^2"),
+    info(121, "Y acceleration", "Y axis acceleration", "mm/sec^2"),
+    info(122, "Z acceleration", "Z axis acceleration", "mm/sec^2"),
+    info(130, "X max travel", "X axis travel from home (soft limits)", "mm"),
+    info(131, "Y max travel", "Y axis travel from home (soft limits)", "mm"),
+    info(132, "Z max travel", "Z axis travel from home (soft limits)", "mm"),
+];
+
+/// A device setting value joined with its metadata
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GrblSetting {
+    pub number: u32,
+    pub value: String,
+    pub name: String,
+    pub description: String,
+    pub units: String,
+}
+
+/// Describe a setting read from the device; unknown (e.g. vendor-specific)
+/// settings get a generic name and no units.
+pub fn describe_setting(number: u32, value: &str) -> GrblSetting {
+    match SETTINGS_METADATA.iter().find(|s| s.number == number) {
+        Some(info) => GrblSetting {
+            number,
+            value: value.to_string(),
+            name: info.name.to_string(),
+            description: info.description.to_string(),
+            units: info.units.to_string(),
+        },
+        None => GrblSetting {
+            number,
+            value: value.to_string(),
+            name: format!("Setting ${number}"),
+            description: "Not a standard GRBL v1.1 setting".to_string(),
+            units: String::new(),
+        },
+    }
+}
+
+/// Whether a setting value can be sent as one `$N=value` line: printable
+/// ASCII without the realtime characters GRBL acts on mid-line (`?`, `!`,
+/// `~`). A newline or reset byte would let a value such as `1\n$RST=*`
+/// run another command.
+pub fn is_valid_setting_value(value: &str) -> bool {
+    value
+        .bytes()
+        .all(|b| (b.is_ascii_graphic() || b == b' ') && !matches!(b, b'?' | b'!' | b'~'))
+}
+
+/// GRBL v1.1 alarm descriptions, indexed by code - 1
+const ALARM_DESCRIPTIONS: &[&str] = &[
+    "Hard limit triggered. Position is likely lost; re-homing is recommended",
+    "Motion target exceeds machine travel. Position retained; safe to unlock",
+    "Reset while in motion. Position may be lost; re-homing is recommended",
+    "Probe fail: probe was not in the expected state before the cycle",
+    "Probe fail: probe did not contact the workpiece within travel",
+    "Homing fail: reset during the homing cycle",
+    "Homing fail: safety door opened during the homing cycle",
+    "Homing fail: could not clear the limit switch when pulling off",
+    "Homing fail: limit switch not found within search distance",
+];
+
+/// An alarm code with its description
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AlarmInfo {
+    pub code: u32,
+    pub description: String,
+}
+
+/// Describe an alarm code; codes beyond GRBL v1.1's get a generic description.
+pub fn describe_alarm(code: u32) -> AlarmInfo {
+    let description = code
+        .checked_sub(1)
+        .and_then(|i| ALARM_DESCRIPTIONS.get(i as usize))
+        .map_or_else(|| format!("Unknown alarm {code}"), |d| d.to_string());
+    AlarmInfo { code, description }
+}
+
 /// Build a jog command.
 ///
 /// # Arguments
@@ -112,15 +287,101 @@ pub fn build_jog_command(
     }
 
     // Feed rate
-    cmd.push_str(&format!(" F{:.3}", feed));
+    cmd.push_str(&format!(" F{:.*}", JOG_FEED_DECIMALS, feed));
     cmd.push('\n');
 
     cmd
 }
 
+/// Build a jog to an absolute machine position in mm (`$J=G53 G21 ...`),
+/// whatever the units and work offsets in effect.
+pub fn build_machine_jog_command(target: Position, feed: f64) -> String {
+    format!(
+        "$J=G53 G21 X{:.3} Y{:.3} Z{:.3} F{:.*}\n",
+        target.x, target.y, target.z, JOG_FEED_DECIMALS, feed
+    )
+}
+
+/// Jog axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum JogAxis {
+    X,
+    Y,
+    Z,
+}
+
+/// Jog direction along an axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum JogDirection {
+    Positive,
+    Negative,
+}
+
+/// A named jog step and feed, bound to a number key in the UI
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct JogPreset {
+    /// Display label
+    pub label: String,
+    /// Step distance in mm
+    pub step_mm: f64,
+    /// Feed rate in mm/min
+    pub feed: f64,
+}
+
+impl JogPreset {
+    /// Incremental X/Y/Z distances for one step along `axis`
+    pub fn step(&self, axis: JogAxis, direction: JogDirection) -> [Option<f64>; 3] {
+        let distance = match direction {
+            JogDirection::Positive => self.step_mm,
+            JogDirection::Negative => -self.step_mm,
+        };
+        [JogAxis::X, JogAxis::Y, JogAxis::Z].map(|a| (a == axis).then_some(distance))
+    }
+}
+
+/// Jog presets used until the user configures their own
+pub fn default_jog_presets() -> Vec<JogPreset> {
+    [(0.1, 100.0), (1.0, 500.0), (10.0, 1000.0), (50.0, 3000.0)]
+        .into_iter()
+        .map(|(step_mm, feed)| JogPreset {
+            label: format!("{} mm", step_mm),
+            step_mm,
+            feed,
+        })
+        .collect()
+}
+
 /// Jog cancel command (real-time)
 pub const JOG_CANCEL: u8 = 0x85;
 
+/// Terminator appended to each line sent to the device. Realtime
+/// commands are single bytes and never get one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum LineEnding {
+    /// `\n`, as stock GRBL expects
+    #[default]
+    Lf,
+    /// `\r\n`, for forks that want it. Stock GRBL answers the empty line
+    /// after `\r` with an extra `ok`, which throws off response matching.
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    /// `line` with any trailing line ending replaced by this one
+    pub fn terminate(&self, line: &str) -> String {
+        let mut terminated = line.trim_end_matches(['\r', '\n']).to_string();
+        terminated.push_str(self.as_str());
+        terminated
+    }
+}
+
 /// Units for frame GCode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum Units {
@@ -190,7 +451,87 @@ impl Default for FrameMode {
     }
 }
 
-/// Build GCode for tracing a rectangular frame/boundary.
+/// Status report contents chosen by the `$10` mask (GRBL 1.1 layout)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReportFields {
+    /// Report `MPos` (bit 0); without it GRBL reports `WPos` instead
+    pub machine_pos: bool,
+    /// Report `Bf` planner blocks and RX bytes free (bit 1)
+    pub buffer: bool,
+}
+
+impl ReportFields {
+    const MACHINE_POS_BIT: u8 = 1;
+    const BUFFER_BIT: u8 = 2;
+
+    /// Fields selected by a `$10` value; other bits are ignored
+    pub fn from_mask(mask: u8) -> Self {
+        Self {
+            machine_pos: mask & Self::MACHINE_POS_BIT != 0,
+            buffer: mask & Self::BUFFER_BIT != 0,
+        }
+    }
+
+    /// The `$10` value selecting these fields
+    pub fn mask(self) -> u8 {
+        let mut mask = 0;
+        if self.machine_pos {
+            mask |= Self::MACHINE_POS_BIT;
+        }
+        if self.buffer {
+            mask |= Self::BUFFER_BIT;
+        }
+        mask
+    }
+
+    /// Fields left out that the app relies on: `MPos` for positions and
+    /// jog travel checks, `Bf` for buffer monitoring while streaming
+    pub fn missing_required(self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        if !self.machine_pos {
+            missing.push("MPos");
+        }
+        if !self.buffer {
+            missing.push("Bf");
+        }
+        missing
+    }
+}
+
+/// Feedback message GRBL sends once an `M2`/`M30` has run, after all
+/// motion before it has finished
+pub const PROGRAM_END_MESSAGE: &str = "Pgm End";
+
+/// Zero-length dwell: GRBL acknowledges it only once the planner has drained,
+/// so its `ok` marks the end of all preceding motion.
+pub const SYNC_DWELL: &str = "G4 P0";
+
+/// One side of a frame trace
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameEdge {
+    /// Move along this edge
+    pub gcode: String,
+    /// X of the corner reached at the end of the edge
+    pub x: f64,
+    /// Y of the corner reached at the end of the edge
+    pub y: f64,
+    /// Edge length (in frame units)
+    pub length: f64,
+}
+
+/// A frame trace split into setup, edges, and teardown so callers can
+/// report progress per edge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FramePlan {
+    /// Modal setup, travel to the start corner, and laser start
+    pub setup: Vec<String>,
+    /// The four edges, in trace order
+    pub edges: Vec<FrameEdge>,
+    /// Laser stop
+    pub teardown: Vec<String>,
+}
+
+/// Plan the trace of a rectangular frame/boundary.
 ///
 /// Returns to starting position after trace.
 ///
@@ -201,7 +542,7 @@ impl Default for FrameMode {
 /// * `power` - Laser power (S value, typically 0-1000)
 /// * `units` - Units mode (mm or inches)
 /// * `mode` - Laser mode (M4 low power, M3 constant, or laser off)
-pub fn build_frame_gcode(
+pub fn build_frame_plan(
     x_min: f64,
     x_max: f64,
     y_min: f64,
@@ -210,50 +551,170 @@ pub fn build_frame_gcode(
     power: u32,
     units: Units,
     mode: FrameMode,
-) -> String {
+) -> FramePlan {
     // Normalize bounds (ensure min <= max)
     let (x0, x1) = if x_min <= x_max { (x_min, x_max) } else { (x_max, x_min) };
     let (y0, y1) = if y_min <= y_max { (y_min, y_max) } else { (y_max, y_min) };
 
-    let mut gcode = String::new();
-
     // G90 = absolute positioning, G20/G21 = inches/mm mode
-    gcode.push_str(&format!("G90 {}\n", units.gcode()));
+    let mut setup = vec![format!("G90 {}", units.gcode())];
 
     // Move to start position FIRST (always rapid, laser off)
     // This must happen before enabling laser to avoid burning during travel
-    gcode.push_str(&format!("G0 X{x0:.3} Y{y0:.3}\n"));
+    setup.push(format!("G0 X{x0:.3} Y{y0:.3}"));
 
     // Start laser mode if applicable (after reaching start position)
-    if let Some(start) = mode.start_gcode(power) {
-        gcode.push_str(&start);
-        gcode.push('\n');
-    }
-
-    // Trace the rectangle
-    if mode.use_feed_moves() {
-        // G1 moves with feed rate (laser fires in M3/M4 mode)
-        gcode.push_str(&format!("G1 X{x1:.3} Y{y0:.3} F{feed:.0}\n"));
-        gcode.push_str(&format!("G1 X{x1:.3} Y{y1:.3}\n"));
-        gcode.push_str(&format!("G1 X{x0:.3} Y{y1:.3}\n"));
-        gcode.push_str(&format!("G1 X{x0:.3} Y{y0:.3}\n"));
-    } else {
-        // G0 rapid moves (no laser, just checking travel)
-        gcode.push_str(&format!("G0 X{x1:.3} Y{y0:.3}\n"));
-        gcode.push_str(&format!("G0 X{x1:.3} Y{y1:.3}\n"));
-        gcode.push_str(&format!("G0 X{x0:.3} Y{y1:.3}\n"));
-        gcode.push_str(&format!("G0 X{x0:.3} Y{y0:.3}\n"));
-    }
+    setup.extend(mode.start_gcode(power));
+
+    // Trace the rectangle: G1 moves with feed rate (laser fires in M3/M4
+    // mode), or G0 rapid moves (no laser, just checking travel)
+    let corners = [(x1, y0), (x1, y1), (x0, y1), (x0, y0)];
+    let mut from = (x0, y0);
+    let edges = corners
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, y))| {
+            let length = (x - from.0).hypot(y - from.1);
+            from = (x, y);
+            let gcode = match (mode.use_feed_moves(), i) {
+                (true, 0) => format!("G1 X{x:.3} Y{y:.3} F{feed:.FRAME_FEED_DECIMALS$}"),
+                (true, _) => format!("G1 X{x:.3} Y{y:.3}"),
+                (false, _) => format!("G0 X{x:.3} Y{y:.3}"),
+            };
+            FrameEdge {
+                gcode,
+                x,
+                y,
+                length,
+            }
+        })
+        .collect();
 
     // Stop laser if applicable
-    if let Some(stop) = mode.stop_gcode() {
-        gcode.push_str(stop);
-        gcode.push('\n');
+    let teardown = mode.stop_gcode().map(String::from).into_iter().collect();
+
+    FramePlan {
+        setup,
+        edges,
+        teardown,
+    }
+}
+
+/// Coordinate frame for a go-to-zero move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ZeroTarget {
+    /// Work coordinate origin (G90)
+    Work,
+    /// Machine coordinate origin (G53)
+    Machine,
+}
+
+/// Build GCode for moving to the work or machine XY origin.
+///
+/// Raises Z to `safe_z` (work coordinates) first if given. Uses a rapid
+/// (G0) move unless a feed rate is supplied, in which case G1 is used.
+pub fn build_go_to_zero_gcode(
+    target: ZeroTarget,
+    feed: Option<f64>,
+    safe_z: Option<f64>,
+) -> String {
+    let mut gcode = String::new();
+
+    if let Some(z) = safe_z {
+        gcode.push_str(&format!("G90 G0 Z{z:.3}\n"));
+    }
+
+    let frame = match target {
+        ZeroTarget::Work => "G90",
+        ZeroTarget::Machine => "G53",
+    };
+    match feed {
+        Some(feed) => gcode.push_str(&format!("{frame} G1 X0 Y0 F{feed:.0}\n")),
+        None => gcode.push_str(&format!("{frame} G0 X0 Y0\n")),
+    }
+
+    gcode
+}
+
+/// Build GCode for a rapid move to a park position in machine coordinates.
+///
+/// Raises Z to `safe_z` (work coordinates) first if given.
+pub fn build_park_gcode(x: f64, y: f64, safe_z: Option<f64>) -> String {
+    let mut gcode = String::new();
+
+    if let Some(z) = safe_z {
+        gcode.push_str(&format!("G90 G0 Z{z:.3}\n"));
+    }
+    gcode.push_str(&format!("G53 G0 X{x:.3} Y{y:.3}\n"));
+
+    gcode
+}
+
+/// Machine positions GRBL stores for `G28` and `G30`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum PredefinedPosition {
+    G28,
+    G30,
+}
+
+impl PredefinedPosition {
+    /// G number (28 or 30), as reported by `$#`
+    pub fn code(&self) -> u32 {
+        match self {
+            PredefinedPosition::G28 => 28,
+            PredefinedPosition::G30 => 30,
+        }
+    }
+
+    /// Command storing the current machine position (`G28.1`/`G30.1`)
+    pub fn set_command(&self) -> String {
+        format!("G{}.1", self.code())
     }
+}
+
+/// Build GCode for moving to a stored `G28`/`G30` position.
+///
+/// Without axis words `G28`/`G30` rapids all axes straight to the stored
+/// position, Z included, so a low Z can drag across the work. Z is raised
+/// to `safe_z` (work coordinates) first if given; the final move still
+/// ends at the stored Z.
+pub fn build_predefined_position_gcode(which: PredefinedPosition, safe_z: Option<f64>) -> String {
+    let mut gcode = String::new();
+
+    if let Some(z) = safe_z {
+        gcode.push_str(&format!("G90 G0 Z{z:.3}\n"));
+    }
+    gcode.push_str(&format!("G{}\n", which.code()));
 
     gcode
 }
 
+/// Build GCode for a rapid move to absolute coordinates.
+///
+/// Targets machine coordinates (`G53`) when `machine_coords` is set, the
+/// active work coordinate system otherwise. Axes left as None don't move.
+/// Coordinates are in `units`, which the line sets rather than trusting
+/// the device's modal state.
+pub fn build_rapid_move_gcode(
+    x: Option<f64>,
+    y: Option<f64>,
+    z: Option<f64>,
+    machine_coords: bool,
+    units: Units,
+) -> String {
+    let mut gcode = format!("G90 {}", units.gcode());
+    if machine_coords {
+        gcode.push_str(" G53");
+    }
+    gcode.push_str(" G0");
+    for (letter, value) in [('X', x), ('Y', y), ('Z', z)] {
+        if let Some(value) = value {
+            gcode.push_str(&format!(" {letter}{value:.3}"));
+        }
+    }
+    gcode
+}
+
 /// Response types from GRBL
 #[derive(Debug, Clone, PartialEq)]
 pub enum Response {
@@ -267,6 +728,14 @@ pub enum Response {
     Status(String),
     /// Feedback message [MSG:...]
     Message(String),
+    /// G-code parser state [GC:...]
+    ParserState(String),
+    /// Probe result [PRB:x,y,z:s]
+    Probe { position: Position, success: bool },
+    /// Stored coordinate parameter from `$#`, e.g. [G54:x,y,z] or [G28:x,y,z]
+    Parameter { code: u32, position: Position },
+    /// Tool length offset from `$#` [TLO:z]
+    ToolLengthOffset(f64),
     /// Welcome message (Grbl X.Xx ['$' for help])
     Welcome(String),
     /// Settings value ($N=value)
@@ -275,6 +744,76 @@ pub enum Response {
     Other(String),
 }
 
+/// Firmware version parsed from the welcome banner
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FirmwareVersion {
+    /// Version string (e.g. "1.1h")
+    pub version: String,
+    /// Firmware variant if the banner names one (e.g. "FluidNC v3.7.8", "grblHAL")
+    pub variant: Option<String>,
+}
+
+/// Whether the firmware has `$J=` jogging, added in GRBL 1.1 (0.9 and
+/// earlier have none). Firmware built on GRBL 1.1 reports 1.1 or later.
+pub fn supports_jogging(firmware: &FirmwareVersion) -> bool {
+    let mut parts = firmware.version.split('.').map(|part| {
+        let digits = part.len() - part.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        part[..digits].parse::<u32>().unwrap_or(0)
+    });
+    let (major, minor) = (parts.next().unwrap_or(0), parts.next().unwrap_or(0));
+    (major, minor) >= (1, 1)
+}
+
+/// Parse a welcome banner into a firmware version.
+///
+/// Handles `Grbl 1.1h ['$' for help]`, `GrblHAL 1.1f ['$' or '$HELP' for help]`,
+/// and banners with extra text before the help hint such as
+/// `Grbl 3.7 [FluidNC v3.7.8 (wifi) '$' for help]`.
+pub fn parse_welcome(line: &str) -> Option<FirmwareVersion> {
+    let (name, rest) = line.trim().split_once(' ')?;
+    let is_hal = name.eq_ignore_ascii_case("grblhal");
+    if name != "Grbl" && !is_hal {
+        return None;
+    }
+
+    let (version, banner) = rest.split_once(' ').unwrap_or((rest, ""));
+    if !version.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let hint = banner
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split("'$'")
+        .next()
+        .unwrap_or("")
+        .trim();
+    let variant = if is_hal {
+        Some("grblHAL".to_string())
+    } else {
+        (!hint.is_empty()).then(|| hint.to_string())
+    };
+
+    Some(FirmwareVersion {
+        version: version.to_string(),
+        variant,
+    })
+}
+
+/// Whether `$I` build info lists an `[OPT:...]` option code, e.g. `Z`
+/// for builds with `HOMING_FORCE_SET_ORIGIN`.
+pub fn has_build_option(build_info: &[String], code: char) -> bool {
+    build_info
+        .iter()
+        .filter_map(|line| line.trim().strip_prefix("[OPT:"))
+        .any(|opt| {
+            opt.split(',')
+                .next()
+                .is_some_and(|codes| codes.contains(code))
+        })
+}
+
 /// Parse a single line response from GRBL.
 pub fn parse_response(line: &str) -> Response {
     let line = line.trim();
@@ -305,7 +844,38 @@ pub fn parse_response(line: &str) -> Response {
         }
     }
 
-    if line.starts_with("Grbl ") {
+    if let Some(state) = line.strip_prefix("[GC:") {
+        if let Some(state) = state.strip_suffix(']') {
+            return Response::ParserState(state.to_string());
+        }
+    }
+
+    if let Some(param) = line.strip_prefix("[G") {
+        if let Some((code, coords)) = param.strip_suffix(']').and_then(|p| p.split_once(':')) {
+            if let (Ok(code), Some(position)) = (code.parse::<u32>(), Position::parse(coords)) {
+                return Response::Parameter { code, position };
+            }
+        }
+    }
+
+    if let Some(tlo) = line.strip_prefix("[TLO:") {
+        if let Some(Ok(offset)) = tlo.strip_suffix(']').map(str::parse::<f64>) {
+            return Response::ToolLengthOffset(offset);
+        }
+    }
+
+    if let Some(probe) = line.strip_prefix("[PRB:") {
+        if let Some((coords, flag)) = probe.strip_suffix(']').and_then(|p| p.rsplit_once(':')) {
+            if let Some(position) = Position::parse(coords) {
+                return Response::Probe {
+                    position,
+                    success: flag == "1",
+                };
+            }
+        }
+    }
+
+    if parse_welcome(line).is_some() {
         return Response::Welcome(line.to_string());
     }
 
@@ -324,6 +894,55 @@ pub fn parse_response(line: &str) -> Response {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_describe_setting() {
+        let max_rate = describe_setting(110, "5000.000");
+        assert_eq!(max_rate.name, "X max rate");
+        assert_eq!(max_rate.units, "mm/min");
+        assert_eq!(max_rate.value, "5000.000");
+
+        let unknown = describe_setting(340, "1");
+        assert_eq!(unknown.name, "Setting $340");
+        assert!(unknown.units.is_empty());
+    }
+
+    #[test]
+    fn test_setting_value_validation() {
+        for value in ["80.000", "255", "grbl-laser", "my laser"] {
+            assert!(is_valid_setting_value(value), "{value}");
+        }
+        for value in [
+            "1\n$RST=*",
+            "1\r",
+            "1?",
+            "1!",
+            "~",
+            "1\u{18}",
+            "80\u{85}",
+            "é",
+        ] {
+            assert!(!is_valid_setting_value(value), "{value:?}");
+        }
+    }
+
+    #[test]
+    fn test_line_ending_terminate() {
+        assert_eq!(LineEnding::Lf.terminate("G0 X1"), "G0 X1\n");
+        assert_eq!(LineEnding::CrLf.terminate("G0 X1"), "G0 X1\r\n");
+        // An existing ending is replaced, not doubled
+        assert_eq!(LineEnding::CrLf.terminate("G0 X1\n"), "G0 X1\r\n");
+        assert_eq!(LineEnding::Lf.terminate("G0 X1\r\n"), "G0 X1\n");
+        assert_eq!(LineEnding::default(), LineEnding::Lf);
+    }
+
+    #[test]
+    fn test_describe_alarm() {
+        assert!(describe_alarm(1).description.starts_with("Hard limit"));
+        assert!(describe_alarm(9).description.contains("search distance"));
+        assert_eq!(describe_alarm(0).description, "Unknown alarm 0");
+        assert_eq!(describe_alarm(14).description, "Unknown alarm 14");
+    }
+
     #[test]
     fn test_jog_command() {
         let cmd = build_jog_command(Some(10.0), None, None, 1000.0, true);
@@ -331,6 +950,140 @@ mod tests {
 
         let cmd = build_jog_command(Some(-5.0), Some(5.0), None, 500.0, false);
         assert_eq!(cmd, "$J=G90 X-5.000 Y5.000 F500.000\n");
+
+        let target = Position {
+            x: -10.0,
+            y: -20.5,
+            z: 0.0,
+        };
+        assert_eq!(
+            build_machine_jog_command(target, 2000.0),
+            "$J=G53 G21 X-10.000 Y-20.500 Z0.000 F2000.000\n"
+        );
+    }
+
+    #[test]
+    fn test_report_fields_mask() {
+        for mask in 0..4 {
+            assert_eq!(ReportFields::from_mask(mask).mask(), mask);
+        }
+        let fields = ReportFields::from_mask(2);
+        assert!(!fields.machine_pos && fields.buffer);
+        assert_eq!(fields.missing_required(), ["MPos"]);
+        // GRBL 0.9 style bits above bit 1 are dropped
+        assert_eq!(ReportFields::from_mask(0x13).mask(), 3);
+        assert!(ReportFields::from_mask(3).missing_required().is_empty());
+    }
+
+    #[test]
+    fn test_supports_jogging() {
+        let firmware = |version: &str| FirmwareVersion {
+            version: version.into(),
+            variant: None,
+        };
+        assert!(supports_jogging(&firmware("1.1h")));
+        assert!(supports_jogging(&firmware("3.7")));
+        assert!(!supports_jogging(&firmware("0.9j")));
+        assert!(!supports_jogging(&firmware("1.0c")));
+    }
+
+    #[test]
+    fn test_frame_plan() {
+        let plan = build_frame_plan(
+            50.0,
+            10.0,
+            0.0,
+            20.0,
+            1000.0,
+            10,
+            Units::Mm,
+            FrameMode::LowPower,
+        );
+        assert_eq!(plan.setup, ["G90 G21", "G0 X10.000 Y0.000", "M4 S10"]);
+        assert_eq!(plan.edges.len(), 4);
+        assert_eq!(plan.edges[0].gcode, "G1 X50.000 Y0.000 F1000");
+        assert_eq!((plan.edges[1].x, plan.edges[1].y), (50.0, 20.0));
+        assert_eq!(plan.edges[1].length, 20.0);
+        assert_eq!(plan.edges[3].gcode, "G1 X10.000 Y0.000");
+        assert_eq!(plan.teardown, ["M5"]);
+
+        let guide = build_frame_plan(
+            0.0,
+            5.0,
+            0.0,
+            5.0,
+            1000.0,
+            10,
+            Units::Mm,
+            FrameMode::LaserOff,
+        );
+        assert_eq!(guide.setup.len(), 2);
+        assert!(guide.teardown.is_empty());
+        assert!(guide.edges.iter().all(|e| e.gcode.starts_with("G0 ")));
+    }
+
+    #[test]
+    fn test_jog_preset_step() {
+        let preset = JogPreset {
+            label: "Fine".into(),
+            step_mm: 0.5,
+            feed: 200.0,
+        };
+        assert_eq!(
+            preset.step(JogAxis::Y, JogDirection::Negative),
+            [None, Some(-0.5), None]
+        );
+        assert_eq!(default_jog_presets()[0].label, "0.1 mm");
+    }
+
+    #[test]
+    fn test_park_gcode() {
+        assert_eq!(
+            build_park_gcode(-5.0, -390.0, Some(3.0)),
+            "G90 G0 Z3.000\nG53 G0 X-5.000 Y-390.000\n"
+        );
+    }
+
+    #[test]
+    fn test_rapid_move_gcode() {
+        assert_eq!(
+            build_rapid_move_gcode(Some(10.0), None, Some(-1.5), false, Units::Mm),
+            "G90 G21 G0 X10.000 Z-1.500"
+        );
+        assert_eq!(
+            build_rapid_move_gcode(Some(-5.0), Some(-20.25), None, true, Units::Mm),
+            "G90 G21 G53 G0 X-5.000 Y-20.250"
+        );
+        assert_eq!(
+            build_rapid_move_gcode(Some(1.5), None, None, false, Units::Inches),
+            "G90 G20 G0 X1.500"
+        );
+    }
+
+    #[test]
+    fn test_predefined_position_gcode() {
+        assert_eq!(PredefinedPosition::G28.set_command(), "G28.1");
+        assert_eq!(PredefinedPosition::G30.set_command(), "G30.1");
+        assert_eq!(
+            build_predefined_position_gcode(PredefinedPosition::G28, None),
+            "G28\n"
+        );
+        assert_eq!(
+            build_predefined_position_gcode(PredefinedPosition::G30, Some(5.0)),
+            "G90 G0 Z5.000\nG30\n"
+        );
+    }
+
+    #[test]
+    fn test_go_to_zero_gcode() {
+        assert_eq!(
+            build_go_to_zero_gcode(ZeroTarget::Work, None, None),
+            "G90 G0 X0 Y0\n"
+        );
+        assert_eq!(
+            build_go_to_zero_gcode(ZeroTarget::Machine, Some(3000.0), Some(5.0)),
+            "G90 G0 Z5.000\nG53 G1 X0 Y0 F3000\n"
+        );
     }
 
     #[test]
@@ -342,5 +1095,97 @@ mod tests {
             parse_response("<Idle|MPos:0.000,0.000,0.000>"),
             Response::Status(_)
         ));
+        assert_eq!(
+            parse_response("[GC:G0 G54 G17 G21 G90 G94 M5 M9 T0 F0 S0]"),
+            Response::ParserState("G0 G54 G17 G21 G90 G94 M5 M9 T0 F0 S0".into())
+        );
+    }
+
+    #[test]
+    fn test_has_build_option() {
+        let info = vec![
+            "[VER:1.1h.20190825:]".to_string(),
+            "[OPT:VZL,15,128]".to_string(),
+        ];
+        assert!(has_build_option(&info, 'Z'));
+        assert!(!has_build_option(&info, 'H'));
+        // Buffer sizes after the codes aren't option codes
+        assert!(!has_build_option(&info, '1'));
+        assert!(!has_build_option(&[], 'Z'));
+    }
+
+    #[test]
+    fn test_parse_welcome() {
+        assert_eq!(
+            parse_welcome("Grbl 1.1h ['$' for help]"),
+            Some(FirmwareVersion {
+                version: "1.1h".into(),
+                variant: None,
+            })
+        );
+        assert_eq!(
+            parse_welcome("Grbl 3.7 [FluidNC v3.7.8 (wifi) '$' for help]"),
+            Some(FirmwareVersion {
+                version: "3.7".into(),
+                variant: Some("FluidNC v3.7.8 (wifi)".into()),
+            })
+        );
+        assert_eq!(
+            parse_welcome("GrblHAL 1.1f ['$' or '$HELP' for help]").and_then(|v| v.variant),
+            Some("grblHAL".into())
+        );
+        assert_eq!(parse_welcome("Grbl is great"), None);
+        assert_eq!(parse_welcome("ok"), None);
+    }
+
+    #[test]
+    fn test_reset_commands() {
+        assert_eq!(ResetKind::Settings.command(), "$RST=$");
+        assert_eq!(ResetKind::Parameters.command(), "$RST=#");
+        assert_eq!(ResetKind::All.command(), "$RST=*");
+        assert!(!ResetKind::Parameters.resets_settings());
+    }
+
+    #[test]
+    fn test_parse_parameter_responses() {
+        assert_eq!(
+            parse_response("[G54:0.000,0.000,0.000]"),
+            Response::Parameter {
+                code: 54,
+                position: Position::new(0.0, 0.0, 0.0),
+            }
+        );
+        assert_eq!(
+            parse_response("[G28:-10.000,-350.500,-1.000]"),
+            Response::Parameter {
+                code: 28,
+                position: Position::new(-10.0, -350.5, -1.0),
+            }
+        );
+        assert_eq!(
+            parse_response("[TLO:0.250]"),
+            Response::ToolLengthOffset(0.25)
+        );
+        // Parser state shares the prefix but isn't a parameter
+        assert!(matches!(
+            parse_response("[GC:G0 G54 G17 G21 G90 G94 M5 M9 T0 F0 S0]"),
+            Response::ParserState(_)
+        ));
+        assert!(matches!(parse_response("[G54:bad]"), Response::Other(_)));
+    }
+
+    #[test]
+    fn test_parse_probe_response() {
+        assert_eq!(
+            parse_response("[PRB:0.000,0.000,-5.250:1]"),
+            Response::Probe {
+                position: Position::new(0.0, 0.0, -5.25),
+                success: true,
+            }
+        );
+        assert!(matches!(
+            parse_response("[PRB:1.000,2.000,0.000:0]"),
+            Response::Probe { success: false, .. }
+        ));
     }
 }
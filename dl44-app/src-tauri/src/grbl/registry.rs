@@ -0,0 +1,190 @@
+//! Controllers for several devices at once.
+//!
+//! Each device has its own [`Controller`], and so its own serial worker, so
+//! devices run independently. Commands name a device by id; leaving the id
+//! out picks the default device, which always exists, so single-device
+//! setups never deal with ids.
+
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use super::controller::{ConnectionState, Controller, ControllerError};
+
+/// Identifies a device in the registry
+pub type DeviceId = u32;
+
+/// Device used when a command doesn't name one; never removed
+pub const DEFAULT_DEVICE: DeviceId = 0;
+
+/// A registered device and its connection, for the UI
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DeviceInfo {
+    pub id: DeviceId,
+    pub connection: ConnectionState,
+}
+
+struct Devices {
+    controllers: BTreeMap<DeviceId, Arc<Controller>>,
+    next_id: DeviceId,
+}
+
+/// Controllers keyed by device id
+pub struct ControllerRegistry {
+    devices: Mutex<Devices>,
+}
+
+impl ControllerRegistry {
+    /// Registry holding just the default device
+    pub fn new() -> Self {
+        Self {
+            devices: Mutex::new(Devices {
+                controllers: BTreeMap::from([(DEFAULT_DEVICE, Controller::new_shared())]),
+                next_id: DEFAULT_DEVICE + 1,
+            }),
+        }
+    }
+
+    /// The default device's controller
+    pub fn default_controller(&self) -> Arc<Controller> {
+        self.devices.lock().controllers[&DEFAULT_DEVICE].clone()
+    }
+
+    /// Controller for `id`, or the default device for None
+    pub fn get(&self, id: Option<DeviceId>) -> Result<Arc<Controller>, ControllerError> {
+        let id = id.unwrap_or(DEFAULT_DEVICE);
+        self.devices
+            .lock()
+            .controllers
+            .get(&id)
+            .cloned()
+            .ok_or(ControllerError::UnknownDevice(id))
+    }
+
+    /// All devices in id order
+    pub fn all(&self) -> Vec<(DeviceId, Arc<Controller>)> {
+        self.devices
+            .lock()
+            .controllers
+            .iter()
+            .map(|(id, controller)| (*id, controller.clone()))
+            .collect()
+    }
+
+    /// Devices with their connection state
+    pub fn list(&self) -> Vec<DeviceInfo> {
+        self.all()
+            .into_iter()
+            .map(|(id, controller)| DeviceInfo {
+                id,
+                connection: controller.connection_state(),
+            })
+            .collect()
+    }
+
+    /// Register another device, set up like the default one (see
+    /// [`Controller::inherit_settings`]). Its serial log goes to a
+    /// `device-N` folder inside the default device's log directory, so
+    /// the devices' logs don't mix.
+    pub fn add(&self) -> (DeviceId, Arc<Controller>) {
+        let controller = Controller::new_shared();
+        let mut devices = self.devices.lock();
+        let id = devices.next_id;
+        devices.next_id += 1;
+
+        let default = &devices.controllers[&DEFAULT_DEVICE];
+        controller.inherit_settings(default);
+        if let Some(dir) = default.serial_log().directory() {
+            controller
+                .serial_log()
+                .set_directory(&dir.join(format!("device-{}", id)));
+        }
+
+        devices.controllers.insert(id, controller.clone());
+        log::info!("Added device {}", id);
+        (id, controller)
+    }
+
+    /// A device that isn't connected or connecting, preferring the lowest
+    /// id; None when every device is in use
+    pub fn free_device(&self) -> Option<(DeviceId, Arc<Controller>)> {
+        self.all().into_iter().find(|(_, controller)| {
+            matches!(
+                controller.connection_state(),
+                ConnectionState::Disconnected | ConnectionState::Error { .. }
+            )
+        })
+    }
+
+    /// Shut down and forget a device. The default device can't be removed.
+    pub fn remove(&self, id: DeviceId) -> Result<(), ControllerError> {
+        if id == DEFAULT_DEVICE {
+            return Err(ControllerError::InvalidState(
+                "The default device can't be removed".into(),
+            ));
+        }
+        let controller = self
+            .devices
+            .lock()
+            .controllers
+            .remove(&id)
+            .ok_or(ControllerError::UnknownDevice(id))?;
+        controller.shutdown();
+        log::info!("Removed device {}", id);
+        Ok(())
+    }
+
+    /// Make every device safe and disconnect (for app exit)
+    pub fn shutdown_all(&self) {
+        for (_, controller) in self.all() {
+            controller.shutdown();
+        }
+    }
+}
+
+impl Default for ControllerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_devices_are_added_and_removed() {
+        let registry = ControllerRegistry::new();
+        let default = registry.default_controller();
+        assert!(Arc::ptr_eq(&registry.get(None).unwrap(), &default));
+        assert_eq!(
+            registry.free_device().map(|(id, _)| id),
+            Some(DEFAULT_DEVICE)
+        );
+
+        let (id, controller) = registry.add();
+        assert_eq!(id, 1);
+        assert!(Arc::ptr_eq(&registry.get(Some(id)).unwrap(), &controller));
+        assert_eq!(
+            registry.list().iter().map(|d| d.id).collect::<Vec<_>>(),
+            [0, 1]
+        );
+
+        assert!(matches!(
+            registry.remove(DEFAULT_DEVICE),
+            Err(ControllerError::InvalidState(_))
+        ));
+        assert!(registry.remove(id).is_ok());
+        assert!(matches!(
+            registry.get(Some(id)),
+            Err(ControllerError::UnknownDevice(1))
+        ));
+        assert!(matches!(
+            registry.remove(id),
+            Err(ControllerError::UnknownDevice(1))
+        ));
+
+        // Ids aren't reused
+        assert_eq!(registry.add().0, 2);
+    }
+}
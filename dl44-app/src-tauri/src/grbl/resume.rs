@@ -0,0 +1,286 @@
+//! Resuming an interrupted program.
+//!
+//! After a power loss the device forgets its position and modal state, but
+//! with homing enabled the machine position can be recovered by re-homing.
+//! The program lines before the resume point are scanned for the modal
+//! state they leave behind (units, distance mode, motion mode, feed,
+//! spindle and position), which is restored with a short preamble before
+//! streaming continues from the resume line.
+//!
+//! Positions are tracked in work coordinates, so the work offset in effect
+//! when the job ran (stored by GRBL in EEPROM) must not have changed.
+
+use super::protocol::Units;
+use super::stream::strip_line_comments;
+
+/// Modal state left behind by the lines before a resume point
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResumeState {
+    pub units: Units,
+    /// Absolute (G90) rather than incremental (G91) distances
+    pub absolute: bool,
+    /// Last motion mode word (`G0`..`G3`)
+    pub motion: Option<u32>,
+    /// Feed rate in program units per minute
+    pub feed: Option<f64>,
+    /// Spindle/laser power
+    pub power: Option<f64>,
+    /// Spindle mode word (3 = M3, 4 = M4), None while off (M5)
+    pub spindle: Option<u32>,
+    /// Last work position per axis (mm), None until set by a move
+    pub position: [Option<f64>; 3],
+}
+
+impl Default for ResumeState {
+    /// GRBL's state after power-up
+    fn default() -> Self {
+        Self {
+            units: Units::Mm,
+            absolute: true,
+            motion: None,
+            feed: None,
+            power: None,
+            spindle: None,
+            position: [None; 3],
+        }
+    }
+}
+
+/// Letter/value words of a G-code line, with comments removed
+fn words(line: &str) -> Vec<(char, f64)> {
+    let line = strip_line_comments(line).to_ascii_uppercase();
+    let mut words = Vec::new();
+    let mut chars = line.chars().filter(|c| !c.is_whitespace()).peekable();
+    while let Some(letter) = chars.next() {
+        if !letter.is_ascii_alphabetic() {
+            continue;
+        }
+        let mut number = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+')) {
+            number.push(c);
+        }
+        if let Ok(value) = number.parse() {
+            words.push((letter, value));
+        }
+    }
+    words
+}
+
+impl ResumeState {
+    /// Modal state after running `lines`
+    pub fn scan(lines: &[String]) -> Self {
+        let mut state = Self::default();
+        for line in lines {
+            state.apply(line);
+        }
+        state
+    }
+
+    fn apply(&mut self, line: &str) {
+        // System commands and jogs don't touch the program's modal state
+        if line.trim_start().starts_with('$') {
+            return;
+        }
+        let words = words(line);
+        let codes = |letter: char| {
+            words
+                .iter()
+                .filter(move |(l, _)| *l == letter)
+                .map(|(_, v)| *v)
+        };
+
+        // Axis words of these commands aren't moves in work coordinates
+        let non_motion = codes('G').any(|g| [10.0, 28.0, 30.0, 53.0, 92.0].contains(&g));
+        // Returns to a stored position, and machine coordinate moves, end
+        // where the work position can't be known
+        let returns = codes('G').any(|g| g == 28.0 || g == 30.0);
+        let machine = codes('G').any(|g| g == 53.0);
+        for g in codes('G') {
+            match g as u32 {
+                0..=3 if g.fract() == 0.0 => self.motion = Some(g as u32),
+                20 => self.units = Units::Inches,
+                21 => self.units = Units::Mm,
+                90 => self.absolute = true,
+                91 => self.absolute = false,
+                _ => {}
+            }
+        }
+        for m in codes('M') {
+            match m as u32 {
+                3 | 4 => self.spindle = Some(m as u32),
+                5 | 2 | 30 => self.spindle = None,
+                _ => {}
+            }
+        }
+        if let Some(feed) = codes('F').next_back() {
+            self.feed = Some(feed);
+        }
+        if let Some(power) = codes('S').next_back() {
+            self.power = Some(power);
+        }
+        if returns || machine {
+            // The named axes move; a bare G28/G30 moves them all
+            let named = ['X', 'Y', 'Z'].map(|letter| codes(letter).next().is_some());
+            let all = returns && !named.contains(&true);
+            for (position, named) in self.position.iter_mut().zip(named) {
+                if named || all {
+                    *position = None;
+                }
+            }
+        }
+        if non_motion {
+            return;
+        }
+
+        let scale = match self.units {
+            Units::Mm => 1.0,
+            Units::Inches => 25.4,
+        };
+        for (axis, letter) in ['X', 'Y', 'Z'].into_iter().enumerate() {
+            let Some(value) = codes(letter).next_back() else {
+                continue;
+            };
+            let value = value * scale;
+            self.position[axis] = if self.absolute {
+                Some(value)
+            } else {
+                // Incremental moves from an unknown position stay unknown
+                self.position[axis].map(|p| p + value)
+            };
+        }
+    }
+
+    /// Lines restoring this state on a freshly homed machine.
+    ///
+    /// Moves to the last position with the laser off (XY at the homed
+    /// height, then Z), then restores spindle, motion mode, feed and
+    /// distance mode. An arc mode (`G2`/`G3`) isn't restored, since GRBL
+    /// refuses it without axis words (`error:26`): the resume line has to
+    /// carry its own motion word.
+    pub fn preamble(&self) -> Vec<String> {
+        let scale = match self.units {
+            Units::Mm => 1.0,
+            Units::Inches => 1.0 / 25.4,
+        };
+        let axis = |i: usize, letter: char| {
+            self.position[i].map(|p| format!(" {}{:.3}", letter, p * scale))
+        };
+
+        let mut lines = vec![
+            match self.units {
+                Units::Mm => "G21".to_string(),
+                Units::Inches => "G20".to_string(),
+            },
+            "G90".to_string(),
+            "M5".to_string(),
+        ];
+        let xy: String = [axis(0, 'X'), axis(1, 'Y')].into_iter().flatten().collect();
+        if !xy.is_empty() {
+            lines.push(format!("G0{}", xy));
+        }
+        if let Some(z) = axis(2, 'Z') {
+            lines.push(format!("G0{}", z));
+        }
+        let power = self.power.map(|s| format!(" S{}", s)).unwrap_or_default();
+        match self.spindle {
+            Some(m) => lines.push(format!("M{}{}", m, power)),
+            None if !power.is_empty() => lines.push(power.trim_start().to_string()),
+            None => {}
+        }
+        let motion = self.motion.filter(|g| *g <= 1).map(|g| format!("G{}", g));
+        let feed = self.feed.map(|f| format!("F{}", f));
+        let modal: Vec<String> = [motion, feed].into_iter().flatten().collect();
+        if !modal.is_empty() {
+            lines.push(modal.join(" "));
+        }
+        if !self.absolute {
+            lines.push("G91".to_string());
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(program: &[&str]) -> Vec<String> {
+        program.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_scan_tracks_modal_state() {
+        let state = ResumeState::scan(&lines(&[
+            "G21 G90 (setup)",
+            "M4 S0",
+            "G0 X10 Y20",
+            "G1X15Y25F1200S800 ; cut",
+            "G28 X0 Y0",
+            "X16",
+            "G91",
+            "G1 Y1 Z-2",
+            "$J=G90 X0 Y0 F1000",
+        ]));
+        assert_eq!(state.units, Units::Mm);
+        assert!(!state.absolute);
+        assert_eq!(state.motion, Some(1));
+        assert_eq!((state.feed, state.power), (Some(1200.0), Some(800.0)));
+        assert_eq!(state.spindle, Some(4));
+        // G28 left Y at the stored position, and Z was never set
+        // absolutely, so the incremental move leaves both unknown
+        assert_eq!(state.position, [Some(16.0), None, None]);
+
+        assert_eq!(
+            state.preamble(),
+            [
+                "G21",
+                "G90",
+                "M5",
+                "G0 X16.000",
+                "M4 S800",
+                "G1 F1200",
+                "G91"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preamble_in_inches() {
+        let state = ResumeState::scan(&lines(&["G20", "G0 X1 Y2 Z0.5", "M5", "S300"]));
+        assert_eq!(state.position, [Some(25.4), Some(50.8), Some(12.7)]);
+        assert_eq!(
+            state.preamble(),
+            [
+                "G20",
+                "G90",
+                "M5",
+                "G0 X1.000 Y2.000",
+                "G0 Z0.500",
+                "S300",
+                "G0"
+            ]
+        );
+        assert_eq!(ResumeState::scan(&[]).preamble(), ["G21", "G90", "M5"]);
+    }
+
+    #[test]
+    fn test_return_moves_forget_position() {
+        let state = ResumeState::scan(&lines(&["G0 X1 Y2 Z3", "G30"]));
+        assert_eq!(state.position, [None; 3]);
+        let state = ResumeState::scan(&lines(&["G0 X1 Y2 Z3", "G53 G0 Z-1"]));
+        assert_eq!(state.position, [Some(1.0), Some(2.0), None]);
+        // Storing the position doesn't move
+        let state = ResumeState::scan(&lines(&["G0 X1 Y2 Z3", "G28.1"]));
+        assert_eq!(state.position, [Some(1.0), Some(2.0), Some(3.0)]);
+    }
+
+    #[test]
+    fn test_preamble_skips_arc_mode() {
+        let state = ResumeState::scan(&lines(&["G0 X0 Y0", "G2 X10 Y0 I5 J0 F300"]));
+        assert_eq!(state.motion, Some(2));
+        assert_eq!(
+            state.preamble(),
+            ["G21", "G90", "M5", "G0 X10.000 Y0.000", "F300"]
+        );
+    }
+}
@@ -12,6 +12,41 @@ pub enum SerialError {
     EnumerationFailed(#[from] serialport::Error),
 }
 
+/// Why a port couldn't be opened, so the UI can suggest a fix
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ConnectErrorKind {
+    /// The port doesn't exist (unplugged, or a stale path)
+    PortNotFound,
+    /// The user may not open the port (e.g. not in the `dialout` group)
+    PermissionDenied,
+    /// Another program has the port open
+    PortBusy,
+    /// The port opened but nothing answered (wrong baud rate or device)
+    NoResponse,
+    Other(String),
+}
+
+impl ConnectErrorKind {
+    /// Classify an error from opening a port
+    pub fn from_open_error(e: &serialport::Error) -> Self {
+        use serialport::ErrorKind as Kind;
+        use std::io::ErrorKind as Io;
+
+        // EBUSY reaches serialport's Unix backend as an Unknown error
+        if e.description.to_lowercase().contains("busy") {
+            return Self::PortBusy;
+        }
+        match e.kind() {
+            Kind::NoDevice | Kind::Io(Io::NotFound) => Self::PortNotFound,
+            Kind::Io(Io::ResourceBusy) => Self::PortBusy,
+            // Windows reports a port opened elsewhere as access denied
+            Kind::Io(Io::PermissionDenied) if cfg!(windows) => Self::PortBusy,
+            Kind::Io(Io::PermissionDenied) => Self::PermissionDenied,
+            _ => Self::Other(e.description.clone()),
+        }
+    }
+}
+
 /// Information about an available serial port
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PortInfo {
@@ -28,33 +63,98 @@ pub struct PortInfo {
 }
 
 /// List available serial ports.
+///
+/// A device unplugged while the OS enumerates can leave a broken entry
+/// (no name, or a device node that's already gone); those are logged and
+/// skipped rather than failing the whole list.
 pub fn list_ports() -> Result<Vec<PortInfo>, SerialError> {
     let ports = serialport::available_ports()?;
+    Ok(ports.into_iter().filter_map(port_info).collect())
+}
+
+/// Convert an enumerated port, or None if it vanished
+fn port_info(p: serialport::SerialPortInfo) -> Option<PortInfo> {
+    if p.port_name.trim().is_empty() {
+        log::warn!("Skipping serial port without a name");
+        return None;
+    }
+    // Windows COM names aren't paths; elsewhere the device node must exist
+    if cfg!(unix) && !std::path::Path::new(&p.port_name).exists() {
+        log::warn!("Skipping serial port {}: no longer present", p.port_name);
+        return None;
+    }
 
-    Ok(ports
-        .into_iter()
-        .map(|p| {
-            let (port_type, manufacturer, product, serial_number) = match p.port_type {
-                serialport::SerialPortType::UsbPort(info) => (
-                    "USB".to_string(),
-                    info.manufacturer,
-                    info.product,
-                    info.serial_number,
-                ),
-                serialport::SerialPortType::PciPort => ("PCI".to_string(), None, None, None),
-                serialport::SerialPortType::BluetoothPort => {
-                    ("Bluetooth".to_string(), None, None, None)
-                }
-                serialport::SerialPortType::Unknown => ("Unknown".to_string(), None, None, None),
+    let (port_type, manufacturer, product, serial_number) = match p.port_type {
+        serialport::SerialPortType::UsbPort(info) => (
+            "USB".to_string(),
+            info.manufacturer,
+            info.product,
+            info.serial_number,
+        ),
+        serialport::SerialPortType::PciPort => ("PCI".to_string(), None, None, None),
+        serialport::SerialPortType::BluetoothPort => ("Bluetooth".to_string(), None, None, None),
+        serialport::SerialPortType::Unknown => ("Unknown".to_string(), None, None, None),
+    };
+
+    Some(PortInfo {
+        path: p.port_name,
+        port_type,
+        manufacturer,
+        product,
+        serial_number,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialport::ErrorKind;
+
+    #[test]
+    fn test_open_errors_map_to_kinds() {
+        let kind = |kind, description: &str| {
+            ConnectErrorKind::from_open_error(&serialport::Error::new(kind, description))
+        };
+
+        assert_eq!(
+            kind(ErrorKind::NoDevice, "No such file or directory"),
+            ConnectErrorKind::PortNotFound
+        );
+        assert_eq!(
+            kind(ErrorKind::Unknown, "Device or resource busy"),
+            ConnectErrorKind::PortBusy
+        );
+        assert_eq!(
+            kind(ErrorKind::InvalidInput, "Invalid baud rate"),
+            ConnectErrorKind::Other("Invalid baud rate".into())
+        );
+
+        #[cfg(unix)]
+        {
+            let os = |code| {
+                ConnectErrorKind::from_open_error(&std::io::Error::from_raw_os_error(code).into())
             };
+            // ENOENT, EACCES, EBUSY
+            assert_eq!(os(2), ConnectErrorKind::PortNotFound);
+            assert_eq!(os(13), ConnectErrorKind::PermissionDenied);
+            assert_eq!(os(16), ConnectErrorKind::PortBusy);
+        }
+    }
+
+    #[test]
+    fn test_vanished_ports_are_skipped() {
+        let port = |name: &str| serialport::SerialPortInfo {
+            port_name: name.into(),
+            port_type: serialport::SerialPortType::PciPort,
+        };
 
-            PortInfo {
-                path: p.port_name,
-                port_type,
-                manufacturer,
-                product,
-                serial_number,
-            }
-        })
-        .collect())
+        assert!(port_info(port("")).is_none());
+        #[cfg(unix)]
+        {
+            assert!(port_info(port("/dev/dl44-unplugged")).is_none());
+            let info = port_info(port("/dev/null")).unwrap();
+            assert_eq!(info.path, "/dev/null");
+            assert_eq!(info.port_type, "PCI");
+        }
+    }
 }
@@ -0,0 +1,352 @@
+//! Persistent log of serial traffic.
+//!
+//! When enabled, lines sent to and received from the device are appended to
+//! a log file named after the current workspace, so the communication of a
+//! long session can be reviewed afterwards. Status queries and reports are
+//! left out; at the poll rate they would drown everything else.
+//!
+//! The file is rotated once it reaches a configurable size: `name.log`
+//! becomes `name.log.1`, `name.log.1` becomes `name.log.2`, and so on, with
+//! the oldest dropped. A write failure is logged and turns the log off until
+//! it is enabled again.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default size at which the log is rotated
+pub const DEFAULT_MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Smallest accepted rotation size, so a bad setting can't rotate every line
+pub const MIN_MAX_LOG_BYTES: u64 = 16 * 1024;
+
+/// Rotated files kept per workspace (`name.log.1` ..= `name.log.N`)
+const ROTATED_LOGS: u32 = 3;
+
+/// Log name used while the workspace hasn't been saved
+const UNSAVED_WORKSPACE_NAME: &str = "untitled";
+
+/// Serial log options
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SerialLogSettings {
+    /// Append serial traffic to the workspace's log file
+    pub enabled: bool,
+    /// Size in bytes at which the log file is rotated
+    pub max_bytes: u64,
+}
+
+impl Default for SerialLogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_bytes: DEFAULT_MAX_LOG_BYTES,
+        }
+    }
+}
+
+/// Direction of a logged line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+impl Direction {
+    fn marker(self) -> &'static str {
+        match self {
+            Direction::Sent => ">",
+            Direction::Received => "<",
+        }
+    }
+}
+
+/// A log file on disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogFileInfo {
+    pub path: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Last modification (seconds since the Unix epoch)
+    pub modified: Option<u64>,
+}
+
+#[derive(Default)]
+struct LogState {
+    settings: SerialLogSettings,
+    /// Directory holding the log files (None until known)
+    dir: Option<PathBuf>,
+    /// File stem of the current workspace's log
+    name: String,
+    /// Open log file and its current size
+    file: Option<(File, u64)>,
+}
+
+impl LogState {
+    fn path(&self) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        Some(dir.join(format!("{}.log", self.name)))
+    }
+
+    fn write(&mut self, entry: &str) -> io::Result<()> {
+        let Some(path) = self.path() else {
+            return Ok(());
+        };
+        if self.file.is_none() {
+            fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))?;
+            let file = OpenOptions::new().create(true).append(true).open(&path)?;
+            let size = file.metadata()?.len();
+            self.file = Some((file, size));
+        }
+        if self
+            .file
+            .as_ref()
+            .is_some_and(|(_, size)| *size >= self.settings.max_bytes)
+        {
+            self.file = None;
+            rotate(&path)?;
+            self.file = Some((File::create(&path)?, 0));
+        }
+        if let Some((file, size)) = self.file.as_mut() {
+            file.write_all(entry.as_bytes())?;
+            *size += entry.len() as u64;
+        }
+        Ok(())
+    }
+}
+
+/// Shift `name.log` -> `name.log.1` -> ... -> `name.log.N`, dropping the oldest
+fn rotate(path: &Path) -> io::Result<()> {
+    let rotated = |n: u32| PathBuf::from(format!("{}.{}", path.display(), n));
+    for n in (1..ROTATED_LOGS).rev() {
+        if rotated(n).exists() {
+            fs::rename(rotated(n), rotated(n + 1))?;
+        }
+    }
+    fs::rename(path, rotated(1))
+}
+
+/// Appends serial traffic to a per-workspace log file
+pub struct SerialLog {
+    state: Mutex<LogState>,
+}
+
+impl SerialLog {
+    /// Create a disabled log with no directory
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(LogState {
+                name: UNSAVED_WORKSPACE_NAME.into(),
+                ..LogState::default()
+            }),
+        }
+    }
+
+    pub fn settings(&self) -> SerialLogSettings {
+        self.state.lock().settings
+    }
+
+    /// Update the options; the rotation size is raised to [`MIN_MAX_LOG_BYTES`].
+    pub fn set_settings(&self, settings: SerialLogSettings) {
+        let mut state = self.state.lock();
+        state.settings = SerialLogSettings {
+            max_bytes: settings.max_bytes.max(MIN_MAX_LOG_BYTES),
+            ..settings
+        };
+        if !settings.enabled {
+            state.file = None;
+        }
+    }
+
+    /// Set the directory log files are written to
+    pub fn set_directory(&self, dir: &Path) {
+        let mut state = self.state.lock();
+        state.dir = Some(dir.to_path_buf());
+        state.file = None;
+    }
+
+    /// Directory log files are written to, if known
+    pub fn directory(&self) -> Option<PathBuf> {
+        self.state.lock().dir.clone()
+    }
+
+    /// Switch to the log of the workspace saved at `workspace_path`, or the
+    /// shared log for unsaved workspaces.
+    pub fn set_workspace(&self, workspace_path: Option<&Path>) {
+        let name = workspace_path
+            .and_then(|p| p.file_stem())
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| UNSAVED_WORKSPACE_NAME.into());
+        let mut state = self.state.lock();
+        if state.name != name {
+            state.name = name;
+            state.file = None;
+        }
+    }
+
+    /// Take over another log's options and workspace, keeping this log's
+    /// directory
+    pub fn inherit(&self, other: &SerialLog) {
+        let (settings, name) = {
+            let other = other.state.lock();
+            (other.settings, other.name.clone())
+        };
+        let mut state = self.state.lock();
+        state.settings = settings;
+        state.name = name;
+        state.file = None;
+    }
+
+    /// Append a line, if logging is enabled.
+    pub fn record(&self, direction: Direction, line: &str) {
+        let line = line.trim_end();
+        if line.is_empty() {
+            return;
+        }
+        let mut state = self.state.lock();
+        if !state.settings.enabled {
+            return;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let entry = format!(
+            "{}.{:03} {} {}\n",
+            now.as_secs(),
+            now.subsec_millis(),
+            direction.marker(),
+            line
+        );
+        // Stop rather than warn on every line of a long session
+        if let Err(e) = state.write(&entry) {
+            log::warn!("Failed to write serial log, disabling it: {}", e);
+            state.settings.enabled = false;
+            state.file = None;
+        }
+    }
+
+    /// Log files in the log directory, newest first
+    pub fn log_files(&self) -> Vec<LogFileInfo> {
+        let Some(dir) = self.directory() else {
+            return Vec::new();
+        };
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<LogFileInfo> = entries
+            .flatten()
+            .filter(|entry| is_log_file(&entry.path()))
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                let modified = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs());
+                Some(LogFileInfo {
+                    path: entry.path().to_string_lossy().into_owned(),
+                    size: metadata.len(),
+                    modified,
+                })
+            })
+            .collect();
+        files.sort_by(|a, b| b.modified.cmp(&a.modified).then(a.path.cmp(&b.path)));
+        files
+    }
+}
+
+impl Default for SerialLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `path` is a serial log or one of its rotations
+pub fn is_log_file(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    match name.rsplit_once(".log") {
+        Some((stem, suffix)) => {
+            !stem.is_empty()
+                && (suffix.is_empty()
+                    || suffix
+                        .strip_prefix('.')
+                        .is_some_and(|n| n.parse::<u32>().is_ok()))
+        }
+        None => false,
+    }
+}
+
+/// Last `lines` lines of a log file
+pub fn read_tail(path: &Path, lines: usize) -> io::Result<Vec<String>> {
+    let mut tail = VecDeque::with_capacity(lines.min(10_000));
+    for line in BufReader::new(File::open(path)?).lines() {
+        if tail.len() == lines {
+            tail.pop_front();
+        }
+        if lines > 0 {
+            tail.push_back(line?);
+        }
+    }
+    Ok(tail.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_rotates_and_reads_tail() {
+        let dir = std::env::temp_dir().join(format!("dl44-serial-log-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let log = SerialLog::new();
+        log.set_directory(&dir);
+        log.set_workspace(Some(Path::new("/projects/coasters.dl44")));
+        log.record(Direction::Sent, "G0 X1");
+        assert!(log.log_files().is_empty(), "disabled log writes nothing");
+
+        log.set_settings(SerialLogSettings {
+            enabled: true,
+            max_bytes: 0,
+        });
+        // ~100 bytes per entry, so the 16 KiB minimum rotates a few times
+        let line = format!("G1 X{}", "1".repeat(80));
+        for _ in 0..500 {
+            log.record(Direction::Sent, &line);
+        }
+        log.record(Direction::Received, "ok");
+
+        let current = dir.join("coasters.log");
+        let tail = read_tail(&current, 2).unwrap();
+        assert_eq!(tail.len(), 2);
+        assert!(tail[0].ends_with(&format!("> {}", line)));
+        assert!(tail[1].ends_with("< ok"));
+
+        let names: Vec<String> = log
+            .log_files()
+            .iter()
+            .map(|f| f.path.rsplit(['/', '\\']).next().unwrap().to_string())
+            .collect();
+        assert!(names.contains(&"coasters.log".to_string()));
+        assert!(names.contains(&"coasters.log.3".to_string()));
+        assert!(!names.contains(&"coasters.log.4".to_string()));
+        assert!(fs::metadata(&current).unwrap().len() <= MIN_MAX_LOG_BYTES + 200);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_is_log_file() {
+        assert!(is_log_file(Path::new("/logs/untitled.log")));
+        assert!(is_log_file(Path::new("/logs/untitled.log.2")));
+        assert!(!is_log_file(Path::new("/logs/.log")));
+        assert!(!is_log_file(Path::new("/logs/notes.txt")));
+        assert!(!is_log_file(Path::new("/logs/untitled.log.bak")));
+    }
+}
@@ -0,0 +1,201 @@
+//! GRBL settings backup files.
+//!
+//! Settings read with `$$` can be saved as plain text (one `$N=value` per
+//! line, as GRBL prints them) or as a JSON object mapping setting numbers
+//! to values. Text backups may contain blank lines and `;` or `( ... )`
+//! comments, which are skipped on import.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use thiserror::Error;
+
+use super::protocol::{is_valid_setting_value, SETTINGS_METADATA};
+use super::stream::strip_line_comments;
+
+/// Settings backup file format, chosen by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsFormat {
+    /// `.txt`: `$N=value` lines
+    Text,
+    /// `.json`: `{ "N": "value", ... }`
+    Json,
+}
+
+impl SettingsFormat {
+    /// Format for a path's extension (`.txt` or `.json`)
+    pub fn from_path(path: &Path) -> Result<Self, SettingsFileError> {
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match ext.as_str() {
+            "txt" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(SettingsFileError::UnsupportedFormat(ext)),
+        }
+    }
+}
+
+/// Settings backup errors
+#[derive(Error, Debug, PartialEq)]
+pub enum SettingsFileError {
+    #[error("Unsupported settings file type \".{0}\" (expected .txt or .json)")]
+    UnsupportedFormat(String),
+
+    #[error("Line {line}: expected $N=value, found \"{text}\"")]
+    InvalidLine { line: usize, text: String },
+
+    #[error("Invalid settings JSON: {0}")]
+    InvalidJson(String),
+
+    #[error("Setting ${0} has an empty value")]
+    EmptyValue(u32),
+
+    #[error("Setting ${0} has control or realtime characters in its value")]
+    InvalidValue(u32),
+
+    #[error("No settings found in file")]
+    Empty,
+}
+
+/// Serialize settings in the given format
+pub fn format_settings(settings: &BTreeMap<u32, String>, format: SettingsFormat) -> String {
+    match format {
+        SettingsFormat::Text => {
+            let mut text = String::from("; GRBL settings backup\n");
+            for (number, value) in settings {
+                let line = match SETTINGS_METADATA.iter().find(|s| s.number == *number) {
+                    Some(info) => format!("${}={} ; {}\n", number, value, info.name),
+                    None => format!("${}={}\n", number, value),
+                };
+                text.push_str(&line);
+            }
+            text
+        }
+        SettingsFormat::Json => {
+            // Infallible for string keys and values
+            serde_json::to_string_pretty(settings).unwrap_or_default()
+        }
+    }
+}
+
+/// Parse a settings backup, in file order.
+///
+/// Fails on the first malformed entry, so nothing is written from a file
+/// that isn't a settings backup. Values must be safe to send as one line
+/// (see [`is_valid_setting_value`]).
+pub fn parse_settings(
+    contents: &str,
+    format: SettingsFormat,
+) -> Result<Vec<(u32, String)>, SettingsFileError> {
+    let settings = match format {
+        SettingsFormat::Text => parse_text(contents)?,
+        SettingsFormat::Json => {
+            let map: BTreeMap<u32, String> = serde_json::from_str(contents)
+                .map_err(|e| SettingsFileError::InvalidJson(e.to_string()))?;
+            map.into_iter()
+                .map(|(n, v)| (n, v.trim().to_string()))
+                .collect()
+        }
+    };
+    if let Some((number, _)) = settings.iter().find(|(_, v)| v.is_empty()) {
+        return Err(SettingsFileError::EmptyValue(*number));
+    }
+    if let Some((number, _)) = settings.iter().find(|(_, v)| !is_valid_setting_value(v)) {
+        return Err(SettingsFileError::InvalidValue(*number));
+    }
+    if settings.is_empty() {
+        return Err(SettingsFileError::Empty);
+    }
+    Ok(settings)
+}
+
+fn parse_text(contents: &str) -> Result<Vec<(u32, String)>, SettingsFileError> {
+    let mut settings = Vec::new();
+    for (i, raw) in contents.lines().enumerate() {
+        let line = strip_line_comments(raw);
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let setting = line
+            .strip_prefix('$')
+            .and_then(|rest| rest.split_once('='))
+            .and_then(|(n, v)| Some((n.trim().parse::<u32>().ok()?, v.trim().to_string())));
+        match setting {
+            Some(setting) => settings.push(setting),
+            None => {
+                return Err(SettingsFileError::InvalidLine {
+                    line: i + 1,
+                    text: raw.trim().to_string(),
+                })
+            }
+        }
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_round_trip() {
+        let settings: BTreeMap<u32, String> = [(32, "1"), (100, "80.000"), (300, "abc")]
+            .into_iter()
+            .map(|(n, v)| (n, v.to_string()))
+            .collect();
+        let expected: Vec<(u32, String)> = settings.clone().into_iter().collect();
+
+        for format in [SettingsFormat::Text, SettingsFormat::Json] {
+            let contents = format_settings(&settings, format);
+            assert_eq!(parse_settings(&contents, format).unwrap(), expected);
+        }
+        let text = format_settings(&settings, SettingsFormat::Text);
+        assert!(text.contains("$100=80.000 ; X steps/mm\n"));
+        assert!(text.contains("$300=abc\n"));
+    }
+
+    #[test]
+    fn test_text_import_skips_comments_and_validates() {
+        let contents = "; backup\n\n(machine A)\n$30=1000 ; max power\n  $32 = 1\n";
+        assert_eq!(
+            parse_settings(contents, SettingsFormat::Text).unwrap(),
+            [(30, "1000".to_string()), (32, "1".to_string())]
+        );
+
+        assert_eq!(
+            parse_settings("$30=1000\nG0 X10\n", SettingsFormat::Text),
+            Err(SettingsFileError::InvalidLine {
+                line: 2,
+                text: "G0 X10".into()
+            })
+        );
+        assert_eq!(
+            parse_settings("$30=\n", SettingsFormat::Text),
+            Err(SettingsFileError::EmptyValue(30))
+        );
+        assert_eq!(
+            parse_settings("; nothing here\n", SettingsFormat::Text),
+            Err(SettingsFileError::Empty)
+        );
+        // A value must not smuggle in another line or realtime command
+        assert_eq!(
+            parse_settings("{\"30\": \"1\\n$RST=*\"}", SettingsFormat::Json),
+            Err(SettingsFileError::InvalidValue(30))
+        );
+        assert_eq!(
+            parse_settings("$32=1!\n", SettingsFormat::Text),
+            Err(SettingsFileError::InvalidValue(32))
+        );
+        assert!(matches!(
+            parse_settings("{\"x\": \"1\"}", SettingsFormat::Json),
+            Err(SettingsFileError::InvalidJson(_))
+        ));
+        assert!(SettingsFormat::from_path(Path::new("backup.cfg")).is_err());
+        assert_eq!(
+            SettingsFormat::from_path(Path::new("backup.JSON")),
+            Ok(SettingsFormat::Json)
+        );
+    }
+}
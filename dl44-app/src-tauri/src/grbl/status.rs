@@ -4,8 +4,11 @@
 //! `<State|MPos:x,y,z|FS:feed,spindle|WCO:x,y,z|Ov:f,r,s|...>`
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
+use super::protocol::{Response, Units};
+
 /// Machine operating state
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
@@ -68,6 +71,24 @@ impl Position {
         Self { x, y, z }
     }
 
+    /// Scale all axes by a factor
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self {
+            x: self.x * factor,
+            y: self.y * factor,
+            z: self.z * factor,
+        }
+    }
+
+    /// Difference of two positions, axis by axis
+    pub fn minus(&self, other: &Position) -> Self {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+
     /// Parse from comma-separated values: "x,y,z"
     pub fn parse(s: &str) -> Option<Self> {
         let parts: Vec<&str> = s.split(',').collect();
@@ -94,6 +115,15 @@ pub struct Overrides {
     pub spindle: u32,
 }
 
+impl Overrides {
+    /// All overrides at 100%, as GRBL sets them on reset
+    pub const NORMAL: Self = Self {
+        feed: 100,
+        rapid: 100,
+        spindle: 100,
+    };
+}
+
 /// Accessory state flags
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 pub struct Accessories {
@@ -108,6 +138,10 @@ pub struct Accessories {
 pub struct MachineStatus {
     /// Current machine state
     pub state: MachineState,
+    /// State suffix, e.g. 1 for `Hold:1` (hold still decelerating) or 2 for
+    /// `Door:2`; None when the state has no suffix
+    #[serde(default)]
+    pub sub_state: Option<u8>,
     /// Machine position (absolute)
     pub machine_pos: Position,
     /// Work position (with offsets applied)
@@ -128,9 +162,30 @@ pub struct MachineStatus {
     pub buffer: Option<(u32, u32)>,
     /// Line number being executed
     pub line_number: Option<u32>,
+    /// Units the device reported positions in (`$13`); positions above are always mm
+    #[serde(default)]
+    pub report_units: Units,
+    /// Machine position as reported, if it was converted from inches
+    #[serde(default)]
+    pub reported_machine_pos: Option<Position>,
+    /// Work position as reported, if it was converted from inches
+    #[serde(default)]
+    pub reported_work_pos: Option<Position>,
 }
 
 impl MachineStatus {
+    /// Whether cycle start can resume from the current state.
+    ///
+    /// `Hold:1` is still decelerating and `Door:1..3` is stopped, parking, or
+    /// restoring; only `Hold:0` and `Door:0` are ready. A missing suffix
+    /// (older firmware) is treated as ready.
+    pub fn can_resume(&self) -> bool {
+        match self.state {
+            MachineState::Hold | MachineState::Door => self.sub_state.unwrap_or(0) == 0,
+            _ => true,
+        }
+    }
+
     /// Parse a GRBL status report string.
     ///
     /// Format: `<State|MPos:x,y,z|WPos:x,y,z|FS:f,s|Ov:f,r,s|...>`
@@ -144,6 +199,9 @@ impl MachineStatus {
         // First part is always the state
         if let Some(state_str) = parts.next() {
             status.state = state_str.parse().unwrap_or(MachineState::Unknown);
+            status.sub_state = state_str
+                .split_once(':')
+                .and_then(|(_, sub)| sub.parse().ok());
         }
 
         // Parse remaining fields
@@ -206,11 +264,7 @@ impl MachineStatus {
         // Calculate work position from machine position and offset if needed
         if status.work_pos.is_none() {
             if let Some(wco) = status.work_offset {
-                status.work_pos = Some(Position {
-                    x: status.machine_pos.x - wco.x,
-                    y: status.machine_pos.y - wco.y,
-                    z: status.machine_pos.z - wco.z,
-                });
+                status.work_pos = Some(status.machine_pos.minus(&wco));
             }
         }
 
@@ -218,6 +272,219 @@ impl MachineStatus {
     }
 }
 
+/// G-code parser modal state from a `$G` query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParserState {
+    /// Motion mode (G0, G1, G2, G3, G38.x, G80)
+    pub motion_mode: String,
+    /// Active work coordinate system (G54-G59)
+    pub coordinate_system: String,
+    /// Plane select (G17, G18, G19)
+    pub plane: String,
+    /// Units mode (G20/G21)
+    pub units: Units,
+    /// Distance mode: true = absolute (G90), false = incremental (G91)
+    pub absolute: bool,
+    /// Feed rate mode (G93, G94)
+    pub feed_rate_mode: String,
+    /// Spindle/laser state (M3, M4, M5)
+    pub spindle: String,
+    /// Coolant state (M7, M8, M9)
+    pub coolant: String,
+    /// Active tool number
+    pub tool: u32,
+    /// Programmed feed rate
+    pub feed_rate: f64,
+    /// Programmed spindle speed / laser power
+    pub spindle_speed: f64,
+}
+
+impl Default for ParserState {
+    fn default() -> Self {
+        Self {
+            motion_mode: "G0".into(),
+            coordinate_system: "G54".into(),
+            plane: "G17".into(),
+            units: Units::Mm,
+            absolute: true,
+            feed_rate_mode: "G94".into(),
+            spindle: "M5".into(),
+            coolant: "M9".into(),
+            tool: 0,
+            feed_rate: 0.0,
+            spindle_speed: 0.0,
+        }
+    }
+}
+
+impl ParserState {
+    /// Parse the body of a `[GC:...]` response.
+    ///
+    /// Format: `G0 G54 G17 G21 G90 G94 M5 M9 T0 F0 S0`
+    pub fn parse(state: &str) -> Option<Self> {
+        let mut parsed = ParserState::default();
+        let mut seen = false;
+
+        for word in state.split_whitespace() {
+            let (Some(letter), Some(value)) = (word.get(..1), word.get(1..)) else {
+                continue;
+            };
+            match (letter, value) {
+                ("G", "0" | "1" | "2" | "3" | "80") | ("G", "38.2" | "38.3" | "38.4" | "38.5") => {
+                    parsed.motion_mode = word.to_string();
+                }
+                ("G", "54" | "55" | "56" | "57" | "58" | "59") => {
+                    parsed.coordinate_system = word.to_string();
+                }
+                ("G", "17" | "18" | "19") => parsed.plane = word.to_string(),
+                ("G", "20") => parsed.units = Units::Inches,
+                ("G", "21") => parsed.units = Units::Mm,
+                ("G", "90") => parsed.absolute = true,
+                ("G", "91") => parsed.absolute = false,
+                ("G", "93" | "94") => parsed.feed_rate_mode = word.to_string(),
+                ("M", "3" | "4" | "5") => parsed.spindle = word.to_string(),
+                ("M", "7" | "8" | "9") => parsed.coolant = word.to_string(),
+                ("T", v) => parsed.tool = v.parse().ok()?,
+                ("F", v) => parsed.feed_rate = v.parse().ok()?,
+                ("S", v) => parsed.spindle_speed = v.parse().ok()?,
+                _ => continue,
+            }
+            seen = true;
+        }
+
+        seen.then_some(parsed)
+    }
+}
+
+/// Last probe cycle result
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProbeResult {
+    /// Machine position where the probe stopped
+    pub position: Position,
+    /// Whether the probe made contact
+    pub success: bool,
+}
+
+/// Stored coordinate parameters reported by `$#`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Parameters {
+    /// G54-G59 work coordinate offsets, keyed by G number
+    pub work_offsets: BTreeMap<u32, Position>,
+    /// G28 predefined position (machine coordinates)
+    pub g28: Option<Position>,
+    /// G30 predefined position (machine coordinates)
+    pub g30: Option<Position>,
+    /// G92 coordinate offset
+    pub g92: Option<Position>,
+    /// Tool length offset along Z
+    pub tool_length_offset: Option<f64>,
+    pub probe: Option<ProbeResult>,
+}
+
+impl Parameters {
+    /// Collect parameters from the lines of a `$#` response
+    pub fn from_responses(responses: &[Response]) -> Self {
+        let mut params = Self::default();
+        for response in responses {
+            match *response {
+                Response::Parameter { code: 28, position } => params.g28 = Some(position),
+                Response::Parameter { code: 30, position } => params.g30 = Some(position),
+                Response::Parameter { code: 92, position } => params.g92 = Some(position),
+                Response::Parameter { code, position } => {
+                    params.work_offsets.insert(code, position);
+                }
+                Response::ToolLengthOffset(offset) => params.tool_length_offset = Some(offset),
+                Response::Probe { position, success } => {
+                    params.probe = Some(ProbeResult { position, success });
+                }
+                _ => {}
+            }
+        }
+        params
+    }
+
+    /// Convert values reported in inches (`$13=1`) to mm.
+    pub fn convert_from_inches(&mut self) {
+        for position in self.work_offsets.values_mut() {
+            *position = position.scaled(MM_PER_INCH);
+        }
+        for position in [&mut self.g28, &mut self.g30, &mut self.g92]
+            .into_iter()
+            .flatten()
+        {
+            *position = position.scaled(MM_PER_INCH);
+        }
+        if let Some(probe) = &mut self.probe {
+            probe.position = probe.position.scaled(MM_PER_INCH);
+        }
+        self.tool_length_offset = self.tool_length_offset.map(|z| z * MM_PER_INCH);
+    }
+}
+
+/// Millimeters per inch
+const MM_PER_INCH: f64 = 25.4;
+
+impl MachineStatus {
+    /// Convert a status parsed from an inch-mode report (`$13=1`) to mm.
+    ///
+    /// Positions, work offset, and feed rate are scaled; the reported
+    /// positions are kept in `reported_machine_pos`/`reported_work_pos`.
+    /// Work position is derived before scaling, so MPos/WCO stay consistent.
+    pub fn convert_from_inches(&mut self) {
+        if self.report_units == Units::Inches {
+            return;
+        }
+        self.report_units = Units::Inches;
+        self.reported_machine_pos = Some(self.machine_pos);
+        self.reported_work_pos = self.work_pos;
+
+        self.machine_pos = self.machine_pos.scaled(MM_PER_INCH);
+        self.work_pos = self.work_pos.map(|p| p.scaled(MM_PER_INCH));
+        self.work_offset = self.work_offset.map(|p| p.scaled(MM_PER_INCH));
+        self.feed_rate = self.feed_rate.map(|f| f * MM_PER_INCH);
+    }
+
+    /// Use the work offset from an earlier report when this one has no
+    /// `WCO`, deriving the work position from it.
+    ///
+    /// GRBL only sends `WCO` every 10-30 reports (or after it changes), so a
+    /// missing field means the offset is unchanged. `wco` is in mm; call
+    /// this after [`MachineStatus::convert_from_inches`].
+    pub fn fill_work_offset(&mut self, wco: Position) {
+        if self.work_offset.is_some() {
+            return;
+        }
+        self.work_offset = Some(wco);
+        if self.work_pos.is_none() {
+            self.work_pos = Some(self.machine_pos.minus(&wco));
+            self.reported_work_pos = self
+                .reported_machine_pos
+                .map(|p| p.minus(&wco.scaled(1.0 / MM_PER_INCH)));
+        }
+    }
+}
+
+/// A feed rate in both units the UI can show
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeedRates {
+    pub mm_per_min: f64,
+    pub in_per_min: f64,
+}
+
+impl FeedRates {
+    /// Feed rate of `feed` `units`/min
+    pub fn new(feed: f64, units: Units) -> Self {
+        let mm_per_min = match units {
+            Units::Mm => feed,
+            Units::Inches => feed * MM_PER_INCH,
+        };
+        Self {
+            mm_per_min,
+            in_per_min: mm_per_min / MM_PER_INCH,
+        }
+    }
+}
+
 fn parse_accessories(s: &str) -> Accessories {
     Accessories {
         spindle_cw: s.contains('S'),
@@ -239,6 +506,18 @@ mod tests {
         assert_eq!(status.feed_rate, Some(0.0));
     }
 
+    #[test]
+    fn test_parse_buffer_state() {
+        let status = MachineStatus::parse("<Run|MPos:0.000,0.000,0.000|Bf:12,96>").unwrap();
+        assert_eq!(status.buffer, Some((12, 96)));
+
+        // Bf disabled via $10, or malformed
+        let status = MachineStatus::parse("<Run|MPos:0.000,0.000,0.000|FS:500,0>").unwrap();
+        assert_eq!(status.buffer, None);
+        let status = MachineStatus::parse("<Run|MPos:0.000,0.000,0.000|Bf:12>").unwrap();
+        assert_eq!(status.buffer, None);
+    }
+
     #[test]
     fn test_parse_run_status() {
         let status =
@@ -251,6 +530,35 @@ mod tests {
         assert!(status.overrides.is_some());
     }
 
+    #[test]
+    fn test_parameters_from_responses() {
+        let responses: Vec<Response> = [
+            "[G54:-200.000,-150.000,0.000]",
+            "[G55:0.000,0.000,0.000]",
+            "[G28:1.000,2.000,3.000]",
+            "[G30:0.000,0.000,0.000]",
+            "[G92:0.000,0.000,0.000]",
+            "[TLO:0.100]",
+            "[PRB:0.000,0.000,-1.000:0]",
+            "ok",
+        ]
+        .iter()
+        .map(|line| crate::grbl::protocol::parse_response(line))
+        .collect();
+
+        let mut params = Parameters::from_responses(&responses);
+        assert_eq!(params.work_offsets.len(), 2);
+        assert_eq!(params.work_offsets[&54], Position::new(-200.0, -150.0, 0.0));
+        assert_eq!(params.g28, Some(Position::new(1.0, 2.0, 3.0)));
+        assert_eq!(params.tool_length_offset, Some(0.1));
+        assert_eq!(params.probe.map(|p| p.success), Some(false));
+
+        params.convert_from_inches();
+        assert_eq!(params.work_offsets[&54].x, -5080.0);
+        assert_eq!(params.g28.map(|p| p.y), Some(50.8));
+        assert_eq!(params.probe.map(|p| p.position.z), Some(-25.4));
+    }
+
     #[test]
     fn test_parse_with_wco() {
         let status =
@@ -260,6 +568,69 @@ mod tests {
         assert_eq!(work.y, 45.0);
     }
 
+    #[test]
+    fn test_parse_parser_state() {
+        let state = ParserState::parse("G1 G54 G17 G20 G91 G94 M4 M9 T0 F1500. S800.").unwrap();
+        assert_eq!(state.motion_mode, "G1");
+        assert_eq!(state.units, Units::Inches);
+        assert!(!state.absolute);
+        assert_eq!(state.spindle, "M4");
+        assert_eq!(state.feed_rate, 1500.0);
+        assert_eq!(state.spindle_speed, 800.0);
+        assert!(ParserState::parse("").is_none());
+    }
+
+    #[test]
+    fn test_convert_inch_report() {
+        let mut status =
+            MachineStatus::parse("<Idle|MPos:2.000,1.000,0.000|WCO:0.500,0.250,0.000|FS:40,0>")
+                .unwrap();
+        status.convert_from_inches();
+
+        assert_eq!(status.report_units, Units::Inches);
+        assert!((status.machine_pos.x - 50.8).abs() < 1e-9);
+        let work = status.work_pos.unwrap();
+        assert!((work.x - 38.1).abs() < 1e-9);
+        assert!((work.y - 19.05).abs() < 1e-9);
+        assert_eq!(status.reported_machine_pos.unwrap().x, 2.0);
+        assert!((status.feed_rate.unwrap() - 1016.0).abs() < 1e-9);
+
+        // Converting twice must not double-scale
+        status.convert_from_inches();
+        assert!((status.machine_pos.x - 50.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_feed_rates_in_both_units() {
+        let mm = FeedRates::new(1270.0, Units::Mm);
+        assert_eq!(mm.mm_per_min, 1270.0);
+        assert!((mm.in_per_min - 50.0).abs() < 1e-9);
+
+        let inches = FeedRates::new(40.0, Units::Inches);
+        assert!((inches.mm_per_min - 1016.0).abs() < 1e-9);
+        assert!((inches.in_per_min - 40.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_sub_state() {
+        let hold_done = MachineStatus::parse("<Hold:0|MPos:0.000,0.000,0.000>").unwrap();
+        assert_eq!(hold_done.state, MachineState::Hold);
+        assert_eq!(hold_done.sub_state, Some(0));
+        assert!(hold_done.can_resume());
+
+        let holding = MachineStatus::parse("<Hold:1|MPos:0.000,0.000,0.000>").unwrap();
+        assert_eq!(holding.sub_state, Some(1));
+        assert!(!holding.can_resume());
+
+        let door = MachineStatus::parse("<Door:2|MPos:0.000,0.000,0.000>").unwrap();
+        assert_eq!(door.state, MachineState::Door);
+        assert_eq!(door.sub_state, Some(2));
+        assert!(!door.can_resume());
+
+        let idle = MachineStatus::parse("<Idle|MPos:0.000,0.000,0.000>").unwrap();
+        assert_eq!(idle.sub_state, None);
+    }
+
     #[test]
     fn test_machine_state_parsing() {
         assert_eq!("Idle".parse::<MachineState>().unwrap(), MachineState::Idle);
@@ -0,0 +1,114 @@
+//! Line-by-line step mode for debugging programs.
+//!
+//! A step session holds a program and sends one line per step, so the user
+//! can watch what each line does before confirming the next. Blank lines
+//! and full-line comments are stepped over.
+
+use super::stream::prepare_line;
+
+/// A program line addressed by its index in the program
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StepLine {
+    /// Zero-based index among all program lines
+    pub index: usize,
+    /// Line as sent (trimmed)
+    pub line: String,
+}
+
+/// Where a step session is, for the UI
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StepStatus {
+    /// Line sent by the last step
+    pub current: Option<StepLine>,
+    /// Line the next step will send; None once the program is done
+    pub next: Option<StepLine>,
+    /// Lines in the program, including blank and comment lines
+    pub total_lines: usize,
+}
+
+/// A program being sent one line at a time
+#[derive(Debug, Default)]
+pub struct StepSession {
+    lines: Vec<String>,
+    current: Option<usize>,
+    next: Option<usize>,
+}
+
+impl StepSession {
+    pub fn new(lines: Vec<String>) -> Self {
+        let mut session = Self {
+            lines,
+            current: None,
+            next: None,
+        };
+        session.next = session.sendable_from(0);
+        session
+    }
+
+    /// First line at or after `from` that would be sent
+    fn sendable_from(&self, from: usize) -> Option<usize> {
+        (from..self.lines.len()).find(|&i| prepare_line(&self.lines[i], false).is_some())
+    }
+
+    fn step_line(&self, index: Option<usize>) -> Option<StepLine> {
+        let index = index?;
+        Some(StepLine {
+            index,
+            line: prepare_line(&self.lines[index], false)?,
+        })
+    }
+
+    /// Line the next step sends
+    pub fn next_line(&self) -> Option<StepLine> {
+        self.step_line(self.next)
+    }
+
+    /// Mark the next line as sent
+    pub fn advance(&mut self) {
+        if let Some(next) = self.next {
+            self.current = Some(next);
+            self.next = self.sendable_from(next + 1);
+        }
+    }
+
+    pub fn status(&self) -> StepStatus {
+        StepStatus {
+            current: self.step_line(self.current),
+            next: self.next_line(),
+            total_lines: self.lines.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_skip_blank_and_comment_lines() {
+        let lines = ["; header", "G0 X1", "", "(move)", "  G1 X2 F100  "];
+        let mut session = StepSession::new(lines.iter().map(|l| l.to_string()).collect());
+
+        let status = session.status();
+        assert_eq!(status.current, None);
+        assert_eq!(status.next.as_ref().map(|l| l.index), Some(1));
+        assert_eq!(status.total_lines, 5);
+
+        session.advance();
+        assert_eq!(
+            session.next_line(),
+            Some(StepLine {
+                index: 4,
+                line: "G1 X2 F100".into()
+            })
+        );
+        session.advance();
+        let status = session.status();
+        assert_eq!(status.current.map(|l| l.index), Some(4));
+        assert_eq!(status.next, None);
+
+        // Stepping past the end is a no-op
+        session.advance();
+        assert_eq!(session.status().current.map(|l| l.index), Some(4));
+    }
+}
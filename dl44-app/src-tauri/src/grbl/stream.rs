@@ -0,0 +1,724 @@
+//! G-code program streaming with RX buffer accounting.
+//!
+//! Implements GRBL's "character counting" streaming protocol: lines are sent
+//! as long as the total size of unacknowledged lines fits in the device's
+//! serial RX buffer, and each `ok`/`error` frees the space of the oldest
+//! in-flight line. This keeps the planner fed without overflowing the buffer.
+//!
+//! The job itself is pure bookkeeping; the worker thread drives the I/O.
+//!
+//! Acknowledgements arrive in bursts as the planner drains, so the line
+//! rate is sampled over an interval and smoothed with an exponential moving
+//! average before it is used for the remaining-time estimate.
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::protocol::LineEnding;
+
+/// GRBL serial RX buffer size (bytes)
+pub const RX_BUFFER_SIZE: usize = 128;
+
+/// Default weight of the newest sample in the smoothed line rate
+pub const DEFAULT_RATE_SMOOTHING: f64 = 0.2;
+
+/// Smallest accepted smoothing factor (heaviest smoothing)
+pub const MIN_RATE_SMOOTHING: f64 = 0.01;
+
+/// Minimum time a line rate sample covers
+const RATE_SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Source of raw program lines
+pub type LineSource = Box<dyn Iterator<Item = io::Result<String>> + Send>;
+
+/// Callback run once with the final progress when a job stops
+pub type FinishCallback = Box<dyn FnOnce(&StreamProgress) + Send>;
+
+/// Callback run for each line GRBL rejects
+pub type LineErrorCallback = Box<dyn FnMut(&StreamLineError) + Send>;
+
+/// Streaming job state
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum StreamState {
+    /// Lines are still being sent or awaiting acknowledgement
+    Running,
+    /// All lines sent and acknowledged
+    Completed,
+    /// Stopped by the user (soft reset / disconnect)
+    Cancelled,
+    /// Stopped because of a GRBL error, alarm, or I/O failure
+    Failed(String),
+}
+
+/// Progress of a streaming job (shared between worker and controller)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct StreamProgress {
+    pub state: StreamState,
+    /// Total number of lines that will be sent
+    pub total_lines: usize,
+    /// Lines written to the serial port
+    pub lines_sent: usize,
+    /// Lines acknowledged with `ok`
+    pub lines_completed: usize,
+    /// Program line index of the last line acknowledged with `ok`.
+    ///
+    /// Acknowledged lines may still be queued in the planner, so a job
+    /// interrupted by power loss should resume a little before this line.
+    pub last_acked_line: Option<usize>,
+    /// Zero-based index, among all program lines (including blank and
+    /// comment lines), of the last line GRBL rejected with `error:N`
+    pub error_line: Option<usize>,
+    /// Lines GRBL rejected; only above 1 when continuing on errors
+    pub error_count: usize,
+    /// Lines acknowledged per second over the last sample
+    pub line_rate: Option<f64>,
+    /// Exponential moving average of `line_rate`
+    pub smoothed_line_rate: Option<f64>,
+    /// Estimated seconds until all lines are acknowledged, from the
+    /// smoothed rate
+    pub remaining_secs: Option<f64>,
+    /// GRBL reported the program end (`[MSG:Pgm End]`)
+    #[serde(default)]
+    pub program_end: bool,
+    /// The machine has run every line, not just acknowledged it: the
+    /// program ended, or the machine went Idle with an empty planner after
+    /// the last acknowledgement
+    #[serde(default)]
+    pub finished: bool,
+}
+
+impl StreamProgress {
+    /// Whether the job is still running
+    pub fn is_running(&self) -> bool {
+        self.state == StreamState::Running
+    }
+}
+
+/// Samples the line acknowledgement rate
+struct RateTracker {
+    /// Weight of the newest sample in the moving average (0..=1)
+    smoothing: f64,
+    /// Start of the current sample and lines acknowledged by then
+    sample_start: Option<(Instant, usize)>,
+}
+
+impl RateTracker {
+    /// Count acknowledgements up to `now`, updating the rates in `progress`
+    /// once the current sample covers [`RATE_SAMPLE_INTERVAL`].
+    fn record(&mut self, progress: &mut StreamProgress, now: Instant) {
+        let acked = progress.lines_completed + progress.error_count;
+        let Some((start, start_acked)) = self.sample_start else {
+            // Time to the first ack is mostly the planner filling up
+            self.sample_start = Some((now, acked));
+            return;
+        };
+        let elapsed = now.duration_since(start);
+        if elapsed < RATE_SAMPLE_INTERVAL {
+            return;
+        }
+
+        let rate = (acked - start_acked) as f64 / elapsed.as_secs_f64();
+        let smoothed = match progress.smoothed_line_rate {
+            Some(prev) => self.smoothing * rate + (1.0 - self.smoothing) * prev,
+            None => rate,
+        };
+        progress.line_rate = Some(rate);
+        progress.smoothed_line_rate = Some(smoothed);
+        progress.remaining_secs =
+            (smoothed > 0.0).then(|| progress.total_lines.saturating_sub(acked) as f64 / smoothed);
+        self.sample_start = Some((now, acked));
+    }
+}
+
+/// A line GRBL rejected with `error:N` while streaming
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StreamLineError {
+    /// GRBL error code
+    pub code: u32,
+    /// Zero-based index of the line among all program lines
+    pub line: Option<usize>,
+    /// Lines rejected so far in this job, including this one
+    pub error_count: usize,
+}
+
+/// Prepare a raw program line for sending.
+///
+/// Returns `None` for lines that should be skipped (blank lines and
+/// full-line comments). With `strip_comments`, inline `( ... )` and
+/// `;` comments are removed too, and lines left blank are skipped.
+pub fn prepare_line(raw: &str, strip_comments: bool) -> Option<String> {
+    let line = raw.trim();
+    if line.is_empty() || line.starts_with(';') || line.starts_with('(') {
+        return None;
+    }
+    if !strip_comments {
+        return Some(line.to_string());
+    }
+
+    let stripped = strip_line_comments(line);
+    let stripped = stripped.trim();
+    if stripped.is_empty() {
+        None
+    } else {
+        Some(stripped.to_string())
+    }
+}
+
+/// Remove `( ... )` and `;`-to-end-of-line comments from a line.
+///
+/// GRBL doesn't nest parentheses, but a stray `(` inside a comment is
+/// tolerated by tracking depth. An unclosed `(` comments out the rest of
+/// the line.
+pub fn strip_line_comments(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut depth = 0usize;
+
+    for c in line.chars() {
+        match c {
+            ';' if depth == 0 => break,
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            _ if depth == 0 => out.push(c),
+            _ => {}
+        }
+    }
+
+    out
+}
+
+/// A G-code program being streamed to the device
+pub struct StreamJob {
+    source: LineSource,
+    /// Lines sent before the source, outside the program's line numbering
+    preamble: VecDeque<String>,
+    /// Next line to send and its program line index, held until it fits
+    /// in the RX buffer
+    pending: Option<(String, Option<usize>)>,
+    /// Program line index of the next line pulled from the source
+    next_index: usize,
+    /// Byte lengths and program line indices of sent lines awaiting
+    /// acknowledgement (oldest first)
+    in_flight: VecDeque<(usize, Option<usize>)>,
+    /// Bytes currently occupying the device RX buffer
+    buffered: usize,
+    /// Source has no more lines
+    exhausted: bool,
+    /// Remove inline comments before sending
+    strip_comments: bool,
+    /// Keep streaming after `error:N` instead of failing the job
+    continue_on_error: bool,
+    /// Sending is held so other commands can run between program lines
+    suspended: bool,
+    /// Terminator appended to each sent line (counted in the RX buffer)
+    line_ending: LineEnding,
+    progress: Arc<Mutex<StreamProgress>>,
+    rate: RateTracker,
+    on_finish: Option<FinishCallback>,
+    on_line_error: Option<LineErrorCallback>,
+}
+
+impl StreamJob {
+    /// Create a job from a line source.
+    ///
+    /// `total_lines` is used for progress reporting only.
+    pub fn new(source: LineSource, total_lines: usize, strip_comments: bool) -> Self {
+        Self {
+            source,
+            preamble: VecDeque::new(),
+            pending: None,
+            next_index: 0,
+            in_flight: VecDeque::new(),
+            buffered: 0,
+            exhausted: false,
+            strip_comments,
+            continue_on_error: false,
+            suspended: false,
+            line_ending: LineEnding::Lf,
+            progress: Arc::new(Mutex::new(StreamProgress {
+                state: StreamState::Running,
+                total_lines,
+                lines_sent: 0,
+                lines_completed: 0,
+                last_acked_line: None,
+                error_line: None,
+                error_count: 0,
+                line_rate: None,
+                smoothed_line_rate: None,
+                remaining_secs: None,
+                program_end: false,
+                finished: false,
+            })),
+            rate: RateTracker {
+                smoothing: DEFAULT_RATE_SMOOTHING,
+                sample_start: None,
+            },
+            on_finish: None,
+            on_line_error: None,
+        }
+    }
+
+    /// Create a job from in-memory lines.
+    pub fn from_lines(lines: Vec<String>, strip_comments: bool) -> Self {
+        let total = lines
+            .iter()
+            .filter(|l| prepare_line(l, strip_comments).is_some())
+            .count();
+        Self::new(Box::new(lines.into_iter().map(Ok)), total, strip_comments)
+    }
+
+    /// Create a job streaming `lines` from `start_index` on. Program line
+    /// indices in progress still count from the start of `lines`.
+    pub fn from_lines_at(lines: Vec<String>, start_index: usize, strip_comments: bool) -> Self {
+        let rest = lines.into_iter().skip(start_index).collect();
+        let mut job = Self::from_lines(rest, strip_comments);
+        job.next_index = start_index;
+        job
+    }
+
+    /// Send `lines` before the program, e.g. to restore modal state.
+    /// They count towards progress but have no program line index.
+    pub fn with_preamble(mut self, lines: Vec<String>) -> Self {
+        self.progress.lock().total_lines += lines.len();
+        self.preamble.extend(lines);
+        self
+    }
+
+    /// Run `on_finish` with the final progress once the job completes,
+    /// fails, or is cancelled.
+    pub fn on_finish(mut self, on_finish: impl FnOnce(&StreamProgress) + Send + 'static) -> Self {
+        self.on_finish = Some(Box::new(on_finish));
+        self
+    }
+
+    /// Keep streaming past lines GRBL rejects, calling `on_line_error` for
+    /// each, instead of stopping at the first. Alarms still stop the job.
+    pub fn continue_on_error(mut self, continue_on_error: bool) -> Self {
+        self.continue_on_error = continue_on_error;
+        self
+    }
+
+    /// Run `on_line_error` for each line GRBL rejects
+    pub fn on_line_error(
+        mut self,
+        on_line_error: impl FnMut(&StreamLineError) + Send + 'static,
+    ) -> Self {
+        self.on_line_error = Some(Box::new(on_line_error));
+        self
+    }
+
+    /// Set the weight of the newest sample in the smoothed line rate,
+    /// clamped to [`MIN_RATE_SMOOTHING`]..=1 (1 disables smoothing).
+    pub fn rate_smoothing(mut self, smoothing: f64) -> Self {
+        self.rate.smoothing = if smoothing.is_finite() {
+            smoothing.clamp(MIN_RATE_SMOOTHING, 1.0)
+        } else {
+            DEFAULT_RATE_SMOOTHING
+        };
+        self
+    }
+
+    /// Terminate sent lines with `line_ending` instead of `\n`
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Shared progress handle
+    pub fn progress(&self) -> Arc<Mutex<StreamProgress>> {
+        self.progress.clone()
+    }
+
+    /// Hold or resume sending program lines. Lines already sent are still
+    /// acknowledged, and a suspended job doesn't complete.
+    pub fn set_suspended(&mut self, suspended: bool) {
+        self.suspended = suspended;
+        if !suspended {
+            self.complete_if_drained();
+        }
+    }
+
+    /// Whether sending is held and every sent line has been acknowledged,
+    /// so the device's RX buffer holds nothing of this job
+    pub fn is_suspended_and_drained(&self) -> bool {
+        self.suspended && self.in_flight.is_empty()
+    }
+
+    /// Take the next line to send if it fits in the RX buffer.
+    ///
+    /// The returned line includes the line ending and is counted as
+    /// in flight. A line too long for an empty buffer is still sent once
+    /// the buffer drains, so GRBL can report the error.
+    pub fn next_line(&mut self) -> Option<String> {
+        if !self.is_running() || self.suspended {
+            return None;
+        }
+
+        if self.pending.is_none() {
+            self.pending = self.pull_line();
+            if self.pending.is_none() {
+                self.complete_if_drained();
+                return None;
+            }
+        }
+
+        let ending = self.line_ending.as_str();
+        let len = self.pending.as_ref()?.0.len() + ending.len();
+        if self.buffered + len > RX_BUFFER_SIZE && !self.in_flight.is_empty() {
+            return None;
+        }
+
+        let (mut line, index) = self.pending.take()?;
+        line.push_str(ending);
+        self.in_flight.push_back((len, index));
+        self.buffered += len;
+        self.progress.lock().lines_sent += 1;
+        Some(line)
+    }
+
+    /// Pull the next sendable line and its program line index, from the
+    /// preamble first and then the source.
+    fn pull_line(&mut self) -> Option<(String, Option<usize>)> {
+        if let Some(line) = self.preamble.pop_front() {
+            return Some((line, None));
+        }
+        while !self.exhausted {
+            match self.source.next() {
+                Some(Ok(raw)) => {
+                    let index = self.next_index;
+                    self.next_index += 1;
+                    let line = prepare_line(&raw, self.strip_comments);
+                    if !raw.trim().is_empty() && line.as_deref() != Some(raw.trim()) {
+                        // Keep stripped comments visible in the log
+                        log::debug!("Stream comment: {}", raw.trim());
+                    }
+                    if let Some(line) = line {
+                        return Some((line, Some(index)));
+                    }
+                }
+                Some(Err(e)) => {
+                    self.fail(format!("Read error: {}", e));
+                    return None;
+                }
+                None => self.exhausted = true,
+            }
+        }
+        None
+    }
+
+    /// Record an `ok` for the oldest in-flight line.
+    pub fn ack_ok(&mut self) {
+        let index = self.release_oldest();
+        {
+            let mut progress = self.progress.lock();
+            progress.lines_completed += 1;
+            progress.last_acked_line = index.or(progress.last_acked_line);
+            self.rate.record(&mut progress, Instant::now());
+        }
+        self.complete_if_drained();
+    }
+
+    /// Record an `error:N` for the oldest in-flight line.
+    ///
+    /// Stops the job unless it continues on errors.
+    pub fn ack_error(&mut self, code: u32) {
+        let index = self.release_oldest();
+        let error = {
+            let mut progress = self.progress.lock();
+            progress.error_line = index;
+            progress.error_count += 1;
+            self.rate.record(&mut progress, Instant::now());
+            StreamLineError {
+                code,
+                line: index,
+                error_count: progress.error_count,
+            }
+        };
+        if let Some(on_line_error) = self.on_line_error.as_mut() {
+            on_line_error(&error);
+        }
+        // Program lines are numbered from 1, as the UI shows them
+        let location = match index {
+            Some(index) => format!("at line {}", index + 1),
+            None => "in the resume preamble".to_string(),
+        };
+        if self.continue_on_error {
+            log::warn!("Continuing past GRBL error {} {}", code, location);
+            self.complete_if_drained();
+        } else {
+            self.fail(format!("GRBL error {} {}", code, location));
+        }
+    }
+
+    /// Mark the job completed once every line has been sent and acknowledged.
+    fn complete_if_drained(&mut self) {
+        if self.exhausted && self.pending.is_none() && self.in_flight.is_empty() && !self.suspended
+        {
+            self.finish(StreamState::Completed);
+        }
+    }
+
+    /// Free the oldest in-flight line, returning its program line index
+    fn release_oldest(&mut self) -> Option<usize> {
+        let (len, index) = self.in_flight.pop_front()?;
+        self.buffered -= len;
+        index
+    }
+
+    /// Record GRBL's program end message
+    pub fn program_ended(&mut self) {
+        self.progress.lock().program_end = true;
+    }
+
+    /// Stop the job with a failure reason.
+    pub fn fail(&mut self, reason: String) {
+        self.finish(StreamState::Failed(reason));
+    }
+
+    /// Stop the job because the user cancelled it.
+    pub fn cancel(&mut self) {
+        self.finish(StreamState::Cancelled);
+    }
+
+    fn finish(&mut self, state: StreamState) {
+        let finished = {
+            let mut progress = self.progress.lock();
+            if !progress.is_running() {
+                return;
+            }
+            progress.state = state;
+            progress.clone()
+        };
+        if let Some(on_finish) = self.on_finish.take() {
+            on_finish(&finished);
+        }
+    }
+
+    /// Whether the job is still running
+    pub fn is_running(&self) -> bool {
+        self.progress.lock().is_running()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(lines: &[&str]) -> StreamJob {
+        StreamJob::from_lines(lines.iter().map(|l| l.to_string()).collect(), true)
+    }
+
+    #[test]
+    fn test_prepare_line_skips_blank_and_comments() {
+        assert_eq!(prepare_line("  G1 X10  ", true), Some("G1 X10".into()));
+        assert_eq!(prepare_line("   ", true), None);
+        assert_eq!(prepare_line("; header", false), None);
+        assert_eq!(prepare_line("(comment)", false), None);
+    }
+
+    #[test]
+    fn test_strip_inline_comments() {
+        assert_eq!(
+            prepare_line("G1 X10 ; trailing", true),
+            Some("G1 X10".into())
+        );
+        assert_eq!(
+            prepare_line("G1 (move) X10 (fast)", true),
+            Some("G1  X10".into())
+        );
+        assert_eq!(
+            prepare_line("G1 X10 (outer (inner) still comment) Y5", true),
+            Some("G1 X10  Y5".into())
+        );
+        assert_eq!(prepare_line("G0 X0 (unclosed", true), Some("G0 X0".into()));
+        assert_eq!(prepare_line("  (a) ; b", true), None);
+        // Without stripping, inline comments are sent as-is
+        assert_eq!(
+            prepare_line("G1 X10 ; trailing", false),
+            Some("G1 X10 ; trailing".into())
+        );
+    }
+
+    #[test]
+    fn test_stream_respects_rx_buffer() {
+        let long = format!("G1 X{}", "1".repeat(60)); // 65 bytes with newline
+        let mut job = job(&[&long, &long, "G0 X0"]);
+
+        assert!(job.next_line().is_some());
+        // Second long line would overflow the 128 byte buffer
+        assert!(job.next_line().is_none());
+
+        job.ack_ok();
+        assert!(job.next_line().is_some());
+        assert!(job.next_line().is_some());
+        assert!(job.next_line().is_none());
+
+        job.ack_ok();
+        assert!(job.is_running());
+        job.ack_ok();
+
+        let progress = job.progress().lock().clone();
+        assert_eq!(progress.state, StreamState::Completed);
+        assert_eq!(progress.total_lines, 3);
+        assert_eq!(progress.lines_completed, 3);
+    }
+
+    #[test]
+    fn test_crlf_counts_towards_rx_buffer() {
+        let line = format!("G1 X{}", "1".repeat(58)); // 64 bytes with CRLF
+        let mut job = job(&[&line, &line]).with_line_ending(LineEnding::CrLf);
+
+        assert_eq!(job.next_line(), Some(format!("{}\r\n", line)));
+        assert!(job.next_line().is_some(), "exactly fills the buffer");
+        assert_eq!(job.buffered, RX_BUFFER_SIZE);
+    }
+
+    #[test]
+    fn test_suspended_job_drains_without_completing() {
+        let mut job = job(&["G1 X1", "G1 X2"]);
+        assert!(job.next_line().is_some());
+        job.set_suspended(true);
+        assert!(job.next_line().is_none());
+        assert!(!job.is_suspended_and_drained());
+
+        job.ack_ok();
+        assert!(job.is_suspended_and_drained());
+        job.set_suspended(false);
+        assert_eq!(job.next_line(), Some("G1 X2\n".into()));
+
+        // Nothing is left, but a suspended job keeps running
+        assert!(job.next_line().is_none());
+        job.set_suspended(true);
+        job.ack_ok();
+        assert!(job.is_running());
+        job.set_suspended(false);
+        assert_eq!(job.progress().lock().state, StreamState::Completed);
+    }
+
+    #[test]
+    fn test_stream_stops_on_error() {
+        let mut job = job(&["G1 X1", "G1 X2", "G1 X3"]);
+        assert!(job.next_line().is_some());
+        job.ack_error(20);
+        assert!(!job.is_running());
+        assert!(job.next_line().is_none());
+        assert!(matches!(
+            job.progress().lock().state,
+            StreamState::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn test_error_reports_program_line() {
+        let finished = Arc::new(Mutex::new(None));
+        let slot = finished.clone();
+        let mut job = job(&["; header", "G1 X1", "", "G1 X2", "G1 X3"])
+            .on_finish(move |p| *slot.lock() = Some(p.clone()));
+
+        assert!(job.next_line().is_some());
+        assert!(job.next_line().is_some());
+        job.ack_ok();
+        job.ack_error(33);
+
+        let progress = finished.lock().take().unwrap();
+        assert_eq!(progress.error_line, Some(3));
+        // Numbered from 1, past the skipped comment and blank lines
+        assert_eq!(
+            progress.state,
+            StreamState::Failed("GRBL error 33 at line 4".into())
+        );
+        // Later acknowledgements don't report the job again
+        job.cancel();
+        assert!(finished.lock().is_none());
+    }
+
+    #[test]
+    fn test_continue_on_error_counts_errors() {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let seen = errors.clone();
+        let mut job = job(&["G1 X1", "G1 X2 (bad)", "G1 X3"])
+            .continue_on_error(true)
+            .on_line_error(move |e| seen.lock().push(e.clone()));
+
+        while job.next_line().is_some() {}
+        job.ack_ok();
+        job.ack_error(20);
+        assert!(job.is_running());
+        job.ack_error(33);
+
+        let progress = job.progress().lock().clone();
+        assert_eq!(progress.state, StreamState::Completed);
+        assert_eq!((progress.lines_completed, progress.error_count), (1, 2));
+        assert_eq!(progress.error_line, Some(2));
+        let errors = errors.lock();
+        assert_eq!(errors.len(), 2);
+        assert_eq!((errors[0].code, errors[0].line), (20, Some(1)));
+        assert_eq!(errors[1].error_count, 2);
+    }
+
+    #[test]
+    fn test_line_rate_is_smoothed() {
+        let mut rate = RateTracker {
+            smoothing: 0.5,
+            sample_start: None,
+        };
+        let mut progress = job(&["G1 X1"]).progress().lock().clone();
+        progress.total_lines = 100;
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        rate.record(&mut progress, at(0));
+        progress.lines_completed = 10;
+        rate.record(&mut progress, at(100));
+        assert_eq!(progress.line_rate, None, "sample still too short");
+
+        rate.record(&mut progress, at(1000));
+        assert_eq!(progress.line_rate, Some(10.0));
+        assert_eq!(progress.smoothed_line_rate, Some(10.0));
+        assert_eq!(progress.remaining_secs, Some(9.0));
+
+        // A burst moves the smoothed rate only halfway
+        progress.lines_completed = 40;
+        rate.record(&mut progress, at(2000));
+        assert_eq!(progress.line_rate, Some(30.0));
+        assert_eq!(progress.smoothed_line_rate, Some(20.0));
+        assert_eq!(progress.remaining_secs, Some(3.0));
+
+        // Stalled: rate drops but the estimate stays finite
+        rate.record(&mut progress, at(3000));
+        assert_eq!(progress.line_rate, Some(0.0));
+        assert_eq!(progress.remaining_secs, Some(6.0));
+    }
+
+    #[test]
+    fn test_resumed_job_keeps_program_indices() {
+        let lines = ["G1 X1", "G1 X2", "; note", "G1 X3"];
+        let mut job =
+            StreamJob::from_lines_at(lines.iter().map(|l| l.to_string()).collect(), 1, true)
+                .with_preamble(vec!["G0 X1".into()]);
+        assert_eq!(job.progress().lock().total_lines, 3);
+
+        assert_eq!(job.next_line().as_deref(), Some("G0 X1\n"));
+        job.ack_ok();
+        assert_eq!(job.progress().lock().last_acked_line, None);
+        assert_eq!(job.next_line().as_deref(), Some("G1 X2\n"));
+        job.ack_ok();
+        assert_eq!(job.progress().lock().last_acked_line, Some(1));
+        assert_eq!(job.next_line().as_deref(), Some("G1 X3\n"));
+        job.ack_ok();
+        assert!(job.next_line().is_none());
+
+        let progress = job.progress().lock().clone();
+        assert_eq!(progress.last_acked_line, Some(3));
+        assert_eq!(progress.state, StreamState::Completed);
+    }
+
+    #[test]
+    fn test_empty_program_completes() {
+        let mut job = job(&["", "; only comments"]);
+        assert!(job.next_line().is_none());
+        assert_eq!(job.progress().lock().state, StreamState::Completed);
+    }
+}
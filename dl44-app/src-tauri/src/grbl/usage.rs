@@ -0,0 +1,210 @@
+//! Laser usage counters for maintenance.
+//!
+//! While a program streams, the status reports are integrated into the
+//! time and distance the laser spent on. Each finished job is added to
+//! totals persisted as JSON, so diode or tube hours survive restarts.
+//! Like preferences, a file that can't be read or written is logged and
+//! otherwise ignored.
+//!
+//! The laser counts as on while the machine runs with a nonzero spindle
+//! speed in the report. In laser mode (`$32=1`) GRBL keeps the laser off
+//! during rapids, which a status report doesn't show, so the totals are
+//! an upper bound.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use super::status::{MachineState, MachineStatus, Position};
+
+/// File name of the usage counters within the app data directory
+pub const USAGE_FILE: &str = "usage.json";
+
+/// Reports further apart than this don't count as a continuous burn
+/// (e.g. polling stalled while the app was busy)
+const MAX_SAMPLE_GAP_SECS: f64 = 5.0;
+
+/// Accumulated laser usage over all jobs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// Seconds the laser was on
+    #[serde(default)]
+    pub total_on_seconds: f64,
+    /// Distance travelled with the laser on (mm)
+    #[serde(default)]
+    pub total_distance_mm: f64,
+    /// Jobs streamed to the end or stopped
+    #[serde(default)]
+    pub job_count: u64,
+}
+
+/// Laser usage of one job, integrated from status reports
+#[derive(Debug, Default)]
+pub struct JobUsage {
+    on_seconds: f64,
+    distance_mm: f64,
+    /// Time and machine position of the previous report
+    last_sample: Option<(Instant, Position)>,
+}
+
+impl JobUsage {
+    /// Add the interval since the previous report if the laser is on now
+    pub fn sample(&mut self, status: &MachineStatus, at: Instant) {
+        let laser_on = status.state == MachineState::Run
+            && status.spindle_speed.is_some_and(|speed| speed > 0.0);
+        if let Some((last_at, last_pos)) = self.last_sample {
+            let elapsed = at.saturating_duration_since(last_at).as_secs_f64();
+            if laser_on && elapsed <= MAX_SAMPLE_GAP_SECS {
+                let delta = status.machine_pos.minus(&last_pos);
+                self.on_seconds += elapsed;
+                self.distance_mm += delta.x.hypot(delta.y).hypot(delta.z);
+            }
+        }
+        self.last_sample = Some((at, status.machine_pos));
+    }
+}
+
+/// Persisted usage totals, shared by all devices
+#[derive(Debug)]
+pub struct UsageCounter {
+    path: Mutex<Option<PathBuf>>,
+    stats: Mutex<UsageStats>,
+}
+
+impl UsageCounter {
+    /// Create zeroed, in-memory counters
+    pub fn new() -> Self {
+        Self {
+            path: Mutex::new(None),
+            stats: Mutex::new(UsageStats::default()),
+        }
+    }
+
+    /// Load totals from `path` and persist future changes there.
+    ///
+    /// A missing or unreadable file starts from zero.
+    pub fn load(&self, path: &Path) {
+        match std::fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str(&json) {
+                Ok(stats) => *self.stats.lock() = stats,
+                Err(e) => log::warn!("Ignoring invalid usage stats {}: {}", path.display(), e),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => log::warn!("Failed to read usage stats {}: {}", path.display(), e),
+        }
+        *self.path.lock() = Some(path.to_path_buf());
+    }
+
+    /// Current totals
+    pub fn stats(&self) -> UsageStats {
+        self.stats.lock().clone()
+    }
+
+    /// Add a finished job to the totals
+    pub fn record(&self, job: &JobUsage) {
+        {
+            let mut stats = self.stats.lock();
+            stats.total_on_seconds += job.on_seconds;
+            stats.total_distance_mm += job.distance_mm;
+            stats.job_count += 1;
+        }
+        self.save();
+    }
+
+    /// Zero the totals (e.g. after replacing the diode)
+    pub fn reset(&self) {
+        *self.stats.lock() = UsageStats::default();
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = self.path.lock().clone() else {
+            return;
+        };
+        let json = match serde_json::to_string_pretty(&*self.stats.lock()) {
+            Ok(json) => json,
+            Err(e) => {
+                log::warn!("Failed to serialize usage stats: {}", e);
+                return;
+            }
+        };
+        if let Some(dir) = path.parent() {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                log::warn!("Failed to create {}: {}", dir.display(), e);
+                return;
+            }
+        }
+        if let Err(e) = std::fs::write(&path, json) {
+            log::warn!("Failed to write usage stats {}: {}", path.display(), e);
+        }
+    }
+}
+
+impl Default for UsageCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn status(state: MachineState, speed: f64, x: f64) -> MachineStatus {
+        MachineStatus {
+            state,
+            spindle_speed: Some(speed),
+            machine_pos: Position::new(x, 0.0, 0.0),
+            ..MachineStatus::default()
+        }
+    }
+
+    #[test]
+    fn test_job_usage_counts_laser_on_intervals() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut job = JobUsage::default();
+        job.sample(&status(MachineState::Run, 0.0, 0.0), at(0));
+        job.sample(&status(MachineState::Run, 500.0, 3.0), at(1));
+        job.sample(&status(MachineState::Hold, 500.0, 3.0), at(2));
+        job.sample(&status(MachineState::Run, 500.0, 7.0), at(3));
+        // Polling stalled; the gap isn't counted
+        job.sample(&status(MachineState::Run, 500.0, 20.0), at(30));
+        assert_eq!((job.on_seconds, job.distance_mm), (2.0, 7.0));
+    }
+
+    #[test]
+    fn test_usage_persists_and_resets() {
+        let dir = std::env::temp_dir().join(format!("dl44-usage-{}", std::process::id()));
+        let path = dir.join(USAGE_FILE);
+
+        let counter = UsageCounter::new();
+        counter.load(&path);
+        let job = JobUsage {
+            on_seconds: 90.0,
+            distance_mm: 1200.0,
+            last_sample: None,
+        };
+        counter.record(&job);
+        counter.record(&job);
+
+        let reloaded = UsageCounter::new();
+        reloaded.load(&path);
+        assert_eq!(
+            reloaded.stats(),
+            UsageStats {
+                total_on_seconds: 180.0,
+                total_distance_mm: 2400.0,
+                job_count: 2,
+            }
+        );
+
+        reloaded.reset();
+        counter.load(&path);
+        assert_eq!(counter.stats(), UsageStats::default());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -10,38 +10,80 @@
 //! - Worker thread processes requests and sends responses via oneshot channels
 //! - Worker handles retries, timeouts, and buffer management internally
 //! - Response channel timeout is dynamic based on command type
+//!
+//! Concurrency while a command is pending:
+//! - The worker is single-threaded, so a request queued behind a long
+//!   command (e.g. a 2 minute homing cycle) isn't seen until it finishes.
+//! - Two side channels bypass the request queue: a [`CancelToken`] to abort
+//!   the wait, and a [`RealtimeQueue`] of realtime bytes (soft reset, feed
+//!   hold, ...). Blocking wait loops poll both every iteration, so a soft
+//!   reset reaches the device within a few milliseconds even mid-homing.
+//! - After queueing, the handle also sends a `FlushRealtime` request to wake
+//!   an idle worker; if the bytes were already written it's a no-op.
 
 use std::io::{BufRead, BufReader, Write};
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
+use parking_lot::Mutex;
 use serialport::{DataBits, FlowControl, Parity, SerialPort, StopBits};
 use thiserror::Error;
 
-use super::protocol::{self, Response};
+use super::protocol::{self, LineEnding, Response};
+use super::serial::ConnectErrorKind;
+use super::serial_log::{Direction, SerialLog};
 use super::status::MachineStatus;
+use super::stream::{StreamJob, StreamProgress};
 
 /// Retry/timeout configuration
 pub const DEFAULT_RETRIES: u32 = 2;
 pub const DEFAULT_TIMEOUT_MS: u64 = 500;
 pub const STATUS_TIMEOUT_MS: u64 = 300;
 
+/// Timeout for the welcome banner after a soft reset
+const WELCOME_TIMEOUT_MS: u64 = 1000;
+
+/// How long [`test_port`] waits for a status report, long enough for a
+/// board that reset as the port opened to boot
+pub const TEST_PORT_TIMEOUT_MS: u64 = 2500;
+
+/// Interval between [`test_port`]'s status queries
+const TEST_PORT_RETRY_MS: u64 = 250;
+
+/// Timeout for multi-line queries ($G, $$, $I, ...)
+pub const QUERY_TIMEOUT_MS: u64 = 1000;
+
 /// Timeout for homing - can take 30+ seconds on large machines
 pub const HOMING_TIMEOUT_MS: u64 = 120_000; // 2 minutes
 
 /// Base response channel timeout (added to command timeout)
 const RESPONSE_CHANNEL_MARGIN_MS: u64 = 1000;
 
+/// How long the worker waits for a request before pumping an active stream
+const STREAM_POLL_MS: u64 = 1;
+
 /// Worker errors
 #[derive(Error, Debug, Clone)]
 pub enum WorkerError {
-    #[error("Failed to open port: {0}")]
-    OpenFailed(String),
+    #[error("Failed to open port: {1}")]
+    OpenFailed(ConnectErrorKind, String),
+
+    /// The port opened but the device sent no welcome and didn't answer a
+    /// status query
+    #[error("No response from device (check the baud rate)")]
+    NoResponse,
 
     #[error("I/O error: {0}")]
     Io(String),
 
+    /// The port went away (cable pulled, adapter reset); the worker has
+    /// dropped the connection
+    #[error("Serial device disconnected: {0}")]
+    Disconnected(String),
+
     #[error("Not connected")]
     NotConnected,
 
@@ -56,6 +98,23 @@ pub enum WorkerError {
 
     #[error("Worker thread not responding")]
     WorkerDead,
+
+    #[error("A job is already streaming")]
+    Busy,
+
+    #[error("Command cancelled")]
+    Cancelled,
+}
+
+impl WorkerError {
+    /// Why a connect attempt failed with this error
+    pub fn connect_error_kind(&self) -> ConnectErrorKind {
+        match self {
+            WorkerError::OpenFailed(kind, _) => kind.clone(),
+            WorkerError::NoResponse | WorkerError::Timeout { .. } => ConnectErrorKind::NoResponse,
+            other => ConnectErrorKind::Other(other.to_string()),
+        }
+    }
 }
 
 /// Response channel type
@@ -73,6 +132,9 @@ pub enum WorkerRequest {
     /// Disconnect from current port
     Disconnect { response_tx: ResponseTx<()> },
 
+    /// Soft reset the device and wait for the welcome message
+    Reset { response_tx: ResponseTx<String> },
+
     /// Send a command and wait for ok/error (with retries)
     SendCommand {
         command: String,
@@ -81,18 +143,50 @@ pub enum WorkerRequest {
         response_tx: ResponseTx<()>,
     },
 
+    /// Send a query command and collect all response lines up to ok/error
+    SendQuery {
+        command: String,
+        timeout_ms: u64,
+        response_tx: ResponseTx<Vec<Response>>,
+    },
+
+    /// Start streaming a G-code program (returns once the job is accepted)
+    StartStream {
+        job: StreamJob,
+        response_tx: ResponseTx<()>,
+    },
+
+    /// Hold or resume sending the active stream's lines; answers whether
+    /// a stream is now suspended with none of its lines in flight
+    SuspendStream {
+        suspended: bool,
+        response_tx: ResponseTx<bool>,
+    },
+
     /// Send a real-time command (single byte, no response expected)
     SendRealtime {
         byte: u8,
         response_tx: ResponseTx<()>,
     },
 
+    /// Write any queued priority realtime bytes (no response)
+    FlushRealtime,
+
     /// Query status and wait for status report
     QueryStatus {
         timeout_ms: u64,
         response_tx: ResponseTx<StatusQueryResult>,
     },
 
+    /// Set the line ending for commands, now and on later connections
+    SetLineEnding {
+        line_ending: LineEnding,
+        response_tx: ResponseTx<()>,
+    },
+
+    /// Answer immediately, without touching the serial port
+    Ping { response_tx: ResponseTx<()> },
+
     /// Shutdown the worker thread
     Shutdown,
 }
@@ -107,21 +201,99 @@ pub struct StatusQueryResult {
     pub is_fresh: bool,
 }
 
+/// Cancellation token for the request the worker is currently executing.
+///
+/// Each cancellable request gets a unique ID while it runs. Cancelling
+/// targets whichever ID is active at that moment, so a cancel issued while
+/// the worker is idle can't leak into the next request.
+#[derive(Debug, Default)]
+struct CancelToken {
+    next_id: AtomicU64,
+    active: AtomicU64,
+    cancelled: AtomicU64,
+}
+
+impl CancelToken {
+    /// Mark a new cancellable request as active.
+    fn begin(&self) {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        self.active.store(id, Ordering::SeqCst);
+    }
+
+    /// Mark the active request as finished.
+    fn end(&self) {
+        self.active.store(0, Ordering::SeqCst);
+    }
+
+    /// Cancel the active request. Returns false if nothing was running.
+    fn cancel(&self) -> bool {
+        let id = self.active.load(Ordering::SeqCst);
+        if id == 0 {
+            return false;
+        }
+        self.cancelled.store(id, Ordering::SeqCst);
+        true
+    }
+
+    /// Whether the active request has been cancelled.
+    fn is_cancelled(&self) -> bool {
+        let id = self.active.load(Ordering::SeqCst);
+        id != 0 && self.cancelled.load(Ordering::SeqCst) == id
+    }
+}
+
+/// Realtime bytes queued for delivery outside the request channel.
+///
+/// Lets a soft reset reach the device while the worker is blocked waiting
+/// on a command (the request channel is stuck behind that command).
+#[derive(Debug, Default)]
+struct RealtimeQueue {
+    bytes: Mutex<Vec<u8>>,
+}
+
+impl RealtimeQueue {
+    fn push(&self, byte: u8) {
+        self.bytes.lock().push(byte);
+    }
+
+    /// Take all queued bytes (oldest first).
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.bytes.lock())
+    }
+}
+
 /// Handle to communicate with the serial worker
 pub struct WorkerHandle {
     request_tx: Sender<WorkerRequest>,
     thread_handle: Option<JoinHandle<()>>,
+    /// Shared with the worker; set from other threads to abort a blocking command
+    cancel: Arc<CancelToken>,
+    /// Shared with the worker; realtime bytes that skip the request queue
+    realtime: Arc<RealtimeQueue>,
+    /// Shared with the worker; persistent log of serial traffic
+    serial_log: Arc<SerialLog>,
 }
 
 impl WorkerHandle {
     /// Spawn a new serial worker thread
     pub fn spawn() -> Self {
         let (request_tx, request_rx) = mpsc::channel();
+        let cancel = Arc::new(CancelToken::default());
+        let worker_cancel = cancel.clone();
+        let realtime = Arc::new(RealtimeQueue::default());
+        let worker_realtime = realtime.clone();
+        let serial_log = Arc::new(SerialLog::new());
+        let worker_serial_log = serial_log.clone();
 
         let thread_handle = thread::Builder::new()
             .name("grbl-serial-worker".into())
             .spawn(move || {
-                let mut worker = SerialWorker::new(request_rx);
+                let mut worker = SerialWorker::new(
+                    request_rx,
+                    worker_cancel,
+                    worker_realtime,
+                    worker_serial_log,
+                );
                 worker.run();
             })
             .expect("Failed to spawn serial worker thread");
@@ -129,9 +301,17 @@ impl WorkerHandle {
         Self {
             request_tx,
             thread_handle: Some(thread_handle),
+            cancel,
+            realtime,
+            serial_log,
         }
     }
 
+    /// Persistent log of serial traffic
+    pub fn serial_log(&self) -> Arc<SerialLog> {
+        self.serial_log.clone()
+    }
+
     /// Send a request to the worker and wait for response.
     ///
     /// The response timeout is dynamic based on the expected command duration.
@@ -183,6 +363,13 @@ impl WorkerHandle {
         })
     }
 
+    /// Soft reset the device and wait for the welcome message
+    pub fn reset(&self) -> Result<String, WorkerError> {
+        self.send_request_with_timeout(WELCOME_TIMEOUT_MS, |response_tx| WorkerRequest::Reset {
+            response_tx,
+        })
+    }
+
     /// Send a command with default retry/timeout policy
     pub fn send_command(&self, command: &str) -> Result<(), WorkerError> {
         self.send_command_with_policy(command, DEFAULT_RETRIES, DEFAULT_TIMEOUT_MS)
@@ -207,6 +394,41 @@ impl WorkerHandle {
         })
     }
 
+    /// Send a query command (e.g. `$G`, `$$`) and collect its response lines
+    pub fn send_query(&self, command: &str, timeout_ms: u64) -> Result<Vec<Response>, WorkerError> {
+        self.send_request_with_timeout(timeout_ms, |response_tx| WorkerRequest::SendQuery {
+            command: command.to_string(),
+            timeout_ms,
+            response_tx,
+        })
+    }
+
+    /// Start streaming a G-code program.
+    ///
+    /// Returns the shared progress handle once the worker has accepted the job;
+    /// lines are sent in the background while other requests are serviced.
+    pub fn start_stream(&self, job: StreamJob) -> Result<Arc<Mutex<StreamProgress>>, WorkerError> {
+        let progress = job.progress();
+        self.send_request_with_timeout(500, |response_tx| WorkerRequest::StartStream {
+            job,
+            response_tx,
+        })?;
+        Ok(progress)
+    }
+
+    /// Hold or resume sending the active stream's lines.
+    ///
+    /// Once a suspended stream's sent lines are all acknowledged, commands
+    /// and queries are accepted between its lines. Returns whether a
+    /// stream is now suspended with all its sent lines acknowledged, i.e.
+    /// none are waiting in the device's RX buffer.
+    pub fn suspend_stream(&self, suspended: bool) -> Result<bool, WorkerError> {
+        self.send_request_with_timeout(0, |response_tx| WorkerRequest::SuspendStream {
+            suspended,
+            response_tx,
+        })
+    }
+
     /// Send a real-time command
     pub fn send_realtime(&self, byte: u8) -> Result<(), WorkerError> {
         self.send_request_with_timeout(500, |response_tx| WorkerRequest::SendRealtime {
@@ -231,6 +453,51 @@ impl WorkerHandle {
         })
     }
 
+    /// Abort the command or query the worker is currently waiting on.
+    ///
+    /// Bypasses the request channel (which is blocked behind the pending
+    /// command); the command returns `WorkerError::Cancelled` within one
+    /// poll interval. Returns false if no cancellable request was running.
+    pub fn cancel_pending(&self) -> bool {
+        self.cancel.cancel()
+    }
+
+    /// Send a real-time byte ahead of any pending command.
+    ///
+    /// The byte skips the request channel and is written by the worker's
+    /// wait loop within one poll interval, even mid-homing. A soft reset
+    /// also cancels the pending command. Returns once the byte is queued.
+    pub fn send_realtime_priority(&self, byte: u8) -> Result<(), WorkerError> {
+        self.realtime.push(byte);
+        if byte == protocol::realtime::SOFT_RESET {
+            self.cancel.cancel();
+        }
+        // Wake the worker in case it's idle and not polling the queue
+        self.request_tx
+            .send(WorkerRequest::FlushRealtime)
+            .map_err(|_| WorkerError::WorkerDead)
+    }
+
+    /// Set the line ending appended to commands and streamed lines.
+    ///
+    /// Applies to the open connection (a running stream keeps its own)
+    /// and to later connections.
+    pub fn set_line_ending(&self, line_ending: LineEnding) -> Result<(), WorkerError> {
+        self.send_request_with_timeout(0, |response_tx| WorkerRequest::SetLineEnding {
+            line_ending,
+            response_tx,
+        })
+    }
+
+    /// Check that the worker thread is alive and taking requests.
+    ///
+    /// Never touches the serial port, so a failure (`WorkerDead`) means the
+    /// thread is gone or stuck, not that the device stopped answering. A
+    /// worker busy with a long command (e.g. homing) can't answer either.
+    pub fn ping(&self) -> Result<(), WorkerError> {
+        self.send_request_with_timeout(0, |response_tx| WorkerRequest::Ping { response_tx })
+    }
+
     /// Shutdown the worker (called on drop)
     pub fn shutdown(&self) {
         let _ = self.request_tx.send(WorkerRequest::Shutdown);
@@ -246,20 +513,100 @@ impl Drop for WorkerHandle {
     }
 }
 
+/// Whether an I/O error means the device is gone rather than a transient failure
+fn is_disconnect(e: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    if matches!(
+        e.kind(),
+        ErrorKind::BrokenPipe | ErrorKind::NotConnected | ErrorKind::UnexpectedEof
+    ) {
+        return true;
+    }
+    // EIO (Linux, unplugged USB serial), ENXIO ("Device not configured", macOS), ENODEV
+    #[cfg(unix)]
+    let gone: &[i32] = &[5, 6, 19];
+    // ERROR_GEN_FAILURE, ERROR_DEVICE_NOT_CONNECTED
+    #[cfg(windows)]
+    let gone: &[i32] = &[31, 1167];
+    #[cfg(not(any(unix, windows)))]
+    let gone: &[i32] = &[];
+    e.raw_os_error().is_some_and(|code| gone.contains(&code))
+}
+
+fn io_error(e: std::io::Error) -> WorkerError {
+    if is_disconnect(&e) {
+        WorkerError::Disconnected(e.to_string())
+    } else {
+        WorkerError::Io(e.to_string())
+    }
+}
+
+fn serial_error(e: serialport::Error) -> WorkerError {
+    match e.kind() {
+        serialport::ErrorKind::NoDevice => WorkerError::Disconnected(e.to_string()),
+        _ => WorkerError::Io(e.to_string()),
+    }
+}
+
+/// Read the next complete line from `reader`.
+///
+/// A read that times out mid-line leaves the text received so far in
+/// `partial`, and the next call continues it, so a line split across reads
+/// isn't parsed as two fragments. A complete `<...>` status frame is
+/// returned even before its newline arrives. Returns `Ok(None)` while no
+/// complete line is available.
+fn read_complete_line(
+    reader: &mut impl BufRead,
+    partial: &mut String,
+) -> std::io::Result<Option<String>> {
+    let result = reader.read_line(partial);
+    let text = partial.trim();
+    if partial.ends_with('\n') || (text.starts_with('<') && text.ends_with('>')) {
+        let line = text.to_string();
+        partial.clear();
+        return Ok(Some(line));
+    }
+    match result {
+        Ok(_) => Ok(None),
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => Ok(None),
+        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => Ok(None),
+        Err(e) => {
+            partial.clear();
+            Err(e)
+        }
+    }
+}
+
 /// Internal worker state
 struct SerialWorker {
     request_rx: Receiver<WorkerRequest>,
     connection: Option<SerialConnection>,
+    /// Program currently being streamed, if any
+    job: Option<StreamJob>,
+    /// Line ending for the next connection and stream
+    line_ending: LineEnding,
+    cancel: Arc<CancelToken>,
+    realtime: Arc<RealtimeQueue>,
+    serial_log: Arc<SerialLog>,
 }
 
 /// Internal serial connection wrapper
 struct SerialConnection {
     port: Box<dyn SerialPort>,
     reader: BufReader<Box<dyn SerialPort>>,
+    /// Start of a line whose remainder hasn't arrived yet
+    partial: String,
+    line_ending: LineEnding,
+    log: Arc<SerialLog>,
 }
 
 impl SerialConnection {
-    fn open(path: &str, baud_rate: u32) -> Result<Self, WorkerError> {
+    fn open(
+        path: &str,
+        baud_rate: u32,
+        line_ending: LineEnding,
+        log: Arc<SerialLog>,
+    ) -> Result<Self, WorkerError> {
         let port = serialport::new(path, baud_rate)
             .data_bits(DataBits::Eight)
             .parity(Parity::None)
@@ -267,44 +614,51 @@ impl SerialConnection {
             .flow_control(FlowControl::None)
             .timeout(Duration::from_millis(50)) // Short timeout for non-blocking reads
             .open()
-            .map_err(|e| WorkerError::OpenFailed(e.to_string()))?;
+            .map_err(|e| {
+                WorkerError::OpenFailed(ConnectErrorKind::from_open_error(&e), e.to_string())
+            })?;
 
         let reader_port = port
             .try_clone()
             .map_err(|e| WorkerError::Io(e.to_string()))?;
         let reader = BufReader::new(reader_port);
 
-        Ok(Self { port, reader })
+        Ok(Self {
+            port,
+            reader,
+            partial: String::new(),
+            line_ending,
+            log,
+        })
     }
 
     fn write_bytes(&mut self, data: &[u8]) -> Result<(), WorkerError> {
-        self.port
-            .write_all(data)
-            .map_err(|e| WorkerError::Io(e.to_string()))?;
-        self.port
-            .flush()
-            .map_err(|e| WorkerError::Io(e.to_string()))?;
+        self.port.write_all(data).map_err(io_error)?;
+        self.port.flush().map_err(io_error)?;
+        match data {
+            [protocol::realtime::STATUS_QUERY] => {}
+            // Single bytes are realtime commands, most of them unprintable
+            [byte] => self.log.record(Direction::Sent, &format!("0x{:02X}", byte)),
+            _ => self
+                .log
+                .record(Direction::Sent, &String::from_utf8_lossy(data)),
+        }
         Ok(())
     }
 
     fn send_command(&mut self, cmd: &str) -> Result<(), WorkerError> {
-        let cmd = if cmd.ends_with('\n') {
-            cmd.to_string()
-        } else {
-            format!("{}\n", cmd)
-        };
+        let cmd = self.line_ending.terminate(cmd);
         self.write_bytes(cmd.as_bytes())
     }
 
     fn read_line(&mut self) -> Result<Option<String>, WorkerError> {
-        let mut line = String::new();
-        match self.reader.read_line(&mut line) {
-            Ok(0) => Ok(None),
-            Ok(_) => Ok(Some(line.trim().to_string())),
-            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => Ok(None),
-            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => Ok(None),
-            Err(e) => Err(WorkerError::Io(e.to_string())),
+        let line = read_complete_line(&mut self.reader, &mut self.partial).map_err(io_error)?;
+        if let Some(line) = &line {
+            if !line.starts_with('<') {
+                self.log.record(Direction::Received, line);
+            }
         }
+        Ok(line)
     }
 
     /// Drain all pending input from the serial buffer.
@@ -324,18 +678,71 @@ impl SerialConnection {
         responses
     }
 
+    /// Clear buffers, send a soft reset, and wait for the welcome banner.
+    ///
+    /// Returns the welcome message, or an empty string if none arrived in time.
+    fn reset_and_wait_for_welcome(&mut self) -> Result<String, WorkerError> {
+        let _ = self.clear_buffers();
+        self.write_bytes(&[protocol::realtime::SOFT_RESET])?;
+
+        let start = Instant::now();
+        let timeout = Duration::from_millis(WELCOME_TIMEOUT_MS);
+
+        while start.elapsed() < timeout {
+            if let Ok(Some(line)) = self.read_line() {
+                let response = protocol::parse_response(&line);
+                if let Response::Welcome(msg) = response {
+                    return Ok(msg);
+                }
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        Ok(String::new())
+    }
+
+    /// Send a status query and wait for any reply.
+    fn responds_to_status_query(&mut self) -> Result<bool, WorkerError> {
+        self.write_bytes(&[protocol::realtime::STATUS_QUERY])?;
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(WELCOME_TIMEOUT_MS) {
+            if let Some(line) = self.read_line()? {
+                if !line.is_empty() {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Whether there is unread input (buffered or pending on the port).
+    fn has_input(&mut self) -> bool {
+        !self.reader.buffer().is_empty() || self.port.bytes_to_read().unwrap_or(0) > 0
+    }
+
     fn clear_buffers(&mut self) -> Result<(), WorkerError> {
+        self.partial.clear();
         self.port
             .clear(serialport::ClearBuffer::All)
-            .map_err(|e| WorkerError::Io(e.to_string()))
+            .map_err(serial_error)
     }
 }
 
 impl SerialWorker {
-    fn new(request_rx: Receiver<WorkerRequest>) -> Self {
+    fn new(
+        request_rx: Receiver<WorkerRequest>,
+        cancel: Arc<CancelToken>,
+        realtime: Arc<RealtimeQueue>,
+        serial_log: Arc<SerialLog>,
+    ) -> Self {
         Self {
             request_rx,
             connection: None,
+            job: None,
+            line_ending: LineEnding::Lf,
+            cancel,
+            realtime,
+            serial_log,
         }
     }
 
@@ -343,20 +750,34 @@ impl SerialWorker {
         log::info!("Serial worker started");
 
         loop {
-            match self.request_rx.recv() {
+            // While streaming, poll for requests so the stream keeps moving
+            let request = if self.job.is_some() {
+                self.request_rx
+                    .recv_timeout(Duration::from_millis(STREAM_POLL_MS))
+            } else {
+                self.request_rx
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected)
+            };
+
+            match request {
                 Ok(WorkerRequest::Shutdown) => {
                     log::info!("Serial worker shutting down");
                     break;
                 }
                 Ok(request) => self.handle_request(request),
-                Err(_) => {
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
                     log::warn!("Request channel closed, worker exiting");
                     break;
                 }
             }
+
+            self.pump_stream();
         }
 
         // Clean up connection on exit
+        self.cancel_stream();
         self.connection = None;
         log::info!("Serial worker stopped");
     }
@@ -377,59 +798,128 @@ impl SerialWorker {
                 let _ = response_tx.send(result);
             }
 
+            WorkerRequest::Reset { response_tx } => {
+                let result = self
+                    .connection
+                    .as_mut()
+                    .ok_or(WorkerError::NotConnected)
+                    .and_then(|conn| conn.reset_and_wait_for_welcome());
+                let result = self.drop_if_disconnected(result);
+                let _ = response_tx.send(result);
+            }
+
             WorkerRequest::SendCommand {
                 command,
                 retries,
                 timeout_ms,
                 response_tx,
             } => {
+                self.cancel.begin();
                 let result = self.handle_send_command(&command, retries, timeout_ms);
+                self.cancel.end();
+                let result = self.drop_if_disconnected(result);
                 let _ = response_tx.send(result);
             }
 
+            WorkerRequest::SendQuery {
+                command,
+                timeout_ms,
+                response_tx,
+            } => {
+                self.cancel.begin();
+                let result = self.handle_send_query(&command, timeout_ms);
+                self.cancel.end();
+                let result = self.drop_if_disconnected(result);
+                let _ = response_tx.send(result);
+            }
+
+            WorkerRequest::StartStream { job, response_tx } => {
+                let result = self.handle_start_stream(job);
+                let _ = response_tx.send(result);
+            }
+
+            WorkerRequest::SuspendStream {
+                suspended,
+                response_tx,
+            } => {
+                if let Some(job) = self.job.as_mut() {
+                    job.set_suspended(suspended);
+                }
+                let drained = self
+                    .job
+                    .as_ref()
+                    .is_some_and(|job| job.is_suspended_and_drained());
+                let _ = response_tx.send(Ok(drained));
+            }
+
             WorkerRequest::SendRealtime { byte, response_tx } => {
                 let result = self.handle_send_realtime(byte);
+                let result = self.drop_if_disconnected(result);
                 let _ = response_tx.send(result);
             }
 
+            WorkerRequest::FlushRealtime => {
+                for byte in self.realtime.take() {
+                    let result = self.handle_send_realtime(byte);
+                    if let Err(e) = self.drop_if_disconnected(result) {
+                        log::warn!("Dropped priority realtime 0x{:02X}: {}", byte, e);
+                    }
+                }
+            }
+
             WorkerRequest::QueryStatus {
                 timeout_ms,
                 response_tx,
             } => {
                 let result = self.handle_query_status(timeout_ms);
+                let result = self.drop_if_disconnected(result);
                 let _ = response_tx.send(result);
             }
 
+            WorkerRequest::SetLineEnding {
+                line_ending,
+                response_tx,
+            } => {
+                self.line_ending = line_ending;
+                if let Some(conn) = self.connection.as_mut() {
+                    conn.line_ending = line_ending;
+                }
+                let _ = response_tx.send(Ok(()));
+            }
+
+            WorkerRequest::Ping { response_tx } => {
+                let _ = response_tx.send(Ok(()));
+            }
+
             WorkerRequest::Shutdown => unreachable!(),
         }
     }
 
+    /// Drop the connection (and any stream) if `result` shows the device is gone
+    fn drop_if_disconnected<T>(
+        &mut self,
+        result: Result<T, WorkerError>,
+    ) -> Result<T, WorkerError> {
+        if let Err(WorkerError::Disconnected(msg)) = &result {
+            log::warn!("Serial device lost: {}", msg);
+            self.cancel_stream();
+            self.connection = None;
+        }
+        result
+    }
+
     fn handle_connect(&mut self, port: &str, baud_rate: u32) -> Result<String, WorkerError> {
         // Disconnect if already connected
         self.connection = None;
 
         log::info!("Connecting to {} at {} baud", port, baud_rate);
 
-        let mut conn = SerialConnection::open(port, baud_rate)?;
-
-        // Clear buffers and send soft reset
-        let _ = conn.clear_buffers();
-        conn.write_bytes(&[protocol::realtime::SOFT_RESET])?;
-
-        // Wait for and collect welcome message
-        let start = Instant::now();
-        let timeout = Duration::from_millis(1000);
-        let mut welcome_message = String::new();
+        let mut conn =
+            SerialConnection::open(port, baud_rate, self.line_ending, self.serial_log.clone())?;
 
-        while start.elapsed() < timeout {
-            if let Ok(Some(line)) = conn.read_line() {
-                let response = protocol::parse_response(&line);
-                if let Response::Welcome(msg) = response {
-                    welcome_message = msg;
-                    break;
-                }
-            }
-            thread::sleep(Duration::from_millis(10));
+        let welcome_message = conn.reset_and_wait_for_welcome()?;
+        if welcome_message.is_empty() && !conn.responds_to_status_query()? {
+            return Err(WorkerError::NoResponse);
         }
 
         self.connection = Some(conn);
@@ -443,6 +933,7 @@ impl SerialWorker {
             return Err(WorkerError::NotConnected);
         }
 
+        self.cancel_stream();
         self.connection = None;
         log::info!("Disconnected");
         Ok(())
@@ -454,6 +945,9 @@ impl SerialWorker {
         max_retries: u32,
         timeout_ms: u64,
     ) -> Result<(), WorkerError> {
+        if !self.accepts_commands() {
+            return Err(WorkerError::Busy);
+        }
         let conn = self.connection.as_mut().ok_or(WorkerError::NotConnected)?;
 
         let timeout = Duration::from_millis(timeout_ms);
@@ -482,6 +976,12 @@ impl SerialWorker {
             // Wait for ok/error response
             let start = Instant::now();
             while start.elapsed() < timeout {
+                // Priority bytes first, so a soft reset lands before we bail out
+                write_queued_realtime(conn, &self.realtime, &mut self.job)?;
+                if self.cancel.is_cancelled() {
+                    log::info!("Command cancelled: {}", command.trim());
+                    return Err(WorkerError::Cancelled);
+                }
                 if let Ok(Some(line)) = conn.read_line() {
                     let response = protocol::parse_response(&line);
                     match response {
@@ -520,13 +1020,150 @@ impl SerialWorker {
         }
     }
 
+    fn handle_send_query(
+        &mut self,
+        command: &str,
+        timeout_ms: u64,
+    ) -> Result<Vec<Response>, WorkerError> {
+        if !self.accepts_commands() {
+            return Err(WorkerError::Busy);
+        }
+        let conn = self.connection.as_mut().ok_or(WorkerError::NotConnected)?;
+
+        // Drain stale responses so they aren't mistaken for query output
+        conn.drain_input();
+
+        log::debug!("Sending query: {}", command.trim());
+        conn.send_command(command)?;
+
+        let start = Instant::now();
+        let timeout = Duration::from_millis(timeout_ms);
+        let mut lines = Vec::new();
+
+        while start.elapsed() < timeout {
+            write_queued_realtime(conn, &self.realtime, &mut self.job)?;
+            if self.cancel.is_cancelled() {
+                log::info!("Query cancelled: {}", command.trim());
+                return Err(WorkerError::Cancelled);
+            }
+            if let Ok(Some(line)) = conn.read_line() {
+                if line.is_empty() {
+                    continue;
+                }
+                match protocol::parse_response(&line) {
+                    Response::Ok => return Ok(lines),
+                    Response::Error(code) => {
+                        log::warn!("GRBL error {} for query {}", code, command.trim());
+                        return Err(WorkerError::GrblError(code));
+                    }
+                    Response::Alarm(code) => {
+                        log::warn!("GRBL alarm {}", code);
+                        return Err(WorkerError::Alarm(code));
+                    }
+                    // Status reports may be interleaved; they aren't part of the query output
+                    Response::Status(_) => {}
+                    response => lines.push(response),
+                }
+            } else {
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+
+        log::warn!("Query timeout: {}", command.trim());
+        Err(WorkerError::Timeout { attempts: 1 })
+    }
+
+    /// Whether a command can go out now: no stream, or one suspended with
+    /// nothing left in the device's RX buffer
+    fn accepts_commands(&self) -> bool {
+        match &self.job {
+            Some(job) => job.is_suspended_and_drained(),
+            None => true,
+        }
+    }
+
     fn handle_send_realtime(&mut self, byte: u8) -> Result<(), WorkerError> {
         let conn = self.connection.as_mut().ok_or(WorkerError::NotConnected)?;
         conn.write_bytes(&[byte])?;
         log::debug!("Sent realtime command: 0x{:02X}", byte);
+
+        // A soft reset flushes GRBL's buffers, so any stream is over
+        if byte == protocol::realtime::SOFT_RESET {
+            self.cancel_stream();
+        }
         Ok(())
     }
 
+    fn handle_start_stream(&mut self, job: StreamJob) -> Result<(), WorkerError> {
+        if self.job.is_some() {
+            return Err(WorkerError::Busy);
+        }
+        let conn = self.connection.as_mut().ok_or(WorkerError::NotConnected)?;
+
+        let stale = conn.drain_input();
+        if !stale.is_empty() {
+            log::debug!("Drained {} stale response(s) before streaming", stale.len());
+        }
+
+        log::info!("Starting G-code stream");
+        self.job = Some(job.with_line_ending(self.line_ending));
+        Ok(())
+    }
+
+    /// Cancel the active stream, if any.
+    fn cancel_stream(&mut self) {
+        cancel_job(&mut self.job);
+    }
+
+    /// Advance the active stream: consume responses, then send lines that fit.
+    fn pump_stream(&mut self) {
+        let Some(job) = self.job.as_mut() else {
+            return;
+        };
+        let Some(conn) = self.connection.as_mut() else {
+            job.fail("Not connected".into());
+            self.job = None;
+            return;
+        };
+
+        let mut lost = false;
+
+        // Consume whatever the device has sent without blocking
+        while conn.has_input() {
+            match conn.read_line() {
+                Ok(Some(line)) if !line.is_empty() => {
+                    route_stream_response(job, &protocol::parse_response(&line));
+                }
+                Ok(_) => break,
+                Err(e) => {
+                    lost = matches!(e, WorkerError::Disconnected(_));
+                    job.fail(e.to_string());
+                    break;
+                }
+            }
+        }
+
+        // Fill the RX buffer
+        while let Some(line) = job.next_line() {
+            log::trace!("Streaming: {}", line.trim());
+            if let Err(e) = conn.write_bytes(line.as_bytes()) {
+                lost = matches!(e, WorkerError::Disconnected(_));
+                job.fail(e.to_string());
+                break;
+            }
+        }
+
+        if lost {
+            log::warn!("Serial device lost while streaming");
+            self.connection = None;
+        }
+
+        if !job.is_running() {
+            log::info!("G-code stream finished: {:?}", job.progress().lock().state);
+            self.job = None;
+        }
+    }
+
     fn handle_query_status(&mut self, timeout_ms: u64) -> Result<StatusQueryResult, WorkerError> {
         let conn = self.connection.as_mut().ok_or(WorkerError::NotConnected)?;
 
@@ -547,6 +1184,12 @@ impl SerialWorker {
         while start.elapsed() < timeout {
             if let Ok(Some(line)) = conn.read_line() {
                 let response = protocol::parse_response(&line);
+                // Acknowledgements belong to the active stream, not this query
+                if let Some(job) = self.job.as_mut() {
+                    if route_stream_response(job, &response) {
+                        continue;
+                    }
+                }
                 match response {
                     Response::Status(report) => {
                         if let Some(status) = MachineStatus::parse(&report) {
@@ -579,9 +1222,317 @@ impl SerialWorker {
     }
 }
 
+/// Apply an `ok`/`error`/alarm response to a streaming job.
+///
+/// Returns true if the response was an acknowledgement consumed by the job.
+fn route_stream_response(job: &mut StreamJob, response: &Response) -> bool {
+    match response {
+        Response::Ok => {
+            job.ack_ok();
+            true
+        }
+        Response::Error(code) => {
+            log::warn!("GRBL error {} while streaming", code);
+            job.ack_error(*code);
+            true
+        }
+        Response::Alarm(code) => {
+            log::warn!("GRBL alarm {} while streaming", code);
+            job.fail(format!("ALARM:{}", code));
+            false
+        }
+        Response::Message(message) if message == protocol::PROGRAM_END_MESSAGE => {
+            job.program_ended();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Outcome of [`test_port`]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TestResult {
+    /// A GRBL status report came back
+    pub responded: bool,
+    /// Welcome banner, if the device sent one (e.g. rebooting as the port
+    /// opened)
+    pub welcome: Option<String>,
+    /// Time from the answered status query to its report (0 without one)
+    pub latency_ms: u64,
+}
+
+/// Open a port, ask for a status report, and close it again.
+///
+/// Only `?` is sent, so the machine's state is left alone, though boards
+/// that reset when the port opens (most Arduino-based ones) will reboot.
+/// The query is repeated while the device boots, for up to
+/// [`TEST_PORT_TIMEOUT_MS`]. Runs on the calling thread, independent of
+/// any worker.
+pub fn test_port(path: &str, baud_rate: u32) -> Result<TestResult, WorkerError> {
+    let mut conn = SerialConnection::open(
+        path,
+        baud_rate,
+        LineEnding::default(),
+        Arc::new(SerialLog::new()),
+    )?;
+    let mut result = TestResult {
+        responded: false,
+        welcome: None,
+        latency_ms: 0,
+    };
+
+    let start = Instant::now();
+    let mut query_sent: Option<Instant> = None;
+    while start.elapsed() < Duration::from_millis(TEST_PORT_TIMEOUT_MS) {
+        let query_due = match query_sent {
+            Some(at) => at.elapsed() >= Duration::from_millis(TEST_PORT_RETRY_MS),
+            None => true,
+        };
+        if query_due {
+            conn.write_bytes(&[protocol::realtime::STATUS_QUERY])?;
+            query_sent = Some(Instant::now());
+        }
+        let response = conn
+            .read_line()?
+            .map(|line| protocol::parse_response(&line));
+        match response {
+            Some(Response::Welcome(msg)) => result.welcome = Some(msg),
+            Some(Response::Status(report)) if MachineStatus::parse(&report).is_some() => {
+                result.responded = true;
+                result.latency_ms = query_sent.map_or(0, |at| at.elapsed().as_millis() as u64);
+                break;
+            }
+            _ => {}
+        }
+    }
+    Ok(result)
+}
+
+/// Cancel a stream, if any.
+fn cancel_job(job: &mut Option<StreamJob>) {
+    if let Some(mut job) = job.take() {
+        log::info!("G-code stream cancelled");
+        job.cancel();
+    }
+}
+
+/// Write priority realtime bytes from inside a blocking wait loop.
+///
+/// As in [`SerialWorker::handle_send_realtime`], a soft reset ends `job`:
+/// a stream set aside while a command runs (jogging during a hold) must
+/// not resume into the reset device.
+fn write_queued_realtime(
+    conn: &mut SerialConnection,
+    queue: &RealtimeQueue,
+    job: &mut Option<StreamJob>,
+) -> Result<(), WorkerError> {
+    for byte in queue.take() {
+        conn.write_bytes(&[byte])?;
+        log::debug!("Sent priority realtime command: 0x{:02X}", byte);
+        if byte == protocol::realtime::SOFT_RESET {
+            cancel_job(job);
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
-mod tests {
+pub(super) mod tests {
     use super::*;
+    use std::collections::VecDeque;
+
+    /// Fake GRBL on a pseudo-terminal: answers a soft reset with the
+    /// welcome, `$G` with a parser state in `M4`, and each line with `ok`,
+    /// recording lines and realtime bytes (`~` and `!` included).
+    /// Returns the port path, the slave end (keep it open), and the record.
+    #[cfg(unix)]
+    pub(crate) fn fake_device() -> (String, serialport::TTYPort, Arc<Mutex<Vec<String>>>) {
+        fake_device_reporting(Arc::new(Mutex::new(
+            "<Idle|MPos:0.000,0.000,0.000|FS:0,0>".into(),
+        )))
+    }
+
+    /// [`fake_device`] answering `?` with whatever `report` holds, so a
+    /// test can walk the machine through states.
+    #[cfg(unix)]
+    pub(crate) fn fake_device_reporting(
+        report: Arc<Mutex<String>>,
+    ) -> (String, serialport::TTYPort, Arc<Mutex<Vec<String>>>) {
+        use std::io::Read;
+
+        let (mut master, slave) = serialport::TTYPort::pair().unwrap();
+        let path = slave.name().unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let record = Arc::downgrade(&received);
+        thread::spawn(move || {
+            let mut line = String::new();
+            let mut buf = [0u8; 256];
+            while let Some(received) = record.upgrade() {
+                let n = match master.read(&mut buf) {
+                    Ok(n) => n,
+                    Err(e) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+                    Err(_) => return,
+                };
+                for &byte in &buf[..n] {
+                    let reply = match byte {
+                        protocol::realtime::SOFT_RESET => "\r\nGrbl 1.1h ['$' for help]\r\n",
+                        protocol::realtime::STATUS_QUERY => {
+                            let report = format!("{}\r\n", report.lock());
+                            let _ = master.write_all(report.as_bytes());
+                            ""
+                        }
+                        b'\n' => {
+                            let line = std::mem::take(&mut line);
+                            let reply = match line.as_str() {
+                                "$G" => "[GC:G1 G54 G17 G21 G90 G94 M4 M9 T0 F600 S500]\r\nok\r\n",
+                                _ => "ok\r\n",
+                            };
+                            received.lock().push(line);
+                            reply
+                        }
+                        b'\r' => "",
+                        protocol::realtime::CYCLE_START | protocol::realtime::FEED_HOLD => {
+                            received.lock().push((byte as char).to_string());
+                            ""
+                        }
+                        0x80.. => {
+                            received.lock().push(format!("0x{:02X}", byte));
+                            ""
+                        }
+                        _ => {
+                            line.push(byte as char);
+                            ""
+                        }
+                    };
+                    let _ = master.write_all(reply.as_bytes());
+                }
+            }
+        });
+        (path, slave, received)
+    }
+
+    #[test]
+    fn test_program_end_message_reaches_job() {
+        let mut job = StreamJob::from_lines(vec!["M2".into()], false);
+        let door = protocol::parse_response("[MSG:Check Door]");
+        assert!(!route_stream_response(&mut job, &door));
+        assert!(!job.progress().lock().program_end);
+        let end = protocol::parse_response("[MSG:Pgm End]");
+        assert!(route_stream_response(&mut job, &end));
+        assert!(job.progress().lock().program_end);
+    }
+
+    #[test]
+    fn test_io_errors_distinguish_disconnect() {
+        use std::io::{Error, ErrorKind};
+
+        assert!(matches!(
+            io_error(Error::from(ErrorKind::BrokenPipe)),
+            WorkerError::Disconnected(_)
+        ));
+        #[cfg(unix)]
+        assert!(matches!(
+            io_error(Error::from_raw_os_error(6)),
+            WorkerError::Disconnected(_)
+        ));
+        assert!(matches!(
+            io_error(Error::from(ErrorKind::TimedOut)),
+            WorkerError::Io(_)
+        ));
+        assert!(matches!(
+            io_error(Error::other("framing error")),
+            WorkerError::Io(_)
+        ));
+    }
+
+    /// Yields each chunk as one read, with a timeout after every chunk
+    struct ChunkedReader(VecDeque<Option<&'static str>>);
+
+    impl std::io::Read for ChunkedReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.pop_front() {
+                Some(Some(chunk)) => {
+                    buf[..chunk.len()].copy_from_slice(chunk.as_bytes());
+                    Ok(chunk.len())
+                }
+                Some(None) => Err(std::io::ErrorKind::TimedOut.into()),
+                None => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_status_line_is_reassembled() {
+        let chunks = [
+            "<Idle|MPos:1.000,2.0",
+            "00,0.000|FS:0,0>\r\nok\r\n",
+            "<Run|MPos:0.000,0.000,0.000|FS:100,0>",
+            "error:",
+            "20\r\n",
+        ];
+        let mut reader = BufReader::new(ChunkedReader(
+            chunks.iter().flat_map(|c| [Some(*c), None]).collect(),
+        ));
+        let mut partial = String::new();
+        let mut lines = Vec::new();
+        for _ in 0..12 {
+            if let Some(line) = read_complete_line(&mut reader, &mut partial).unwrap() {
+                lines.push(line);
+            }
+        }
+
+        assert_eq!(
+            lines,
+            [
+                "<Idle|MPos:1.000,2.000,0.000|FS:0,0>",
+                "ok",
+                "<Run|MPos:0.000,0.000,0.000|FS:100,0>",
+                "error:20"
+            ]
+        );
+        assert!(MachineStatus::parse(&lines[0]).is_some());
+        assert!(partial.is_empty());
+    }
+
+    #[test]
+    fn test_cancel_token_targets_active_request() {
+        let token = CancelToken::default();
+
+        // Nothing running: cancel is a no-op and doesn't leak forward
+        assert!(!token.cancel());
+        token.begin();
+        assert!(!token.is_cancelled());
+
+        assert!(token.cancel());
+        assert!(token.is_cancelled());
+        token.end();
+
+        token.begin();
+        assert!(!token.is_cancelled());
+        token.end();
+    }
+
+    #[test]
+    fn test_priority_realtime_without_connection() {
+        let handle = WorkerHandle::spawn();
+        // Queued and flushed by the idle worker; dropped since not connected
+        assert!(handle
+            .send_realtime_priority(protocol::realtime::SOFT_RESET)
+            .is_ok());
+        assert!(handle.query_status().is_err());
+        assert!(handle.realtime.take().is_empty());
+    }
+
+    #[test]
+    fn test_ping_without_connection() {
+        let handle = WorkerHandle::spawn();
+        assert!(handle.ping().is_ok());
+        // Still answered while the port is closed; commands are not
+        assert!(matches!(
+            handle.send_command("G0 X1"),
+            Err(WorkerError::NotConnected)
+        ));
+    }
 
     #[test]
     fn test_worker_spawn_and_shutdown() {
@@ -589,4 +1540,49 @@ mod tests {
         // Worker should shutdown cleanly when handle is dropped
         drop(handle);
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_port_probe_sends_only_status_queries() {
+        let (path, _slave, received) = fake_device();
+        let result = test_port(&path, 115200).unwrap();
+        assert!(result.responded);
+        assert_eq!(result.welcome, None);
+        assert!(result.latency_ms < TEST_PORT_TIMEOUT_MS);
+        // `?` isn't recorded; nothing else was sent
+        assert!(received.lock().is_empty());
+
+        assert!(matches!(
+            test_port("/dev/dl44-missing", 115200),
+            Err(WorkerError::OpenFailed(ConnectErrorKind::PortNotFound, _))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_queued_soft_reset_cancels_stream() {
+        let (path, _slave, _received) = fake_device();
+        let mut conn = SerialConnection::open(
+            &path,
+            115200,
+            LineEnding::default(),
+            Arc::new(SerialLog::new()),
+        )
+        .unwrap();
+        let queue = RealtimeQueue::default();
+        let mut job = Some(StreamJob::from_lines(vec!["G1 X1".into()], false));
+        let progress = job.as_ref().unwrap().progress();
+
+        queue.push(protocol::realtime::FEED_HOLD);
+        write_queued_realtime(&mut conn, &queue, &mut job).unwrap();
+        assert!(job.is_some());
+
+        queue.push(protocol::realtime::SOFT_RESET);
+        write_queued_realtime(&mut conn, &queue, &mut job).unwrap();
+        assert!(job.is_none());
+        assert!(matches!(
+            progress.lock().state,
+            super::super::stream::StreamState::Cancelled
+        ));
+    }
 }
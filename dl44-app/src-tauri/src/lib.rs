@@ -3,75 +3,227 @@
 //! Tauri backend providing GRBL device communication and control.
 
 mod commands;
+mod events;
+mod gcode;
 mod grbl;
+mod preferences;
 mod workspace;
 mod workspace_commands;
 
 use commands::AppState;
-use grbl::Controller;
+use events::{HeartbeatEmitter, StatusStream};
+use grbl::usage::{UsageCounter, USAGE_FILE};
+use grbl::{ControllerRegistry, DEFAULT_DEVICE};
+use preferences::{Preferences, PREFERENCES_FILE};
 use workspace_commands::WorkspaceState;
 use std::sync::Arc;
+use tauri::Manager;
 
 pub fn run() {
     // Initialize logging
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
-    // Create shared controller
-    let controller = Arc::new(Controller::new());
+    // Controllers keyed by device; the default device always exists
+    let devices = Arc::new(ControllerRegistry::new());
 
     // Create workspace state
     let workspace = Arc::new(WorkspaceState::new());
 
+    // Heartbeat runs for the app lifetime, connected or not
+    let heartbeat = HeartbeatEmitter::new();
+
+    // Status stream polls the device while connected
+    let status_stream = StatusStream::new();
+
+    // Preferences load once the config directory is known (in setup)
+    let preferences = Arc::new(Preferences::new());
+    let preferences_for_setup = preferences.clone();
+
+    // Laser usage totals load once the data directory is known (in setup)
+    let usage = Arc::new(UsageCounter::new());
+    let usage_for_setup = usage.clone();
+
+    // Background threads hold their own references, so the controllers are
+    // never dropped on exit; shut them down explicitly instead
+    let devices_for_setup = devices.clone();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .manage(AppState {
-            controller: controller.clone(),
+            devices: devices.clone(),
+            heartbeat: heartbeat.clone(),
+            status_stream: status_stream.clone(),
+            preferences,
+            usage,
         })
         .manage(workspace)
+        .setup(move |app| {
+            match app.path().app_config_dir() {
+                Ok(dir) => preferences_for_setup.load(&dir.join(PREFERENCES_FILE)),
+                Err(e) => log::warn!("No config directory for preferences: {}", e),
+            }
+            match app.path().app_data_dir() {
+                Ok(dir) => usage_for_setup.load(&dir.join(USAGE_FILE)),
+                Err(e) => log::warn!("No data directory for usage stats: {}", e),
+            }
+            let controller_for_setup = devices_for_setup.default_controller();
+            controller_for_setup.set_usage_counter(usage_for_setup);
+            controller_for_setup.set_jog_presets(preferences_for_setup.jog_presets());
+            controller_for_setup.set_jog_travel_check(preferences_for_setup.jog_travel_check());
+            controller_for_setup.set_min_feed(preferences_for_setup.min_feed());
+            let line_ending = preferences_for_setup.line_ending();
+            if let Err(e) = controller_for_setup.set_line_ending(line_ending) {
+                log::warn!("Failed to apply the saved line ending: {}", e);
+            }
+            let serial_log = controller_for_setup.serial_log();
+            serial_log.set_settings(preferences_for_setup.serial_log());
+            match app.path().app_log_dir() {
+                Ok(dir) => serial_log.set_directory(&dir),
+                Err(e) => log::warn!("No log directory for the serial log: {}", e),
+            }
+            heartbeat.spawn(app.handle().clone(), devices_for_setup);
+            status_stream.spawn(app.handle().clone(), DEFAULT_DEVICE, &controller_for_setup);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Connection commands
             commands::list_serial_ports,
             commands::get_baud_rates,
+            commands::get_last_baud_for_port,
+            commands::supported_import_formats,
+            commands::list_devices,
+            commands::remove_device,
             commands::connect,
+            commands::connect_async,
+            commands::test_port,
             commands::disconnect,
             commands::get_connection_state,
+            commands::get_connection_events,
             commands::is_connected,
             // Status commands
             commands::poll_status,
             commands::get_status,
             commands::get_controller_snapshot,
+            commands::get_last_error,
+            commands::set_heartbeat_interval,
+            commands::set_poll_rates,
+            commands::get_parser_state,
+            commands::get_parameters,
+            commands::get_spindle_power_percent,
             // Control commands
             commands::home,
             commands::unlock,
+            commands::get_current_alarm,
+            commands::reset_grbl,
+            commands::get_grbl_settings_described,
+            commands::get_grbl_setting,
+            commands::ensure_laser_mode,
+            commands::set_report_fields,
+            commands::get_auto_laser_mode,
+            commands::set_auto_laser_mode,
+            commands::export_grbl_settings,
+            commands::import_grbl_settings,
             commands::jog,
+            commands::jog_during_hold,
+            commands::jog_preset,
+            commands::get_jog_presets,
+            commands::set_jog_presets,
+            commands::get_jog_travel_check,
+            commands::set_jog_travel_check,
+            commands::get_min_feed,
+            commands::set_min_feed,
+            commands::get_line_ending,
+            commands::set_line_ending,
+            // Serial log commands
+            commands::get_serial_log_settings,
+            commands::set_serial_log_settings,
+            commands::get_log_files,
+            commands::read_log_tail,
             commands::jog_cancel,
             commands::feed_hold,
             commands::cycle_start,
             commands::soft_reset,
+            commands::stop_all,
+            commands::abort_current_command,
+            commands::recover_device,
             // Override commands
             commands::feed_override,
             commands::rapid_override,
             commands::spindle_override,
             // Frame command
             commands::run_frame,
+            // Positioning commands
+            commands::go_to_zero,
+            commands::go_to_machine_zero,
+            commands::park,
+            commands::set_g28_position,
+            commands::set_g30_position,
+            commands::go_to_g28,
+            commands::go_to_g30,
+            commands::rapid_move,
+            // Streaming commands
+            commands::run_gcode_program,
+            commands::run_gcode_file,
+            commands::validate_gcode,
+            commands::resume_from_line,
+            commands::get_job_progress,
+            commands::set_eta_smoothing,
+            commands::get_usage_stats,
+            commands::reset_usage_stats,
+            // Step mode commands
+            commands::step_mode_begin,
+            commands::step_next,
+            commands::step_abort,
             // Workspace commands
             workspace_commands::get_workspace,
             workspace_commands::get_workspace_settings,
+            workspace_commands::get_workspace_summary,
+            workspace_commands::is_workspace_dirty,
             workspace_commands::update_workspace_settings,
+            workspace_commands::get_bed_presets,
+            workspace_commands::apply_bed_preset,
             workspace_commands::get_documents,
+            workspace_commands::get_document_image,
+            workspace_commands::preview_raster,
+            workspace_commands::rasterize_document,
             workspace_commands::get_workspace_bounds,
+            workspace_commands::get_job_frame,
+            workspace_commands::get_workspace_bounds_all,
+            workspace_commands::get_document_bounds,
+            workspace_commands::get_all_document_bounds,
+            workspace_commands::find_overlapping_documents,
+            workspace_commands::inspect_file,
             workspace_commands::import_document,
             workspace_commands::import_document_bytes,
             workspace_commands::remove_document,
             workspace_commands::update_document_transform,
+            workspace_commands::update_transforms,
+            workspace_commands::snap_document_to_grid,
+            workspace_commands::tile_document,
             workspace_commands::update_document_visibility,
+            workspace_commands::rename_document,
+            workspace_commands::set_document_cut_settings,
             workspace_commands::reorder_document,
+            workspace_commands::reorder_document_relative,
+            workspace_commands::bring_document_to_front,
+            workspace_commands::send_document_to_back,
+            workspace_commands::generate_workspace_gcode,
+            workspace_commands::preflight_job,
+            workspace_commands::get_gcode_header_enabled,
+            workspace_commands::set_gcode_header_enabled,
+            workspace_commands::run_workspace_job,
             workspace_commands::clear_workspace,
             workspace_commands::save_workspace_to_file,
             workspace_commands::load_workspace_from_file,
+            workspace_commands::load_workspace_from_bytes,
             workspace_commands::get_workspace_file_path,
             workspace_commands::new_workspace,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(move |_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                devices.shutdown_all();
+            }
+        });
 }
@@ -0,0 +1,304 @@
+//! Persisted user preferences.
+//!
+//! Stored as JSON in the app config directory. Failures to read or write the
+//! file are logged and otherwise ignored; preferences are a convenience.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::grbl::protocol::{self, JogPreset, LineEnding};
+use crate::grbl::serial_log::SerialLogSettings;
+use crate::grbl::PortInfo;
+
+/// File name of the preferences file within the app config directory
+pub const PREFERENCES_FILE: &str = "preferences.json";
+
+/// Serialized preference data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PreferencesData {
+    /// Last successful baud rate, keyed by [`port_key`]
+    #[serde(default)]
+    port_bauds: HashMap<String, u32>,
+    /// Jog step/feed presets, in number-key order
+    #[serde(default = "protocol::default_jog_presets")]
+    jog_presets: Vec<JogPreset>,
+    /// Serial log options
+    #[serde(default)]
+    serial_log: SerialLogSettings,
+    /// Start exported G-code with descriptive header comments
+    #[serde(default = "default_gcode_header")]
+    gcode_header: bool,
+    /// Line ending sent after each command
+    #[serde(default)]
+    line_ending: LineEnding,
+    /// Refuse incremental jogs across machine zero out of the homed travel
+    #[serde(default = "default_jog_travel_check")]
+    jog_travel_check: bool,
+    /// Lowest feed accepted for jogs and frames (units/min)
+    #[serde(default = "default_min_feed")]
+    min_feed: f64,
+    /// Turn on laser mode (`$32=1`) after connecting if it's off, instead
+    /// of only warning
+    #[serde(default)]
+    auto_laser_mode: bool,
+}
+
+fn default_gcode_header() -> bool {
+    true
+}
+
+fn default_jog_travel_check() -> bool {
+    true
+}
+
+fn default_min_feed() -> f64 {
+    protocol::DEFAULT_MIN_FEED
+}
+
+impl Default for PreferencesData {
+    fn default() -> Self {
+        Self {
+            port_bauds: HashMap::new(),
+            jog_presets: protocol::default_jog_presets(),
+            serial_log: SerialLogSettings::default(),
+            gcode_header: default_gcode_header(),
+            line_ending: LineEnding::default(),
+            jog_travel_check: default_jog_travel_check(),
+            min_feed: default_min_feed(),
+            auto_laser_mode: false,
+        }
+    }
+}
+
+/// Key identifying a device across reconnects.
+///
+/// USB serial numbers survive the port path changing (`COM3` -> `COM4`),
+/// so they take precedence; ports without one fall back to the path.
+fn port_key(path: &str, serial_number: Option<&str>) -> String {
+    match serial_number.filter(|s| !s.is_empty()) {
+        Some(serial) => format!("serial:{}", serial),
+        None => format!("path:{}", path),
+    }
+}
+
+/// Application preferences with optional file persistence
+pub struct Preferences {
+    path: Mutex<Option<PathBuf>>,
+    data: Mutex<PreferencesData>,
+}
+
+impl Preferences {
+    /// Create empty, in-memory preferences
+    pub fn new() -> Self {
+        Self {
+            path: Mutex::new(None),
+            data: Mutex::new(PreferencesData::default()),
+        }
+    }
+
+    /// Load preferences from `path` and persist future changes there.
+    ///
+    /// A missing or unreadable file leaves the defaults in place.
+    pub fn load(&self, path: &Path) {
+        match std::fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str(&json) {
+                Ok(data) => *self.data.lock() = data,
+                Err(e) => log::warn!("Ignoring invalid preferences {}: {}", path.display(), e),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => log::warn!("Failed to read preferences {}: {}", path.display(), e),
+        }
+        *self.path.lock() = Some(path.to_path_buf());
+    }
+
+    /// Last baud rate that connected successfully to this port
+    pub fn last_baud_for_port(&self, path: &str, serial_number: Option<&str>) -> Option<u32> {
+        let data = self.data.lock();
+        data.port_bauds
+            .get(&port_key(path, serial_number))
+            .or_else(|| data.port_bauds.get(&port_key(path, None)))
+            .copied()
+    }
+
+    /// Remember a baud rate that connected successfully
+    pub fn remember_baud(&self, path: &str, serial_number: Option<&str>, baud: u32) {
+        let key = port_key(path, serial_number);
+        {
+            let mut data = self.data.lock();
+            if data.port_bauds.get(&key) == Some(&baud) {
+                return;
+            }
+            data.port_bauds.insert(key, baud);
+        }
+        self.save();
+    }
+
+    /// Configured jog presets
+    pub fn jog_presets(&self) -> Vec<JogPreset> {
+        self.data.lock().jog_presets.clone()
+    }
+
+    /// Replace the jog presets
+    pub fn set_jog_presets(&self, presets: Vec<JogPreset>) {
+        self.data.lock().jog_presets = presets;
+        self.save();
+    }
+
+    /// Serial log options
+    pub fn serial_log(&self) -> SerialLogSettings {
+        self.data.lock().serial_log
+    }
+
+    /// Replace the serial log options
+    pub fn set_serial_log(&self, settings: SerialLogSettings) {
+        self.data.lock().serial_log = settings;
+        self.save();
+    }
+
+    /// Whether exported G-code starts with header comments
+    pub fn gcode_header(&self) -> bool {
+        self.data.lock().gcode_header
+    }
+
+    /// Turn exported G-code header comments on or off
+    pub fn set_gcode_header(&self, enabled: bool) {
+        self.data.lock().gcode_header = enabled;
+        self.save();
+    }
+
+    /// Line ending sent after each command
+    pub fn line_ending(&self) -> LineEnding {
+        self.data.lock().line_ending
+    }
+
+    /// Change the line ending sent after each command
+    pub fn set_line_ending(&self, line_ending: LineEnding) {
+        self.data.lock().line_ending = line_ending;
+        self.save();
+    }
+
+    /// Whether jogs across machine zero out of the homed travel are refused
+    pub fn jog_travel_check(&self) -> bool {
+        self.data.lock().jog_travel_check
+    }
+
+    /// Turn the jog travel check on or off
+    pub fn set_jog_travel_check(&self, enabled: bool) {
+        self.data.lock().jog_travel_check = enabled;
+        self.save();
+    }
+
+    /// Lowest feed accepted for jogs and frames
+    pub fn min_feed(&self) -> f64 {
+        self.data.lock().min_feed
+    }
+
+    /// Change the lowest feed accepted for jogs and frames
+    pub fn set_min_feed(&self, min_feed: f64) {
+        self.data.lock().min_feed = min_feed;
+        self.save();
+    }
+
+    /// Whether laser mode is turned on automatically after connecting
+    pub fn auto_laser_mode(&self) -> bool {
+        self.data.lock().auto_laser_mode
+    }
+
+    /// Opt in or out of turning on laser mode after connecting
+    pub fn set_auto_laser_mode(&self, enabled: bool) {
+        self.data.lock().auto_laser_mode = enabled;
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = self.path.lock().clone() else {
+            return;
+        };
+        let json = match serde_json::to_string_pretty(&*self.data.lock()) {
+            Ok(json) => json,
+            Err(e) => {
+                log::warn!("Failed to serialize preferences: {}", e);
+                return;
+            }
+        };
+        if let Some(dir) = path.parent() {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                log::warn!("Failed to create {}: {}", dir.display(), e);
+                return;
+            }
+        }
+        if let Err(e) = std::fs::write(&path, json) {
+            log::warn!("Failed to write preferences {}: {}", path.display(), e);
+        }
+    }
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Serial number of the port at `path`, if it is currently listed
+pub fn serial_number_for(ports: &[PortInfo], path: &str) -> Option<String> {
+    ports
+        .iter()
+        .find(|p| p.path == path)
+        .and_then(|p| p.serial_number.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_baud_follows_serial_number_across_paths() {
+        let prefs = Preferences::new();
+        prefs.remember_baud("COM3", Some("A1B2"), 250000);
+
+        assert_eq!(prefs.last_baud_for_port("COM4", Some("A1B2")), Some(250000));
+        assert_eq!(prefs.last_baud_for_port("COM3", Some("OTHER")), None);
+        assert_eq!(prefs.last_baud_for_port("COM3", None), None);
+    }
+
+    #[test]
+    fn test_baud_falls_back_to_path() {
+        let prefs = Preferences::new();
+        prefs.remember_baud("/dev/ttyUSB0", None, 57600);
+
+        assert_eq!(prefs.last_baud_for_port("/dev/ttyUSB0", None), Some(57600));
+        assert_eq!(
+            prefs.last_baud_for_port("/dev/ttyUSB0", Some("XYZ")),
+            Some(57600)
+        );
+    }
+
+    #[test]
+    fn test_preferences_persist() {
+        let dir = std::env::temp_dir().join(format!("dl44-prefs-{}", std::process::id()));
+        let path = dir.join(PREFERENCES_FILE);
+
+        let prefs = Preferences::new();
+        prefs.load(&path);
+        prefs.remember_baud("COM3", Some("A1B2"), 115200);
+
+        let fine = JogPreset {
+            label: "Fine".into(),
+            step_mm: 0.05,
+            feed: 50.0,
+        };
+        prefs.set_jog_presets(vec![fine.clone()]);
+
+        let reloaded = Preferences::new();
+        reloaded.load(&path);
+        assert_eq!(reloaded.jog_presets(), [fine]);
+        assert_eq!(
+            reloaded.last_baud_for_port("COM9", Some("A1B2")),
+            Some(115200)
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -47,6 +47,14 @@ impl BoundingBox {
         self.y_max = self.y_max.max(other.y_max);
     }
 
+    /// Whether the boxes overlap with positive area (touching edges don't count)
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.x_min < other.x_max
+            && other.x_min < self.x_max
+            && self.y_min < other.y_max
+            && other.y_min < self.y_max
+    }
+
     /// Offset the bounding box by a translation
     pub fn translate(&mut self, dx: f64, dy: f64) {
         self.x_min += dx;
@@ -89,10 +97,16 @@ pub struct BitmapContent {
     pub width: u32,
     /// Image height in pixels
     pub height: u32,
-    /// Base64-encoded image data (for frontend rendering)
+    /// Base64-encoded image data (for frontend rendering). Empty in
+    /// document lists, which leave it out (see [`Document::without_image_data`])
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub data_url: String,
     /// Original file format
     pub format: String,
+    /// Small PNG data URL for the document list, None if the image
+    /// couldn't be decoded (SVGs use `raw_svg` as their own thumbnail)
+    #[serde(default)]
+    pub thumbnail: Option<String>,
 }
 
 /// Document content variant
@@ -127,6 +141,86 @@ impl Default for Transform {
     }
 }
 
+impl Transform {
+    /// Round the position to the nearest multiple of `spacing` (no-op if spacing <= 0)
+    pub fn snap_to_grid(&mut self, spacing: f64) {
+        if spacing <= 0.0 {
+            return;
+        }
+        self.x = (self.x / spacing).round() * spacing;
+        self.y = (self.y / spacing).round() * spacing;
+    }
+
+    /// Check that the transform is usable for bounds and framing math:
+    /// all fields finite and a positive scale. Rotation is wrapped into
+    /// [0, 360).
+    pub fn normalized(self) -> Result<Self, String> {
+        for (name, value) in [
+            ("x", self.x),
+            ("y", self.y),
+            ("scale", self.scale),
+            ("rotation", self.rotation),
+        ] {
+            if !value.is_finite() {
+                return Err(format!("Transform {} must be a finite number", name));
+            }
+        }
+        if self.scale <= 0.0 {
+            return Err(format!("Scale must be positive (got {})", self.scale));
+        }
+        let rotation = self.rotation.rem_euclid(360.0);
+        Ok(Self {
+            // rem_euclid rounds tiny negative angles up to exactly 360
+            rotation: if rotation >= 360.0 { 0.0 } else { rotation },
+            ..self
+        })
+    }
+}
+
+/// Laser operation type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CutMode {
+    /// Cut through the material following vector paths
+    Cut,
+    /// Raster fill (scanline engraving)
+    Engrave,
+    /// Light vector pass that marks the surface without cutting through
+    Score,
+}
+
+/// Per-document laser settings used for job generation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CutSettings {
+    /// Feed rate (mm/min)
+    pub feed: f64,
+    /// Laser power (S value)
+    pub power: u32,
+    /// Number of passes
+    pub passes: u32,
+    /// Operation type
+    pub mode: CutMode,
+}
+
+impl CutSettings {
+    /// Default settings for a document kind (bitmaps engrave, SVGs cut)
+    pub fn default_for(kind: &DocumentKind) -> Self {
+        match kind {
+            DocumentKind::Bitmap(_) => Self {
+                feed: 3000.0,
+                power: 300,
+                passes: 1,
+                mode: CutMode::Engrave,
+            },
+            DocumentKind::Svg(_) => Self {
+                feed: 500.0,
+                power: 1000,
+                passes: 1,
+                mode: CutMode::Cut,
+            },
+        }
+    }
+}
+
 /// A document in the workspace
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Document {
@@ -146,9 +240,18 @@ pub struct Document {
     pub locked: bool,
     /// Original bounds (before transform)
     pub original_bounds: BoundingBox,
+    /// Laser settings for this document (None = defaults for its kind)
+    #[serde(default)]
+    pub cut_settings: Option<CutSettings>,
 }
 
 impl Document {
+    /// Cut settings to use for job generation
+    pub fn effective_cut_settings(&self) -> CutSettings {
+        self.cut_settings
+            .unwrap_or_else(|| CutSettings::default_for(&self.kind))
+    }
+
     /// Get the transformed bounding box in workspace coordinates
     pub fn workspace_bounds(&self) -> BoundingBox {
         let mut bounds = self.original_bounds;
@@ -166,6 +269,33 @@ impl Document {
         // Note: rotation not handled yet (would need proper matrix transform)
         bounds
     }
+
+    /// Copy without the full bitmap data, for listing documents. The
+    /// image is fetched separately when it's rendered, so it isn't
+    /// cloned here.
+    pub fn without_image_data(&self) -> Self {
+        let kind = match &self.kind {
+            DocumentKind::Svg(svg) => DocumentKind::Svg(svg.clone()),
+            DocumentKind::Bitmap(bitmap) => DocumentKind::Bitmap(BitmapContent {
+                width: bitmap.width,
+                height: bitmap.height,
+                data_url: String::new(),
+                format: bitmap.format.clone(),
+                thumbnail: bitmap.thumbnail.clone(),
+            }),
+        };
+        Self {
+            id: self.id,
+            name: self.name.clone(),
+            source_path: self.source_path.clone(),
+            kind,
+            transform: self.transform,
+            visible: self.visible,
+            locked: self.locked,
+            original_bounds: self.original_bounds,
+            cut_settings: self.cut_settings,
+        }
+    }
 }
 
 /// List of documents in the workspace
@@ -175,6 +305,17 @@ pub struct DocumentList {
     next_id: DocumentId,
 }
 
+/// Outcome of applying transforms to several documents at once
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TransformBatch {
+    /// Documents moved, with the transform actually applied
+    pub updated: Vec<(DocumentId, Transform)>,
+    /// Locked documents, left unchanged
+    pub locked: Vec<DocumentId>,
+    /// Requested IDs with no document, skipped
+    pub not_found: Vec<DocumentId>,
+}
+
 impl DocumentList {
     pub fn new() -> Self {
         Self {
@@ -201,6 +342,36 @@ impl DocumentList {
         }
     }
 
+    /// Duplicate a document, returning the ID of the copy
+    pub fn duplicate(&mut self, id: DocumentId) -> Option<DocumentId> {
+        let copy = self.get(id)?.clone();
+        Some(self.add(copy))
+    }
+
+    /// Apply transforms to several documents, skipping locked and unknown
+    /// ones. Positions are snapped to `grid_spacing` if given.
+    pub fn apply_transforms(
+        &mut self,
+        updates: Vec<(DocumentId, Transform)>,
+        grid_spacing: Option<f64>,
+    ) -> TransformBatch {
+        let mut batch = TransformBatch::default();
+        for (id, mut transform) in updates {
+            match self.get_mut(id) {
+                Some(doc) if doc.locked => batch.locked.push(id),
+                Some(doc) => {
+                    if let Some(spacing) = grid_spacing {
+                        transform.snap_to_grid(spacing);
+                    }
+                    doc.transform = transform;
+                    batch.updated.push((id, transform));
+                }
+                None => batch.not_found.push(id),
+            }
+        }
+        batch
+    }
+
     /// Get a document by ID
     pub fn get(&self, id: DocumentId) -> Option<&Document> {
         self.documents.iter().find(|d| d.id == id)
@@ -216,6 +387,18 @@ impl DocumentList {
         &self.documents
     }
 
+    /// Copy with each document [without its image data](Document::without_image_data)
+    pub fn without_image_data(&self) -> Self {
+        Self {
+            documents: self
+                .documents
+                .iter()
+                .map(Document::without_image_data)
+                .collect(),
+            next_id: self.next_id,
+        }
+    }
+
     /// Get all visible documents
     pub fn visible(&self) -> impl Iterator<Item = &Document> {
         self.documents.iter().filter(|d| d.visible)
@@ -230,6 +413,36 @@ impl DocumentList {
         bounds
     }
 
+    /// Compute combined bounds of all documents, including hidden ones
+    pub fn combined_bounds_all(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::default();
+        for doc in &self.documents {
+            bounds.merge(&doc.workspace_bounds());
+        }
+        bounds
+    }
+
+    /// Pairs of visible documents whose workspace bounds overlap, in list order.
+    ///
+    /// Bounds-level only: two shapes can share bounds area without any of
+    /// their paths actually crossing. Hidden documents aren't cut, so they
+    /// are skipped.
+    pub fn overlapping_pairs(&self) -> Vec<(DocumentId, DocumentId)> {
+        let bounds: Vec<_> = self
+            .visible()
+            .map(|d| (d.id, d.workspace_bounds()))
+            .collect();
+        let mut pairs = Vec::new();
+        for (i, (a_id, a)) in bounds.iter().enumerate() {
+            for (b_id, b) in &bounds[i + 1..] {
+                if a.intersects(b) {
+                    pairs.push((*a_id, *b_id));
+                }
+            }
+        }
+        pairs
+    }
+
     /// Check if empty
     pub fn is_empty(&self) -> bool {
         self.documents.is_empty()
@@ -246,12 +459,198 @@ impl DocumentList {
         self.next_id = 1;
     }
 
-    /// Reorder document (move to new index)
-    pub fn reorder(&mut self, id: DocumentId, new_index: usize) {
-        if let Some(old_idx) = self.documents.iter().position(|d| d.id == id) {
-            let doc = self.documents.remove(old_idx);
-            let insert_idx = new_index.min(self.documents.len());
-            self.documents.insert(insert_idx, doc);
+    /// Reorder document (move to new index).
+    ///
+    /// Returns None if there's no such document, otherwise whether it
+    /// moved. The other z-order helpers return the same.
+    pub fn reorder(&mut self, id: DocumentId, new_index: usize) -> Option<bool> {
+        let old_idx = self.documents.iter().position(|d| d.id == id)?;
+        let doc = self.documents.remove(old_idx);
+        let insert_idx = new_index.min(self.documents.len());
+        self.documents.insert(insert_idx, doc);
+        Some(insert_idx != old_idx)
+    }
+
+    /// Move a document by `delta` positions in z-order (positive = toward front),
+    /// clamped to the list bounds
+    pub fn reorder_relative(&mut self, id: DocumentId, delta: i32) -> Option<bool> {
+        let old_idx = self.documents.iter().position(|d| d.id == id)?;
+        let max_idx = self.documents.len() as i64 - 1;
+        let new_idx = (old_idx as i64 + delta as i64).clamp(0, max_idx);
+        self.reorder(id, new_idx as usize)
+    }
+
+    /// Move a document to the front (drawn last, on top)
+    pub fn bring_to_front(&mut self, id: DocumentId) -> Option<bool> {
+        self.reorder(id, self.documents.len())
+    }
+
+    /// Move a document to the back (drawn first, underneath)
+    pub fn send_to_back(&mut self, id: DocumentId) -> Option<bool> {
+        self.reorder(id, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc_at(x: f64, y: f64, visible: bool) -> Document {
+        Document {
+            id: 0,
+            name: "doc".into(),
+            source_path: None,
+            kind: DocumentKind::Svg(SvgContent {
+                width: 10.0,
+                height: 10.0,
+                paths: Vec::new(),
+                raw_svg: String::new(),
+            }),
+            transform: Transform {
+                x,
+                y,
+                ..Transform::default()
+            },
+            visible,
+            locked: false,
+            original_bounds: BoundingBox::new(0.0, 0.0, 10.0, 10.0),
+            cut_settings: None,
         }
     }
+
+    #[test]
+    fn test_without_image_data() {
+        let mut doc = doc_at(0.0, 0.0, true);
+        doc.kind = DocumentKind::Bitmap(BitmapContent {
+            width: 1,
+            height: 1,
+            data_url: "data:image/png;base64,AAAA".into(),
+            format: "png".into(),
+            thumbnail: Some("data:image/png;base64,BBBB".into()),
+        });
+
+        let listed = serde_json::to_value(doc.without_image_data()).unwrap();
+        assert!(listed["kind"].get("data_url").is_none());
+        assert_eq!(listed["kind"]["thumbnail"], "data:image/png;base64,BBBB");
+
+        // Saved documents keep the data and still load without it
+        let saved = serde_json::to_value(&doc).unwrap();
+        assert_eq!(saved["kind"]["data_url"], "data:image/png;base64,AAAA");
+        let loaded: Document = serde_json::from_value(listed).unwrap();
+        assert!(matches!(loaded.kind, DocumentKind::Bitmap(c) if c.data_url.is_empty()));
+
+        let mut list = DocumentList::new();
+        let id = list.add(doc);
+        let has_data = |list: &DocumentList| match &list.get(id).unwrap().kind {
+            DocumentKind::Bitmap(c) => !c.data_url.is_empty(),
+            DocumentKind::Svg(_) => false,
+        };
+        assert!(has_data(&list));
+        assert!(!has_data(&list.without_image_data()));
+    }
+
+    #[test]
+    fn test_transform_normalized() {
+        let transform = |x: f64, scale: f64, rotation: f64| Transform {
+            x,
+            scale,
+            rotation,
+            ..Transform::default()
+        };
+
+        assert!(transform(0.0, 0.0, 0.0).normalized().is_err());
+        assert!(transform(0.0, -1.0, 0.0).normalized().is_err());
+        assert!(transform(f64::NAN, 1.0, 0.0).normalized().is_err());
+        assert!(transform(0.0, f64::INFINITY, 0.0).normalized().is_err());
+
+        let rotations = [(-90.0, 270.0), (720.0, 0.0), (45.0, 45.0), (-1e-20, 0.0)];
+        for (rotation, expected) in rotations {
+            let normalized = transform(5.0, 2.0, rotation).normalized().unwrap();
+            assert_eq!(normalized.rotation, expected);
+            assert_eq!((normalized.x, normalized.scale), (5.0, 2.0));
+        }
+    }
+
+    #[test]
+    fn test_apply_transforms_skips_locked_and_missing() {
+        let mut list = DocumentList::new();
+        let a = list.add(doc_at(0.0, 0.0, true));
+        let b = list.add(doc_at(0.0, 0.0, true));
+        list.get_mut(b).unwrap().locked = true;
+
+        let moved = Transform {
+            x: 12.4,
+            y: 7.6,
+            ..Transform::default()
+        };
+        let batch = list.apply_transforms(vec![(a, moved), (b, moved), (99, moved)], Some(5.0));
+
+        assert_eq!(batch.updated.len(), 1);
+        assert_eq!(batch.updated[0].0, a);
+        assert_eq!((batch.locked, batch.not_found), (vec![b], vec![99]));
+        let applied = list.get(a).unwrap().transform;
+        assert_eq!((applied.x, applied.y), (10.0, 10.0));
+        assert_eq!(list.get(b).unwrap().transform.x, 0.0);
+    }
+
+    #[test]
+    fn test_reorder_reports_moves() {
+        let mut list = DocumentList::new();
+        let a = list.add(doc_at(0.0, 0.0, true));
+        let b = list.add(doc_at(0.0, 0.0, true));
+        let order = |list: &DocumentList| list.all().iter().map(|d| d.id).collect::<Vec<_>>();
+
+        assert_eq!(list.bring_to_front(b), Some(false));
+        assert_eq!(list.reorder_relative(a, -1), Some(false));
+        assert_eq!(list.reorder_relative(a, 5), Some(true));
+        assert_eq!(order(&list), [b, a]);
+        assert_eq!(list.send_to_back(a), Some(true));
+        assert_eq!(order(&list), [a, b]);
+        assert_eq!(list.reorder(99, 0), None);
+        assert_eq!(list.send_to_back(99), None);
+    }
+
+    #[test]
+    fn test_intersects_touching_overlapping_disjoint() {
+        let a = BoundingBox::new(0.0, 0.0, 10.0, 10.0);
+        let touching = BoundingBox::new(10.0, 0.0, 20.0, 10.0);
+        let corner = BoundingBox::new(10.0, 10.0, 20.0, 20.0);
+        let overlapping = BoundingBox::new(5.0, 5.0, 15.0, 15.0);
+        let inside = BoundingBox::new(2.0, 2.0, 3.0, 3.0);
+        let disjoint = BoundingBox::new(30.0, 0.0, 40.0, 10.0);
+
+        assert!(!a.intersects(&touching));
+        assert!(!a.intersects(&corner));
+        assert!(a.intersects(&overlapping) && overlapping.intersects(&a));
+        assert!(a.intersects(&inside));
+        assert!(!a.intersects(&disjoint));
+    }
+
+    #[test]
+    fn test_overlapping_pairs() {
+        let mut list = DocumentList::new();
+        let first = list.add(doc_at(0.0, 0.0, true));
+        let second = list.add(doc_at(5.0, 5.0, true));
+        list.add(doc_at(10.0, 15.0, true));
+        // Hidden documents are ignored
+        list.add(doc_at(40.0, 40.0, false));
+        list.add(doc_at(45.0, 45.0, true));
+
+        assert_eq!(list.overlapping_pairs(), [(first, second)]);
+    }
+
+    #[test]
+    fn test_combined_bounds_visible_vs_all() {
+        let mut list = DocumentList::new();
+        list.add(doc_at(0.0, 0.0, true));
+        list.add(doc_at(50.0, 20.0, false));
+        list.add(doc_at(5.0, 5.0, true));
+
+        let visible = list.combined_bounds();
+        assert_eq!((visible.x_max, visible.y_max), (15.0, 15.0));
+
+        let all = list.combined_bounds_all();
+        assert_eq!((all.x_min, all.y_min), (0.0, 0.0));
+        assert_eq!((all.x_max, all.y_max), (60.0, 30.0));
+    }
 }
@@ -0,0 +1,516 @@
+//! DXF import.
+//!
+//! Reads the 2D geometry of ASCII DXF files (LINE, LWPOLYLINE, CIRCLE and
+//! ARC entities in the ENTITIES section) into polylines. The result is
+//! stored as SVG content so the canvas and job generation treat it like
+//! any other vector import. Blocks, INSERTs and legacy POLYLINE entities
+//! are ignored.
+
+use std::f64::consts::PI;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use super::document::{BoundingBox, DocumentKind, SvgContent, SvgPath};
+use super::import::{ImportError, ImportOptions, Importer};
+
+/// Default maximum chord deviation when tessellating curves (mm)
+pub const DEFAULT_CHORD_TOLERANCE: f64 = 0.05;
+
+/// Stroke width used for the generated SVG preview (mm)
+const STROKE_WIDTH: f64 = 0.1;
+
+type Point = (f64, f64);
+
+/// A tessellated entity in DXF coordinates (y-up, mm)
+#[derive(Debug, Clone, PartialEq)]
+struct Shape {
+    points: Vec<Point>,
+    closed: bool,
+}
+
+/// DXF importer; curves are tessellated to
+/// [`ImportOptions::chord_tolerance`]
+pub struct DxfImporter;
+
+impl Importer for DxfImporter {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["dxf"]
+    }
+
+    fn import(
+        &self,
+        _path: &Path,
+        bytes: &[u8],
+        options: &ImportOptions,
+    ) -> Result<(DocumentKind, BoundingBox), ImportError> {
+        let tolerance = options.chord_tolerance;
+        if !(tolerance.is_finite() && tolerance > 0.0) {
+            return Err(ImportError::InvalidOption(format!(
+                "chord tolerance must be positive, got {tolerance}"
+            )));
+        }
+        let text = String::from_utf8_lossy(bytes);
+        let shapes = parse_dxf(&text, tolerance)?;
+        shapes_to_document(&shapes)
+    }
+}
+
+/// Split DXF text into (group code, value) pairs
+fn group_pairs(text: &str) -> Result<Vec<(i32, &str)>, ImportError> {
+    let mut lines = text.lines();
+    let mut pairs = Vec::new();
+    while let Some(code) = lines.next() {
+        let code = code.trim();
+        // Values may be empty, codes never are (trailing blank lines)
+        if code.is_empty() {
+            continue;
+        }
+        let code: i32 = code
+            .parse()
+            .map_err(|_| ImportError::DxfParse(format!("invalid group code '{code}'")))?;
+        let value = lines
+            .next()
+            .ok_or_else(|| ImportError::DxfParse("unexpected end of file".into()))?;
+        pairs.push((code, value.trim()));
+    }
+    Ok(pairs)
+}
+
+/// Millimetres per drawing unit for a `$INSUNITS` value (unitless = mm)
+fn units_to_mm(insunits: &str) -> f64 {
+    match insunits.parse::<i32>().unwrap_or(0) {
+        1 => 25.4,
+        2 => 304.8,
+        5 => 10.0,
+        6 => 1000.0,
+        _ => 1.0,
+    }
+}
+
+/// Parse all supported entities into shapes (mm, y-up)
+fn parse_dxf(text: &str, tolerance: f64) -> Result<Vec<Shape>, ImportError> {
+    if text.starts_with("AutoCAD Binary DXF") {
+        return Err(ImportError::DxfParse("binary DXF is not supported".into()));
+    }
+
+    let pairs = group_pairs(text)?;
+    let mut unit_scale = 1.0;
+    let mut section = "";
+    let mut shapes = Vec::new();
+
+    let mut i = 0;
+    while i < pairs.len() {
+        match pairs[i] {
+            (0, "SECTION") => {
+                if let Some(&(2, name)) = pairs.get(i + 1) {
+                    section = name;
+                    i += 2;
+                    continue;
+                }
+            }
+            (0, "ENDSEC") => section = "",
+            (9, "$INSUNITS") if section == "HEADER" => {
+                if let Some(&(70, units)) = pairs.get(i + 1) {
+                    unit_scale = units_to_mm(units);
+                }
+            }
+            (0, entity) if section == "ENTITIES" => {
+                let end = pairs[i + 1..]
+                    .iter()
+                    .position(|&(code, _)| code == 0)
+                    .map_or(pairs.len(), |n| i + 1 + n);
+                let groups = &pairs[i + 1..end];
+                if let Some(shape) = entity_shape(entity, groups, unit_scale, tolerance)? {
+                    shapes.push(shape);
+                }
+                i = end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    Ok(shapes)
+}
+
+/// First value of a numeric group, if present
+fn group_f64(groups: &[(i32, &str)], code: i32) -> Result<Option<f64>, ImportError> {
+    groups
+        .iter()
+        .find(|&&(c, _)| c == code)
+        .map(|&(_, value)| parse_f64(value))
+        .transpose()
+}
+
+/// Value of a numeric group that the entity requires
+fn require_f64(groups: &[(i32, &str)], code: i32, entity: &str) -> Result<f64, ImportError> {
+    group_f64(groups, code)?
+        .ok_or_else(|| ImportError::DxfParse(format!("{entity} is missing group {code}")))
+}
+
+fn parse_f64(value: &str) -> Result<f64, ImportError> {
+    value
+        .parse()
+        .map_err(|_| ImportError::DxfParse(format!("invalid number '{value}'")))
+}
+
+/// Convert one entity to a shape; unsupported entity types yield `None`
+fn entity_shape(
+    entity: &str,
+    groups: &[(i32, &str)],
+    scale: f64,
+    tolerance: f64,
+) -> Result<Option<Shape>, ImportError> {
+    let mut shape = match entity {
+        "LINE" => Shape {
+            points: vec![
+                (
+                    require_f64(groups, 10, entity)? * scale,
+                    require_f64(groups, 20, entity)? * scale,
+                ),
+                (
+                    require_f64(groups, 11, entity)? * scale,
+                    require_f64(groups, 21, entity)? * scale,
+                ),
+            ],
+            closed: false,
+        },
+        "CIRCLE" | "ARC" => {
+            let center = (
+                require_f64(groups, 10, entity)? * scale,
+                require_f64(groups, 20, entity)? * scale,
+            );
+            let radius = require_f64(groups, 40, entity)? * scale;
+            if radius <= 0.0 {
+                return Ok(None);
+            }
+            if entity == "CIRCLE" {
+                let mut points = arc_points(center, radius, 0.0, 2.0 * PI, tolerance);
+                points.pop();
+                Shape {
+                    points,
+                    closed: true,
+                }
+            } else {
+                let start = require_f64(groups, 50, entity)?.to_radians();
+                let end = require_f64(groups, 51, entity)?.to_radians();
+                // Arcs run counter-clockwise from start to end angle
+                let mut sweep = end - start;
+                if sweep <= 0.0 {
+                    sweep += 2.0 * PI;
+                }
+                Shape {
+                    points: arc_points(center, radius, start, sweep, tolerance),
+                    closed: false,
+                }
+            }
+        }
+        "LWPOLYLINE" => lwpolyline_shape(groups, scale, tolerance)?,
+        _ => return Ok(None),
+    };
+
+    // A negative extrusion direction mirrors the entity's coordinate system in X
+    if entity != "LINE" && group_f64(groups, 230)?.is_some_and(|z| z < 0.0) {
+        for point in &mut shape.points {
+            point.0 = -point.0;
+        }
+    }
+
+    Ok((!shape.points.is_empty()).then_some(shape))
+}
+
+/// Build a shape from LWPOLYLINE vertices, expanding bulged segments to arcs
+fn lwpolyline_shape(
+    groups: &[(i32, &str)],
+    scale: f64,
+    tolerance: f64,
+) -> Result<Shape, ImportError> {
+    // (vertex, bulge of the segment starting at it)
+    let mut vertices: Vec<(Point, f64)> = Vec::new();
+    let mut flags = 0;
+    for &(code, value) in groups {
+        match code {
+            10 => vertices.push(((parse_f64(value)? * scale, 0.0), 0.0)),
+            20 => {
+                if let Some(vertex) = vertices.last_mut() {
+                    vertex.0 .1 = parse_f64(value)? * scale;
+                }
+            }
+            42 => {
+                if let Some(vertex) = vertices.last_mut() {
+                    vertex.1 = parse_f64(value)?;
+                }
+            }
+            70 => flags = value.parse::<i32>().unwrap_or(0),
+            _ => {}
+        }
+    }
+
+    let closed = flags & 1 != 0;
+    let Some(&(first, _)) = vertices.first() else {
+        return Ok(Shape {
+            points: Vec::new(),
+            closed,
+        });
+    };
+
+    let segment_count = if closed {
+        vertices.len()
+    } else {
+        vertices.len() - 1
+    };
+    let mut points = vec![first];
+    for i in 0..segment_count {
+        let (start, bulge) = vertices[i];
+        let (end, _) = vertices[(i + 1) % vertices.len()];
+        if bulge == 0.0 {
+            points.push(end);
+        } else {
+            points.extend(bulge_points(start, end, bulge, tolerance));
+        }
+    }
+    // The closing segment ends back at the first vertex
+    if closed && points.len() > 1 {
+        points.pop();
+    }
+
+    Ok(Shape { points, closed })
+}
+
+/// Points along a circular arc, including both endpoints.
+///
+/// Segment count keeps the chord deviation within `tolerance`, with at
+/// least one segment per quarter turn.
+fn arc_points(center: Point, radius: f64, start: f64, sweep: f64, tolerance: f64) -> Vec<Point> {
+    let step = 2.0 * (1.0 - tolerance / radius).clamp(-1.0, 1.0).acos();
+    let segments = (sweep.abs() / step.max(1e-3))
+        .ceil()
+        .max((sweep.abs() / (PI / 2.0)).ceil())
+        .max(1.0) as usize;
+
+    (0..=segments)
+        .map(|k| {
+            let angle = start + sweep * k as f64 / segments as f64;
+            (
+                center.0 + radius * angle.cos(),
+                center.1 + radius * angle.sin(),
+            )
+        })
+        .collect()
+}
+
+/// Points along a bulged polyline segment, excluding its start point.
+///
+/// The bulge is the tangent of a quarter of the included angle; positive
+/// bulges run counter-clockwise.
+fn bulge_points(start: Point, end: Point, bulge: f64, tolerance: f64) -> Vec<Point> {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    if dx == 0.0 && dy == 0.0 {
+        return vec![end];
+    }
+
+    // Center lies on the chord's perpendicular bisector, left of the chord
+    // for counter-clockwise arcs
+    let offset = (1.0 - bulge * bulge) / (4.0 * bulge);
+    let center = (
+        (start.0 + end.0) / 2.0 - dy * offset,
+        (start.1 + end.1) / 2.0 + dx * offset,
+    );
+    let radius = (start.0 - center.0).hypot(start.1 - center.1);
+    let start_angle = (start.1 - center.1).atan2(start.0 - center.0);
+    let sweep = 4.0 * bulge.atan();
+
+    let mut points = arc_points(center, radius, start_angle, sweep, tolerance);
+    points.remove(0);
+    // Land exactly on the next vertex
+    if let Some(last) = points.last_mut() {
+        *last = end;
+    }
+    points
+}
+
+/// Build SVG document content from shapes, flipping DXF's y-up axis to
+/// the workspace's y-down and moving the extents to the origin
+fn shapes_to_document(shapes: &[Shape]) -> Result<(DocumentKind, BoundingBox), ImportError> {
+    let mut points = shapes.iter().flat_map(|shape| shape.points.iter());
+    let Some(&(x0, y0)) = points.next() else {
+        return Err(ImportError::DxfParse(
+            "no LINE, LWPOLYLINE, CIRCLE or ARC entities found".into(),
+        ));
+    };
+    let extents = points.fold(BoundingBox::new(x0, y0, x0, y0), |b, &(x, y)| {
+        BoundingBox::new(
+            b.x_min.min(x),
+            b.y_min.min(y),
+            b.x_max.max(x),
+            b.y_max.max(y),
+        )
+    });
+
+    let (width, height) = (extents.width(), extents.height());
+    let mut paths = Vec::with_capacity(shapes.len());
+    let mut raw_svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}mm" height="{height}mm" viewBox="0 0 {width} {height}">"#
+    );
+    for shape in shapes {
+        let mut d = String::new();
+        for (i, &(x, y)) in shape.points.iter().enumerate() {
+            let command = if i == 0 { "M" } else { " L" };
+            let _ = write!(
+                d,
+                "{command} {:.3} {:.3}",
+                x - extents.x_min,
+                extents.y_max - y
+            );
+        }
+        if shape.closed {
+            d.push_str(" Z");
+        }
+        let _ = write!(
+            raw_svg,
+            r#"<path d="{d}" fill="none" stroke="black" stroke-width="{STROKE_WIDTH}"/>"#
+        );
+        paths.push(SvgPath {
+            d,
+            stroke: Some("black".into()),
+            fill: Some("none".into()),
+            stroke_width: STROKE_WIDTH,
+        });
+    }
+    raw_svg.push_str("</svg>");
+
+    let content = SvgContent {
+        width,
+        height,
+        paths,
+        raw_svg,
+    };
+    let bounds = BoundingBox::new(0.0, 0.0, width, height);
+
+    Ok((DocumentKind::Svg(content), bounds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dxf(header: &str, entities: &str) -> String {
+        format!(
+            "0\nSECTION\n2\nHEADER\n{header}0\nENDSEC\n0\nSECTION\n2\nENTITIES\n{entities}0\nENDSEC\n0\nEOF\n"
+        )
+    }
+
+    fn import(text: &str) -> (SvgContent, BoundingBox) {
+        match DxfImporter.import(
+            Path::new("test.dxf"),
+            text.as_bytes(),
+            &ImportOptions::default(),
+        ) {
+            Ok((DocumentKind::Svg(svg), bounds)) => (svg, bounds),
+            other => panic!("unexpected import result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_line_flips_y_and_moves_to_origin() {
+        let text = dxf("", "0\nLINE\n8\n0\n10\n5\n20\n5\n11\n15\n21\n10\n");
+        let (svg, bounds) = import(&text);
+        assert_eq!((bounds.width(), bounds.height()), (10.0, 5.0));
+        assert_eq!(svg.paths.len(), 1);
+        assert_eq!(svg.paths[0].d, "M 0.000 5.000 L 10.000 0.000");
+        // Outlines only: closed shapes must not render filled
+        assert_eq!(svg.paths[0].fill.as_deref(), Some("none"));
+    }
+
+    #[test]
+    fn test_insunits_inches() {
+        let text = dxf(
+            "9\n$INSUNITS\n70\n1\n",
+            "0\nLINE\n10\n0\n20\n0\n11\n1\n21\n2\n",
+        );
+        let (_, bounds) = import(&text);
+        assert!((bounds.width() - 25.4).abs() < 1e-9);
+        assert!((bounds.height() - 50.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_circle_within_tolerance() {
+        let shapes = parse_dxf(&dxf("", "0\nCIRCLE\n10\n0\n20\n0\n40\n10\n"), 0.01).unwrap();
+        let circle = &shapes[0];
+        assert!(circle.closed);
+        for window in circle.points.windows(2) {
+            let mid = (
+                (window[0].0 + window[1].0) / 2.0,
+                (window[0].1 + window[1].1) / 2.0,
+            );
+            let deviation = 10.0 - mid.0.hypot(mid.1);
+            assert!(deviation <= 0.01 + 1e-9, "deviation {deviation}");
+        }
+    }
+
+    #[test]
+    fn test_arc_wraps_through_zero() {
+        let text = dxf("", "0\nARC\n10\n0\n20\n0\n40\n1\n50\n270\n51\n90\n");
+        let shapes = parse_dxf(&text, 0.01).unwrap();
+        let points = &shapes[0].points;
+        let first = points[0];
+        let last = points[points.len() - 1];
+        assert!(first.0.abs() < 1e-9 && (first.1 + 1.0).abs() < 1e-9);
+        assert!(last.0.abs() < 1e-9 && (last.1 - 1.0).abs() < 1e-9);
+        // Counter-clockwise from 270° to 90° passes through +X
+        assert!(points.iter().all(|p| p.0 >= -1e-9));
+    }
+
+    #[test]
+    fn test_lwpolyline_bulge_and_closed() {
+        // Semicircle from (0,0) to (2,0) bulging below, closed back along the chord
+        let text = dxf(
+            "",
+            "0\nLWPOLYLINE\n90\n2\n70\n1\n10\n0\n20\n0\n42\n1\n10\n2\n20\n0\n",
+        );
+        let shapes = parse_dxf(&text, 0.01).unwrap();
+        let shape = &shapes[0];
+        assert!(shape.closed);
+        assert_eq!(shape.points[0], (0.0, 0.0));
+        let lowest = shape.points.iter().map(|p| p.1).fold(f64::MAX, f64::min);
+        assert!((lowest + 1.0).abs() < 1e-3);
+
+        let (svg, bounds) = import(&text);
+        assert!((bounds.width() - 2.0).abs() < 1e-9);
+        assert!(svg.paths[0].d.ends_with(" Z"));
+    }
+
+    #[test]
+    fn test_no_entities_is_error() {
+        let result = DxfImporter.import(
+            Path::new("empty.dxf"),
+            dxf("", "").as_bytes(),
+            &ImportOptions::default(),
+        );
+        assert!(matches!(result, Err(ImportError::DxfParse(_))));
+    }
+
+    #[test]
+    fn test_chord_tolerance_from_options() {
+        let text = dxf("", "0\nCIRCLE\n10\n0\n20\n0\n40\n10\n");
+        let segments = |chord_tolerance| match DxfImporter.import(
+            Path::new("circle.dxf"),
+            text.as_bytes(),
+            &ImportOptions { chord_tolerance },
+        ) {
+            Ok((DocumentKind::Svg(svg), _)) => svg.paths[0].d.matches(" L").count(),
+            other => panic!("unexpected import result: {other:?}"),
+        };
+        assert!(segments(1.0) < segments(DEFAULT_CHORD_TOLERANCE));
+
+        let result = DxfImporter.import(
+            Path::new("circle.dxf"),
+            text.as_bytes(),
+            &ImportOptions {
+                chord_tolerance: 0.0,
+            },
+        );
+        assert!(matches!(result, Err(ImportError::InvalidOption(_))));
+    }
+}
@@ -1,12 +1,22 @@
 //! Workspace management for imported documents.
 //!
-//! Handles SVG and bitmap imports, document list, bounds calculation,
+//! Handles SVG, DXF and bitmap imports, document list, bounds calculation,
 //! and workspace persistence.
 
 pub mod document;
+pub mod dxf;
 pub mod import;
 pub mod persistence;
 
-pub use document::{Document, DocumentId, DocumentKind, DocumentList, BoundingBox, Transform};
-pub use import::{import_file, import_from_bytes, ImportError};
-pub use persistence::{WorkspaceData, WorkspaceSettings, save_workspace, load_workspace};
+pub use document::{
+    BoundingBox, CutSettings, Document, DocumentId, DocumentKind, DocumentList, Transform,
+    TransformBatch,
+};
+pub use import::{
+    import_file, import_from_bytes, inspect_file, supported_extensions, ImportError, ImportInfo,
+    ImportOptions,
+};
+pub use persistence::{
+    bed_preset_names, load_workspace, load_workspace_bytes, save_workspace, WorkspaceData,
+    WorkspaceSettings,
+};
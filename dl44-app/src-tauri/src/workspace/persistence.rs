@@ -45,6 +45,108 @@ pub struct WorkspaceSettings {
     pub grid_spacing: f64,
     /// Show grid
     pub show_grid: bool,
+    /// Draw every Nth grid line bold, counted from the origin (0 = none)
+    #[serde(default = "default_grid_major_every")]
+    pub grid_major_every: u32,
+    /// Bed corner where the machine's X0 Y0 sits, so the grid and rulers
+    /// can count from there
+    #[serde(default)]
+    pub origin_corner: OriginCorner,
+    /// Snap document positions to the grid when they are moved
+    #[serde(default)]
+    pub snap_enabled: bool,
+    /// Raise Z to `safe_z` before travel moves (machines with Z / pen-lift)
+    #[serde(default)]
+    pub use_safe_z: bool,
+    /// Travel height in work coordinates (mm)
+    #[serde(default = "default_safe_z")]
+    pub safe_z: f64,
+    /// Park X in machine coordinates (mm)
+    #[serde(default)]
+    pub park_x: f64,
+    /// Park Y in machine coordinates (mm)
+    #[serde(default)]
+    pub park_y: f64,
+}
+
+fn default_safe_z() -> f64 {
+    5.0
+}
+
+fn default_grid_major_every() -> u32 {
+    10
+}
+
+/// Bed corner holding the machine origin, as seen from above with the
+/// front of the machine at the bottom. Most diode lasers home to the
+/// front left; some gantries home to the back and use negative space or
+/// count Y the other way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OriginCorner {
+    #[default]
+    BottomLeft,
+    TopLeft,
+    TopRight,
+    BottomRight,
+}
+
+impl WorkspaceSettings {
+    /// Z height for travel moves, if enabled
+    pub fn travel_z(&self) -> Option<f64> {
+        self.use_safe_z.then_some(self.safe_z)
+    }
+
+    /// Apply a named bed preset, leaving all other settings unchanged.
+    ///
+    /// [`CUSTOM_BED_PRESET`] keeps the current size. Returns false if the
+    /// name is unknown.
+    pub fn apply_bed_preset(&mut self, name: &str) -> bool {
+        if name == CUSTOM_BED_PRESET {
+            return true;
+        }
+        let Some(preset) = BED_PRESETS.iter().find(|p| p.name == name) else {
+            return false;
+        };
+        self.width = preset.width;
+        self.height = preset.height;
+        self.grid_spacing = preset.grid_spacing;
+        true
+    }
+}
+
+/// Bed size preset for a common machine
+#[derive(Debug, Clone, Copy)]
+pub struct BedPreset {
+    pub name: &'static str,
+    /// Bed width in mm
+    pub width: f64,
+    /// Bed height in mm
+    pub height: f64,
+    /// Grid spacing in mm
+    pub grid_spacing: f64,
+}
+
+/// Preset name that keeps the user's own bed size
+pub const CUSTOM_BED_PRESET: &str = "Custom";
+
+/// Known machine bed sizes
+#[rustfmt::skip]
+pub const BED_PRESETS: &[BedPreset] = &[
+    BedPreset { name: "Ortur LM2 400x400", width: 400.0, height: 400.0, grid_spacing: 10.0 },
+    BedPreset { name: "Ortur LM3 400x390", width: 400.0, height: 390.0, grid_spacing: 10.0 },
+    BedPreset { name: "xTool D1 430x400", width: 430.0, height: 400.0, grid_spacing: 10.0 },
+    BedPreset { name: "Sculpfun S9 410x420", width: 410.0, height: 420.0, grid_spacing: 10.0 },
+    BedPreset { name: "Atomstack A5 410x400", width: 410.0, height: 400.0, grid_spacing: 10.0 },
+    BedPreset { name: "Eleksmaker A3 300x200", width: 300.0, height: 200.0, grid_spacing: 10.0 },
+];
+
+/// Names of all bed presets, ending with [`CUSTOM_BED_PRESET`]
+pub fn bed_preset_names() -> Vec<&'static str> {
+    BED_PRESETS
+        .iter()
+        .map(|p| p.name)
+        .chain(std::iter::once(CUSTOM_BED_PRESET))
+        .collect()
 }
 
 impl Default for WorkspaceSettings {
@@ -54,6 +156,24 @@ impl Default for WorkspaceSettings {
             height: 400.0,
             grid_spacing: 10.0,
             show_grid: true,
+            grid_major_every: default_grid_major_every(),
+            origin_corner: OriginCorner::default(),
+            snap_enabled: false,
+            use_safe_z: false,
+            safe_z: default_safe_z(),
+            park_x: 0.0,
+            park_y: 0.0,
+        }
+    }
+}
+
+impl WorkspaceData {
+    /// Copy for the frontend, with documents leaving out their image data
+    pub fn without_image_data(&self) -> Self {
+        Self {
+            version: self.version,
+            documents: self.documents.without_image_data(),
+            settings: self.settings.clone(),
         }
     }
 }
@@ -77,8 +197,13 @@ pub fn save_workspace(path: &Path, data: &WorkspaceData) -> Result<(), Persisten
 
 /// Load workspace from a file
 pub fn load_workspace(path: &Path) -> Result<WorkspaceData, PersistenceError> {
-    let json = fs::read_to_string(path)?;
-    let data: WorkspaceData = serde_json::from_str(&json)?;
+    load_workspace_bytes(&fs::read(path)?)
+}
+
+/// Load workspace from the contents of a workspace file (e.g. dropped
+/// onto the window), with the same checks as [`load_workspace`]
+pub fn load_workspace_bytes(bytes: &[u8]) -> Result<WorkspaceData, PersistenceError> {
+    let data: WorkspaceData = serde_json::from_slice(bytes)?;
 
     // Check version compatibility
     if data.version > FORMAT_VERSION {
@@ -87,3 +212,66 @@ pub fn load_workspace(path: &Path) -> Result<WorkspaceData, PersistenceError> {
 
     Ok(data)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_bed_preset_only_changes_bed() {
+        let mut settings = WorkspaceSettings {
+            use_safe_z: true,
+            show_grid: false,
+            ..WorkspaceSettings::default()
+        };
+
+        assert!(settings.apply_bed_preset("xTool D1 430x400"));
+        assert_eq!((settings.width, settings.height), (430.0, 400.0));
+        assert!(settings.use_safe_z && !settings.show_grid);
+
+        assert!(settings.apply_bed_preset(CUSTOM_BED_PRESET));
+        assert_eq!((settings.width, settings.height), (430.0, 400.0));
+
+        assert!(!settings.apply_bed_preset("Unknown 1x1"));
+        assert!(bed_preset_names().ends_with(&[CUSTOM_BED_PRESET]));
+    }
+
+    #[test]
+    fn test_grid_settings_default_for_older_files() {
+        let json = r#"{"width": 300.0, "height": 200.0, "grid_spacing": 5.0, "show_grid": true}"#;
+        let settings: WorkspaceSettings = serde_json::from_str(json).unwrap();
+        assert_eq!(settings.grid_major_every, 10);
+        assert_eq!(settings.origin_corner, OriginCorner::BottomLeft);
+
+        let settings = WorkspaceSettings {
+            grid_major_every: 4,
+            origin_corner: OriginCorner::TopRight,
+            ..WorkspaceSettings::default()
+        };
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(json.contains(r#""origin_corner":"TopRight""#));
+        let loaded: WorkspaceSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.grid_major_every, 4);
+        assert_eq!(loaded.origin_corner, OriginCorner::TopRight);
+    }
+
+    #[test]
+    fn test_load_workspace_bytes() {
+        let mut data = WorkspaceData::default();
+        data.settings.width = 430.0;
+        let bytes = serde_json::to_vec(&data).unwrap();
+        let loaded = load_workspace_bytes(&bytes).unwrap();
+        assert_eq!(loaded.settings.width, 430.0);
+
+        data.version = FORMAT_VERSION + 1;
+        let bytes = serde_json::to_vec(&data).unwrap();
+        assert!(matches!(
+            load_workspace_bytes(&bytes),
+            Err(PersistenceError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            load_workspace_bytes(b"not json"),
+            Err(PersistenceError::Json(_))
+        ));
+    }
+}
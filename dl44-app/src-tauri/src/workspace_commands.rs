@@ -2,12 +2,21 @@
 
 use parking_lot::Mutex;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, State};
 
+use crate::commands::{AppState, CommandError};
+use crate::events::{self, JOB_ERROR_EVENT};
+use crate::gcode::{
+    self, preflight, render, GcodeHeaderInfo, GenerateError, LineSources, RasterParams,
+};
+use crate::grbl::{ControllerError, DeviceId, StreamProgress, StreamState, DEFAULT_DEVICE};
 use crate::workspace::{
-    import_file, import_from_bytes, load_workspace, save_workspace, BoundingBox, Document,
-    DocumentId, DocumentList, ImportError, Transform, WorkspaceData, WorkspaceSettings,
+    bed_preset_names, import_file, import_from_bytes, load_workspace, load_workspace_bytes,
+    save_workspace, BoundingBox, CutSettings, Document, DocumentId, DocumentKind, DocumentList,
+    ImportError, ImportInfo, ImportOptions, Transform, TransformBatch, WorkspaceData,
+    WorkspaceSettings,
 };
 
 /// Workspace state
@@ -15,6 +24,8 @@ pub struct WorkspaceState {
     pub data: Mutex<WorkspaceData>,
     /// Path to current workspace file (if saved)
     pub current_file: Mutex<Option<PathBuf>>,
+    /// Changed since the last save, load, or new workspace
+    dirty: AtomicBool,
 }
 
 impl WorkspaceState {
@@ -22,8 +33,19 @@ impl WorkspaceState {
         Self {
             data: Mutex::new(WorkspaceData::default()),
             current_file: Mutex::new(None),
+            dirty: AtomicBool::new(false),
         }
     }
+
+    /// Record an unsaved change
+    pub fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether there are unsaved changes
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Relaxed)
+    }
 }
 
 impl Default for WorkspaceState {
@@ -57,8 +79,75 @@ impl From<crate::workspace::persistence::PersistenceError> for WorkspaceError {
     }
 }
 
+impl From<GenerateError> for WorkspaceError {
+    fn from(e: GenerateError) -> Self {
+        Self {
+            message: e.to_string(),
+            code: "GENERATE_ERROR".into(),
+        }
+    }
+}
+
+impl From<ControllerError> for WorkspaceError {
+    fn from(e: ControllerError) -> Self {
+        let e = CommandError::from(e);
+        Self {
+            message: e.message,
+            code: e.code,
+        }
+    }
+}
+
 type WorkspaceResult<T> = Result<T, WorkspaceError>;
 
+/// Payload of the `job-error` event
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct JobError {
+    /// Failure reason, as in the job progress
+    pub message: String,
+    /// Zero-based index of the rejected line in the generated program
+    pub line: Option<usize>,
+    /// Document whose G-code contained the rejected line
+    pub source_document: Option<DocumentId>,
+}
+
+/// What a preflight check is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub enum PreflightKind {
+    /// Running the job: every check
+    Job,
+    /// Tracing the job's outline with the laser off or low: only where it
+    /// goes and how long it takes, since the program itself doesn't run
+    Frame,
+}
+
+/// What a job or its frame would do, for a confirmation dialog before
+/// running it
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PreflightReport {
+    /// Extents of all moves, which a frame traces; None for an empty program
+    pub bounds: Option<BoundingBox>,
+    /// Whether every move stays on the bed
+    pub within_bed: bool,
+    /// Run time without acceleration, so a lower bound (seconds). For a
+    /// frame, the outline at the rapid rate
+    pub estimated_time: f64,
+    /// Program lines, not counting header comments
+    pub line_count: usize,
+    /// Documents with moves outside the bed (jobs only)
+    pub out_of_bounds_documents: Vec<DocumentId>,
+    /// Whether a rapid would move with the laser on: S stays nonzero
+    /// during G0 moves and laser mode (`$32=1`) isn't known to be on
+    /// (jobs only)
+    pub has_laser_on_rapids: bool,
+}
+
+/// Maximum number of grid cells allowed in a single tile operation
+const MAX_TILE_COUNT: u32 = 500;
+
+/// Maximum document name length (characters)
+const MAX_DOCUMENT_NAME_LEN: usize = 100;
+
 /// Get current workspace data
 #[tauri::command]
 pub fn get_workspace(state: State<Arc<WorkspaceState>>) -> WorkspaceData {
@@ -71,19 +160,161 @@ pub fn get_workspace_settings(state: State<Arc<WorkspaceState>>) -> WorkspaceSet
     state.data.lock().settings.clone()
 }
 
+/// Apply workspace settings that the controllers also use
+fn sync_controller_settings(app: &AppState, settings: &WorkspaceSettings) {
+    for (_, controller) in app.devices.all() {
+        controller.set_safe_z(settings.travel_z());
+        controller.set_park_position(settings.park_x, settings.park_y);
+    }
+}
+
+/// Record the workspace's file path; the serial log follows it.
+///
+/// Called when the workspace was just saved, loaded, or started afresh,
+/// so it has no unsaved changes.
+fn set_current_file(state: &WorkspaceState, app: &AppState, path: Option<PathBuf>) {
+    for (_, controller) in app.devices.all() {
+        controller.serial_log().set_workspace(path.as_deref());
+    }
+    *state.current_file.lock() = path;
+    state.dirty.store(false, Ordering::Relaxed);
+}
+
 /// Update workspace settings
 #[tauri::command]
 pub fn update_workspace_settings(
     state: State<Arc<WorkspaceState>>,
+    app: State<AppState>,
     settings: WorkspaceSettings,
 ) {
+    sync_controller_settings(&app, &settings);
     state.data.lock().settings = settings;
+    state.mark_dirty();
+}
+
+/// List bed size preset names (the last, "Custom", keeps the current size)
+#[tauri::command]
+pub fn get_bed_presets() -> Vec<String> {
+    bed_preset_names().into_iter().map(String::from).collect()
+}
+
+/// Set bed width, height, and grid from a named preset.
+///
+/// Documents and other settings are left as they are. Returns the updated
+/// settings.
+#[tauri::command]
+pub fn apply_bed_preset(
+    state: State<Arc<WorkspaceState>>,
+    name: String,
+) -> WorkspaceResult<WorkspaceSettings> {
+    let mut data = state.data.lock();
+    if !data.settings.apply_bed_preset(&name) {
+        return Err(WorkspaceError {
+            message: format!("Unknown bed preset: {}", name),
+            code: "NOT_FOUND".into(),
+        });
+    }
+    state.mark_dirty();
+    Ok(data.settings.clone())
+}
+
+/// Counts, bounds, and save state of the workspace, for the sidebar
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WorkspaceSummary {
+    pub document_count: usize,
+    pub visible_count: usize,
+    /// Combined bounds of the visible documents
+    pub combined_bounds: BoundingBox,
+    /// Bed width and height (mm)
+    pub bed_size: (f64, f64),
+    /// Changed since the last save, load, or new workspace
+    pub dirty: bool,
+    /// Workspace file path, None while unsaved
+    pub current_file: Option<String>,
+}
+
+/// Get the workspace summary in one call
+#[tauri::command]
+pub fn get_workspace_summary(state: State<Arc<WorkspaceState>>) -> WorkspaceSummary {
+    let data = state.data.lock();
+    WorkspaceSummary {
+        document_count: data.documents.all().len(),
+        visible_count: data.documents.visible().count(),
+        combined_bounds: data.documents.combined_bounds(),
+        bed_size: (data.settings.width, data.settings.height),
+        dirty: state.is_dirty(),
+        current_file: state
+            .current_file
+            .lock()
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string()),
+    }
 }
 
 /// Get all documents
 #[tauri::command]
 pub fn get_documents(state: State<Arc<WorkspaceState>>) -> Vec<Document> {
-    state.data.lock().documents.all().to_vec()
+    state
+        .data
+        .lock()
+        .documents
+        .all()
+        .iter()
+        .map(Document::without_image_data)
+        .collect()
+}
+
+/// Get a bitmap document's full image data URL.
+///
+/// `get_documents` leaves image data out to keep the list small; the
+/// canvas fetches it here when it draws the document.
+#[tauri::command]
+pub fn get_document_image(
+    state: State<Arc<WorkspaceState>>,
+    id: DocumentId,
+) -> WorkspaceResult<String> {
+    let data = state.data.lock();
+    match data.documents.get(id).map(|doc| &doc.kind) {
+        Some(DocumentKind::Bitmap(content)) => Ok(content.data_url.clone()),
+        Some(_) => Err(WorkspaceError {
+            message: format!("Document {} is not a bitmap", id),
+            code: "INVALID_ARGUMENT".into(),
+        }),
+        None => Err(WorkspaceError {
+            message: format!("Document {} not found", id),
+            code: "NOT_FOUND".into(),
+        }),
+    }
+}
+
+/// Preview what a bitmap document engraves with `params`, as a PNG data
+/// URL: the image after invert/brightness/contrast/gamma and dithering,
+/// one pixel row per scanline. Omitted parameters take their defaults.
+#[tauri::command]
+pub fn preview_raster(
+    state: State<Arc<WorkspaceState>>,
+    id: DocumentId,
+    params: Option<RasterParams>,
+) -> WorkspaceResult<String> {
+    // Copy the document out so encoding doesn't hold the workspace lock
+    let doc = state.data.lock().documents.get(id).cloned();
+    match doc {
+        Some(doc) => match &doc.kind {
+            DocumentKind::Bitmap(content) => Ok(gcode::raster::preview_raster(
+                content,
+                &doc.workspace_bounds(),
+                &params.unwrap_or_default(),
+            )?),
+            _ => Err(WorkspaceError {
+                message: format!("Document {} is not a bitmap", id),
+                code: "INVALID_ARGUMENT".into(),
+            }),
+        },
+        None => Err(WorkspaceError {
+            message: format!("Document {} not found", id),
+            code: "NOT_FOUND".into(),
+        }),
+    }
 }
 
 /// Get combined bounds of all visible documents
@@ -92,36 +323,109 @@ pub fn get_workspace_bounds(state: State<Arc<WorkspaceState>>) -> BoundingBox {
     state.data.lock().documents.combined_bounds()
 }
 
-/// Import a file into the workspace
+/// Tight frame of what the job would mark, without generating G-code.
+///
+/// For showing and tracing the frame before cutting ("frame then cut").
+/// SVGs are measured by their paths rather than their canvas, so the frame
+/// can be smaller than `get_workspace_bounds`. Empty when nothing visible
+/// would be marked.
+#[tauri::command]
+pub fn get_job_frame(state: State<Arc<WorkspaceState>>) -> BoundingBox {
+    gcode::job_frame(&state.data.lock().documents).unwrap_or_default()
+}
+
+/// Get combined bounds of all documents, including hidden ones
+///
+/// For fitting the view to everything; framing uses `get_workspace_bounds`.
+#[tauri::command]
+pub fn get_workspace_bounds_all(state: State<Arc<WorkspaceState>>) -> BoundingBox {
+    state.data.lock().documents.combined_bounds_all()
+}
+
+/// Get a single document's bounds in workspace coordinates
+#[tauri::command]
+pub fn get_document_bounds(
+    state: State<Arc<WorkspaceState>>,
+    id: DocumentId,
+) -> Option<BoundingBox> {
+    state
+        .data
+        .lock()
+        .documents
+        .get(id)
+        .map(|doc| doc.workspace_bounds())
+}
+
+/// Find pairs of visible documents whose bounds overlap.
+///
+/// Compares bounding boxes only, so a pair may be reported even when the
+/// shapes themselves don't cross. Boxes that merely touch don't count.
+#[tauri::command]
+pub fn find_overlapping_documents(
+    state: State<Arc<WorkspaceState>>,
+) -> Vec<(DocumentId, DocumentId)> {
+    state.data.lock().documents.overlapping_pairs()
+}
+
+/// Get workspace bounds for every document (including hidden ones)
+#[tauri::command]
+pub fn get_all_document_bounds(
+    state: State<Arc<WorkspaceState>>,
+) -> Vec<(DocumentId, BoundingBox)> {
+    state
+        .data
+        .lock()
+        .documents
+        .all()
+        .iter()
+        .map(|doc| (doc.id, doc.workspace_bounds()))
+        .collect()
+}
+
+/// Report what a file contains without adding it to the workspace.
+/// Omitted options take their defaults.
+#[tauri::command]
+pub fn inspect_file(path: String, options: Option<ImportOptions>) -> WorkspaceResult<ImportInfo> {
+    Ok(crate::workspace::inspect_file(
+        &PathBuf::from(path),
+        &options.unwrap_or_default(),
+    )?)
+}
+
+/// Import a file into the workspace. Omitted options take their defaults.
 #[tauri::command]
 pub fn import_document(
     state: State<Arc<WorkspaceState>>,
     path: String,
+    options: Option<ImportOptions>,
 ) -> WorkspaceResult<Document> {
     let path = PathBuf::from(path);
-    let doc = import_file(&path)?;
+    let doc = import_file(&path, &options.unwrap_or_default())?;
 
     let mut data = state.data.lock();
-    let id = data.documents.add(doc.clone());
+    let id = data.documents.add(doc);
+    state.mark_dirty();
 
-    // Return the document with assigned ID
-    Ok(data.documents.get(id).cloned().unwrap())
+    // Return the document with assigned ID, listed as by `get_documents`
+    Ok(data.documents.get(id).unwrap().without_image_data())
 }
 
-/// Import from raw bytes (for drag-drop)
+/// Import from raw bytes (for drag-drop), as [`import_document`]
 #[tauri::command]
 pub fn import_document_bytes(
     state: State<Arc<WorkspaceState>>,
     name: String,
     bytes: Vec<u8>,
     mime_type: String,
+    options: Option<ImportOptions>,
 ) -> WorkspaceResult<Document> {
-    let doc = import_from_bytes(&name, &bytes, &mime_type)?;
+    let doc = import_from_bytes(&name, &bytes, &mime_type, &options.unwrap_or_default())?;
 
     let mut data = state.data.lock();
     let id = data.documents.add(doc);
+    state.mark_dirty();
 
-    Ok(data.documents.get(id).cloned().unwrap())
+    Ok(data.documents.get(id).unwrap().without_image_data())
 }
 
 /// Remove a document
@@ -131,21 +435,35 @@ pub fn remove_document(
     id: DocumentId,
 ) -> WorkspaceResult<()> {
     let mut data = state.data.lock();
-    data.documents.remove(id);
+    if data.documents.remove(id).is_some() {
+        state.mark_dirty();
+    }
     Ok(())
 }
 
 /// Update document transform
+///
+/// Rejects non-finite values and non-positive scales, wraps the rotation
+/// into [0, 360), snaps the position to the grid if snapping is enabled,
+/// and returns the transform that was actually applied.
 #[tauri::command]
 pub fn update_document_transform(
     state: State<Arc<WorkspaceState>>,
     id: DocumentId,
     transform: Transform,
-) -> WorkspaceResult<()> {
+) -> WorkspaceResult<Transform> {
+    let mut transform = transform.normalized().map_err(|message| WorkspaceError {
+        message,
+        code: "INVALID_ARGUMENT".into(),
+    })?;
     let mut data = state.data.lock();
+    if data.settings.snap_enabled {
+        transform.snap_to_grid(data.settings.grid_spacing);
+    }
     if let Some(doc) = data.documents.get_mut(id) {
         doc.transform = transform;
-        Ok(())
+        state.mark_dirty();
+        Ok(transform)
     } else {
         Err(WorkspaceError {
             message: format!("Document {} not found", id),
@@ -154,6 +472,204 @@ pub fn update_document_transform(
     }
 }
 
+/// Update the transforms of several documents under one lock, so a
+/// multi-document drag moves the whole selection at once
+///
+/// Every transform is validated as in `update_document_transform` before
+/// any is applied; one invalid transform rejects the whole batch. Locked
+/// and unknown documents are skipped and reported.
+#[tauri::command]
+pub fn update_transforms(
+    state: State<Arc<WorkspaceState>>,
+    updates: Vec<(DocumentId, Transform)>,
+) -> WorkspaceResult<TransformBatch> {
+    let updates = updates
+        .into_iter()
+        .map(|(id, transform)| {
+            transform
+                .normalized()
+                .map(|transform| (id, transform))
+                .map_err(|message| WorkspaceError {
+                    message: format!("Document {}: {}", id, message),
+                    code: "INVALID_ARGUMENT".into(),
+                })
+        })
+        .collect::<WorkspaceResult<Vec<_>>>()?;
+
+    let mut data = state.data.lock();
+    let grid_spacing = data
+        .settings
+        .snap_enabled
+        .then_some(data.settings.grid_spacing);
+    let batch = data.documents.apply_transforms(updates, grid_spacing);
+    if !batch.updated.is_empty() {
+        state.mark_dirty();
+    }
+    Ok(batch)
+}
+
+/// Replace an SVG document with a bitmap rendered at `dpi`, for vectors
+/// meant to be engraved as an image.
+///
+/// The bitmap keeps the document's transform and bounds, so it stays where
+/// it was on the bed. Returns the updated document without its image
+/// data, which `get_document_image` fetches.
+#[tauri::command]
+pub fn rasterize_document(
+    state: State<Arc<WorkspaceState>>,
+    id: DocumentId,
+    dpi: f64,
+) -> WorkspaceResult<Document> {
+    let invalid = |message: String| WorkspaceError {
+        message,
+        code: "INVALID_ARGUMENT".into(),
+    };
+    if !(dpi.is_finite() && dpi > 0.0) {
+        return Err(invalid(format!("Invalid DPI: {}", dpi)));
+    }
+
+    // Render from a copy so a large image doesn't hold the workspace lock
+    let doc = state.data.lock().documents.get(id).cloned();
+    let Some(doc) = doc else {
+        return Err(WorkspaceError {
+            message: format!("Document {} not found", id),
+            code: "NOT_FOUND".into(),
+        });
+    };
+    let DocumentKind::Svg(svg) = &doc.kind else {
+        return Err(invalid(format!("Document {} is not an SVG", id)));
+    };
+    let (width, height) = render::render_dimensions(&doc.workspace_bounds(), dpi);
+    if width as u64 * height as u64 > render::MAX_RENDER_PIXELS {
+        return Err(invalid(format!(
+            "{}x{} px at {} DPI is too large to render",
+            width, height, dpi
+        )));
+    }
+    let content = render::bitmap_content(&render::render_svg(&doc, svg, dpi)?)?;
+
+    let mut data = state.data.lock();
+    let Some(doc) = data.documents.get_mut(id) else {
+        return Err(WorkspaceError {
+            message: format!("Document {} not found", id),
+            code: "NOT_FOUND".into(),
+        });
+    };
+    doc.kind = DocumentKind::Bitmap(content);
+    state.mark_dirty();
+    Ok(doc.without_image_data())
+}
+
+/// Snap a document's position to the nearest grid intersection
+#[tauri::command]
+pub fn snap_document_to_grid(
+    state: State<Arc<WorkspaceState>>,
+    id: DocumentId,
+) -> WorkspaceResult<Transform> {
+    let mut data = state.data.lock();
+    let spacing = data.settings.grid_spacing;
+    if let Some(doc) = data.documents.get_mut(id) {
+        doc.transform.snap_to_grid(spacing);
+        state.mark_dirty();
+        Ok(doc.transform)
+    } else {
+        Err(WorkspaceError {
+            message: format!("Document {} not found", id),
+            code: "NOT_FOUND".into(),
+        })
+    }
+}
+
+/// Distance between neighbouring tiles of a document spanning `bounds`,
+/// refusing a tile count outside 1..=[`MAX_TILE_COUNT`], and spacing that
+/// isn't finite or would overlap whole tiles
+fn tile_pitch(
+    cols: u32,
+    rows: u32,
+    bounds: &BoundingBox,
+    spacing_x: f64,
+    spacing_y: f64,
+) -> WorkspaceResult<(f64, f64)> {
+    let invalid = |message: String| WorkspaceError {
+        message,
+        code: "INVALID_ARGUMENT".into(),
+    };
+    let count = cols.saturating_mul(rows);
+    if count == 0 || count > MAX_TILE_COUNT {
+        return Err(invalid(format!(
+            "Tile count must be between 1 and {} (got {}x{})",
+            MAX_TILE_COUNT, cols, rows
+        )));
+    }
+    let pitch = |axis: &str, size: f64, spacing: f64| {
+        if !spacing.is_finite() {
+            return Err(invalid(format!("Invalid {} spacing: {}", axis, spacing)));
+        }
+        if spacing < 0.0 && -spacing >= size {
+            return Err(invalid(format!(
+                "{} spacing {} overlaps the {:.3} mm document",
+                axis, spacing, size
+            )));
+        }
+        Ok(size + spacing)
+    };
+    Ok((
+        pitch("X", bounds.width(), spacing_x)?,
+        pitch("Y", bounds.height(), spacing_y)?,
+    ))
+}
+
+/// Tile a document into a cols x rows grid array
+///
+/// The original document occupies the first cell; copies fill the rest,
+/// separated by `spacing_x`/`spacing_y` mm between document edges.
+/// Negative spacing overlaps neighbours, by less than a whole document.
+/// Returns the created copies, without their image data.
+#[tauri::command]
+pub fn tile_document(
+    state: State<Arc<WorkspaceState>>,
+    id: DocumentId,
+    cols: u32,
+    rows: u32,
+    spacing_x: f64,
+    spacing_y: f64,
+) -> WorkspaceResult<Vec<Document>> {
+    let mut data = state.data.lock();
+    let (origin, bounds) = match data.documents.get(id) {
+        Some(doc) => (doc.transform, doc.workspace_bounds()),
+        None => {
+            return Err(WorkspaceError {
+                message: format!("Document {} not found", id),
+                code: "NOT_FOUND".into(),
+            })
+        }
+    };
+
+    let (pitch_x, pitch_y) = tile_pitch(cols, rows, &bounds, spacing_x, spacing_y)?;
+    let mut created = Vec::with_capacity((cols * rows) as usize - 1);
+
+    for row in 0..rows {
+        for col in 0..cols {
+            if row == 0 && col == 0 {
+                continue;
+            }
+            let Some(copy_id) = data.documents.duplicate(id) else {
+                continue;
+            };
+            if let Some(copy) = data.documents.get_mut(copy_id) {
+                copy.transform.x = origin.x + col as f64 * pitch_x;
+                copy.transform.y = origin.y + row as f64 * pitch_y;
+                created.push(copy.without_image_data());
+            }
+        }
+    }
+
+    if !created.is_empty() {
+        state.mark_dirty();
+    }
+    Ok(created)
+}
+
 /// Update document visibility
 #[tauri::command]
 pub fn update_document_visibility(
@@ -164,6 +680,7 @@ pub fn update_document_visibility(
     let mut data = state.data.lock();
     if let Some(doc) = data.documents.get_mut(id) {
         doc.visible = visible;
+        state.mark_dirty();
         Ok(())
     } else {
         Err(WorkspaceError {
@@ -173,6 +690,77 @@ pub fn update_document_visibility(
     }
 }
 
+/// Rename a document, trimming surrounding whitespace.
+///
+/// Returns the updated document without its image data.
+#[tauri::command]
+pub fn rename_document(
+    state: State<Arc<WorkspaceState>>,
+    id: DocumentId,
+    name: String,
+) -> WorkspaceResult<Document> {
+    let name = name.trim();
+    let len = name.chars().count();
+    if len == 0 || len > MAX_DOCUMENT_NAME_LEN {
+        return Err(WorkspaceError {
+            message: format!(
+                "Document name must be 1 to {} characters (got {})",
+                MAX_DOCUMENT_NAME_LEN, len
+            ),
+            code: "INVALID_ARGUMENT".into(),
+        });
+    }
+
+    let mut data = state.data.lock();
+    if let Some(doc) = data.documents.get_mut(id) {
+        doc.name = name.to_string();
+        state.mark_dirty();
+        Ok(doc.without_image_data())
+    } else {
+        Err(WorkspaceError {
+            message: format!("Document {} not found", id),
+            code: "NOT_FOUND".into(),
+        })
+    }
+}
+
+/// Set (or clear, with null) a document's laser settings
+#[tauri::command]
+pub fn set_document_cut_settings(
+    state: State<Arc<WorkspaceState>>,
+    id: DocumentId,
+    settings: Option<CutSettings>,
+) -> WorkspaceResult<()> {
+    let mut data = state.data.lock();
+    if let Some(doc) = data.documents.get_mut(id) {
+        doc.cut_settings = settings;
+        state.mark_dirty();
+        Ok(())
+    } else {
+        Err(WorkspaceError {
+            message: format!("Document {} not found", id),
+            code: "NOT_FOUND".into(),
+        })
+    }
+}
+
+/// Apply a z-order change to document `id`, marking the workspace dirty
+/// only if the document moved
+fn change_order(
+    state: &WorkspaceState,
+    id: DocumentId,
+    change: impl FnOnce(&mut DocumentList) -> Option<bool>,
+) -> WorkspaceResult<()> {
+    let moved = change(&mut state.data.lock().documents).ok_or_else(|| WorkspaceError {
+        message: format!("Document {} not found", id),
+        code: "NOT_FOUND".into(),
+    })?;
+    if moved {
+        state.mark_dirty();
+    }
+    Ok(())
+}
+
 /// Reorder document in the list
 #[tauri::command]
 pub fn reorder_document(
@@ -180,44 +768,335 @@ pub fn reorder_document(
     id: DocumentId,
     new_index: usize,
 ) -> WorkspaceResult<()> {
-    let mut data = state.data.lock();
-    data.documents.reorder(id, new_index);
+    change_order(&state, id, |documents| documents.reorder(id, new_index))
+}
+
+/// Move document up/down in z-order by a relative offset
+#[tauri::command]
+pub fn reorder_document_relative(
+    state: State<Arc<WorkspaceState>>,
+    id: DocumentId,
+    delta: i32,
+) -> WorkspaceResult<()> {
+    change_order(&state, id, |documents| {
+        documents.reorder_relative(id, delta)
+    })
+}
+
+/// Move document to the front of the z-order
+#[tauri::command]
+pub fn bring_document_to_front(
+    state: State<Arc<WorkspaceState>>,
+    id: DocumentId,
+) -> WorkspaceResult<()> {
+    change_order(&state, id, |documents| documents.bring_to_front(id))
+}
+
+/// Move document to the back of the z-order
+#[tauri::command]
+pub fn send_document_to_back(
+    state: State<Arc<WorkspaceState>>,
+    id: DocumentId,
+) -> WorkspaceResult<()> {
+    change_order(&state, id, |documents| documents.send_to_back(id))
+}
+
+/// Generate the job for all visible documents without checking it
+/// against the bed, along with the documents and settings it came from
+fn generate_unchecked(
+    state: &WorkspaceState,
+    app: &AppState,
+    device_id: Option<DeviceId>,
+    raster: Option<RasterParams>,
+    power_schedule: Option<Vec<u32>>,
+) -> WorkspaceResult<(Vec<String>, LineSources, DocumentList, WorkspaceSettings)> {
+    // Snapshot so raster generation doesn't hold the workspace lock
+    let (documents, settings) = {
+        let data = state.data.lock();
+        (data.documents.clone(), data.settings.clone())
+    };
+    let raster = raster.unwrap_or_default();
+    let power_schedule = power_schedule.unwrap_or_default();
+    let controller = app.controller(device_id)?;
+    let max_power = controller.max_power();
+    let (lines, sources) = gcode::generate_job(
+        &documents,
+        &raster,
+        &power_schedule,
+        settings.travel_z(),
+        max_power,
+    )?;
+    Ok((lines, sources, documents, settings))
+}
+
+/// Generate the job for all visible documents, with each line's source
+/// and the header describing it
+fn generate_program(
+    state: &WorkspaceState,
+    app: &AppState,
+    device_id: Option<DeviceId>,
+    raster: Option<RasterParams>,
+    power_schedule: Option<Vec<u32>>,
+) -> WorkspaceResult<(Vec<String>, LineSources, GcodeHeaderInfo)> {
+    let (lines, sources, documents, settings) =
+        generate_unchecked(state, app, device_id, raster, power_schedule)?;
+    gcode::check_bed_limits(&lines, settings.width, settings.height)?;
+
+    let workspace = state
+        .current_file
+        .lock()
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned());
+    let header = GcodeHeaderInfo::new(
+        &lines,
+        &sources,
+        &documents,
+        workspace,
+        settings.width,
+        settings.height,
+    );
+    Ok((lines, sources, header))
+}
+
+/// Generate a G-code program for all visible documents.
+///
+/// Omitted raster parameters take their defaults. Fails if any move,
+/// including raster overscan travel, leaves the bed. Power is clamped to
+/// the `$30` of the device (the default one unless `device_id` is given);
+/// a power schedule (S value per pass) must stay within it.
+///
+/// Unless turned off in preferences, the program starts with comments
+/// describing the app version, workspace, bed and operations.
+#[tauri::command]
+pub fn generate_workspace_gcode(
+    state: State<Arc<WorkspaceState>>,
+    app: State<AppState>,
+    raster: Option<RasterParams>,
+    power_schedule: Option<Vec<u32>>,
+    device_id: Option<DeviceId>,
+) -> WorkspaceResult<Vec<String>> {
+    let (lines, _, header) = generate_program(&state, &app, device_id, raster, power_schedule)?;
+    if lines.is_empty() || !app.preferences.gcode_header() {
+        return Ok(lines);
+    }
+    let mut program = header.render();
+    program.extend(lines);
+    Ok(program)
+}
+
+/// Check the job [`generate_workspace_gcode`] would produce, or its frame,
+/// without failing when it leaves the bed.
+///
+/// For a job, reports its extents, which documents leave the bed, a run
+/// time estimate, and whether any rapid would fire the laser. A frame
+/// only traces the extents, so it gets them with its own time estimate.
+/// Rapids are timed at the slower of the device's cached `$110`/`$111`,
+/// or a default when settings haven't been read; nothing is sent to the
+/// device.
+#[tauri::command]
+pub fn preflight_job(
+    state: State<Arc<WorkspaceState>>,
+    app: State<AppState>,
+    kind: PreflightKind,
+    raster: Option<RasterParams>,
+    power_schedule: Option<Vec<u32>>,
+    device_id: Option<DeviceId>,
+) -> WorkspaceResult<PreflightReport> {
+    let (lines, sources, _, settings) =
+        generate_unchecked(&state, &app, device_id, raster, power_schedule)?;
+    let controller = app.controller(device_id)?;
+    let rapid_rate = [110, 111]
+        .into_iter()
+        .filter_map(|n| controller.cached_setting(n)?.trim().parse::<f64>().ok())
+        .filter(|rate| *rate > 0.0)
+        .reduce(f64::min)
+        .unwrap_or(preflight::DEFAULT_RAPID_RATE);
+    let laser_mode = controller.cached_setting(32).map(|v| v.trim() == "1");
+
+    Ok(preflight_report(
+        kind,
+        &lines,
+        &sources,
+        (settings.width, settings.height),
+        rapid_rate,
+        laser_mode,
+    ))
+}
+
+/// The checks [`preflight_job`] runs for `kind`, on a bed of `bed`
+/// (width, height) mm
+fn preflight_report(
+    kind: PreflightKind,
+    lines: &[String],
+    sources: &LineSources,
+    bed: (f64, f64),
+    rapid_rate: f64,
+    laser_mode: Option<bool>,
+) -> PreflightReport {
+    let (width, height) = bed;
+    let bounds = gcode::job::program_extents(lines);
+    let mut report = PreflightReport {
+        bounds,
+        within_bed: gcode::check_bed_limits(lines, width, height).is_ok(),
+        estimated_time: 0.0,
+        line_count: lines.len(),
+        out_of_bounds_documents: Vec::new(),
+        has_laser_on_rapids: false,
+    };
+    if kind == PreflightKind::Frame {
+        report.estimated_time =
+            bounds.map_or(0.0, |b| 2.0 * (b.width() + b.height()) / rapid_rate * 60.0);
+        return report;
+    }
+
+    let check = preflight::check_program(lines, sources, rapid_rate);
+    let on_bed =
+        |b: &BoundingBox| b.x_min >= 0.0 && b.y_min >= 0.0 && b.x_max <= width && b.y_max <= height;
+    for (id, extents) in &check.document_extents {
+        if !on_bed(extents) && !report.out_of_bounds_documents.contains(id) {
+            report.out_of_bounds_documents.push(*id);
+        }
+    }
+    report.estimated_time = check.estimated_seconds;
+    report.has_laser_on_rapids = !check.powered_rapids.is_empty() && laser_mode != Some(true);
+    report
+}
+
+/// Whether generated G-code starts with header comments
+#[tauri::command]
+pub fn get_gcode_header_enabled(app: State<AppState>) -> bool {
+    app.preferences.gcode_header()
+}
+
+/// Turn header comments in generated G-code on or off
+#[tauri::command]
+pub fn set_gcode_header_enabled(app: State<AppState>, enabled: bool) {
+    app.preferences.set_gcode_header(enabled);
+}
+
+/// Generate and stream a G-code program for all visible documents.
+///
+/// Generation works as for [`generate_workspace_gcode`]. If GRBL rejects a
+/// line, a `job-error` event names the document that line came from.
+#[tauri::command]
+pub fn run_workspace_job(
+    state: State<Arc<WorkspaceState>>,
+    app: State<AppState>,
+    handle: AppHandle,
+    raster: Option<RasterParams>,
+    power_schedule: Option<Vec<u32>>,
+    strip_comments: bool,
+    device_id: Option<DeviceId>,
+) -> WorkspaceResult<()> {
+    let (lines, sources, _) = generate_program(&state, &app, device_id, raster, power_schedule)?;
+    let id = device_id.unwrap_or(DEFAULT_DEVICE);
+    let on_finish = move |progress: &StreamProgress| {
+        let StreamState::Failed(message) = &progress.state else {
+            return;
+        };
+        let error = JobError {
+            message: message.clone(),
+            line: progress.error_line,
+            source_document: progress.error_line.and_then(|l| sources.document_at(l)),
+        };
+        if let Err(e) = events::emit_for_device(&handle, JOB_ERROR_EVENT, id, error) {
+            log::warn!("Failed to emit job error: {}", e);
+        }
+    };
+    app.controller(device_id)?
+        .run_gcode_program_with(lines, strip_comments, on_finish)?;
     Ok(())
 }
 
 /// Clear all documents
+///
+/// Fails with `UNSAVED_CHANGES` as for [`load_workspace_from_file`].
 #[tauri::command]
-pub fn clear_workspace(state: State<Arc<WorkspaceState>>) {
-    let mut data = state.data.lock();
-    data.documents.clear();
-    *state.current_file.lock() = None;
+pub fn clear_workspace(
+    state: State<Arc<WorkspaceState>>,
+    app: State<AppState>,
+    force: Option<bool>,
+) -> WorkspaceResult<()> {
+    ensure_saved_or_forced(&state, force)?;
+    state.data.lock().documents.clear();
+    set_current_file(&state, &app, None);
+    Ok(())
 }
 
 /// Save workspace to file
 #[tauri::command]
 pub fn save_workspace_to_file(
     state: State<Arc<WorkspaceState>>,
+    app: State<AppState>,
     path: String,
 ) -> WorkspaceResult<()> {
     let path = PathBuf::from(&path);
     let data = state.data.lock();
     save_workspace(&path, &data)?;
     drop(data);
-    *state.current_file.lock() = Some(path);
+    set_current_file(&state, &app, Some(path));
     Ok(())
 }
 
-/// Load workspace from file
+/// Refuse to replace a workspace that has unsaved changes unless `force`
+fn ensure_saved_or_forced(state: &WorkspaceState, force: Option<bool>) -> WorkspaceResult<()> {
+    if state.is_dirty() && !force.unwrap_or(false) {
+        return Err(WorkspaceError {
+            message: "The workspace has unsaved changes".into(),
+            code: "UNSAVED_CHANGES".into(),
+        });
+    }
+    Ok(())
+}
+
+/// Whether the workspace has changed since it was last saved, loaded, or created
+#[tauri::command]
+pub fn is_workspace_dirty(state: State<Arc<WorkspaceState>>) -> bool {
+    state.is_dirty()
+}
+
+/// Load workspace from file. The returned data leaves out image data,
+/// as `get_documents` does.
+///
+/// Fails with `UNSAVED_CHANGES` if the current workspace has unsaved
+/// changes, unless `force` is set.
 #[tauri::command]
 pub fn load_workspace_from_file(
     state: State<Arc<WorkspaceState>>,
+    app: State<AppState>,
     path: String,
+    force: Option<bool>,
 ) -> WorkspaceResult<WorkspaceData> {
+    ensure_saved_or_forced(&state, force)?;
     let path = PathBuf::from(&path);
     let data = load_workspace(&path)?;
-    *state.data.lock() = data.clone();
-    *state.current_file.lock() = Some(path);
-    Ok(data)
+    sync_controller_settings(&app, &data.settings);
+    let listed = data.without_image_data();
+    *state.data.lock() = data;
+    set_current_file(&state, &app, Some(path));
+    Ok(listed)
+}
+
+/// Load a workspace from file contents the frontend already has (a
+/// dropped `.dl44` file, cloud storage). There's no path, so the
+/// workspace is untitled afterwards, like a new one.
+///
+/// Fails with `UNSAVED_CHANGES` as for [`load_workspace_from_file`].
+#[tauri::command]
+pub fn load_workspace_from_bytes(
+    state: State<Arc<WorkspaceState>>,
+    app: State<AppState>,
+    bytes: Vec<u8>,
+    force: Option<bool>,
+) -> WorkspaceResult<WorkspaceData> {
+    ensure_saved_or_forced(&state, force)?;
+    let data = load_workspace_bytes(&bytes)?;
+    sync_controller_settings(&app, &data.settings);
+    let listed = data.without_image_data();
+    *state.data.lock() = data;
+    set_current_file(&state, &app, None);
+    Ok(listed)
 }
 
 /// Get current workspace file path
@@ -231,8 +1110,115 @@ pub fn get_workspace_file_path(state: State<Arc<WorkspaceState>>) -> Option<Stri
 }
 
 /// Create new workspace (clears current)
+///
+/// Fails with `UNSAVED_CHANGES` as for [`load_workspace_from_file`].
 #[tauri::command]
-pub fn new_workspace(state: State<Arc<WorkspaceState>>) {
-    *state.data.lock() = WorkspaceData::default();
-    *state.current_file.lock() = None;
+pub fn new_workspace(
+    state: State<Arc<WorkspaceState>>,
+    app: State<AppState>,
+    force: Option<bool>,
+) -> WorkspaceResult<()> {
+    ensure_saved_or_forced(&state, force)?;
+    let data = WorkspaceData::default();
+    sync_controller_settings(&app, &data.settings);
+    *state.data.lock() = data;
+    set_current_file(&state, &app, None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::document::SvgContent;
+
+    #[test]
+    fn test_unsaved_changes_need_force() {
+        let state = WorkspaceState::new();
+        assert!(ensure_saved_or_forced(&state, None).is_ok());
+
+        state.mark_dirty();
+        match ensure_saved_or_forced(&state, None) {
+            Err(e) => assert_eq!(e.code, "UNSAVED_CHANGES"),
+            Ok(()) => panic!("dirty workspace replaced without force"),
+        }
+        assert!(ensure_saved_or_forced(&state, Some(false)).is_err());
+        assert!(ensure_saved_or_forced(&state, Some(true)).is_ok());
+    }
+
+    #[test]
+    fn test_preflight_kinds() {
+        let lines: Vec<String> = ["M4 S0", "G0 X0 Y0", "G1 X30 Y40 F600 S500", "G0 X130 Y40"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let mut sources = LineSources::default();
+        sources.start(0, Some(1));
+        sources.start(3, Some(2));
+        let report = |kind| preflight_report(kind, &lines, &sources, (100.0, 100.0), 1200.0, None);
+
+        let job = report(PreflightKind::Job);
+        assert!(!job.within_bed);
+        assert_eq!(job.line_count, 4);
+        assert_eq!(job.out_of_bounds_documents, [2]);
+        assert!(job.has_laser_on_rapids);
+        // 50mm at 600, then 100mm rapid at 1200
+        assert!((job.estimated_time - 10.0).abs() < 1e-9);
+
+        // A frame traces the 130 x 40 extents with the program's own
+        // checks left out
+        let frame = report(PreflightKind::Frame);
+        let bounds = frame.bounds.unwrap();
+        assert_eq!((bounds.width(), bounds.height()), (130.0, 40.0));
+        assert!(!frame.within_bed);
+        assert!(frame.out_of_bounds_documents.is_empty());
+        assert!(!frame.has_laser_on_rapids);
+        assert!((frame.estimated_time - 17.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_change_order_marks_dirty_only_on_move() {
+        let state = WorkspaceState::new();
+        let id = state.data.lock().documents.add(Document {
+            id: 0,
+            name: "doc".into(),
+            source_path: None,
+            kind: DocumentKind::Svg(SvgContent {
+                width: 10.0,
+                height: 10.0,
+                paths: Vec::new(),
+                raw_svg: String::new(),
+            }),
+            transform: Default::default(),
+            visible: true,
+            locked: false,
+            original_bounds: BoundingBox::new(0.0, 0.0, 10.0, 10.0),
+            cut_settings: None,
+        });
+
+        // A lone document is already at the front
+        assert!(change_order(&state, id, |documents| documents.bring_to_front(id)).is_ok());
+        assert!(!state.is_dirty());
+        match change_order(&state, 99, |documents| documents.send_to_back(99)) {
+            Err(e) => assert_eq!(e.code, "NOT_FOUND"),
+            Ok(()) => panic!("missing document reordered"),
+        }
+        assert!(!state.is_dirty());
+    }
+
+    #[test]
+    fn test_tile_pitch() {
+        let bounds = BoundingBox::new(0.0, 0.0, 20.0, 10.0);
+        assert_eq!(tile_pitch(3, 2, &bounds, 5.0, -2.5).unwrap(), (25.0, 7.5));
+
+        let invalid = |result: WorkspaceResult<(f64, f64)>| {
+            result.is_err_and(|e| e.code == "INVALID_ARGUMENT")
+        };
+        assert!(invalid(tile_pitch(0, 2, &bounds, 0.0, 0.0)));
+        assert!(invalid(tile_pitch(MAX_TILE_COUNT, 2, &bounds, 0.0, 0.0)));
+        assert!(invalid(tile_pitch(2, 2, &bounds, f64::NAN, 0.0)));
+        assert!(invalid(tile_pitch(2, 2, &bounds, 0.0, f64::INFINITY)));
+        // Copies may overlap, but not land on top of each other
+        assert!(invalid(tile_pitch(2, 2, &bounds, -20.0, 0.0)));
+        assert!(invalid(tile_pitch(2, 2, &bounds, 0.0, -10.5)));
+    }
 }
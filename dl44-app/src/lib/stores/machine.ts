@@ -3,6 +3,7 @@
  */
 import { writable, derived, get } from "svelte/store";
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 
 // Types matching Rust structs
 
@@ -39,6 +40,8 @@ export type MachineState =
 
 export interface MachineStatus {
   state: MachineState;
+  /** State suffix (Hold:1 = still stopping, Door:0-3); null if none */
+  sub_state: number | null;
   machine_pos: Position;
   work_pos: Position | null;
   work_offset: Position | null;
@@ -51,11 +54,30 @@ export interface MachineStatus {
   line_number: number | null;
 }
 
+/** Why a connection failed (unit variants serialize as strings) */
+export type ConnectErrorKind =
+  | "PortNotFound"
+  | "PermissionDenied"
+  | "PortBusy"
+  | "NoResponse"
+  | { Other: string };
+
 export type ConnectionState =
   | { Disconnected: null }
   | { Connecting: null }
   | { Connected: { port: string; baud: number } }
-  | { Error: string };
+  | { Error: { message: string; kind: ConnectErrorKind } };
+
+/** Device used when a command doesn't name one */
+export const DEFAULT_DEVICE = 0;
+
+export interface DeviceInfo {
+  id: number;
+  connection: ConnectionState;
+}
+
+/** Payload of a per-device event (`status`, `heartbeat`, `frame-progress`, ...) */
+export type DeviceEvent<T> = T & { device_id: number };
 
 export interface PortInfo {
   path: string;
@@ -65,15 +87,71 @@ export interface PortInfo {
   serial_number: string | null;
 }
 
+/** Firmware version parsed from the welcome banner */
+export interface FirmwareVersion {
+  version: string;
+  variant: string | null;
+}
+
+/**
+ * Backend ControllerError, serialized as a tagged enum: unit variants are
+ * strings ("NotConnected"), others objects ({ Alarm: 9 }).
+ */
+export type ControllerErrorKind =
+  | "NotConnected"
+  | "AlreadyConnected"
+  | "Cancelled"
+  | { Serial: string }
+  | { Disconnected: string }
+  | { Timeout: number }
+  | { GrblError: number }
+  | { Alarm: number }
+  | { InvalidState: string }
+  | { File: string }
+  | { Internal: string };
+
+/** Most recent controller error */
+export interface LastError {
+  error: ControllerErrorKind;
+  message: string;
+}
+
+/** Alarm code of an error, or null if it isn't an alarm */
+export function alarmCode(error: ControllerErrorKind): number | null {
+  return typeof error === "object" && "Alarm" in error ? error.Alarm : null;
+}
+
 export interface ControllerSnapshot {
   connection: ConnectionState;
   status: MachineStatus;
   welcome_message: string | null;
-  last_error: string | null;
+  firmware: FirmwareVersion | null;
+  last_error: LastError | null;
   /** Pending alarm: [alarm_code, unique_id] for deduplication */
   pending_alarm: [number, number] | null;
   /** Whether the last status poll got a fresh response (false = stale/timeout) */
   status_is_fresh: boolean;
+  /** Free planner blocks (null if the device doesn't report Bf) */
+  planner_free: number | null;
+  /** Free serial RX buffer bytes (null if the device doesn't report Bf) */
+  rx_free: number | null;
+  /** A running job has almost no motion queued and may stutter */
+  buffer_low: boolean;
+  /** Current feed rate in mm/min whatever the device reports in (null until $13 is known) */
+  feed_rate_mm_min: number | null;
+  /** A held job is set aside for jogging; cycle start returns and resumes */
+  hold_jog: boolean;
+  /** A held job's remaining lines are kept back; cycle start runs only the queued moves */
+  hold_drain: boolean;
+}
+
+/** GRBL `$` setting with its v1.1 metadata */
+export interface GrblSetting {
+  number: number;
+  value: string;
+  name: string;
+  description: string;
+  units: string;
 }
 
 /** Structured error from backend commands */
@@ -108,6 +186,22 @@ export function hasError(state: ConnectionState | null | undefined): boolean {
   return state != null && typeof state === "object" && "Error" in state;
 }
 
+/** Suggested fix for a connection failure, if there is one */
+export function connectErrorHint(kind: ConnectErrorKind): string | null {
+  switch (kind) {
+    case "PortNotFound":
+      return "Port not found - check the cable and refresh the port list";
+    case "PermissionDenied":
+      return "Permission denied - add your user to the dialout group and log in again";
+    case "PortBusy":
+      return "Port busy - close other software using the laser";
+    case "NoResponse":
+      return "No response - check the baud rate and that the device is powered";
+    default:
+      return null;
+  }
+}
+
 export function getConnectionInfo(
   state: ConnectionState | null | undefined
 ): { port: string; baud: number } | null {
@@ -153,8 +247,8 @@ export const selectedBaud = writable<number>(115200);
 /** Full controller snapshot */
 export const controllerSnapshot = writable<ControllerSnapshot | null>(null);
 
-/** Status polling interval ID */
-let pollingInterval: ReturnType<typeof setInterval> | null = null;
+/** Unsubscribe handle for the backend status stream */
+let statusUnlisten: UnlistenFn | null = null;
 
 /** Polling active flag */
 export const isPolling = writable(false);
@@ -172,6 +266,7 @@ export const lastError = derived(errors, ($errors) =>
 const defaultConnectionState: ConnectionState = { Disconnected: null };
 const defaultStatus: MachineStatus = {
   state: "unknown",
+  sub_state: null,
   machine_pos: { x: 0, y: 0, z: 0 },
   work_pos: null,
   work_offset: null,
@@ -273,6 +368,8 @@ export function getErrorMessage(error: CommandError): string {
       return `Alarm ${error.details ?? ""}: Machine requires attention`;
     case "NOT_CONNECTED":
       return "Not connected to device";
+    case "DISCONNECTED":
+      return "Device disconnected (cable unplugged or powered off)";
     case "SERIAL_ERROR":
       return `Serial communication error: ${error.message}`;
     case "INVALID_STATE":
@@ -284,10 +381,13 @@ export function getErrorMessage(error: CommandError): string {
 
 // Actions
 
-/** Refresh available serial ports */
-export async function refreshPorts(): Promise<void> {
+/**
+ * Refresh available serial ports. A list from the last second is reused
+ * unless `refresh` asks for a new scan.
+ */
+export async function refreshPorts(refresh = false): Promise<void> {
   try {
-    const portList = await invoke<PortInfo[]>("list_serial_ports");
+    const portList = await invoke<PortInfo[]>("list_serial_ports", { refresh });
     ports.set(portList);
 
     // Auto-select first port if none selected
@@ -311,6 +411,18 @@ export async function loadBaudRates(): Promise<void> {
   }
 }
 
+/** Select the baud rate that last connected to a port, if one was saved */
+export async function selectLastBaudForPort(path: string): Promise<void> {
+  try {
+    const baud = await invoke<number | null>("get_last_baud_for_port", { path });
+    if (baud !== null) {
+      selectedBaud.set(baud);
+    }
+  } catch (e) {
+    console.error("Failed to get saved baud rate:", e);
+  }
+}
+
 /** Connect to the selected port */
 export async function connect(): Promise<void> {
   const port = get(selectedPort);
@@ -320,15 +432,50 @@ export async function connect(): Promise<void> {
     throw new Error("No port selected");
   }
 
+  // Listen before starting so the outcome event can't be missed
+  let resolveOutcome!: (state: ConnectionState) => void;
+  const outcome = new Promise<ConnectionState>((resolve) => (resolveOutcome = resolve));
+  const unlisten = await listen<DeviceEvent<ConnectionState>>(
+    "connection-state-changed",
+    (event) => {
+      if (event.payload.device_id === DEFAULT_DEVICE) resolveOutcome(event.payload);
+    }
+  );
+
   try {
-    await invoke("connect", { port, baudRate: baud });
+    // Returns immediately; the UI shows Connecting while the device answers
+    await invoke("connect_async", { port, baudRate: baud });
     await refreshSnapshot();
-    startPolling();
+
+    const state = await outcome;
+    if (typeof state === "object" && "Error" in state) {
+      throw { message: state.Error.message, code: "SERIAL_ERROR", details: null };
+    }
+    await refreshSnapshot();
+    await startPolling();
   } catch (e) {
     const error = parseError(e);
     addError(error);
     await refreshSnapshot();
     throw error;
+  } finally {
+    unlisten();
+  }
+}
+
+/** List the registered devices with their connection state */
+export async function listDevices(): Promise<DeviceInfo[]> {
+  return invoke<DeviceInfo[]>("list_devices");
+}
+
+/** Disconnect and forget a device other than the default one */
+export async function removeDevice(deviceId: number): Promise<void> {
+  try {
+    await invoke("remove_device", { deviceId });
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
   }
 }
 
@@ -354,48 +501,68 @@ export async function refreshSnapshot(): Promise<void> {
   }
 }
 
-/** Poll status from device */
+/** Surface a NEW alarm detected during polling (once per alarm ID) */
+function surfacePendingAlarm(snapshot: ControllerSnapshot | null): void {
+  if (snapshot?.pending_alarm) {
+    const [alarmCode, alarmId] = snapshot.pending_alarm;
+    // Only surface if this is a new alarm we haven't already shown
+    if (alarmId !== lastSurfacedAlarmId) {
+      lastSurfacedAlarmId = alarmId;
+      addError({
+        message: `Alarm ${alarmCode}: Machine requires attention`,
+        code: "ALARM",
+        details: `code ${alarmCode}`,
+      });
+    }
+  }
+}
+
+/** Poll status from device (one-off; the backend streams status while connected) */
 export async function pollStatus(): Promise<void> {
   try {
     await invoke("poll_status");
     await refreshSnapshot();
-
-    // Check for NEW alarm detected during polling and surface it (once)
-    const snapshot = get(controllerSnapshot);
-    if (snapshot?.pending_alarm) {
-      const [alarmCode, alarmId] = snapshot.pending_alarm;
-      // Only surface if this is a new alarm we haven't already shown
-      if (alarmId !== lastSurfacedAlarmId) {
-        lastSurfacedAlarmId = alarmId;
-        addError({
-          message: `Alarm ${alarmCode}: Machine requires attention`,
-          code: "ALARM",
-          details: `code ${alarmCode}`,
-        });
-      }
-    }
+    surfacePendingAlarm(get(controllerSnapshot));
   } catch (e) {
     // Don't spam errors for polling failures - they're expected during disconnects
     console.warn("Poll status failed:", e);
   }
 }
 
-/** Start automatic status polling */
-export function startPolling(intervalMs: number = 250): void {
+/**
+ * Start receiving status snapshots from the backend status stream.
+ *
+ * The backend adapts its poll rate to the machine state (see setPollRates).
+ */
+export async function startPolling(): Promise<void> {
   stopPolling();
   isPolling.set(true);
-  pollingInterval = setInterval(pollStatus, intervalMs);
+  statusUnlisten = await listen<DeviceEvent<ControllerSnapshot>>("status", (event) => {
+    // This UI drives the default device; other devices have their own ids
+    if (event.payload.device_id !== DEFAULT_DEVICE) return;
+    controllerSnapshot.set(event.payload);
+    surfacePendingAlarm(event.payload);
+  });
 }
 
-/** Stop automatic status polling */
+/** Stop receiving status snapshots */
 export function stopPolling(): void {
-  if (pollingInterval) {
-    clearInterval(pollingInterval);
-    pollingInterval = null;
+  if (statusUnlisten) {
+    statusUnlisten();
+    statusUnlisten = null;
   }
   isPolling.set(false);
 }
 
+/** Set backend status poll rates (Hz) while moving and while idle */
+export async function setPollRates(activeHz: number, idleHz: number): Promise<void> {
+  try {
+    await invoke("set_poll_rates", { activeHz, idleHz });
+  } catch (e) {
+    addError(parseError(e));
+  }
+}
+
 // Control actions with error handling
 
 /** Send home command */
@@ -409,10 +576,73 @@ export async function home(): Promise<void> {
   }
 }
 
-/** Send unlock command */
-export async function unlock(): Promise<void> {
+/** Move to the park position (machine coordinates, from workspace settings) */
+export async function park(): Promise<void> {
   try {
-    await invoke("unlock");
+    await invoke("park");
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+}
+
+/** Send unlock command; resolves to false if the device stayed in alarm */
+export async function unlock(): Promise<boolean> {
+  try {
+    return await invoke<boolean>("unlock");
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+}
+
+/** Read device `$$` settings with names, descriptions, and units */
+export async function getGrblSettingsDescribed(): Promise<GrblSetting[]> {
+  try {
+    return await invoke<GrblSetting[]>("get_grbl_settings_described");
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+}
+
+/** Read one `$N` setting's raw value (null if the device doesn't have it) */
+export async function getGrblSetting(number: number): Promise<string | null> {
+  try {
+    return await invoke<string | null>("get_grbl_setting", { number });
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+}
+
+/** Outcome of restoring one setting from a backup */
+export interface SettingWriteResult {
+  number: number;
+  value: string;
+  /** Why the device rejected the setting, if it did */
+  error: string | null;
+}
+
+/** Save device `$$` settings to a .txt or .json file */
+export async function exportGrblSettings(path: string): Promise<void> {
+  try {
+    await invoke("export_grbl_settings", { path });
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+}
+
+/** Write settings from a .txt or .json backup back to the device */
+export async function importGrblSettings(path: string): Promise<SettingWriteResult[]> {
+  try {
+    return await invoke<SettingWriteResult[]>("import_grbl_settings", { path });
   } catch (e) {
     const error = parseError(e);
     addError(error);
@@ -437,6 +667,240 @@ export async function jog(
   }
 }
 
+/**
+ * Jog while a job is paused by feed hold (GRBL 1.1+). A jog never resumes
+ * the job: while moves are still queued it is refused (see hold_drain) and
+ * cycle start runs just those. Then the laser is turned off for jogging;
+ * cycle start returns to where the job stopped and resumes it.
+ */
+export async function jogDuringHold(
+  x: number | null,
+  y: number | null,
+  z: number | null,
+  feed: number,
+  incremental: boolean = true
+): Promise<void> {
+  try {
+    await invoke("jog_during_hold", { x, y, z, feed, incremental });
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+}
+
+export type JogAxis = "X" | "Y" | "Z";
+
+export type JogDirection = "Positive" | "Negative";
+
+/** Stored jog step/feed preset */
+export interface JogPreset {
+  label: string;
+  step_mm: number;
+  feed: number;
+}
+
+/** Jog presets, in number-key order */
+export const jogPresets = writable<JogPreset[]>([]);
+
+/** Load stored jog presets */
+export async function loadJogPresets(): Promise<void> {
+  try {
+    jogPresets.set(await invoke<JogPreset[]>("get_jog_presets"));
+  } catch (e) {
+    console.error("Failed to get jog presets:", e);
+  }
+}
+
+/** Replace the stored jog presets */
+export async function saveJogPresets(presets: JogPreset[]): Promise<void> {
+  try {
+    await invoke("set_jog_presets", { presets });
+    jogPresets.set(presets);
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+}
+
+/** Whether incremental jogs across machine zero out of the homed travel are refused */
+export async function getJogTravelCheck(): Promise<boolean> {
+  return invoke<boolean>("get_jog_travel_check");
+}
+
+/** Turn the jog travel check on or off */
+export async function setJogTravelCheck(enabled: boolean): Promise<void> {
+  await invoke("set_jog_travel_check", { enabled });
+}
+
+/** Lowest feed accepted for jogs and frames (units/min) */
+export async function getMinFeed(): Promise<number> {
+  return invoke<number>("get_min_feed");
+}
+
+/** Change the lowest feed accepted for jogs and frames */
+export async function setMinFeed(minFeed: number): Promise<void> {
+  try {
+    await invoke("set_min_feed", { minFeed });
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+}
+
+/** Laser usage totals over all jobs, for maintenance */
+export interface UsageStats {
+  total_on_seconds: number;
+  total_distance_mm: number;
+  job_count: number;
+}
+
+/** Get the laser usage totals */
+export async function getUsageStats(): Promise<UsageStats> {
+  return invoke<UsageStats>("get_usage_stats");
+}
+
+/** Zero the laser usage totals (e.g. after replacing the diode) */
+export async function resetUsageStats(): Promise<void> {
+  await invoke("reset_usage_stats");
+}
+
+export type ConnectionEventKind =
+  | "ConnectAttempt"
+  | "Reconnect"
+  | "Connected"
+  | "WelcomeReceived"
+  | "Disconnected"
+  | "ConnectFailed"
+  | "ConnectionLost";
+
+/** A connection lifecycle step, for diagnosing dropped connections */
+export interface ConnectionEvent {
+  /** Milliseconds since the Unix epoch */
+  timestamp_ms: number;
+  kind: ConnectionEventKind;
+  /** Port and baud rate, welcome banner, or error message */
+  message: string | null;
+}
+
+/** Connection events received this session, oldest first */
+export const connectionEvents = writable<DeviceEvent<ConnectionEvent>[]>([]);
+
+/** Final progress of a streamed job, as sent with `job-complete` */
+export interface JobProgress {
+  state: "Running" | "Completed" | "Cancelled" | { Failed: string };
+  total_lines: number;
+  lines_completed: number;
+  error_count: number;
+  /** GRBL reported the program end ([MSG:Pgm End]) */
+  program_end: boolean;
+  /** The machine has run every line, not just acknowledged it */
+  finished: boolean;
+}
+
+/** Last job the machine finished running */
+export const lastCompletedJob = writable<DeviceEvent<JobProgress> | null>(null);
+
+/** Outcome of probing a port with `testPort` */
+export interface PortTestResult {
+  /** A GRBL status report came back */
+  responded: boolean;
+  /** Welcome banner, if the device sent one (e.g. rebooting as the port opened) */
+  welcome: string | null;
+  /** Time from the answered status query to its report */
+  latency_ms: number;
+}
+
+/**
+ * Check whether a port answers like a GRBL device without connecting
+ * (e.g. from a setup wizard). Rejects while a device uses the port.
+ */
+export async function testPort(path: string, baudRate: number): Promise<PortTestResult> {
+  return invoke<PortTestResult>("test_port", { path, baudRate });
+}
+
+/** Get a device's connection events (the backend keeps the last 200) */
+export async function getConnectionEvents(deviceId?: number): Promise<ConnectionEvent[]> {
+  return invoke<ConnectionEvent[]>("get_connection_events", { deviceId });
+}
+
+/** Serial log options */
+export interface SerialLogSettings {
+  enabled: boolean;
+  /** Size in bytes at which the log file is rotated */
+  max_bytes: number;
+}
+
+/** A serial log file (current or rotated) */
+export interface LogFileInfo {
+  path: string;
+  size: number;
+  /** Seconds since the Unix epoch */
+  modified: number | null;
+}
+
+/** Serial log options */
+export const serialLogSettings = writable<SerialLogSettings | null>(null);
+
+/** Load the serial log options */
+export async function loadSerialLogSettings(): Promise<void> {
+  try {
+    serialLogSettings.set(await invoke<SerialLogSettings>("get_serial_log_settings"));
+  } catch (e) {
+    console.error("Failed to get serial log settings:", e);
+  }
+}
+
+/** Enable or disable the serial log and set its rotation size */
+export async function saveSerialLogSettings(settings: SerialLogSettings): Promise<void> {
+  try {
+    await invoke("set_serial_log_settings", { settings });
+    serialLogSettings.set(await invoke<SerialLogSettings>("get_serial_log_settings"));
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+}
+
+/** List serial log files, newest first */
+export async function getLogFiles(): Promise<LogFileInfo[]> {
+  try {
+    return await invoke<LogFileInfo[]>("get_log_files");
+  } catch (e) {
+    console.error("Failed to list log files:", e);
+    return [];
+  }
+}
+
+/** Read the last lines of a serial log file */
+export async function readLogTail(path: string, lines: number): Promise<string[]> {
+  try {
+    return await invoke<string[]>("read_log_tail", { path, lines });
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+}
+
+/** Jog one step of a stored preset */
+export async function jogPreset(
+  presetIndex: number,
+  axis: JogAxis,
+  direction: JogDirection
+): Promise<void> {
+  try {
+    await invoke("jog_preset", { presetIndex, axis, direction });
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+}
+
 /** Cancel active jog */
 export async function jogCancel(): Promise<void> {
   try {
@@ -481,9 +945,81 @@ export async function softReset(): Promise<void> {
   await refreshSnapshot();
 }
 
+/** Stop any jog, job or step session and soft reset to a known state */
+export async function stopAll(): Promise<void> {
+  try {
+    await invoke("stop_all");
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+  await refreshSnapshot();
+}
+
 /** Initialize stores on app start */
 export async function initializeStores(): Promise<void> {
   await Promise.all([refreshPorts(), loadBaudRates(), refreshSnapshot()]);
+  // Backend warnings (e.g. laser mode off after connecting) join the error list
+  await listen<DeviceEvent<DeviceWarning>>("warning", (event) => {
+    const { code, message } = event.payload;
+    addError({ code, message, details: null });
+  });
+  await listen<DeviceEvent<ConnectionEvent>>("connection-event", (event) => {
+    connectionEvents.update((events) => [...events, event.payload].slice(-200));
+  });
+  await listen<DeviceEvent<JobProgress>>("job-complete", (event) => {
+    lastCompletedJob.set(event.payload);
+  });
+}
+
+/** Payload of a backend `warning` event */
+export interface DeviceWarning {
+  code: string;
+  message: string;
+}
+
+/** Turn on laser mode ($32=1) if it's off; resolves to whether it changed */
+export async function ensureLaserMode(): Promise<boolean> {
+  try {
+    return await invoke<boolean>("ensure_laser_mode");
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+}
+
+/** Status report fields selected by $10 */
+export interface ReportFields {
+  /** MPos (otherwise GRBL reports WPos) */
+  machine_pos: boolean;
+  /** Bf planner and RX buffer space */
+  buffer: boolean;
+}
+
+/**
+ * Write $10 and resolve to the fields in effect after reading it back.
+ * Leaving out MPos or Bf raises a warning, since the app relies on them.
+ */
+export async function setReportFields(fields: ReportFields): Promise<ReportFields> {
+  try {
+    return await invoke<ReportFields>("set_report_fields", { fields });
+  } catch (e) {
+    const error = parseError(e);
+    addError(error);
+    throw error;
+  }
+}
+
+/** Whether laser mode is turned on automatically after connecting */
+export async function getAutoLaserMode(): Promise<boolean> {
+  return invoke<boolean>("get_auto_laser_mode");
+}
+
+/** Opt in or out of turning on laser mode automatically after connecting */
+export async function setAutoLaserMode(enabled: boolean): Promise<void> {
+  await invoke("set_auto_laser_mode", { enabled });
 }
 
 // Override types matching Rust enums
@@ -501,6 +1037,30 @@ export type Units = "Mm" | "Inches";
 
 export type FrameMode = "LowPower" | "ConstantPower" | "LaserOff";
 
+/** Frame/boundary trace, `margin` (default 0) outside the bounds on all sides */
+export interface FrameRequest {
+  x_min: number;
+  x_max: number;
+  y_min: number;
+  y_max: number;
+  margin?: number;
+  feed: number;
+  power: number;
+  units?: Units;
+  mode: FrameMode;
+}
+
+/** Frame trace progress, emitted after each edge completes */
+export interface FrameProgress {
+  edge: number;
+  total_edges: number;
+  x: number;
+  y: number;
+}
+
+/** Progress of the running frame trace (null when not framing) */
+export const frameProgress = writable<FrameProgress | null>(null);
+
 // Override actions
 
 /** Adjust feed rate override */
@@ -537,31 +1097,21 @@ export async function spindleOverride(adjust: OverrideAdjust): Promise<void> {
 }
 
 /** Run a frame/boundary trace */
-export async function runFrame(
-  xMin: number,
-  xMax: number,
-  yMin: number,
-  yMax: number,
-  feed: number,
-  power: number,
-  units: Units = "Mm",
-  mode: FrameMode = "LowPower"
-): Promise<void> {
+export async function runFrame(frame: FrameRequest): Promise<void> {
+  frameProgress.set(null);
+  const unlisten = await listen<DeviceEvent<FrameProgress>>("frame-progress", (event) => {
+    if (event.payload.device_id !== DEFAULT_DEVICE) return;
+    frameProgress.set(event.payload);
+  });
   try {
-    await invoke("run_frame", {
-      xMin,
-      xMax,
-      yMin,
-      yMax,
-      feed,
-      power,
-      units,
-      mode,
-    });
+    await invoke("run_frame", { frame });
   } catch (e) {
     const error = parseError(e);
     addError(error);
     throw error;
+  } finally {
+    unlisten();
+    frameProgress.set(null);
   }
 }
 
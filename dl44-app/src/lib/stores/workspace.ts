@@ -30,13 +30,22 @@ export interface SvgContent {
 export interface BitmapContent {
   width: number;
   height: number;
-  data_url: string;
+  /** Omitted from document lists; see getDocumentImage */
+  data_url?: string;
   format: string;
+  thumbnail: string | null;
 }
 
 export type DocumentKind =
   | { type: "Svg"; width: number; height: number; paths: unknown[]; raw_svg: string }
-  | { type: "Bitmap"; width: number; height: number; data_url: string; format: string };
+  | {
+      type: "Bitmap";
+      width: number;
+      height: number;
+      data_url?: string;
+      format: string;
+      thumbnail: string | null;
+    };
 
 export interface Document {
   id: number;
@@ -54,8 +63,22 @@ export interface WorkspaceSettings {
   height: number;
   grid_spacing: number;
   show_grid: boolean;
+  /** Every Nth grid line from the origin is drawn bold (0 = none) */
+  grid_major_every: number;
+  /** Bed corner where the machine's X0 Y0 sits */
+  origin_corner: OriginCorner;
+  /** Raise Z to safe_z before travel moves */
+  use_safe_z: boolean;
+  /** Travel height in work coordinates (mm) */
+  safe_z: number;
+  /** Park position in machine coordinates (mm) */
+  park_x: number;
+  park_y: number;
 }
 
+/** Bed corner holding the machine origin, seen from above, front at the bottom */
+export type OriginCorner = "BottomLeft" | "TopLeft" | "TopRight" | "BottomRight";
+
 export interface WorkspaceData {
   version: number;
   documents: {
@@ -76,6 +99,12 @@ export const workspaceSettings = writable<WorkspaceSettings>({
   height: 400,
   grid_spacing: 10,
   show_grid: true,
+  grid_major_every: 10,
+  origin_corner: "BottomLeft",
+  use_safe_z: false,
+  safe_z: 5,
+  park_x: 0,
+  park_y: 0,
 });
 
 /** Currently selected document ID */
@@ -87,6 +116,15 @@ export const workspaceFilePath = writable<string | null>(null);
 /** Workspace has unsaved changes */
 export const hasUnsavedChanges = writable(false);
 
+/** File extensions the backend can import */
+export const supportedImportFormats = writable<string[]>([]);
+
+/** Pairs of visible document IDs whose bounds overlap */
+export const overlappingDocuments = writable<[number, number][]>([]);
+
+/** Bed size preset names ("Custom" last) */
+export const bedPresets = writable<string[]>([]);
+
 // Derived stores
 
 /** Combined bounds of all visible documents */
@@ -133,16 +171,114 @@ export function getDocumentWorkspaceBounds(doc: Document): BoundingBox {
 
 // Actions
 
+/** Counts, bounds, and save state of the workspace */
+export interface WorkspaceSummary {
+  document_count: number;
+  visible_count: number;
+  /** Combined bounds of the visible documents */
+  combined_bounds: BoundingBox;
+  /** Bed width and height (mm) */
+  bed_size: [number, number];
+  /** Changed since the last save, load, or new workspace */
+  dirty: boolean;
+  current_file: string | null;
+}
+
+/** Get the workspace summary, syncing the unsaved-changes flag */
+export async function getWorkspaceSummary(): Promise<WorkspaceSummary> {
+  const summary = await invoke<WorkspaceSummary>("get_workspace_summary");
+  hasUnsavedChanges.set(summary.dirty);
+  return summary;
+}
+
 /** Refresh documents from backend */
 export async function refreshDocuments(): Promise<void> {
   try {
     const docs = await invoke<Document[]>("get_documents");
     documents.set(docs);
+    await refreshOverlappingDocuments();
   } catch (e) {
     console.error("Failed to get documents:", e);
   }
 }
 
+/** Fetch a bitmap document's full image data URL (left out of get_documents) */
+export async function getDocumentImage(id: number): Promise<string> {
+  return invoke<string>("get_document_image", { id });
+}
+
+/** Raster scan parameters; omitted fields take the backend defaults */
+export interface RasterParams {
+  /** Distance between scanlines (mm) */
+  line_interval?: number;
+  bidirectional?: boolean;
+  overscan_mm?: number;
+  dither?: "None" | "FloydSteinberg" | "Atkinson" | "Threshold";
+  invert?: boolean;
+  /** -1..1, 0 = unchanged */
+  brightness?: number;
+  /** -1..1, 0 = unchanged */
+  contrast?: number;
+  /** > 1 lightens midtones, 1 = unchanged */
+  gamma?: number;
+}
+
+/**
+ * PNG data URL of what a bitmap document engraves with the given raster
+ * parameters (tone adjustments and dithering, one row per scanline)
+ */
+export async function previewRaster(id: number, params?: RasterParams): Promise<string> {
+  return invoke<string>("preview_raster", { id, params });
+}
+
+/**
+ * Tight frame of what the job would mark, without generating G-code.
+ * SVGs are measured by their paths, so this can be smaller than
+ * workspaceBounds. Null when nothing visible would be marked.
+ */
+export async function getJobFrame(): Promise<BoundingBox | null> {
+  const frame = await invoke<BoundingBox>("get_job_frame");
+  return frame.x_max > frame.x_min || frame.y_max > frame.y_min ? frame : null;
+}
+
+/** What a job would do, for a confirmation dialog before cutting */
+/** What a preflight check is for: running the job, or tracing its frame */
+export type PreflightKind = "Job" | "Frame";
+
+export interface PreflightReport {
+  /** Extents of all moves, which a frame traces; null for an empty program */
+  bounds: BoundingBox | null;
+  within_bed: boolean;
+  /** Run time without acceleration, so a lower bound (seconds) */
+  estimated_time: number;
+  line_count: number;
+  out_of_bounds_documents: number[];
+  /** A rapid would move with the laser on (laser mode not known to be on) */
+  has_laser_on_rapids: boolean;
+}
+
+/**
+ * Check the job that would be generated, with default raster parameters,
+ * or its frame. Unlike generating it, this succeeds when the job leaves the bed.
+ */
+export async function preflightJob(
+  kind: PreflightKind = "Job",
+  powerSchedule?: number[],
+  deviceId?: number
+): Promise<PreflightReport> {
+  return invoke<PreflightReport>("preflight_job", { kind, powerSchedule, deviceId });
+}
+
+/** Refresh overlapping document pairs (bounds-level, not path-level) */
+export async function refreshOverlappingDocuments(): Promise<void> {
+  try {
+    const pairs = await invoke<[number, number][]>("find_overlapping_documents");
+    overlappingDocuments.set(pairs);
+  } catch (e) {
+    console.error("Failed to find overlapping documents:", e);
+  }
+}
+
 /** Refresh workspace settings from backend */
 export async function refreshWorkspaceSettings(): Promise<void> {
   try {
@@ -153,10 +289,51 @@ export async function refreshWorkspaceSettings(): Promise<void> {
   }
 }
 
+/** Fetch importable file extensions from backend */
+export async function refreshSupportedImportFormats(): Promise<void> {
+  try {
+    const formats = await invoke<string[]>("supported_import_formats");
+    supportedImportFormats.set(formats);
+  } catch (e) {
+    console.error("Failed to get supported import formats:", e);
+  }
+}
+
+/** Fetch bed size preset names from backend */
+export async function refreshBedPresets(): Promise<void> {
+  try {
+    const presets = await invoke<string[]>("get_bed_presets");
+    bedPresets.set(presets);
+  } catch (e) {
+    console.error("Failed to get bed presets:", e);
+  }
+}
+
+/** Apply a bed size preset (documents are kept) */
+export async function applyBedPreset(name: string): Promise<void> {
+  try {
+    const settings = await invoke<WorkspaceSettings>("apply_bed_preset", { name });
+    workspaceSettings.set(settings);
+    hasUnsavedChanges.set(true);
+  } catch (e) {
+    console.error("Failed to apply bed preset:", e);
+    throw e;
+  }
+}
+
+/** Import settings that aren't part of the file; omitted fields take the backend defaults */
+export interface ImportOptions {
+  /** Maximum distance between a DXF curve and its tessellation (mm) */
+  chord_tolerance?: number;
+}
+
 /** Import a file by path */
-export async function importFile(path: string): Promise<Document | null> {
+export async function importFile(
+  path: string,
+  options?: ImportOptions
+): Promise<Document | null> {
   try {
-    const doc = await invoke<Document>("import_document", { path });
+    const doc = await invoke<Document>("import_document", { path, options });
     await refreshDocuments();
     hasUnsavedChanges.set(true);
     return doc;
@@ -170,13 +347,15 @@ export async function importFile(path: string): Promise<Document | null> {
 export async function importBytes(
   name: string,
   bytes: Uint8Array,
-  mimeType: string
+  mimeType: string,
+  options?: ImportOptions
 ): Promise<Document | null> {
   try {
     const doc = await invoke<Document>("import_document_bytes", {
       name,
       bytes: Array.from(bytes),
       mimeType,
+      options,
     });
     await refreshDocuments();
     hasUnsavedChanges.set(true);
@@ -218,6 +397,32 @@ export async function updateDocumentTransform(
   }
 }
 
+/** Result of a batch transform update */
+export interface TransformBatch {
+  /** Documents moved, with the transform actually applied */
+  updated: [number, Transform][];
+  /** Locked documents, left unchanged */
+  locked: number[];
+  not_found: number[];
+}
+
+/** Update the transforms of several documents at once (e.g. a multi-select drag) */
+export async function updateTransforms(
+  updates: [number, Transform][]
+): Promise<TransformBatch> {
+  try {
+    const batch = await invoke<TransformBatch>("update_transforms", { updates });
+    await refreshDocuments();
+    if (batch.updated.length > 0) {
+      hasUnsavedChanges.set(true);
+    }
+    return batch;
+  } catch (e) {
+    console.error("Failed to update transforms:", e);
+    throw e;
+  }
+}
+
 /** Update document visibility */
 export async function updateDocumentVisibility(
   id: number,
@@ -233,6 +438,35 @@ export async function updateDocumentVisibility(
   }
 }
 
+/** Rename a document (surrounding whitespace is trimmed) */
+export async function renameDocument(id: number, name: string): Promise<Document> {
+  try {
+    const doc = await invoke<Document>("rename_document", { id, name });
+    await refreshDocuments();
+    hasUnsavedChanges.set(true);
+    return doc;
+  } catch (e) {
+    console.error("Failed to rename document:", e);
+    throw e;
+  }
+}
+
+/**
+ * Replace an SVG document with a bitmap rendered at `dpi`, keeping its
+ * position and size
+ */
+export async function rasterizeDocument(id: number, dpi: number): Promise<Document> {
+  try {
+    const doc = await invoke<Document>("rasterize_document", { id, dpi });
+    await refreshDocuments();
+    hasUnsavedChanges.set(true);
+    return doc;
+  } catch (e) {
+    console.error("Failed to rasterize document:", e);
+    throw e;
+  }
+}
+
 /** Save workspace to file */
 export async function saveWorkspace(path: string): Promise<void> {
   try {
@@ -245,10 +479,25 @@ export async function saveWorkspace(path: string): Promise<void> {
   }
 }
 
-/** Load workspace from file */
-export async function loadWorkspace(path: string): Promise<void> {
+/** Whether the backend workspace has unsaved changes */
+export async function isWorkspaceDirty(): Promise<boolean> {
+  return invoke<boolean>("is_workspace_dirty");
+}
+
+/** Whether an error is the backend refusing to drop unsaved changes */
+export function isUnsavedChangesError(e: unknown): boolean {
+  return (e as { code?: string } | null)?.code === "UNSAVED_CHANGES";
+}
+
+/**
+ * Load workspace from file.
+ *
+ * Rejects with an `UNSAVED_CHANGES` error if the current workspace has
+ * unsaved changes; prompt, then retry with `force`.
+ */
+export async function loadWorkspace(path: string, force = false): Promise<void> {
   try {
-    await invoke<WorkspaceData>("load_workspace_from_file", { path });
+    await invoke<WorkspaceData>("load_workspace_from_file", { path, force });
     await refreshDocuments();
     await refreshWorkspaceSettings();
     workspaceFilePath.set(path);
@@ -260,10 +509,31 @@ export async function loadWorkspace(path: string): Promise<void> {
   }
 }
 
-/** Create new workspace */
-export async function newWorkspace(): Promise<void> {
+/**
+ * Load workspace from file contents (e.g. a dropped file). The workspace
+ * is untitled afterwards; rejects on unsaved changes as `loadWorkspace` does.
+ */
+export async function loadWorkspaceFromBytes(bytes: Uint8Array, force = false): Promise<void> {
+  try {
+    await invoke<WorkspaceData>("load_workspace_from_bytes", {
+      bytes: Array.from(bytes),
+      force,
+    });
+    await refreshDocuments();
+    await refreshWorkspaceSettings();
+    workspaceFilePath.set(null);
+    hasUnsavedChanges.set(false);
+    selectedDocumentId.set(null);
+  } catch (e) {
+    console.error("Failed to load workspace:", e);
+    throw e;
+  }
+}
+
+/** Create new workspace; rejects on unsaved changes as `loadWorkspace` does */
+export async function newWorkspace(force = false): Promise<void> {
   try {
-    await invoke("new_workspace");
+    await invoke("new_workspace", { force });
     await refreshDocuments();
     await refreshWorkspaceSettings();
     workspaceFilePath.set(null);
@@ -275,10 +545,10 @@ export async function newWorkspace(): Promise<void> {
   }
 }
 
-/** Clear all documents */
-export async function clearWorkspace(): Promise<void> {
+/** Clear all documents; fails with UNSAVED_CHANGES unless saved or `force` */
+export async function clearWorkspace(force = false): Promise<void> {
   try {
-    await invoke("clear_workspace");
+    await invoke("clear_workspace", { force });
     await refreshDocuments();
     hasUnsavedChanges.set(false);
     selectedDocumentId.set(null);
@@ -290,5 +560,10 @@ export async function clearWorkspace(): Promise<void> {
 
 /** Initialize workspace on app start */
 export async function initializeWorkspace(): Promise<void> {
-  await Promise.all([refreshDocuments(), refreshWorkspaceSettings()]);
+  await Promise.all([
+    refreshDocuments(),
+    refreshWorkspaceSettings(),
+    refreshSupportedImportFormats(),
+    refreshBedPresets(),
+  ]);
 }